@@ -0,0 +1,231 @@
+use crate::components::file_objects::utils::write_with_temp_file;
+use crate::components::project::Project;
+use crate::components::project_check::check_project;
+use crate::util::CheeseError;
+
+use std::path::{Path, PathBuf};
+
+pub const USAGE: &str = "usage: cheese-paper [--data-dir <path>]
+       cheese-paper --export <story|outline> --project <path> [--out <file>]
+       cheese-paper --check --project <path>";
+
+/// Sets where the editor keeps its settings and data, if `--data-dir` isn't passed
+pub const DATA_DIR_ENV: &str = "CHEESE_PAPER_DATA_DIR";
+
+/// Something to do from the command line instead of opening the editor
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliCommand {
+    Export(ExportArgs),
+    /// Look for problems in the project
+    Check {
+        project: PathBuf,
+    },
+}
+
+/// What can be exported from the command line
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The story text, using the export settings saved in the project
+    Story,
+    Outline,
+}
+
+impl ExportFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "story" => Some(Self::Story),
+            "outline" => Some(Self::Outline),
+            _ => None,
+        }
+    }
+}
+
+/// An export to run without opening the editor
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExportArgs {
+    pub format: ExportFormat,
+    pub project: PathBuf,
+    /// Where to write the export, or stdout if this isn't set
+    pub out: Option<PathBuf>,
+}
+
+/// Parse the command line arguments (not including the program name). `Ok(None)` means there's
+/// nothing to do from the command line, so the editor should be opened
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<CliCommand>, String> {
+    let mut args = args.into_iter();
+
+    let mut check = false;
+    let mut format = None;
+    let mut project = None;
+    let mut out = None;
+    let mut any_args = false;
+
+    while let Some(arg) = args.next() {
+        any_args = true;
+
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+
+        match arg.as_str() {
+            "--export" => {
+                let name = value()?;
+                format = Some(
+                    ExportFormat::from_name(&name)
+                        .ok_or_else(|| format!("unknown export format: {name}"))?,
+                );
+            }
+            "--check" => check = true,
+            "--project" => project = Some(PathBuf::from(value()?)),
+            "--out" => out = Some(PathBuf::from(value()?)),
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+
+    if !any_args {
+        return Ok(None);
+    }
+
+    let Some(project) = project else {
+        return Err("--project is required".to_string());
+    };
+
+    match (check, format) {
+        (true, None) if out.is_none() => Ok(Some(CliCommand::Check { project })),
+        (true, _) => Err("--check can't be combined with exporting".to_string()),
+        (false, Some(format)) => Ok(Some(CliCommand::Export(ExportArgs {
+            format,
+            project,
+            out,
+        }))),
+        (false, None) => Err("either --export or --check is required".to_string()),
+    }
+}
+
+#[test]
+fn test_parse_args() {
+    let args = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(args(&[]), Ok(None));
+    assert_eq!(
+        args(&[
+            "--export",
+            "story",
+            "--project",
+            "my_story",
+            "--out",
+            "story.md"
+        ]),
+        Ok(Some(CliCommand::Export(ExportArgs {
+            format: ExportFormat::Story,
+            project: PathBuf::from("my_story"),
+            out: Some(PathBuf::from("story.md")),
+        })))
+    );
+    assert_eq!(
+        args(&["--project", "my_story", "--export", "outline"]),
+        Ok(Some(CliCommand::Export(ExportArgs {
+            format: ExportFormat::Outline,
+            project: PathBuf::from("my_story"),
+            out: None,
+        })))
+    );
+    assert_eq!(
+        args(&["--check", "--project", "my_story"]),
+        Ok(Some(CliCommand::Check {
+            project: PathBuf::from("my_story")
+        }))
+    );
+
+    assert!(args(&["--export", "epub", "--project", "my_story"]).is_err());
+    assert!(args(&["--export", "story"]).is_err());
+    assert!(args(&["--project", "my_story", "--out"]).is_err());
+    assert!(args(&["my_story"]).is_err());
+    assert!(args(&["--check", "--export", "story", "--project", "my_story"]).is_err());
+}
+
+/// Remove `--data-dir <path>` from the arguments, returning the path. Falls back to the
+/// `CHEESE_PAPER_DATA_DIR` environment variable, and then to `None` (the platform default)
+pub fn take_data_dir(args: &mut Vec<String>) -> Result<Option<PathBuf>, String> {
+    let Some(position) = args.iter().position(|arg| arg == "--data-dir") else {
+        return Ok(std::env::var_os(DATA_DIR_ENV)
+            .filter(|data_dir| !data_dir.is_empty())
+            .map(PathBuf::from));
+    };
+
+    if position + 1 >= args.len() {
+        return Err("missing value for --data-dir".to_string());
+    }
+
+    let data_dir = args.remove(position + 1);
+    args.remove(position);
+
+    Ok(Some(PathBuf::from(data_dir)))
+}
+
+#[test]
+fn test_take_data_dir() {
+    let mut args: Vec<String> = ["--data-dir", "portable", "--check", "--project", "my_story"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+    assert_eq!(
+        take_data_dir(&mut args),
+        Ok(Some(PathBuf::from("portable")))
+    );
+    assert_eq!(args, vec!["--check", "--project", "my_story"]);
+
+    let mut args = vec!["--data-dir".to_string()];
+    assert!(take_data_dir(&mut args).is_err());
+}
+
+/// Load the project (without changing anything in it) and write out the export
+pub fn run_export(args: &ExportArgs) -> Result<(), CheeseError> {
+    let project = Project::load_read_only(args.project.clone())?;
+
+    let export = match args.format {
+        ExportFormat::Story => project.export_text(project.metadata.export.export_options()),
+        ExportFormat::Outline => project.export_outline(),
+    };
+
+    match &args.out {
+        Some(out) => write_with_temp_file(out, export)?,
+        None => print!("{export}"),
+    }
+
+    Ok(())
+}
+
+/// Print every problem found in the project, returning whether it's healthy
+pub fn run_check(project: &Path) -> bool {
+    let issues = check_project(project);
+
+    for issue in &issues {
+        println!("{issue}");
+    }
+
+    issues.is_empty()
+}
+
+/// Release builds on Windows don't have a console of their own (so that opening the editor doesn't
+/// open a console window too), so the command line modes use the one they were started from.
+/// Without this, nothing they print would show up
+#[cfg(windows)]
+pub fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    // Fails if there's already a console (e.g., debug builds), or if there's no parent console
+    // (e.g., started from the file explorer), which are both fine
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn attach_parent_console() {}
@@ -0,0 +1,106 @@
+use crate::cheese_error;
+use crate::components::file_objects::utils::{system_time_to_unix_seconds, temp_file_target};
+use crate::components::project::Project;
+use crate::util::CheeseError;
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Folder inside of the project that backups are written to. It's hidden (and not one of the top
+/// level folders), so it isn't loaded or watched like the rest of the project
+pub const BACKUP_FOLDER_NAME: &str = ".backups";
+
+/// Every backup folder starts with this, followed by the time it was made. Anything else in the
+/// backup folder is left alone when pruning
+const BACKUP_PREFIX: &str = "backup-";
+
+impl Project {
+    /// Copy the entire project into a new folder in `.backups/`, see `backup_project`
+    pub fn create_backup(&self, keep: usize) -> Result<PathBuf, CheeseError> {
+        backup_project(&self.get_path(), keep)
+    }
+}
+
+/// Copy the entire project at `project_path` into a new folder in `.backups/`, then delete the
+/// oldest backups so that at most `keep` are left. Returns the folder the backup was written to.
+/// The project doesn't have to be loaded, so this can be done before loading changes anything
+pub fn backup_project(project_path: &Path, keep: usize) -> Result<PathBuf, CheeseError> {
+    // Don't fill some other folder (that was opened by mistake) with backups
+    if !Project::is_project_folder(project_path) {
+        return Err(cheese_error!(
+            "{project_path:?} doesn't look like a project, not backing it up"
+        ));
+    }
+
+    let backup_root = project_path.join(BACKUP_FOLDER_NAME);
+
+    // Zero padded so that sorting the names sorts them by age
+    let timestamp = system_time_to_unix_seconds(SystemTime::now());
+    let mut backup_path = backup_root.join(format!("{BACKUP_PREFIX}{timestamp:012}"));
+    let mut counter = 1;
+    while backup_path.exists() {
+        backup_path = backup_root.join(format!("{BACKUP_PREFIX}{timestamp:012}-{counter}"));
+        counter += 1;
+    }
+
+    std::fs::create_dir_all(&backup_path)?;
+    copy_project_dir(project_path, &backup_path)
+        .map_err(|err| cheese_error!("Failed to back up project to {backup_path:?}: {err}"))?;
+
+    prune_backups(&backup_root, keep)?;
+
+    Ok(backup_path)
+}
+
+/// Copy everything in `source` to `dest`, except for backups, version control, and temporary files
+fn copy_project_dir(source: &Path, dest: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let filename = entry.file_name();
+        let filename_str = filename.to_string_lossy();
+
+        if filename_str == BACKUP_FOLDER_NAME || filename_str == ".git" {
+            continue;
+        }
+
+        let dest_path = dest.join(&filename);
+        if entry.file_type()?.is_dir() {
+            std::fs::create_dir(&dest_path)?;
+            copy_project_dir(&entry.path(), &dest_path)?;
+        } else if temp_file_target(&filename_str).is_some() {
+            continue;
+        } else {
+            std::fs::copy(entry.path(), dest_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Delete the oldest backups in `backup_root` until there are only `keep` left
+fn prune_backups(backup_root: &Path, keep: usize) -> Result<(), CheeseError> {
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(backup_root)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(BACKUP_PREFIX)
+                && entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+        })
+        .map(|entry| entry.path())
+        .collect();
+
+    if backups.len() <= keep {
+        return Ok(());
+    }
+
+    backups.sort();
+    let remove_count = backups.len() - keep;
+    for old_backup in &backups[..remove_count] {
+        log::debug!("Removing old backup {old_backup:?}");
+        std::fs::remove_dir_all(old_backup)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use crate::cheese_error;
+use crate::components::file_objects::{FileID, FileObject};
+use crate::components::project::Project;
+use crate::util::CheeseError;
+
+use egui_ltreeview::DirPosition;
+
+use std::path::Path;
+
+/// Columns that are copied into the character's metadata field of the same name. `name` is
+/// handled separately since it's required
+const IMPORTED_FIELDS: &[&str] = &["summary", "notes"];
+
+/// Split the contents of a CSV file into rows of fields. Fields can be quoted (with `""` for a
+/// literal quote) to contain commas or line breaks. An unterminated quote runs to the end of the
+/// file, which leaves that row with the wrong number of fields
+pub fn parse_csv(contents: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    // The last line doesn't have to end with a line break
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    // Blank lines aren't rows
+    rows.retain(|row| !(row.len() == 1 && row[0].trim().is_empty()));
+
+    rows
+}
+
+impl Project {
+    /// Create a character at the end of `into` for each row of a CSV file. The file needs a header
+    /// row with a `name` column, and `summary` and `notes` columns are used if they're there.
+    /// Rows that don't match the header or don't have a name are skipped
+    pub fn import_characters_csv(
+        &mut self,
+        path: &Path,
+        into: &FileID,
+    ) -> Result<Vec<FileID>, CheeseError> {
+        if !self
+            .objects
+            .get(into)
+            .is_some_and(|object| object.borrow().is_folder())
+        {
+            return Err(cheese_error!("can't import into {into}: not a folder"));
+        }
+
+        let character_type = *self
+            .schema
+            .get_all_file_types()
+            .iter()
+            .find(|file_type| file_type.get_identifier() == "character")
+            .ok_or_else(|| cheese_error!("the {} schema doesn't have characters", self.schema))?;
+
+        let contents = std::fs::read_to_string(path)?;
+        let mut rows = parse_csv(&contents).into_iter();
+
+        let header: Vec<String> = rows
+            .next()
+            .ok_or_else(|| cheese_error!("{path:?} is empty"))?
+            .iter()
+            .map(|column| column.trim().to_lowercase())
+            .collect();
+
+        let name_column = header
+            .iter()
+            .position(|column| column == "name")
+            .ok_or_else(|| cheese_error!("{path:?} doesn't have a name column"))?;
+
+        for column in &header {
+            if column != "name" && !IMPORTED_FIELDS.contains(&column.as_str()) {
+                log::warn!("Ignoring unknown column {column:?} in {path:?}");
+            }
+        }
+
+        let mut imported = Vec::new();
+
+        for (position, row) in rows.enumerate() {
+            // Counting from 1, after the header
+            let row_number = position + 2;
+
+            if row.len() != header.len() {
+                log::warn!(
+                    "Skipping row {row_number} of {path:?}: it has {} fields instead of {}",
+                    row.len(),
+                    header.len()
+                );
+                continue;
+            }
+
+            let name = row[name_column].trim();
+            if name.is_empty() {
+                log::warn!("Skipping row {row_number} of {path:?}: it doesn't have a name");
+                continue;
+            }
+
+            let mut character = self.objects.get(into).unwrap().borrow_mut().create_child(
+                character_type,
+                DirPosition::Last,
+                &self.objects,
+            )?;
+
+            character.get_base_mut().metadata.name = name.to_string();
+            for (column, value) in header.iter().zip(row.iter()) {
+                if IMPORTED_FIELDS.contains(&column.as_str()) {
+                    character.get_base_mut().toml_header[column.as_str()] =
+                        toml_edit::value(value.trim());
+                }
+            }
+            character.load_metadata()?;
+            character.get_base_mut().file.modified = true;
+
+            let new_id = character.id().clone();
+            self.add_object(character);
+
+            // A character with the same name might already be there
+            let new_name = self.unused_child_name(into, &new_id);
+            self.objects[&new_id]
+                .borrow_mut()
+                .get_base_mut()
+                .metadata
+                .name = new_name;
+
+            imported.push(new_id);
+        }
+
+        Ok(imported)
+    }
+}
+
+#[test]
+fn test_parse_csv() {
+    assert_eq!(
+        parse_csv("name,summary\nAlice,A detective\r\n\n\"Bob, Jr.\",\"Says \"\"hi\"\"\noften\""),
+        vec![
+            vec!["name", "summary"],
+            vec!["Alice", "A detective"],
+            vec!["Bob, Jr.", "Says \"hi\"\noften"],
+        ]
+    );
+
+    // An empty last field still counts
+    assert_eq!(parse_csv("a,\n"), vec![vec!["a", ""]]);
+    assert_eq!(parse_csv(""), Vec::<Vec<String>>::new());
+}
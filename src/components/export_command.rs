@@ -0,0 +1,105 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Split a command into arguments on whitespace, keeping anything in quotes (single or double)
+/// together. This isn't a shell, so there's no escaping, globbing, or variables
+pub fn split_command_line(command: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current_arg: Option<String> = None;
+    let mut quote = None;
+
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(open_quote), c) if c == open_quote => quote = None,
+            (Some(_), c) => current_arg.get_or_insert_default().push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                // an empty pair of quotes is still an argument
+                current_arg.get_or_insert_default();
+            }
+            (None, c) if c.is_whitespace() => {
+                if let Some(arg) = current_arg.take() {
+                    args.push(arg);
+                }
+            }
+            (None, c) => current_arg.get_or_insert_default().push(c),
+        }
+    }
+
+    if let Some(arg) = current_arg {
+        args.push(arg);
+    }
+
+    args
+}
+
+#[test]
+fn test_split_command_line() {
+    assert_eq!(
+        split_command_line("pandoc {input}  -o {output}.docx"),
+        vec!["pandoc", "{input}", "-o", "{output}.docx"]
+    );
+    assert_eq!(
+        split_command_line(r#"script "with spaces" 'single "quoted"' ''"#),
+        vec!["script", "with spaces", r#"single "quoted""#, ""]
+    );
+    assert!(split_command_line("   ").is_empty());
+}
+
+/// Run the export post-processing command on an exported file. In every argument, `{input}` is
+/// replaced with the path of the export, and `{output}` with that same path without its extension
+/// (so that the command can pick its own). On failure, the error includes whatever the command
+/// wrote to stderr
+pub fn run_post_command(command: &str, input: &Path) -> Result<(), String> {
+    let input_str = input.to_string_lossy();
+    let output = input.with_extension("");
+    let output_str = output.to_string_lossy();
+
+    let mut args = split_command_line(command).into_iter().map(|arg| {
+        arg.replace("{input}", &input_str)
+            .replace("{output}", &output_str)
+    });
+
+    let Some(program) = args.next() else {
+        return Err("export post-processing command is empty".to_string());
+    };
+
+    let command_output = Command::new(&program)
+        .args(args)
+        .output()
+        .map_err(|err| format!("could not run {program}: {err}"))?;
+
+    if command_output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&command_output.stderr);
+        Err(format!(
+            "{program} failed ({}): {}",
+            command_output.status,
+            stderr.trim()
+        ))
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_post_command() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+    let input = base_dir.path().join("story.md");
+    std::fs::write(&input, "exported text").unwrap();
+
+    run_post_command("cp {input} {output}.txt", &input).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(base_dir.path().join("story.txt")).unwrap(),
+        "exported text"
+    );
+
+    let err = run_post_command("cp {input}", &input).unwrap_err();
+    assert!(err.starts_with("cp failed"));
+
+    // the exit status and the message from stderr should be included
+    let err = run_post_command(r#"sh -c "echo 'no pandoc here' >&2; exit 3""#, &input).unwrap_err();
+    assert_eq!(err, "sh failed (exit status: 3): no pandoc here");
+
+    assert!(run_post_command("", &input).is_err());
+}
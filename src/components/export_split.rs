@@ -0,0 +1,149 @@
+use crate::cheese_error;
+use crate::components::file_objects::utils::{process_name_for_filename, write_with_temp_file};
+use crate::components::project::Project;
+use crate::util::CheeseError;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The filename template for split exports in projects that haven't set their own
+pub const DEFAULT_SPLIT_FILENAME_TEMPLATE: &str = "{index:02}-{name}";
+
+/// Fill in a filename template for one file of a split export. The template can use `{index}`
+/// (counting from 0), `{number}` (counting from 1), and `{name}` (the title, made filename safe).
+/// A width can be given after a colon, with a leading `0` to pad with zeros instead of spaces
+/// (e.g., `{index:03}`). `{{` and `}}` are literal braces
+pub fn fill_filename_template(
+    template: &str,
+    index: usize,
+    name: &str,
+) -> Result<String, CheeseError> {
+    let mut filled = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        filled.push_str(&rest[..start]);
+        let tail = &rest[start..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            filled.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        let Some(end) = tail.find('}').filter(|_| tail.starts_with('{')) else {
+            return Err(cheese_error!(
+                "Unmatched brace in filename template {template:?}"
+            ));
+        };
+        let placeholder = &tail[1..end];
+        rest = &tail[end + 1..];
+
+        let (field, width) = match placeholder.split_once(':') {
+            Some((field, width)) => (field, Some(width)),
+            None => (placeholder, None),
+        };
+
+        let value = match field {
+            "index" => index.to_string(),
+            "number" => (index + 1).to_string(),
+            "name" => process_name_for_filename(name.trim()),
+            _ => {
+                return Err(cheese_error!(
+                    "Unknown field {{{field}}} in filename template, expected index, number, or name"
+                ));
+            }
+        };
+
+        match width {
+            Some(width) => {
+                let zero_padded = width.starts_with('0');
+                let width: usize = width
+                    .parse()
+                    .map_err(|_| cheese_error!("Invalid width {width:?} in filename template"))?;
+                if zero_padded {
+                    filled.push_str(&format!("{value:0>width$}"));
+                } else {
+                    filled.push_str(&format!("{value:>width$}"));
+                }
+            }
+            None => filled.push_str(&value),
+        }
+    }
+    filled.push_str(rest);
+
+    if filled.trim().is_empty() || filled.contains(['/', '\\']) {
+        return Err(cheese_error!(
+            "Filename template {template:?} doesn't produce a valid filename"
+        ));
+    }
+
+    Ok(filled)
+}
+
+#[test]
+fn test_fill_filename_template() {
+    assert_eq!(
+        fill_filename_template("{index:03}-{name}", 4, "The Start").unwrap(),
+        "004-The_Start"
+    );
+    assert_eq!(
+        fill_filename_template("{number}. {name}", 0, "Prologue").unwrap(),
+        "1. Prologue"
+    );
+    assert_eq!(
+        fill_filename_template("{number:3} {{draft}}", 9, "").unwrap(),
+        " 10 {draft}"
+    );
+
+    assert!(fill_filename_template("{title}", 0, "a").is_err());
+    assert!(fill_filename_template("{index:x}", 0, "a").is_err());
+    assert!(fill_filename_template("{index", 0, "a").is_err());
+    assert!(fill_filename_template("index}", 0, "a").is_err());
+    assert!(fill_filename_template("{name}", 0, "  ").is_err());
+    assert!(fill_filename_template("{index}/{name}", 0, "a").is_err());
+}
+
+impl Project {
+    /// Export each object at the top of the text folder (usually chapters) to its own markdown
+    /// file in `dir`, using the project's export settings and naming the files with its split
+    /// filename template (see `fill_filename_template`). Objects that don't export anything (e.g.,
+    /// excluded folders) are skipped, and don't use up an index. Every filename is checked before
+    /// anything is written, returning the paths that were written
+    pub fn export_split(&self, dir: &Path) -> Result<Vec<PathBuf>, CheeseError> {
+        let filename_template = &self.metadata.export.split_filename_template;
+        let mut filenames = HashSet::new();
+        let mut exports = Vec::new();
+
+        let children = self.get_text_folder().borrow().get_base().children.clone();
+        for child_id in children {
+            let export_string = self
+                .export_selected_text(&[child_id.clone()], self.metadata.export.export_options());
+            if export_string.trim().is_empty() {
+                continue;
+            }
+
+            let title = match self.objects.get(&child_id) {
+                Some(child) => child.borrow().get_title(),
+                None => continue,
+            };
+
+            let filename = fill_filename_template(filename_template, exports.len(), &title)?;
+            if !filenames.insert(filename.clone()) {
+                return Err(cheese_error!(
+                    "Filename template {filename_template:?} gives more than one file the name \
+                    {filename:?}"
+                ));
+            }
+            exports.push((dir.join(format!("{filename}.md")), export_string));
+        }
+
+        let mut written = Vec::new();
+        for (path, export_string) in exports {
+            write_with_temp_file(&path, export_string)?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+}
@@ -1,4 +1,5 @@
 mod base_file_object;
+mod lazy_body;
 mod reference;
 mod tools;
 pub mod utils;
@@ -15,13 +16,16 @@ use crate::util::CheeseError;
 use std::fmt::Debug;
 use std::rc::Rc;
 
-pub use utils::{FILENAME_MAX_LENGTH, FOLDER_METADATA_FILE_NAME, HEADER_SPLIT};
+pub use utils::{
+    DEFAULT_INDEX_WIDTH, FILENAME_MAX_LENGTH, FOLDER_METADATA_FILE_NAME, HEADER_SPLIT,
+};
 
 pub use base_file_object::{
-    BaseFileObject, CompileStatus, FileInfo, FileObjectMetadata, IncludeOptions,
+    BaseFileObject, CompileStatus, DiskConflict, FileInfo, FileObjectMetadata, IncludeOptions,
 };
 
-pub use reference::ObjectReference;
+pub use lazy_body::LazyBody;
+pub use reference::{ObjectReference, Relationship};
 
 pub trait FileObject: Debug {
     fn get_type(&self) -> FileType;
@@ -35,6 +39,63 @@ pub trait FileObject: Debug {
     fn load_body(&mut self, body: String);
     /// Gets the contents of the body to be written when saving
     fn get_body(&self) -> String;
+    /// If the body is currently held in memory. Objects without a body are always loaded
+    fn is_body_loaded(&self) -> bool {
+        true
+    }
+    /// Read the body from disk if it isn't loaded yet, failing if it can't be read. Saving does
+    /// this first, so that a body that couldn't be read is never written over the real one
+    fn load_unloaded_body(&self) -> Result<(), CheeseError> {
+        Ok(())
+    }
+
+    /// When this object happens in the story, for objects that can be placed on the timeline
+    fn story_date(&self) -> Option<&str> {
+        None
+    }
+
+    /// How many words this object should have, for objects that can have a target
+    fn target_word_count(&self) -> Option<u64> {
+        None
+    }
+
+    /// A short description to go with the name in a glossary, for objects that belong in one
+    fn glossary_summary(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the body has git conflict markers left in it. This is only checked once the body has
+    /// been loaded, so that it isn't read just to draw the tree
+    fn has_conflict_markers(&self) -> bool {
+        false
+    }
+
+    /// How many words are in the body. Like `has_conflict_markers`, this doesn't need the body to
+    /// be loaded, so it can be used for every object in the project
+    fn word_count(&self) -> usize {
+        0
+    }
+
+    /// Whether the body has been locked against edits. Locked objects can still be exported
+    /// and moved
+    fn is_locked(&self) -> bool {
+        false
+    }
+
+    /// How this object is included in the export, for objects that can be exported
+    fn compile_status(&self) -> Option<&CompileStatus> {
+        None
+    }
+
+    /// Named references from this object to others, for objects that can have them
+    fn relationships(&self) -> &[Relationship] {
+        &[]
+    }
+
+    /// Descriptions of any references from this object to objects that couldn't be found
+    fn broken_references(&self) -> Vec<String> {
+        Vec::new()
+    }
 
     /// Display the outline, writing all relevant non-prose information we have to a single
     /// markdown file that can be scanned/shared easily. We don't (currently) have any selections
@@ -52,11 +113,13 @@ pub trait FileObject: Debug {
     /// (folder and scene)
     ///
     /// `include_break` adds a break at the beginning if appropriate, and this function returns
-    /// `true` if the next function should include a break
+    /// `true` if the next function should include a break. The words of every exported body are
+    /// added to `word_count` (titles and breaks aren't part of the story, so they aren't counted)
     fn generate_export(
         &self,
         _current_depth: u64,
         _export_string: &mut String,
+        _word_count: &mut usize,
         _objects: &FileObjectStore,
         _export_options: &ExportOptions,
         include_break: bool,
@@ -4,8 +4,10 @@ use uuid::Uuid;
 use super::*;
 use crate::components::file_objects::utils::*;
 // use crate::components::file_objects::{Character, Folder, Place, Scene};
+use crate::cheese_error;
 use crate::components::schema::FileType;
 use crate::util::CheeseError;
+use std::cell::OnceCell;
 use std::ffi::OsString;
 use std::fmt::Debug;
 use std::path::PathBuf;
@@ -29,6 +31,11 @@ pub struct FileObjectMetadata {
     /// ID unique across all objects. The reference implementations use UUIDv4, but any string
     /// is acceptable
     pub id: Rc<String>,
+    /// Color shown next to the object in the tree, `None` for no color
+    pub label_color: Option<[u8; 3]>,
+    /// When the object was first created, `None` if it's older than this field and the filesystem
+    /// couldn't tell us
+    pub created: Option<SystemTime>,
 }
 
 #[derive(Debug)]
@@ -47,6 +54,8 @@ impl Default for FileObjectMetadata {
             version: 1u64,
             name: String::new(),
             id: Rc::new(Uuid::new_v4().as_hyphenated().to_string()),
+            label_color: None,
+            created: None,
         }
     }
 }
@@ -59,9 +68,53 @@ pub struct FileInfo {
     /// Path of the file within the dirname
     /// `/foo/bar/` -> `bar`
     pub basename: OsString,
+    /// If an object that's normally a single file (e.g., a character) has been turned into a folder
+    /// to hold other objects, see `FileTypeInfo::can_nest`
+    pub converted_to_folder: bool,
     /// Modified time if the file exists
     pub modtime: Option<SystemTime>,
+    /// If the object needs to be written on the next save (including bookkeeping changes, like
+    /// filling in missing metadata)
     pub modified: bool,
+    /// If the user changed the object since it was last saved. Unlike `modified`, this is only
+    /// set for changes that would be lost if the file was reloaded from disk
+    pub edited: bool,
+    /// The extension of the file, if it isn't the default for the object's type
+    pub extension: Option<String>,
+    /// How many digits the index in the filename is padded to. This is set by the parent, since
+    /// all of the siblings need the same width to sort properly
+    pub index_width: usize,
+    /// The smallest width to use for the indexes of this object's children (it gets widened if
+    /// there are too many children to fit)
+    pub child_index_width: usize,
+    /// Set when the file changed on disk while there were unsaved changes in memory. Until this
+    /// gets resolved, the object won't be saved (or reloaded)
+    pub disk_conflict: Option<DiskConflict>,
+    /// If the body had git conflict markers in it when the file was read. Bodies are loaded
+    /// lazily, so this is what gets checked until the body itself is loaded
+    pub conflict_markers: bool,
+    /// How many words were in the body when the file was read, used in the same way as
+    /// `conflict_markers`
+    pub word_count: usize,
+    /// The filename of the attached image inside of the folder (see `find_image`), looked up the
+    /// first time it's needed so that drawing the object doesn't list the folder every frame
+    pub image_name: OnceCell<Option<OsString>>,
+}
+
+impl FileInfo {
+    /// Mark that the user changed the object, so it gets saved and won't be silently replaced if
+    /// the file also changes on disk
+    pub fn mark_edited(&mut self) {
+        self.modified = true;
+        self.edited = true;
+    }
+}
+
+/// The version of a file that's on disk, held onto when it conflicts with unsaved changes
+#[derive(Debug, Clone)]
+pub struct DiskConflict {
+    pub metadata: String,
+    pub body: Option<String>,
 }
 
 bitflags! {
@@ -85,6 +138,8 @@ bitflags! {
         const INCLUDE_TITLE          = 0b0000_0000_0000_0100;
         const OVERRIDE_BREAK_AT_END  = 0b0000_0000_0000_1000;
         const BREAK_AT_END           = 0b0000_0000_0001_0000;
+        const OVERRIDE_HARD_BREAKS   = 0b0000_0000_0010_0000;
+        const HARD_BREAKS            = 0b0000_0000_0100_0000;
 
         // allow for any bits, in case a future version of cheese-paper sets more
         const _ = !0;
@@ -145,6 +200,31 @@ impl CompileStatus {
             }
         }
     }
+
+    /// Whether single line breaks in the text are kept in the export (e.g., for verse)
+    pub fn hard_breaks(&self) -> IncludeOptions {
+        if self.contains(CompileStatus::HARD_BREAKS | CompileStatus::OVERRIDE_HARD_BREAKS) {
+            IncludeOptions::Always
+        } else if self.contains(CompileStatus::OVERRIDE_HARD_BREAKS) {
+            IncludeOptions::Never
+        } else {
+            IncludeOptions::Default
+        }
+    }
+
+    pub fn set_hard_breaks(&mut self, options: IncludeOptions) {
+        match options {
+            IncludeOptions::Default => self.set(CompileStatus::OVERRIDE_HARD_BREAKS, false),
+            IncludeOptions::Always => {
+                self.set(CompileStatus::OVERRIDE_HARD_BREAKS, true);
+                self.set(CompileStatus::HARD_BREAKS, true);
+            }
+            IncludeOptions::Never => {
+                self.set(CompileStatus::OVERRIDE_HARD_BREAKS, true);
+                self.set(CompileStatus::HARD_BREAKS, false);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
@@ -178,6 +258,26 @@ impl FileObjectMetadata {
             None => file_info.modified = true,
         }
 
+        // Most objects won't have a color, so a missing value doesn't need to be written
+        if let Some(color) = metadata_extract_string(metadata_table, "label_color")? {
+            self.label_color = Some(
+                parse_hex_color(&color)
+                    .ok_or_else(|| cheese_error!("label_color was not a #rrggbb color"))?,
+            );
+        }
+
+        match metadata_extract_u64(metadata_table, "created", false)? {
+            Some(created) => self.created = Some(unix_seconds_to_system_time(created)),
+            None => {
+                // Files from before this was tracked get the best guess we have, which is when the
+                // filesystem says it was created (not every filesystem knows). This only lives in
+                // memory until the next real save, rewriting the file just for it isn't worth it
+                self.created = std::fs::metadata(file_info.dirname.join(&file_info.basename))
+                    .and_then(|metadata| metadata.created())
+                    .ok();
+            }
+        }
+
         Ok(())
     }
 }
@@ -194,16 +294,21 @@ impl BaseFileObject {
             Some(index) => {
                 let truncated_name = truncate_name(base_name, super::FILENAME_MAX_LENGTH);
                 let file_safe_name = process_name_for_filename(truncated_name);
-                let final_name = add_index_to_name(&file_safe_name, index);
+                let final_name = add_index_to_name(&file_safe_name, index, self.file.index_width);
 
                 OsString::from(final_name)
             }
             None => OsString::from(process_name_for_filename(base_name)),
         };
 
-        if !file_type.is_folder() {
+        if !file_type.is_folder() && !self.file.converted_to_folder {
             basename.push(".");
-            basename.push(file_type.extension());
+            basename.push(
+                self.file
+                    .extension
+                    .as_deref()
+                    .unwrap_or(file_type.extension()),
+            );
         }
 
         basename
@@ -212,13 +317,25 @@ impl BaseFileObject {
     /// Create a new file object in a folder
     pub fn new(dirname: PathBuf, index: Option<usize>) -> Self {
         Self {
-            metadata: FileObjectMetadata::default(),
+            metadata: FileObjectMetadata {
+                created: Some(SystemTime::now()),
+                ..Default::default()
+            },
             index,
             file: FileInfo {
                 dirname,
                 basename: OsString::new(),
+                converted_to_folder: false,
                 modtime: None,
                 modified: true, // Newly added files are modified (they don't exist on disk)
+                edited: true,
+                extension: None,
+                index_width: DEFAULT_INDEX_WIDTH,
+                child_index_width: DEFAULT_INDEX_WIDTH,
+                disk_conflict: None,
+                conflict_markers: false,
+                word_count: 0,
+                image_name: OnceCell::new(),
             },
             toml_header: DocumentMut::new(),
             children: Vec::new(),
@@ -229,6 +346,24 @@ impl BaseFileObject {
         self.toml_header["file_format_version"] = toml_edit::value(self.metadata.version as i64);
         self.toml_header["name"] = toml_edit::value(&self.metadata.name);
         self.toml_header["id"] = toml_edit::value(&*self.metadata.id);
+
+        match self.metadata.label_color {
+            Some(color) => {
+                self.toml_header["label_color"] = toml_edit::value(format_hex_color(color))
+            }
+            None => {
+                self.toml_header.remove("label_color");
+            }
+        }
+
+        match self.metadata.created {
+            Some(created) => {
+                self.toml_header["created"] = toml_edit::value(system_time_to_unix_seconds(created))
+            }
+            None => {
+                self.toml_header.remove("created");
+            }
+        }
     }
 }
 impl std::fmt::Display for dyn FileObject {
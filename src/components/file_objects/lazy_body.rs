@@ -0,0 +1,89 @@
+use crate::cheese_error;
+use crate::components::file_objects::utils::{read_file_contents, trim_final_newline};
+use crate::components::text::Text;
+use crate::util::CheeseError;
+
+use std::cell::OnceCell;
+use std::path::{Path, PathBuf};
+
+/// The body of a file object, which is only read from disk the first time it's needed. The
+/// metadata of every object is loaded with the project, but bodies can be large, so we avoid
+/// holding them until something actually asks for them.
+#[derive(Debug, Default)]
+pub struct LazyBody {
+    text: OnceCell<Text>,
+    /// Handed out in place of the body while it can't be read. It's never saved, so the real
+    /// body on disk can't be overwritten with it
+    placeholder: OnceCell<Text>,
+}
+
+impl LazyBody {
+    /// Create a body that has already been loaded (or is new). `None` means the body is still on
+    /// disk and will be read when it's first used
+    pub fn new(body: Option<String>) -> Self {
+        Self {
+            text: match body {
+                Some(body) => OnceCell::from(Text::from(body)),
+                None => OnceCell::new(),
+            },
+            placeholder: OnceCell::new(),
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.text.get().is_some()
+    }
+
+    /// Replace the body (e.g., when reloading the file)
+    pub fn set(&mut self, body: String) {
+        self.text = OnceCell::from(Text::from(body));
+        self.placeholder = OnceCell::new();
+    }
+
+    /// Read the body from `file` if it hasn't been loaded yet. A failed read isn't remembered, so
+    /// the next call tries again
+    pub fn load(&self, file: impl FnOnce() -> PathBuf) -> Result<&Text, CheeseError> {
+        if let Some(text) = self.text.get() {
+            return Ok(text);
+        }
+
+        let text = read_body(&file())?;
+        Ok(self.text.get_or_init(|| text))
+    }
+
+    /// Get the body, reading it from `file` if it hasn't been loaded yet. If it can't be read,
+    /// an empty placeholder is returned instead
+    pub fn get(&self, file: impl FnOnce() -> PathBuf) -> &Text {
+        match self.load(file) {
+            Ok(text) => text,
+            Err(err) => {
+                log::error!("{err}");
+                self.placeholder.get_or_init(Text::default)
+            }
+        }
+    }
+
+    /// Get the body mutably, reading it from `file` if it hasn't been loaded yet. If it can't be
+    /// read, changes go to the placeholder and won't be saved
+    pub fn get_mut(&mut self, file: impl FnOnce() -> PathBuf) -> &mut Text {
+        if self.load(file).is_err() {
+            self.placeholder.get_or_init(Text::default);
+            return self
+                .placeholder
+                .get_mut()
+                .expect("placeholder was just initialized");
+        }
+
+        self.text
+            .get_mut()
+            .expect("body should have been loaded by load")
+    }
+}
+
+fn read_body(file: &Path) -> Result<Text, CheeseError> {
+    let (_metadata, body) = read_file_contents(file)
+        .map_err(|err| cheese_error!("Failed to read body of {file:?}: {err}"))?;
+    Ok(trim_final_newline(&body.unwrap_or_default())
+        .to_string()
+        .into())
+}
@@ -1,7 +1,12 @@
 use cow_utils::CowUtils;
 use icu_casemap::{CaseMapper, CaseMapperBorrowed};
 
+use crate::cheese_error;
+use crate::components::file_objects::utils::metadata_extract_string;
 use crate::components::file_objects::{FileID, FileObjectStore, FileType};
+use crate::util::CheeseError;
+
+use toml_edit::TableLike;
 
 /// A reference to an object that is currently unknown (e.g., does not reference an object
 /// currently loaded into the editor)
@@ -96,6 +101,144 @@ impl ObjectReference {
     }
 }
 
+impl ObjectReference {
+    /// The name to show for this reference, or `None` if it doesn't refer to anything
+    pub fn display_name(&self, objects: &FileObjectStore) -> Option<String> {
+        match self {
+            Self::Known(file_id) => objects
+                .get(file_id)
+                .map(|object| object.borrow().get_title()),
+            Self::Unknown(unknown) if unknown.name.is_empty() => Some(unknown.id.clone()),
+            Self::Unknown(unknown) => Some(unknown.name.clone()),
+            Self::None => None,
+        }
+    }
+}
+
+/// A named connection from one object to another (e.g., a character who is the "sibling of"
+/// another character)
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Relationship {
+    pub relation: String,
+    pub target: ObjectReference,
+}
+
+impl Relationship {
+    /// Read a list of relationships (an array of `{ relation, target }` tables) from the metadata,
+    /// where every target should be of `file_type`
+    pub fn load_list(
+        table: &dyn TableLike,
+        field_name: &str,
+        file_type: FileType,
+    ) -> Result<Vec<Self>, CheeseError> {
+        let Some(item) = table.get(field_name) else {
+            return Ok(Vec::new());
+        };
+
+        let Some(array) = item.as_array() else {
+            return Err(cheese_error!("{field_name} was not an array"));
+        };
+
+        array
+            .iter()
+            .map(|value| {
+                let relationship = value
+                    .as_inline_table()
+                    .ok_or_else(|| cheese_error!("Invalid entry in {field_name}: {value:?}"))?;
+                let relation =
+                    metadata_extract_string(relationship, "relation")?.unwrap_or_default();
+                let target = metadata_extract_string(relationship, "target")?.unwrap_or_default();
+
+                Ok(Self {
+                    relation,
+                    target: ObjectReference::new(target, Some(file_type)),
+                })
+            })
+            .collect()
+    }
+
+    /// Turn a list of relationships into an array for the metadata, skipping any that are blank
+    pub fn save_list(relationships: &[Self], objects: &FileObjectStore) -> toml_edit::Array {
+        relationships
+            .iter()
+            .filter(|relationship| {
+                !relationship.relation.trim().is_empty()
+                    || relationship.target != ObjectReference::None
+            })
+            .map(|relationship| {
+                let mut table = toml_edit::InlineTable::new();
+                table.insert("relation", relationship.relation.trim().into());
+                table.insert("target", relationship.target.to_string(objects).into());
+                toml_edit::Value::InlineTable(table)
+            })
+            .collect()
+    }
+
+    /// Write a list of relationships into `field_name` of the metadata, leaving the field out
+    /// entirely when there aren't any
+    pub fn write_list(
+        table: &mut toml_edit::Table,
+        field_name: &str,
+        relationships: &[Self],
+        objects: &FileObjectStore,
+    ) {
+        let array = Self::save_list(relationships, objects);
+        if array.is_empty() {
+            table.remove(field_name);
+        } else {
+            table[field_name] = toml_edit::value(array);
+        }
+    }
+
+    /// Resolve the targets that weren't loaded yet when the relationships were read
+    pub fn resolve_list(relationships: &mut [Self], objects: &FileObjectStore) {
+        for relationship in relationships {
+            if let ObjectReference::Unknown(unknown_ref) = &relationship.target
+                && let Some(known_target) = unknown_ref.resolve(objects)
+            {
+                relationship.target = ObjectReference::Known(known_target);
+            }
+        }
+    }
+
+    /// Describe every relationship whose target couldn't be found, for `broken_references`
+    pub fn broken_list(relationships: &[Self]) -> Vec<String> {
+        relationships
+            .iter()
+            .filter_map(|relationship| match &relationship.target {
+                ObjectReference::Unknown(unknown_reference) => Some(format!(
+                    "relationship \"{}\" to \"{}\" (id \"{}\")",
+                    relationship.relation, unknown_reference.name, unknown_reference.id
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Write a list of relationships into an outline as a markdown list
+    pub fn write_outline_list(
+        relationships: &[Self],
+        objects: &FileObjectStore,
+        export_string: &mut String,
+    ) {
+        let mut lines = relationships.iter().filter_map(|relationship| {
+            let name = relationship.target.display_name(objects)?;
+            Some(format!("- {} {name}\n", relationship.relation.trim()))
+        });
+
+        let Some(first_line) = lines.next() else {
+            return;
+        };
+
+        export_string.push_str("relationships:\n\n");
+        export_string.push_str(&first_line);
+        for line in lines {
+            export_string.push_str(&line);
+        }
+        export_string.push('\n');
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum WordMatch {
     Exact,
@@ -124,7 +267,10 @@ impl UnknownReference {
 
             // Compare this reference to every object to see if it matches up
             for (id, object_refcell) in objects.iter() {
-                let object = object_refcell.borrow();
+                // The object doing the resolving is already borrowed, and can't refer to itself
+                let Ok(object) = object_refcell.try_borrow() else {
+                    continue;
+                };
 
                 // If we have a known file object type and this doesn't match it (e.g., we're trying to
                 // resolve a character reference and this is a scene, give up)
@@ -188,9 +334,9 @@ impl UnknownReference {
             }
         } else {
             // We have an ID string, we're only looking for that ID
-            if let Some(object_ref) = objects.get(&self.id) {
-                let object = object_ref.borrow();
-
+            if let Some(object_ref) = objects.get(&self.id)
+                && let Ok(object) = object_ref.try_borrow()
+            {
                 if let Some(this_file_type) = self.file_type
                     && this_file_type != object.get_type()
                 {
@@ -1,9 +1,12 @@
-use std::cell::RefCell;
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::cheese_error;
-use crate::components::file_objects::utils::{get_index_from_name, write_with_temp_file};
+use crate::components::file_objects::utils::{
+    IMAGE_EXTENSIONS, IMAGE_FILE_STEM, get_index_from_name, index_width_for, is_image_file,
+    replace_with_copy, write_with_temp_file,
+};
 // use crate::components::file_objects::{Character, Folder, Place, Scene};
 use crate::util::CheeseError;
 use egui_ltreeview::DirPosition;
@@ -13,6 +16,8 @@ use toml_edit::DocumentMut;
 
 use super::*;
 use crate::components::file_objects::utils::read_file_contents;
+use crate::components::markdown::starts_conflict;
+use crate::components::project::TitleStyle;
 
 pub type FileID = Rc<String>;
 
@@ -20,7 +25,17 @@ pub type FileObjectStore = HashMap<FileID, RefCell<Box<dyn FileObject>>>;
 
 impl dyn FileObject {
     pub fn is_folder(&self) -> bool {
-        self.get_type().is_folder()
+        self.get_type().is_folder() || self.get_base().file.converted_to_folder
+    }
+
+    /// Whether an object of `file_type` can be put inside of this one. Types that nest (e.g.,
+    /// places) only hold more of their own type, other folders can hold anything
+    pub fn can_hold(&self, file_type: FileType) -> bool {
+        if self.get_type().can_nest() {
+            self.get_type() == file_type
+        } else {
+            self.is_folder()
+        }
     }
 
     pub fn has_body(&self) -> bool {
@@ -66,6 +81,102 @@ impl dyn FileObject {
         }
     }
 
+    /// Finds the image attached to this object (`image.<ext>` inside of the folder), if any.
+    /// Only folder-based objects can have an image. The folder is only looked through the first
+    /// time, until `forget_image` is called
+    pub fn find_image(&self) -> Option<PathBuf> {
+        if !self.is_folder() {
+            return None;
+        }
+
+        let image_name = self.get_base().file.image_name.get_or_init(|| {
+            std::fs::read_dir(self.get_path())
+                .ok()?
+                .filter_map(|entry| entry.ok())
+                .find(|entry| is_image_file(&entry.path()))
+                .map(|entry| entry.file_name())
+        });
+
+        image_name.as_ref().map(|name| self.get_path().join(name))
+    }
+
+    /// Look for the attached image on disk again the next time it's needed (e.g., after the
+    /// watcher sees it change)
+    pub fn forget_image(&mut self) {
+        self.get_base_mut().file.image_name = OnceCell::new();
+    }
+
+    /// Moves an object that's still a single file (`000-name.toml`) into a folder of its own (as
+    /// `000-name/metadata.toml`), so that it can hold other objects. Only types that can nest
+    /// (e.g., characters) can be converted
+    pub fn convert_to_folder(&mut self) -> Result<(), CheeseError> {
+        if self.is_folder() {
+            return Ok(());
+        }
+
+        if !self.get_type().can_nest() {
+            return Err(cheese_error!("{self} cannot be turned into a folder"));
+        }
+
+        let old_file = self.get_path();
+        let old_basename = self.get_base().file.basename.clone();
+
+        self.get_base_mut().file.converted_to_folder = true;
+        self.get_base_mut().file.basename = self.calculate_filename();
+
+        let folder_path = self.get_path();
+        log::info!("Converting {old_file:?} into folder {folder_path:?}");
+
+        let result = std::fs::create_dir(&folder_path).and_then(|()| {
+            if old_file.exists() {
+                std::fs::rename(&old_file, self.get_file())
+            } else {
+                Ok(())
+            }
+        });
+
+        if let Err(err) = result {
+            let _ = std::fs::remove_dir(&folder_path);
+            self.get_base_mut().file.converted_to_folder = false;
+            self.get_base_mut().file.basename = old_basename;
+            return Err(cheese_error!(
+                "failed to convert {old_file:?} into a folder: {err}"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Copies `source` into this object's folder as its attached image, replacing any image that
+    /// was already there. Returns the path of the newly copied image
+    pub fn set_image(&mut self, source: &Path) -> Result<PathBuf, CheeseError> {
+        // Characters (and similar types) need a folder of their own to keep the image in
+        if !self.is_folder() && self.get_type().can_nest() {
+            self.convert_to_folder()?;
+        }
+
+        if !self.is_folder() {
+            return Err(cheese_error!(
+                "only folder objects can have an image: {self}"
+            ));
+        }
+
+        let extension = source
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .filter(|extension| IMAGE_EXTENSIONS.contains(&extension.as_str()))
+            .ok_or_else(|| cheese_error!("{source:?} does not look like a supported image"))?;
+
+        let dest = self
+            .get_path()
+            .join(format!("{IMAGE_FILE_STEM}.{extension}"));
+        let existing_image = self.find_image();
+        self.forget_image();
+        replace_with_copy(source, &dest, existing_image.as_deref())?;
+
+        Ok(dest)
+    }
+
     /// Determine if the file should be loaded
     fn should_load(&mut self, file_to_read: &Path) -> Result<bool, CheeseError> {
         let current_modtime = match std::fs::metadata(file_to_read) {
@@ -100,9 +211,21 @@ impl dyn FileObject {
 
         let (metadata_str, file_body) = read_file_contents(&file_to_read)?;
 
+        // Something else changed the file while we have changes that haven't been saved yet.
+        // Either way of picking one would silently throw the other away, so hold on to what's on
+        // disk until someone decides
+        if self.get_base().file.edited {
+            log::warn!("{self} was changed on disk while it had unsaved changes");
+            self.get_base_mut().file.disk_conflict = Some(DiskConflict {
+                metadata: metadata_str,
+                body: file_body,
+            });
+            return Ok(());
+        }
+
         let new_toml_header = metadata_str
             .parse::<DocumentMut>()
-            .expect("invalid file metadata header");
+            .map_err(|err| cheese_error!("Error parsing {file_to_read:?}: {err}"))?;
 
         let base_file_object = self.get_base_mut();
 
@@ -111,16 +234,78 @@ impl dyn FileObject {
             .load_base_metadata(new_toml_header.as_table(), &mut base_file_object.file)?;
 
         base_file_object.toml_header = new_toml_header;
+        base_file_object.file.conflict_markers = file_body
+            .as_deref()
+            .is_some_and(|body| body.lines().any(starts_conflict));
+        base_file_object.file.word_count = file_body
+            .as_deref()
+            .map_or(0, |body| body.split_whitespace().count());
 
         self.load_metadata()?;
 
-        if let Some(file_body) = file_body {
+        // If the body was never loaded, it'll get the new version from disk whenever it's needed
+        if let Some(file_body) = file_body
+            && self.is_body_loaded()
+        {
             self.load_body(file_body);
         }
 
         Ok(())
     }
 
+    pub fn has_disk_conflict(&self) -> bool {
+        self.get_base().file.disk_conflict.is_some()
+    }
+
+    /// Convert any CRLF line endings in this object to LF, marking it as modified so that the file
+    /// gets rewritten on the next save
+    pub fn normalize_line_endings(&mut self) -> Result<(), CheeseError> {
+        let header = self.get_base().toml_header.to_string();
+        if header.contains('\r') {
+            self.get_base_mut().toml_header =
+                header
+                    .replace("\r\n", "\n")
+                    .parse::<DocumentMut>()
+                    .map_err(|err| cheese_error!("Error parsing header of {self}: {err}"))?;
+        }
+
+        self.as_editor_mut()
+            .for_each_textbox_mut(&mut |text, _name| {
+                if text.contains('\r') {
+                    **text = text.replace("\r\n", "\n");
+                }
+            });
+
+        self.get_base_mut().file.modified = true;
+
+        Ok(())
+    }
+
+    /// Resolve a conflict with the file on disk by keeping the version in memory, which will
+    /// overwrite the file on the next save
+    pub fn keep_local_version(&mut self) {
+        self.get_base_mut().file.disk_conflict = None;
+    }
+
+    /// Resolve a conflict with the file on disk by throwing away the unsaved changes and loading
+    /// the file again
+    pub fn take_disk_version(&mut self) -> Result<(), CheeseError> {
+        let file = &mut self.get_base_mut().file;
+        file.disk_conflict = None;
+        file.modified = false;
+        file.edited = false;
+        // make sure the file actually gets read
+        file.modtime = None;
+
+        self.reload_file()?;
+
+        self.get_base_mut().file.modtime = std::fs::metadata(self.get_file())
+            .and_then(|metadata| metadata.modified())
+            .ok();
+
+        Ok(())
+    }
+
     pub fn children<'a>(
         &self,
         objects: &'a FileObjectStore,
@@ -141,8 +326,6 @@ impl dyn FileObject {
         &mut self,
         file_type: FileType,
     ) -> Result<Box<dyn FileObject>, CheeseError> {
-        assert!(self.is_folder());
-
         // We know it's at the end, and thus we know that there aren't any children
         self.create_child(file_type, DirPosition::Last, &HashMap::new())
     }
@@ -154,6 +337,11 @@ impl dyn FileObject {
         position: DirPosition<FileID>,
         objects: &FileObjectStore,
     ) -> Result<Box<dyn FileObject>, CheeseError> {
+        // Characters (and similar types) only become folders once something is put inside of them
+        if !self.is_folder() {
+            self.convert_to_folder()?;
+        }
+
         let new_index = match position {
             DirPosition::After(child) => {
                 self.get_base()
@@ -220,6 +408,33 @@ impl dyn FileObject {
         Ok(())
     }
 
+    /// How many objects are inside of this one (counting the children of children)
+    pub fn descendant_count(&self, objects: &FileObjectStore) -> usize {
+        self.children(objects)
+            .map(|child| 1 + child.borrow().descendant_count(objects))
+            .sum()
+    }
+
+    /// Add up the words in the body of this object and everything inside of it, along with their
+    /// word targets, returning `(current, target)`. Every body is counted, even if it doesn't have
+    /// its own target. An object's own target (e.g., a chapter's) replaces the targets of
+    /// everything inside of it instead of adding to them. Uses the cached `word_count`, so bodies
+    /// that haven't been loaded yet stay that way
+    pub fn aggregate_word_target(&self, objects: &FileObjectStore) -> (u64, u64) {
+        let mut current = self.word_count() as u64;
+        let mut target = 0;
+
+        for child_id in &self.get_base().children {
+            if let Some(child) = objects.get(child_id) {
+                let (child_current, child_target) = child.borrow().aggregate_word_target(objects);
+                current += child_current;
+                target += child_target;
+            }
+        }
+
+        (current, self.target_word_count().unwrap_or(target))
+    }
+
     pub fn get_title(&self) -> String {
         if self.get_base().metadata.name.is_empty() {
             self.empty_string_name().to_string()
@@ -234,11 +449,43 @@ impl dyn FileObject {
         for _ in 0..depth {
             export_string.push('#');
         }
+
         export_string.push(' ');
         export_string.push_str(&self.get_title());
         export_string.push_str("\n\n");
     }
 
+    /// Write the title for an export, in the given style (a heading at `heading_level` or bold)
+    pub fn write_export_title(
+        &self,
+        style: TitleStyle,
+        heading_level: u64,
+        export_string: &mut String,
+    ) {
+        match style {
+            TitleStyle::Heading => self.write_title(heading_level, export_string),
+            TitleStyle::Bold => {
+                export_string.push_str("**");
+                export_string.push_str(&self.get_title());
+                export_string.push_str("**\n\n");
+            }
+            TitleStyle::None => {}
+        }
+    }
+
+    /// Write an html comment identifying this scene (`<!-- scene: id=... name="..." index=0 -->`),
+    /// which doesn't show up in the rendered export but lets other tools find where it came from
+    pub fn write_export_comment(&self, export_string: &mut String) {
+        // `--` can't appear inside of an html comment
+        let name = self.get_title().replace("--", "- -");
+
+        export_string.push_str(&format!("<!-- scene: id={} name={name:?}", self.id()));
+        if let Some(index) = self.get_base().index {
+            export_string.push_str(&format!(" index={index}"));
+        }
+        export_string.push_str(" -->\n\n");
+    }
+
     /// For ease of calling, `objects` can contain arbitrary objects, only values contained
     /// in `children` will actually be sorted.
     pub fn fix_indexing(&mut self, objects: &FileObjectStore) {
@@ -247,6 +494,7 @@ impl dyn FileObject {
             self,
             self.get_base().children
         );
+        self.update_child_index_widths(objects);
         for (count, child) in self.children(objects).enumerate() {
             let set_index_result = child.borrow_mut().set_index(count, objects);
             if let Err(err) = set_index_result {
@@ -261,6 +509,23 @@ impl dyn FileObject {
         }
     }
 
+    /// Make sure every child pads its index to the width needed for the number of children in this
+    /// folder. Children that change width get marked as modified so they'll be renamed when saved
+    pub fn update_child_index_widths(&mut self, objects: &FileObjectStore) {
+        let index_width = index_width_for(
+            self.get_base().children.len(),
+            self.get_base().file.child_index_width,
+        );
+
+        for child in self.children(objects) {
+            let mut child = child.borrow_mut();
+            if child.get_base().file.index_width != index_width {
+                child.get_base_mut().file.index_width = index_width;
+                child.get_base_mut().file.modified = true;
+            }
+        }
+    }
+
     /// Reorder the children based on their index (self reported in basename), followed by a call to
     /// fix_indexing. If `recursive` is true, do this for all descendents as well
     pub fn rescan_indexing(&mut self, objects: &FileObjectStore, recursive: bool) {
@@ -269,21 +534,7 @@ impl dyn FileObject {
             return;
         }
 
-        self.get_base_mut().children.sort_by_key(|child_id| {
-            match get_index_from_name(
-                &objects
-                    .get(child_id)
-                    .unwrap()
-                    .borrow()
-                    .get_base()
-                    .file
-                    .basename
-                    .to_string_lossy(),
-            ) {
-                Some(index) => index,
-                None => usize::MAX,
-            }
-        });
+        self.sort_children_by_filename_index(objects);
 
         self.fix_indexing(objects);
 
@@ -294,6 +545,41 @@ impl dyn FileObject {
         }
     }
 
+    /// Reorder the children like `rescan_indexing` (without recursing), but only fix their indexes
+    /// in memory, leaving the filenames on disk alone (e.g., when the project is only being read)
+    pub fn rescan_indexing_in_memory(&mut self, objects: &FileObjectStore) {
+        if !self.is_folder() {
+            return;
+        }
+
+        self.sort_children_by_filename_index(objects);
+
+        for (count, child) in self.children(objects).enumerate() {
+            child.borrow_mut().get_base_mut().index = Some(count);
+        }
+    }
+
+    fn sort_children_by_filename_index(&mut self, objects: &FileObjectStore) {
+        // Indexes are compared as numbers (so `2-` comes before `10-`), with the filename as a
+        // tiebreaker so that duplicate or missing indexes still load in a consistent order
+        self.get_base_mut().children.sort_by_cached_key(|child_id| {
+            let basename = objects
+                .get(child_id)
+                .unwrap()
+                .borrow()
+                .get_base()
+                .file
+                .basename
+                .to_string_lossy()
+                .into_owned();
+
+            match get_index_from_name(&basename) {
+                Some(index) => (index, basename),
+                None => (usize::MAX, basename),
+            }
+        });
+    }
+
     fn move_on_disk(
         &mut self,
         old_path: PathBuf,
@@ -392,6 +678,9 @@ impl dyn FileObject {
     }
 
     pub fn save(&mut self, objects: &FileObjectStore) -> Result<(), CheeseError> {
+        // Children might need to be renamed if there are enough of them to need another digit
+        self.update_child_index_widths(objects);
+
         // First, try to save children, intentionally trying all of them
         let mut errors = vec![];
         for child in self.children(objects) {
@@ -400,7 +689,19 @@ impl dyn FileObject {
             }
         }
 
+        // Saving now would overwrite the changes on disk, so wait until the conflict is resolved
+        if self.has_disk_conflict() {
+            log::debug!("not saving {self} until its conflict with the disk is resolved");
+            return match errors.pop() {
+                Some(err) => Err(err),
+                None => Ok(()),
+            };
+        }
+
         if !self.get_base().file.modified {
+            #[cfg(feature = "metrics")]
+            crate::util::write_counters::record_write_avoided();
+
             // If we had *any* errors, return one of them
             return match errors.pop() {
                 Some(err) => Err(err),
@@ -408,6 +709,11 @@ impl dyn FileObject {
             };
         }
 
+        // If the body can't be read, saving would replace it with an empty one
+        if let Err(err) = self.load_unloaded_body() {
+            return Err(err);
+        }
+
         // For everything that isn't a top level folder: check if the filename on disk matches
         // the name, updating the file on disk if necessary
         if self.get_base().index.is_some() {
@@ -434,6 +740,9 @@ impl dyn FileObject {
 
         write_with_temp_file(self.get_file(), final_str)?;
 
+        #[cfg(feature = "metrics")]
+        crate::util::write_counters::record_write();
+
         let new_modtime = std::fs::metadata(self.get_file())
             .expect("attempted to load file that does not exist")
             .modified()
@@ -442,6 +751,7 @@ impl dyn FileObject {
         // Update modtime based on what we just wrote
         self.get_base_mut().file.modtime = Some(new_modtime);
         self.get_base_mut().file.modified = false;
+        self.get_base_mut().file.edited = false;
 
         // If we had *any* errors, return one of them
         match errors.pop() {
@@ -503,6 +813,10 @@ impl dyn FileObject {
         std::fs::remove_file(self.get_file())?;
 
         if self.is_folder() {
+            // an attached image would otherwise keep the folder from being removed
+            if let Some(image) = self.find_image() {
+                std::fs::remove_file(image)?;
+            }
             std::fs::remove_dir(self.get_path())?;
         }
 
@@ -1,9 +1,11 @@
-use std::io::Write;
+use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
-use tempfile::Builder;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tempfile::{Builder, NamedTempFile};
 use toml_edit::TableLike;
 
 use crate::cheese_error;
+use crate::components::markdown::starts_conflict;
 use crate::util::CheeseError;
 
 /// Value that splits the header of any file that contains non-metadata content
@@ -11,10 +13,24 @@ pub const HEADER_SPLIT: &str = "++++++++";
 
 /// the maximum length of a name before we start trying to truncate it
 pub const FILENAME_MAX_LENGTH: usize = 30;
+/// The number of digits that indexes are padded to in filenames, unless a folder has too many
+/// children for them to fit
+pub const DEFAULT_INDEX_WIDTH: usize = 3;
 
 /// filename of the object within a folder containing its metadata (without extension)
 pub const FOLDER_METADATA_FILE_NAME: &str = "metadata.toml";
 
+/// Extensions that objects with a body can use instead of the default for their type (e.g., `.md`
+/// for scenes). Files with one of these are only loaded if their header says what kind of object
+/// they are, so that unrelated text files in the project don't get picked up
+pub const ALTERNATE_BODY_EXTENSIONS: &[&str] = &["markdown", "txt"];
+
+/// filename (without extension) of the reference image that can be attached to a folder object
+pub const IMAGE_FILE_STEM: &str = "image";
+
+/// extensions (lowercase) that we accept for attached images
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
 /// Generic file utilities
 use regex::Regex;
 
@@ -64,6 +80,24 @@ pub fn process_name_for_filename(name: &str) -> String {
         .into_owned()
 }
 
+/// Check that a name still has something left once it's been made filename safe. Names that are
+/// empty (or made entirely of characters that get replaced) would produce a file called `-` or `_`
+pub fn produces_valid_filename(name: &str) -> bool {
+    !process_name_for_filename(name.trim())
+        .trim_matches(['-', '_'])
+        .is_empty()
+}
+
+#[test]
+fn test_produces_valid_filename() {
+    assert!(produces_valid_filename("Chapter 1"));
+    assert!(produces_valid_filename("  a  "));
+    assert!(!produces_valid_filename(""));
+    assert!(!produces_valid_filename("   "));
+    assert!(!produces_valid_filename("../?*"));
+    assert!(!produces_valid_filename("'_ -"));
+}
+
 #[test]
 fn test_process_name_for_filename() {
     assert_eq!(process_name_for_filename(r"hello world"), "hello_world");
@@ -72,8 +106,43 @@ fn test_process_name_for_filename() {
 }
 
 /// Just adds an index to a name, no real logic
-pub fn add_index_to_name(name: &str, index: usize) -> String {
-    format!("{index:03}-{name}")
+pub fn add_index_to_name(name: &str, index: usize, index_width: usize) -> String {
+    format!("{index:0index_width$}-{name}")
+}
+
+/// Gets the number of digits used for the index in a name, if it has one
+pub fn get_index_width_from_name(name: &str) -> Option<usize> {
+    name.split_once('-')
+        .filter(|(prefix, _suffix)| prefix.parse::<usize>().is_ok())
+        .map(|(prefix, _suffix)| prefix.len())
+}
+
+/// How many digits the indexes in a folder with `child_count` children need to be padded to so
+/// that they all sort correctly as strings (and are at least `min_width`)
+pub fn index_width_for(child_count: usize, min_width: usize) -> usize {
+    let digits = match child_count.saturating_sub(1).checked_ilog10() {
+        Some(log) => log as usize + 1,
+        None => 1,
+    };
+
+    digits.max(min_width)
+}
+
+#[test]
+fn test_index_width() {
+    assert_eq!(add_index_to_name("name", 7, 3), "007-name");
+    assert_eq!(add_index_to_name("name", 1000, 3), "1000-name");
+    assert_eq!(add_index_to_name("name", 7, 4), "0007-name");
+
+    assert_eq!(get_index_width_from_name("0007-name"), Some(4));
+    assert_eq!(get_index_width_from_name("12-name"), Some(2));
+    assert_eq!(get_index_width_from_name("name-with-dashes"), None);
+
+    assert_eq!(index_width_for(0, 3), 3);
+    assert_eq!(index_width_for(1000, 3), 3);
+    assert_eq!(index_width_for(1001, 3), 4);
+    assert_eq!(index_width_for(5, 1), 1);
+    assert_eq!(index_width_for(11, 1), 2);
 }
 
 /// Gets the file index from a string if it exists
@@ -85,6 +154,44 @@ pub fn get_index_from_name(name: &str) -> Option<usize> {
     }
 }
 
+/// Checks if a path looks like an attached image (`image.<ext>` with a known extension)
+pub fn is_image_file(path: &Path) -> bool {
+    path.file_stem().is_some_and(|stem| stem == IMAGE_FILE_STEM)
+        && path.extension().is_some_and(|extension| {
+            IMAGE_EXTENSIONS.contains(&extension.to_string_lossy().to_lowercase().as_str())
+        })
+}
+
+#[test]
+fn test_is_image_file() {
+    assert!(is_image_file(Path::new("/foo/000-place/image.png")));
+    assert!(is_image_file(Path::new("/foo/000-place/image.JPG")));
+    assert!(!is_image_file(Path::new("/foo/000-place/metadata.toml")));
+    assert!(!is_image_file(Path::new("/foo/000-place/picture.png")));
+    assert!(!is_image_file(Path::new("/foo/000-place/image.md")));
+}
+
+/// Copies `source` to `dest` (e.g., `image.png`) in place of `existing` (e.g., `image.jpg`). The
+/// copy is made under a temporary name first, so nothing is lost if it fails, and `existing` is
+/// only removed once the copy is in place. Copying the current image onto itself works too
+pub fn replace_with_copy(
+    source: &Path,
+    dest: &Path,
+    existing: Option<&Path>,
+) -> std::io::Result<()> {
+    let mut file = temp_file_for(dest)?;
+    std::io::copy(&mut std::fs::File::open(source)?, &mut file)?;
+    file.persist(dest)?;
+
+    if let Some(existing) = existing
+        && existing != dest
+    {
+        std::fs::remove_file(existing)?;
+    }
+
+    Ok(())
+}
+
 pub fn create_dir_if_missing(dest_path: &Path) -> std::io::Result<&Path> {
     let dirname = dest_path.parent().expect("Must pass a path with a parent");
 
@@ -95,17 +202,28 @@ pub fn create_dir_if_missing(dest_path: &Path) -> std::io::Result<&Path> {
     Ok(dest_path)
 }
 
-/// Atomically write a file
+/// Create the temporary file that will replace `dest_path`. It's named after the destination (see
+/// `temp_file_target`), so that one left behind by a crash can be matched up with its file
+fn temp_file_for(dest_path: &Path) -> std::io::Result<NamedTempFile> {
+    let dirname = dest_path.parent().expect("Must pass a path with a parent");
+
+    let prefix = match dest_path.file_name() {
+        Some(filename) => format!(".{}.", filename.to_string_lossy()),
+        None => ".".to_string(),
+    };
+
+    Builder::new()
+        .prefix(&prefix)
+        .suffix(TEMP_FILE_SUFFIX)
+        .tempfile_in(dirname)
+}
+
+/// Atomically write a file, see `temp_file_for`
 pub fn write_with_temp_file<P: AsRef<Path>>(
     dest_path: P,
     contents: impl Into<String>,
 ) -> std::io::Result<()> {
-    let dirname = dest_path
-        .as_ref()
-        .parent()
-        .expect("Must pass a path with a parent");
-
-    let mut file = Builder::new().suffix(".tmp").tempfile_in(dirname)?;
+    let mut file = temp_file_for(dest_path.as_ref())?;
 
     file.write_all(contents.into().as_bytes())?;
 
@@ -114,6 +232,18 @@ pub fn write_with_temp_file<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Keep a copy of a config file that couldn't be parsed (as `<name>.invalid`), since we fall back
+/// to defaults and the next save would otherwise overwrite whatever the user had in it
+pub fn preserve_invalid_file(path: &Path) {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(".invalid");
+
+    match std::fs::copy(path, &backup_path) {
+        Ok(_) => log::warn!("Saved a copy of invalid file {path:?} to {backup_path:?}"),
+        Err(err) => log::error!("Failed to save a copy of invalid file {path:?}: {err}"),
+    }
+}
+
 #[test]
 fn test_write_with_temp_file() -> std::io::Result<()> {
     let base_dir = tempfile::TempDir::new()?;
@@ -137,6 +267,46 @@ fn test_write_with_temp_file() -> std::io::Result<()> {
     Ok(())
 }
 
+/// Every temporary file written by `write_with_temp_file` ends with this
+pub const TEMP_FILE_SUFFIX: &str = ".tmp";
+
+/// The name of the file that a temporary file from `write_with_temp_file` was going to replace,
+/// if it's one of ours. They're named `.<filename>.<random>.tmp`
+pub fn temp_file_target(temp_filename: &str) -> Option<&str> {
+    let (target, _random) = temp_filename
+        .strip_prefix('.')?
+        .strip_suffix(TEMP_FILE_SUFFIX)?
+        .rsplit_once('.')?;
+
+    (!target.is_empty()).then_some(target)
+}
+
+#[test]
+fn test_temp_file_target() -> std::io::Result<()> {
+    assert_eq!(
+        temp_file_target(".000-scene.md.a1B2c3.tmp"),
+        Some("000-scene.md")
+    );
+    assert_eq!(
+        temp_file_target(".metadata.toml.a1B2c3.tmp"),
+        Some("metadata.toml")
+    );
+    // temporary files from before they were named after their targets
+    assert_eq!(temp_file_target(".tmpa1B2c3.tmp"), None);
+    assert_eq!(temp_file_target("000-scene.md"), None);
+
+    // every temporary file that gets written can be traced back
+    let base_dir = tempfile::TempDir::new()?;
+    let temp_file = Builder::new()
+        .prefix(".001-chapter.md.")
+        .suffix(TEMP_FILE_SUFFIX)
+        .tempfile_in(base_dir.path())?;
+    let temp_filename = temp_file.path().file_name().unwrap().to_string_lossy();
+    assert_eq!(temp_file_target(&temp_filename), Some("001-chapter.md"));
+
+    Ok(())
+}
+
 pub fn metadata_extract_u64(
     table: &dyn TableLike,
     field_name: &str,
@@ -185,6 +355,167 @@ pub fn metadata_extract_bool(
     })
 }
 
+/// Convert a time into whole seconds since the unix epoch, which is how times are stored in
+/// metadata. Times before the epoch are clamped to it
+pub fn system_time_to_unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs().min(i64::MAX as u64) as i64)
+        .unwrap_or(0)
+}
+
+/// The reverse of `system_time_to_unix_seconds`
+pub fn unix_seconds_to_system_time(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+#[test]
+fn test_unix_seconds_round_trip() {
+    let time = unix_seconds_to_system_time(1_700_000_000);
+    assert_eq!(system_time_to_unix_seconds(time), 1_700_000_000);
+    assert_eq!(system_time_to_unix_seconds(UNIX_EPOCH), 0);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    /// only in the old text
+    Removed(&'a str),
+    /// only in the new text
+    Added(&'a str),
+}
+
+/// A line by line diff from `old` to `new`, based on their longest common subsequence of lines
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+
+    diff.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    diff.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+
+    diff
+}
+
+#[test]
+fn test_diff_lines() {
+    assert_eq!(
+        diff_lines("a\nb\nc", "a\nx\nc\nd"),
+        vec![
+            DiffLine::Same("a"),
+            DiffLine::Removed("b"),
+            DiffLine::Added("x"),
+            DiffLine::Same("c"),
+            DiffLine::Added("d"),
+        ]
+    );
+    assert_eq!(diff_lines("", "new"), vec![DiffLine::Added("new")]);
+    assert!(diff_lines("", "").is_empty());
+}
+
+/// Describe how long ago something happened in a short, human readable way (e.g., "3h ago")
+pub fn format_relative_time(elapsed: std::time::Duration) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const YEAR: u64 = 365 * DAY;
+
+    let seconds = elapsed.as_secs();
+
+    if seconds < MINUTE {
+        "just now".to_string()
+    } else if seconds < HOUR {
+        format!("{}m ago", seconds / MINUTE)
+    } else if seconds < DAY {
+        format!("{}h ago", seconds / HOUR)
+    } else if seconds < YEAR {
+        format!("{}d ago", seconds / DAY)
+    } else {
+        format!("{}y ago", seconds / YEAR)
+    }
+}
+
+#[test]
+fn test_format_relative_time() {
+    use std::time::Duration;
+
+    assert_eq!(format_relative_time(Duration::from_secs(5)), "just now");
+    assert_eq!(format_relative_time(Duration::from_secs(125)), "2m ago");
+    assert_eq!(
+        format_relative_time(Duration::from_secs(3 * 3600 + 59)),
+        "3h ago"
+    );
+    assert_eq!(
+        format_relative_time(Duration::from_secs(86400 * 12)),
+        "12d ago"
+    );
+    assert_eq!(
+        format_relative_time(Duration::from_secs(86400 * 800)),
+        "2y ago"
+    );
+}
+
+/// Parse a color in `#rrggbb` form (the leading `#` is optional)
+pub fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut rgb = [0u8; 3];
+    for (index, channel) in rgb.iter_mut().enumerate() {
+        *channel = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok()?;
+    }
+
+    Some(rgb)
+}
+
+pub fn format_hex_color(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+#[test]
+fn test_hex_color() {
+    assert_eq!(parse_hex_color("#ff8000"), Some([255, 128, 0]));
+    assert_eq!(parse_hex_color("0A0b0C"), Some([10, 11, 12]));
+    assert_eq!(parse_hex_color("#fff"), None);
+    assert_eq!(parse_hex_color("#gg0000"), None);
+    assert_eq!(parse_hex_color("#ff80é"), None);
+
+    assert_eq!(format_hex_color([255, 128, 0]), "#ff8000");
+    assert_eq!(
+        parse_hex_color(&format_hex_color([1, 2, 3])),
+        Some([1, 2, 3])
+    );
+}
+
 pub fn write_outline_property(property_name: &str, property: &str, export_string: &mut String) {
     if property.is_empty() {
         return;
@@ -210,7 +541,83 @@ pub fn write_outline_property(property_name: &str, property: &str, export_string
     }
 }
 
-/// Reads the contents of a file from disk
+/// The split only counts when it's on a line by itself (which is how it's always written), so
+/// that a run of `+` in some text doesn't split the file
+fn is_header_split(line: &str) -> bool {
+    line.trim_end() == HEADER_SPLIT
+}
+
+/// Split a file into its header and body. Everything after the first split is the body, even if
+/// it contains another one
+fn split_header(file_data: &str) -> Option<(&str, &str)> {
+    let mut line_start = 0;
+
+    for line in file_data.split_inclusive('\n') {
+        if is_header_split(line) {
+            // The body starts with the line ending of the split (without any trailing spaces)
+            let split_end = line_start + line.trim_end_matches(['\r', '\n']).len();
+            return Some((&file_data[..line_start], &file_data[split_end..]));
+        }
+        line_start += line.len();
+    }
+
+    None
+}
+
+#[test]
+fn test_split_header() {
+    assert_eq!(
+        split_header("id = \"1\"\n++++++++\n\nbody\n++++++++\nmore body"),
+        Some(("id = \"1\"\n", "\n\nbody\n++++++++\nmore body"))
+    );
+    assert_eq!(split_header("++++++++\nbody"), Some(("", "\nbody")));
+    assert_eq!(
+        split_header("summary = \"C++++++++\"\n++++++++\nbody"),
+        Some(("summary = \"C++++++++\"\n", "\nbody"))
+    );
+    assert_eq!(split_header("a body with ++++++++ in it\n"), None);
+    assert_eq!(
+        split_header("id = \"1\"\n++++++++ not a split\n++++++++  \nbody"),
+        Some(("id = \"1\"\n++++++++ not a split\n", "\nbody"))
+    );
+    assert_eq!(split_header("++++++++++++\nbody"), None);
+}
+
+fn strip_line_ending_prefix(text: &str) -> Option<&str> {
+    text.strip_prefix("\r\n")
+        .or_else(|| text.strip_prefix('\n'))
+}
+
+/// Remove the blank line that gets written between the split and the body, leaving any other
+/// blank lines at the start of the body alone
+fn strip_body_gap(body: &str) -> &str {
+    let body = strip_line_ending_prefix(body).unwrap_or(body);
+    strip_line_ending_prefix(body).unwrap_or(body)
+}
+
+/// Bodies are always written with a newline at the end, which isn't part of the text itself.
+/// Nothing else is trimmed, so that saving a body without changing it writes the same bytes
+pub fn trim_final_newline(body: &str) -> &str {
+    body.strip_suffix("\r\n")
+        .or_else(|| body.strip_suffix('\n'))
+        .unwrap_or(body)
+}
+
+#[test]
+fn test_body_round_trip() {
+    for body in ["", "text", "\n\nindented\n    text  \n\n\n", "  \n"] {
+        // this is how bodies get written in `save`
+        let file_body = format!("\n\n{body}\n");
+        assert_eq!(trim_final_newline(strip_body_gap(&file_body)), body);
+    }
+
+    // files written by hand might not have the gap
+    assert_eq!(strip_body_gap("\ntext\n"), "text\n");
+    assert_eq!(strip_body_gap("text"), "text");
+}
+
+/// Reads the contents of a file from disk. The body is returned as it was written (other than
+/// the gap after the split), see `trim_final_newline`
 pub fn read_file_contents(file_to_read: &Path) -> Result<(String, Option<String>), CheeseError> {
     let extension = match file_to_read.extension() {
         Some(val) => val,
@@ -219,17 +626,172 @@ pub fn read_file_contents(file_to_read: &Path) -> Result<(String, Option<String>
 
     let file_data = std::fs::read_to_string(file_to_read)?;
 
-    let (metadata_str, file_content): (&str, Option<&str>) = if extension == "md" {
-        match file_data.split_once(HEADER_SPLIT) {
+    // Everything other than the metadata files for folders has a body
+    let (metadata_str, file_content): (&str, Option<&str>) = if extension != "toml" {
+        match split_header(&file_data) {
             None => ("", Some(&file_data)),
-            Some((start, end)) => (start, Some(end)),
+            Some((start, end)) => (start, Some(strip_body_gap(end))),
         }
     } else {
         (&file_data, None)
     };
 
-    Ok((
-        metadata_str.to_owned(),
-        file_content.map(|s| s.trim().to_owned()),
-    ))
+    Ok((metadata_str.to_owned(), file_content.map(|s| s.to_owned())))
+}
+
+/// Check if a file uses Windows (CRLF) line endings. Only the first line is read, since files
+/// usually get all of their line endings converted at once (e.g., by git), and bodies can be large
+pub fn has_crlf_line_endings(file_to_read: &Path) -> bool {
+    let Ok(file) = std::fs::File::open(file_to_read) else {
+        return false;
+    };
+
+    let mut first_line = Vec::new();
+    match BufReader::new(file).read_until(b'\n', &mut first_line) {
+        Ok(_) => first_line.ends_with(b"\r\n"),
+        Err(_) => false,
+    }
+}
+
+/// Reads only the metadata header of a file from disk, stopping at the header split so that large
+/// bodies don't need to be read. Matches the header returned by `read_file_contents`
+pub fn read_file_header(file_to_read: &Path) -> Result<String, CheeseError> {
+    let extension = match file_to_read.extension() {
+        Some(val) => val,
+        None => return Err(cheese_error!("value was not string")),
+    };
+
+    if extension == "toml" {
+        return Ok(std::fs::read_to_string(file_to_read)?);
+    }
+
+    let mut reader = BufReader::new(std::fs::File::open(file_to_read)?);
+    let mut header = String::new();
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            // no split, which means the entire file is the body
+            return Ok(String::new());
+        }
+
+        // same as `split_header`, the split has to be on a line by itself
+        if is_header_split(&line) {
+            return Ok(header);
+        }
+
+        header.push_str(&line);
+    }
+}
+
+/// What gets learned about a body while loading its file, so that the body itself doesn't have to
+/// be held onto until it's needed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BodyScan {
+    /// See `FileInfo::conflict_markers`
+    pub conflict_markers: bool,
+    /// See `FileInfo::word_count`
+    pub word_count: usize,
+}
+
+/// Reads through a file (without holding onto it) to check if it has git conflict markers in it
+/// and count the words in the body. Folder metadata files don't have a body, so they're never
+/// checked
+pub fn scan_file_body(file_to_read: &Path) -> BodyScan {
+    let mut scan = BodyScan::default();
+
+    if file_to_read
+        .extension()
+        .is_none_or(|extension| extension == "toml")
+    {
+        return scan;
+    }
+
+    let Ok(file) = std::fs::File::open(file_to_read) else {
+        return scan;
+    };
+
+    let mut found_split = false;
+
+    // Invalid UTF-8 gets reported when the body is read, until then it just isn't a conflict
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if starts_conflict(&line) {
+            scan.conflict_markers = true;
+        }
+
+        // Without a split, the entire file is the body, so words are counted from the start and
+        // then thrown out if they turn out to be the header
+        if !found_split && is_header_split(&line) {
+            found_split = true;
+            scan.word_count = 0;
+            continue;
+        }
+
+        scan.word_count += line.split_whitespace().count();
+    }
+
+    scan
+}
+
+#[test]
+fn test_read_file_header() -> Result<(), CheeseError> {
+    let base_dir = tempfile::TempDir::new()?;
+
+    let contents = [
+        "name = \"scene\"\nid = \"1\"\n++++++++\n\nbody text\n",
+        "name = \"scene\"\n++++++++ trailing\nbody ++++++++ text",
+        "notes = '''\n++++++++ not a split\n'''\n++++++++\nbody\n",
+        "summary = \"a ++++++++ b\"\n++++++++\nbody\n",
+        "just a body\nwith no header\n",
+        "",
+    ];
+
+    for (index, contents) in contents.iter().enumerate() {
+        let path = base_dir.path().join(format!("{index}.md"));
+        std::fs::write(&path, contents)?;
+
+        assert_eq!(read_file_header(&path)?, read_file_contents(&path)?.0);
+    }
+
+    // a line that only starts with the split is still part of the header
+    assert_eq!(
+        read_file_header(&base_dir.path().join("2.md"))?,
+        "notes = '''\n++++++++ not a split\n'''\n"
+    );
+
+    let toml_path = base_dir.path().join("metadata.toml");
+    std::fs::write(&toml_path, "name = \"folder\"\n")?;
+    assert_eq!(read_file_header(&toml_path)?, "name = \"folder\"\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_scan_file_body() -> Result<(), CheeseError> {
+    let base_dir = tempfile::TempDir::new()?;
+
+    let scene_path = base_dir.path().join("scene.md");
+    std::fs::write(
+        &scene_path,
+        "name = \"a long scene name\"\n++++++++\n\nthree words here\n<<<<<<< HEAD\nand two\n",
+    )?;
+    assert_eq!(
+        scan_file_body(&scene_path),
+        BodyScan {
+            conflict_markers: true,
+            word_count: 7,
+        }
+    );
+
+    // without a split, the whole file is the body
+    let body_path = base_dir.path().join("body.md");
+    std::fs::write(&body_path, "just a body\n")?;
+    assert_eq!(scan_file_body(&body_path).word_count, 3);
+
+    let toml_path = base_dir.path().join("metadata.toml");
+    std::fs::write(&toml_path, "name = \"folder\"\n")?;
+    assert_eq!(scan_file_body(&toml_path), BodyScan::default());
+
+    Ok(())
 }
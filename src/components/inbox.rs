@@ -0,0 +1,86 @@
+use crate::cheese_error;
+use crate::components::file_objects::FileID;
+use crate::components::project::Project;
+use crate::util::CheeseError;
+
+use egui_ltreeview::DirPosition;
+
+/// The name of the scene that quick capture notes are added to
+pub const INBOX_NAME: &str = "Inbox";
+
+impl Project {
+    /// Find the inbox scene at the top of the text folder, if there is one
+    pub fn inbox_id(&self) -> Option<FileID> {
+        self.get_text_folder()
+            .borrow()
+            .get_base()
+            .children
+            .iter()
+            .find(|child_id| {
+                self.objects.get(*child_id).is_some_and(|child| {
+                    let child = child.borrow();
+                    child.has_body()
+                        && !child.is_folder()
+                        && child.get_base().metadata.name.trim() == INBOX_NAME
+                })
+            })
+            .cloned()
+    }
+
+    /// Add a note to the end of the inbox scene (as its own paragraph), creating the inbox at the
+    /// end of the text folder if it doesn't exist yet. Returns the id of the inbox
+    pub fn append_to_inbox(&mut self, note: &str) -> Result<FileID, CheeseError> {
+        let note = note.trim();
+        if note.is_empty() {
+            return Err(cheese_error!("Nothing to add to the inbox"));
+        }
+
+        let inbox_id = match self.inbox_id() {
+            Some(inbox_id) => inbox_id,
+            None => self.create_inbox()?,
+        };
+
+        let mut inbox = self
+            .objects
+            .get(&inbox_id)
+            .ok_or_else(|| cheese_error!("Inbox scene {inbox_id} disappeared"))?
+            .borrow_mut();
+
+        inbox
+            .as_editor_mut()
+            .for_each_textbox_mut(&mut |text, name| {
+                if name == "text" {
+                    let body_end = text.trim_end().len();
+                    text.truncate(body_end);
+                    if !text.is_empty() {
+                        text.push_str("\n\n");
+                    }
+                    text.push_str(note);
+                }
+            });
+        inbox.get_base_mut().file.mark_edited();
+
+        Ok(inbox_id)
+    }
+
+    fn create_inbox(&mut self) -> Result<FileID, CheeseError> {
+        let scene_type = *self
+            .schema
+            .get_all_file_types()
+            .iter()
+            .find(|file_type| file_type.has_body() && !file_type.is_folder())
+            .ok_or_else(|| cheese_error!("This project doesn't have a type for scenes"))?;
+
+        let mut inbox = self.get_text_folder().borrow_mut().create_child(
+            scene_type,
+            DirPosition::Last,
+            &self.objects,
+        )?;
+        inbox.get_base_mut().metadata.name = INBOX_NAME.to_string();
+        inbox.get_base_mut().file.mark_edited();
+
+        let inbox_id = inbox.id().clone();
+        self.add_object(inbox);
+        Ok(inbox_id)
+    }
+}
@@ -0,0 +1,114 @@
+use crate::components::project::Project;
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// An external url found somewhere in the project. Everything is owned (instead of using FileIDs)
+/// so that these can be sent to another thread to be checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundLink {
+    pub object_id: String,
+    pub object_title: String,
+    pub url: String,
+}
+
+/// The result of trying to reach a single link
+#[cfg(feature = "link-check")]
+#[derive(Debug, Clone)]
+pub struct LinkCheckResult {
+    pub url: String,
+    /// `Err` contains a description of why the link couldn't be reached
+    pub status: Result<(), String>,
+}
+
+/// Finds all `http://` and `https://` urls in a piece of text, in the order they appear
+pub fn extract_urls(text: &str) -> Vec<&str> {
+    static URL_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"https?://[^\s<>()\[\]{}"'`]+"#).unwrap());
+
+    URL_REGEX
+        .find_iter(text)
+        // punctuation at the end of a url is almost always the end of the sentence instead
+        .map(|url_match| {
+            url_match
+                .as_str()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?'])
+        })
+        .filter(|url| !url.ends_with("://"))
+        .collect()
+}
+
+#[test]
+fn test_extract_urls() {
+    let body = "See https://example.com/maps, and also (http://example.org/a?b=c).\n\
+        Not a link: ftp://example.net or https:// on its own.\n\
+        <https://example.com/trailing>!";
+
+    assert_eq!(
+        extract_urls(body),
+        vec![
+            "https://example.com/maps",
+            "http://example.org/a?b=c",
+            "https://example.com/trailing"
+        ]
+    );
+
+    assert!(extract_urls("no links here").is_empty());
+}
+
+impl Project {
+    /// Collect every external link in every text box of the project
+    pub fn find_links(&self) -> Vec<FoundLink> {
+        let mut links = Vec::new();
+
+        for object in self.objects.values() {
+            let object = object.borrow();
+            object.as_editor().for_each_textbox(&mut |text, _name| {
+                for url in extract_urls(text) {
+                    links.push(FoundLink {
+                        object_id: object.id().to_string(),
+                        object_title: object.get_title(),
+                        url: url.to_string(),
+                    });
+                }
+            });
+        }
+
+        links.sort_by(|a, b| (&a.object_title, &a.url).cmp(&(&b.object_title, &b.url)));
+
+        links
+    }
+
+    /// Check every link in the project on a background thread, sending a result for each unique url
+    /// as it gets checked. The thread exits once every link has been checked (or the receiver
+    /// is dropped)
+    #[cfg(feature = "link-check")]
+    pub fn check_links(&self) -> std::sync::mpsc::Receiver<LinkCheckResult> {
+        let mut urls: Vec<String> = self.find_links().into_iter().map(|link| link.url).collect();
+        urls.sort();
+        urls.dedup();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let agent: ureq::Agent = ureq::Agent::config_builder()
+                .timeout_global(Some(std::time::Duration::from_secs(10)))
+                .build()
+                .into();
+
+            for url in urls {
+                let status = match agent.head(&url).call() {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(err.to_string()),
+                };
+
+                if tx.send(LinkCheckResult { url, status }).is_err() {
+                    // nobody is listening anymore, no point in continuing
+                    break;
+                }
+            }
+        });
+
+        rx
+    }
+}
@@ -0,0 +1,404 @@
+use crate::components::text::{Text, TextUID};
+
+use regex::Regex;
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::LazyLock;
+
+/// A markdown heading (`## Section`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// The number of `#`s, from 1 to 6
+    pub level: usize,
+    pub title: String,
+    /// The byte offset of the start of the heading's line
+    pub offset: usize,
+}
+
+/// A markdown link, either `[text](target)` or `<target>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The text that's shown, which is the target itself for `<target>` links
+    pub text: String,
+    pub target: String,
+    /// The bytes that the whole link takes up
+    pub range: Range<usize>,
+}
+
+/// The structure of a piece of markdown, which is only as detailed as the editor needs. This isn't
+/// a complete markdown parser, but it does know to skip fenced code blocks
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MarkdownDoc {
+    pub headings: Vec<Heading>,
+    pub links: Vec<Link>,
+    /// The bytes that each paragraph takes up (not including its final newline). Paragraphs are
+    /// separated by blank lines, headings, and code blocks
+    pub paragraphs: Vec<Range<usize>>,
+    /// The byte offset of the start of each line of git conflict markers (`<<<<<<<`, `=======`,
+    /// and `>>>>>>>`), left behind by a merge that wasn't finished
+    pub conflict_markers: Vec<usize>,
+    /// How many words are in the whole text
+    pub word_count: usize,
+}
+
+static HEADING_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(#{1,6})[ \t]+(.*?)[ \t#]*\r?$").unwrap());
+
+static LINK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(!?)\[([^\]\n]*)\]\(\s*([^)\s]+)(?:\s+"[^"\n]*")?\s*\)|<([a-zA-Z][a-zA-Z0-9+.-]*:[^>\s]+)>"#)
+        .unwrap()
+});
+
+impl MarkdownDoc {
+    pub fn parse(text: &str) -> Self {
+        let mut doc = Self::default();
+
+        let mut paragraph: Option<Range<usize>> = None;
+        // The characters that opened the current code block (e.g., "```"), if we're in one
+        let mut fence: Option<&str> = None;
+        let mut in_conflict = false;
+        let mut offset = 0;
+
+        for line in text.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            let line = line.trim_end_matches(['\n', '\r']);
+            let trimmed = line.trim_start();
+
+            // Conflicts don't care about the markdown around them, so they're checked first. The
+            // middle marker is only a marker inside of a conflict, otherwise it's an underline
+            let marker = match conflict_marker(line) {
+                Some('<') => {
+                    in_conflict = true;
+                    true
+                }
+                Some('>') if in_conflict => {
+                    in_conflict = false;
+                    true
+                }
+                Some('=' | '|') => in_conflict,
+                _ => false,
+            };
+            if marker {
+                doc.paragraphs.extend(paragraph.take());
+                doc.conflict_markers.push(line_start);
+                continue;
+            }
+
+            if let Some(open_fence) = fence {
+                if trimmed.starts_with(open_fence) {
+                    fence = None;
+                }
+                continue;
+            }
+
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                doc.paragraphs.extend(paragraph.take());
+                fence = Some(&trimmed[..3]);
+                continue;
+            }
+
+            if trimmed.is_empty() {
+                doc.paragraphs.extend(paragraph.take());
+                continue;
+            }
+
+            doc.links
+                .extend(LINK_REGEX.captures_iter(line).filter_map(|captures| {
+                    let whole = captures.get(0).unwrap();
+                    let range = line_start + whole.start()..line_start + whole.end();
+                    if let Some(target) = captures.get(4) {
+                        let target = target.as_str().to_string();
+                        return Some(Link {
+                            text: target.clone(),
+                            target,
+                            range,
+                        });
+                    }
+
+                    // images aren't links
+                    captures[1].is_empty().then(|| Link {
+                        text: captures[2].to_string(),
+                        target: captures[3].to_string(),
+                        range,
+                    })
+                }));
+
+            if let Some(captures) = HEADING_REGEX.captures(line)
+                && !captures[2].is_empty()
+            {
+                doc.paragraphs.extend(paragraph.take());
+                doc.headings.push(Heading {
+                    level: captures[1].len(),
+                    title: captures[2].to_string(),
+                    offset: line_start,
+                });
+                continue;
+            }
+
+            let line_end = line_start + line.len();
+            match &mut paragraph {
+                Some(paragraph) => paragraph.end = line_end,
+                None => paragraph = Some(line_start..line_end),
+            }
+        }
+
+        doc.paragraphs.extend(paragraph);
+        doc.word_count = text.split_whitespace().count();
+
+        doc
+    }
+}
+
+/// If a line starts a git conflict (e.g., `<<<<<<< HEAD`), without parsing anything else. Checking
+/// each line of a body with this finds the same conflicts as `MarkdownDoc::parse`
+pub fn starts_conflict(line: &str) -> bool {
+    conflict_marker(line.trim_end_matches(['\n', '\r'])) == Some('<')
+}
+
+/// The character a git conflict marker is made of, if the line is one (e.g., `<<<<<<< HEAD`)
+fn conflict_marker(line: &str) -> Option<char> {
+    let marker_char = line
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '<' | '=' | '|' | '>'))?;
+    let rest = line.strip_prefix(&marker_char.to_string().repeat(7))?;
+
+    match marker_char {
+        '=' => rest.is_empty(),
+        _ => rest.is_empty() || rest.starts_with(' '),
+    }
+    .then_some(marker_char)
+}
+
+#[test]
+fn test_parse_headings() {
+    let text =
+        "# Part One\nIntro\n\n## The Storm ##\ntext\n####### too deep\n#hashtag\n###   \n### Last";
+    assert_eq!(
+        MarkdownDoc::parse(text).headings,
+        vec![
+            Heading {
+                level: 1,
+                title: "Part One".to_string(),
+                offset: 0,
+            },
+            Heading {
+                level: 2,
+                title: "The Storm".to_string(),
+                offset: 18,
+            },
+            Heading {
+                level: 3,
+                title: "Last".to_string(),
+                offset: text.find("### Last").unwrap(),
+            },
+        ]
+    );
+
+    // headings in code blocks are just code
+    let text = "```\n# not a heading\n```\n# Heading\n~~~md\n## also not\n~~~";
+    let headings = MarkdownDoc::parse(text).headings;
+    assert_eq!(headings.len(), 1);
+    assert_eq!(headings[0].title, "Heading");
+}
+
+#[test]
+fn test_parse_links() {
+    let text = "See [the map](maps/north.png \"North\") and <https://example.com>.\n\
+        ![an image](cover.png) [not a link] [^1]\n\n\
+        ```\n[in code](nowhere)\n```\n\
+        [Last](https://example.org/a_b)";
+    let doc = MarkdownDoc::parse(text);
+
+    assert_eq!(
+        doc.links
+            .iter()
+            .map(|link| (link.text.as_str(), link.target.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("the map", "maps/north.png"),
+            ("https://example.com", "https://example.com"),
+            ("Last", "https://example.org/a_b"),
+        ]
+    );
+    assert_eq!(
+        &text[doc.links[0].range.clone()],
+        "[the map](maps/north.png \"North\")"
+    );
+    assert_eq!(
+        &text[doc.links[2].range.clone()],
+        "[Last](https://example.org/a_b)"
+    );
+}
+
+#[test]
+fn test_parse_paragraphs() {
+    let text = "# Title\nFirst line\nsecond line\n\n\nSecond paragraph\n## Section\nThird\n```\ncode\n```\nFourth";
+    let doc = MarkdownDoc::parse(text);
+
+    assert_eq!(
+        doc.paragraphs
+            .iter()
+            .map(|range| &text[range.clone()])
+            .collect::<Vec<_>>(),
+        vec![
+            "First line\nsecond line",
+            "Second paragraph",
+            "Third",
+            "Fourth"
+        ]
+    );
+}
+
+/// A parsed copy of a piece of text, which is only parsed again once the text has changed
+#[derive(Debug, Default)]
+pub struct ParsedText {
+    cache: RefCell<Option<(TextUID, usize, Rc<MarkdownDoc>)>>,
+}
+
+impl ParsedText {
+    pub fn get(&self, text: &Text) -> Rc<MarkdownDoc> {
+        let mut cache = self.cache.borrow_mut();
+
+        if let Some((uid, version, doc)) = cache.as_ref()
+            && *uid == text.struct_uid
+            && *version == text.version
+        {
+            return doc.clone();
+        }
+
+        let doc = Rc::new(MarkdownDoc::parse(text));
+        *cache = Some((text.struct_uid, text.version, doc.clone()));
+        doc
+    }
+}
+
+#[test]
+fn test_parsed_text_cache() {
+    let mut text = Text::from("# One".to_string());
+    let parsed = ParsedText::default();
+
+    let first = parsed.get(&text);
+    assert!(Rc::ptr_eq(&first, &parsed.get(&text)));
+
+    text.push_str("\n# Two");
+    let second = parsed.get(&text);
+    assert!(!Rc::ptr_eq(&first, &second));
+    assert_eq!(second.headings.len(), 2);
+
+    // a new body (e.g., from reloading the file) is a different text, even at the same version
+    let reloaded = Text::from("no headings".to_string());
+    assert!(parsed.get(&reloaded).headings.is_empty());
+}
+
+/// Markers around emphasized text, longest first so that `***both***` isn't read as `*` around
+/// `**both**`. Underscores only count at the edges of words, so `snake_case` is left alone
+static EMPHASIS_REGEXES: LazyLock<[(Regex, &str); 5]> = LazyLock::new(|| {
+    [
+        (
+            Regex::new(r"\*\*\*([^\s*](?:.*?[^\s*])?)\*\*\*").unwrap(),
+            "$1",
+        ),
+        (Regex::new(r"\*\*([^\s*](?:.*?[^\s*])?)\*\*").unwrap(), "$1"),
+        (Regex::new(r"\*([^\s*](?:.*?[^\s*])?)\*").unwrap(), "$1"),
+        (Regex::new(r"~~([^\s~](?:.*?[^\s~])?)~~").unwrap(), "$1"),
+        (
+            Regex::new(r"(^|\W)_{1,3}([^\s_](?:.*?[^\s_])?)_{1,3}(\W|$)").unwrap(),
+            "$1$2$3",
+        ),
+    ]
+});
+
+/// Remove markdown syntax from text so only the words are left (e.g., for text to speech):
+/// heading markers, emphasis markers, and links (keeping their text)
+pub fn strip_markdown(text: &str) -> String {
+    let doc = MarkdownDoc::parse(text);
+
+    // Parts of the text to replace, which never overlap
+    let mut replacements: Vec<(Range<usize>, &str)> = doc
+        .links
+        .iter()
+        .map(|link| (link.range.clone(), link.text.as_str()))
+        .collect();
+
+    // Only the markers around the title are removed, so that links in it are still found
+    for heading in &doc.headings {
+        let line = text[heading.offset..]
+            .split(['\n', '\r'])
+            .next()
+            .unwrap_or_default();
+        let title_start = line.len()
+            - line
+                .trim_start_matches('#')
+                .trim_start_matches([' ', '\t'])
+                .len();
+        let title_end = title_start + heading.title.len();
+
+        replacements.push((heading.offset..heading.offset + title_start, ""));
+        replacements.push((heading.offset + title_end..heading.offset + line.len(), ""));
+    }
+
+    replacements.sort_by_key(|(range, _replacement)| range.start);
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for (range, replacement) in replacements {
+        stripped.push_str(&text[last_end..range.start]);
+        stripped.push_str(replacement);
+        last_end = range.end;
+    }
+    stripped.push_str(&text[last_end..]);
+
+    for (regex, replacement) in EMPHASIS_REGEXES.iter() {
+        stripped = regex.replace_all(&stripped, *replacement).into_owned();
+    }
+
+    stripped
+}
+
+#[test]
+fn test_strip_markdown() {
+    assert_eq!(
+        strip_markdown("Some **bold**, *italic*, ***both***, and ~~struck~~ words."),
+        "Some bold, italic, both, and struck words."
+    );
+    assert_eq!(
+        strip_markdown("_Underlined_ but not snake_case, __twice__"),
+        "Underlined but not snake_case, twice"
+    );
+    assert_eq!(
+        strip_markdown("See [the map](maps/north.png) or <https://example.com>."),
+        "See the map or https://example.com."
+    );
+    assert_eq!(
+        strip_markdown("## The [Storm](storm.md) ##\nIt **rained**.\n\n* a list\n\n***"),
+        "The Storm\nIt rained.\n\n* a list\n\n***"
+    );
+}
+
+#[test]
+fn test_parse_conflict_markers() {
+    let text = "Before\n<<<<<<< HEAD\nIt rained.\n=======\nIt snowed.\n>>>>>>> other-branch\nAfter";
+    let doc = MarkdownDoc::parse(text);
+
+    assert_eq!(
+        doc.conflict_markers
+            .iter()
+            .map(|offset| &text[*offset..*offset + 7])
+            .collect::<Vec<_>>(),
+        vec!["<<<<<<<", "=======", ">>>>>>>"]
+    );
+    assert_eq!(
+        doc.paragraphs
+            .iter()
+            .map(|range| &text[range.clone()])
+            .collect::<Vec<_>>(),
+        vec!["Before", "It rained.", "It snowed.", "After"]
+    );
+
+    // Underlines and arrows that aren't part of a conflict are just text
+    let doc = MarkdownDoc::parse("Title\n=======\n\n>>>>>>> quoted\n<<<<<<<<< arrows");
+    assert!(doc.conflict_markers.is_empty());
+}
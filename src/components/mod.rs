@@ -1,8 +1,19 @@
+pub mod backup;
+pub mod character_import;
+pub mod export_command;
+pub mod export_split;
 pub mod file_objects;
+pub mod inbox;
+pub mod link_check;
+pub mod markdown;
 pub mod project;
+pub mod project_check;
+pub mod replace;
 pub mod schema;
+pub mod search_index;
 #[cfg(test)]
 mod test;
 pub mod text;
+pub mod timeline;
 
 pub use schema::Schema;
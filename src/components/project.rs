@@ -1,30 +1,37 @@
 use crate::cheese_error;
-use crate::components::file_objects::{FileInfo, FileObject, FileObjectMetadata, FileObjectStore};
-use crate::components::schema::Schema;
-use crate::components::text::Text;
+use crate::components::export_split::DEFAULT_SPLIT_FILENAME_TEMPLATE;
+use crate::components::file_objects::{
+    CompileStatus, FileInfo, FileObject, FileObjectMetadata, FileObjectStore, Relationship,
+};
+use crate::components::schema::{FileType, Schema, preload_headers};
+use crate::components::search_index::SearchIndex;
+use crate::components::text::{Text, count_export_words, renumber_footnotes, strip_comments};
 use crate::schemas::{DEFAULT_SCHEMA, resolve_schema};
 use crate::util::CheeseError;
 
+use egui_ltreeview::DirPosition;
 use notify::event::RenameMode;
 use notify::{EventKind, event::ModifyKind};
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{DebouncedEvent, Debouncer, RecommendedCache, new_debouncer};
 
-use std::cell::RefCell;
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashSet;
 use std::collections::{HashMap, VecDeque};
 use std::ffi::OsString;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 use toml_edit::DocumentMut;
 
-use crate::components::file_objects::{FOLDER_METADATA_FILE_NAME, FileID};
+use crate::components::file_objects::{DEFAULT_INDEX_WIDTH, FOLDER_METADATA_FILE_NAME, FileID};
 
 use crate::components::file_objects::utils::{
+    ALTERNATE_BODY_EXTENSIONS, IMAGE_EXTENSIONS, has_crlf_line_endings, is_image_file,
     metadata_extract_bool, metadata_extract_string, metadata_extract_u64,
-    process_name_for_filename, write_outline_property, write_with_temp_file,
+    process_name_for_filename, produces_valid_filename, replace_with_copy,
+    system_time_to_unix_seconds, temp_file_target, write_outline_property, write_with_temp_file,
 };
 
 type RecommendedDebouncer = Debouncer<RecommendedWatcher, RecommendedCache>;
@@ -38,30 +45,84 @@ pub struct Project {
     pub metadata: ProjectMetadata,
     pub base_metadata: FileObjectMetadata,
     pub file: FileInfo,
-    /// The list of top level folders. The order is hardcoded for now but this can be relaxed later
+    /// The list of top level folders. The first one is always the story text, the rest are in the
+    /// order they're listed in the project file
     pub top_level_folders: Vec<FileID>,
+    /// The names of the top level folders (in the same order as `top_level_folders`), which are
+    /// also used (lowercased) for their folder names on disk
+    pub top_level_folder_names: Vec<String>,
     pub objects: FileObjectStore,
+    /// Index of the words in every object, built on the first search and kept up to date as
+    /// objects are loaded
+    pub search_index: SearchIndex,
+    /// Objects whose files use CRLF line endings, found when the project was loaded. These are
+    /// left alone unless the user asks for them to be converted (see `normalize_line_endings`)
+    pub crlf_files: Vec<FileID>,
+    /// Temporary files left behind by a write that was interrupted (e.g., by a crash), found when
+    /// the project was loaded. Only ones whose file is missing or empty are kept, and they're left
+    /// alone unless the user asks for them to be recovered (see `recover_files`)
+    pub recoverable_files: Vec<RecoverableFile>,
     toml_header: DocumentMut,
 
     last_added_event: Option<Instant>,
     event_queue: VecDeque<DebouncedEvent>,
     file_event_rx: WatcherReceiver,
+    /// Files that `save` just wrote, with their modtime after the write and when they were saved.
+    /// The watcher events for these writes are ignored so that the objects don't get reloaded for
+    /// no reason (see `is_own_save`)
+    recent_saves: HashMap<PathBuf, (SystemTime, Instant)>,
 
     /// We don't need to do anything to the watcher, but we stop getting events if it's dropped
     _watcher: RecommendedDebouncer,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ProjectMetadata {
     pub summary: Text,
     pub notes: Text,
     pub genre: String,
     pub author: String,
     pub email: String,
+    /// How many words the story should be when it's done, if the author has a goal
+    pub word_goal: Option<u64>,
+    /// Extensions to use for types with bodies instead of their defaults (e.g., `txt` for scenes),
+    /// keyed by the type identifier
+    pub file_extensions: HashMap<String, String>,
+    /// Names to give new objects instead of leaving them blank (e.g., "Untitled Chapter"), keyed
+    /// by the type identifier. Types without one show their `empty_string_name` until renamed
+    pub default_names: HashMap<String, String>,
+    /// Regular expressions for words that spellcheck should never flag (e.g., `\w+iel` for names
+    /// in an invented language). Each one has to match an entire word
+    pub spellcheck_ignore_patterns: Vec<String>,
+    /// The smallest number of digits to pad indexes to in filenames, `DEFAULT_INDEX_WIDTH` if
+    /// this isn't set
+    pub index_width: Option<u64>,
+    /// Whether the editor saves changes on its own. When this is off (e.g., for a project on
+    /// read-only media), changes are only written when saving explicitly or closing the project
+    pub autosave_enabled: bool,
 
     pub export: ProjectExportSettings,
 }
 
+impl Default for ProjectMetadata {
+    fn default() -> Self {
+        Self {
+            summary: Text::default(),
+            notes: Text::default(),
+            genre: String::new(),
+            author: String::new(),
+            email: String::new(),
+            word_goal: None,
+            file_extensions: HashMap::new(),
+            default_names: HashMap::new(),
+            spellcheck_ignore_patterns: Vec::new(),
+            index_width: None,
+            autosave_enabled: true,
+            export: ProjectExportSettings::default(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ProjectExportSettings {
     pub include_all_folder_titles: bool,
@@ -73,6 +134,37 @@ pub struct ProjectExportSettings {
     pub include_scene_title_depth: u64,
 
     pub insert_break_at_end: bool,
+
+    /// added to the level of every heading in the export, for pasting into a document that already
+    /// has its own title
+    pub heading_offset: u64,
+
+    /// add the total number of exported words at the very end, for submissions that ask for it
+    pub append_word_count: bool,
+
+    /// how to name the files when exporting each chapter separately, see `fill_filename_template`
+    pub split_filename_template: String,
+
+    /// how folder titles are written, when they're included
+    pub folder_title_style: TitleStyle,
+    /// how scene titles are written, when they're included
+    pub scene_title_style: TitleStyle,
+
+    /// keep single line breaks in scenes (which markdown would join into one paragraph), scenes
+    /// can override this
+    pub preserve_hard_breaks: bool,
+
+    /// put a comment with the id, name, and index of each scene before its text, so that other
+    /// tools can match the exported text back up with the scenes
+    pub scene_metadata_comments: bool,
+
+    /// add a glossary of the characters and places (with their summaries) after the story, to help
+    /// readers keep track of everyone
+    pub append_glossary: bool,
+
+    /// remove markdown formatting (emphasis, heading markers, and link syntax) from scenes, for a
+    /// plain text export (e.g., for text to speech)
+    pub strip_markdown: bool,
 }
 
 impl Default for ProjectExportSettings {
@@ -83,6 +175,50 @@ impl Default for ProjectExportSettings {
             include_all_scene_titles: false,
             include_scene_title_depth: 1,
             insert_break_at_end: true,
+            heading_offset: 0,
+            append_word_count: false,
+            split_filename_template: DEFAULT_SPLIT_FILENAME_TEMPLATE.to_string(),
+            folder_title_style: TitleStyle::Heading,
+            scene_title_style: TitleStyle::Heading,
+            preserve_hard_breaks: false,
+            scene_metadata_comments: false,
+            append_glossary: false,
+            strip_markdown: false,
+        }
+    }
+}
+
+impl ProjectExportSettings {
+    /// Turn the settings (as they're stored) into the options that `export_text` uses
+    pub fn export_options(&self) -> ExportOptions {
+        let folder_title_depth = if self.include_all_folder_titles {
+            ExportDepth::All
+        } else if self.include_folder_title_depth == 0 {
+            ExportDepth::None
+        } else {
+            ExportDepth::Some(self.include_folder_title_depth)
+        };
+
+        let scene_title_depth = if self.include_all_scene_titles {
+            ExportDepth::All
+        } else if self.include_scene_title_depth == 0 {
+            ExportDepth::None
+        } else {
+            ExportDepth::Some(self.include_scene_title_depth)
+        };
+
+        ExportOptions {
+            folder_title_depth,
+            scene_title_depth,
+            insert_breaks: self.insert_break_at_end,
+            heading_offset: self.heading_offset,
+            append_word_count: self.append_word_count,
+            folder_title_style: self.folder_title_style,
+            scene_title_style: self.scene_title_style,
+            preserve_hard_breaks: self.preserve_hard_breaks,
+            scene_metadata_comments: self.scene_metadata_comments,
+            append_glossary: self.append_glossary,
+            strip_markdown: self.strip_markdown,
         }
     }
 }
@@ -102,8 +238,142 @@ impl ProjectMetadata {
 
 const PROJECT_INFO_NAME: &str = "project.toml";
 
+/// filename (without extension) of the project's cover image, which sits next to the project file
+const COVER_FILE_STEM: &str = "cover";
+
+/// Finds the cover image of the project at `project_path` (`cover.<ext>` next to the project
+/// file), if it has one. This doesn't need the project to be loaded, so that the project chooser
+/// can show covers
+pub fn find_project_cover(project_path: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(project_path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_stem().is_some_and(|stem| stem == COVER_FILE_STEM)
+                && path.extension().is_some_and(|extension| {
+                    IMAGE_EXTENSIONS.contains(&extension.to_string_lossy().to_lowercase().as_str())
+                })
+                && path.is_file()
+        })
+}
+
+/// The top level folders of projects that haven't configured their own
+pub const DEFAULT_TOP_LEVEL_FOLDERS: [&str; 3] = ["Text", "Characters", "Worldbuilding"];
+
+/// Read the list of top level folder names from the project file, returning `None` if it isn't
+/// set (in which case the defaults should be used)
+fn read_top_level_folder_names(
+    toml_header: &DocumentMut,
+) -> Result<Option<Vec<String>>, CheeseError> {
+    let Some(item) = toml_header.get("top_level_folders") else {
+        return Ok(None);
+    };
+
+    let Some(array) = item.as_array() else {
+        return Err(cheese_error!(
+            "Invalid value found for 'top_level_folders' key: {item:?}"
+        ));
+    };
+
+    let names = array
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .map(|name| name.to_string())
+                .ok_or_else(|| cheese_error!("Invalid top level folder name: {value:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if names.is_empty() {
+        return Err(cheese_error!(
+            "Project must have at least one top level folder"
+        ));
+    }
+
+    Ok(Some(names))
+}
+
+/// Read the extensions that the project uses instead of the defaults, which are optional
+fn read_file_extensions(toml_header: &DocumentMut) -> Result<HashMap<String, String>, CheeseError> {
+    let Some(item) = toml_header.get("file_extensions") else {
+        return Ok(HashMap::new());
+    };
+
+    let Some(table) = item.as_table_like() else {
+        return Err(cheese_error!(
+            "Invalid value found for 'file_extensions' key: {item:?}"
+        ));
+    };
+
+    table
+        .iter()
+        .map(|(file_type, value)| {
+            let extension = value
+                .as_str()
+                .filter(|extension| {
+                    *extension == "md" || ALTERNATE_BODY_EXTENSIONS.contains(extension)
+                })
+                .ok_or_else(|| {
+                    cheese_error!("Invalid extension for {file_type} in file_extensions: {value:?}")
+                })?;
+
+            Ok((file_type.to_string(), extension.to_string()))
+        })
+        .collect()
+}
+
+/// Read the names to give new objects, which are optional
+fn read_default_names(toml_header: &DocumentMut) -> Result<HashMap<String, String>, CheeseError> {
+    let Some(item) = toml_header.get("default_names") else {
+        return Ok(HashMap::new());
+    };
+
+    let Some(table) = item.as_table_like() else {
+        return Err(cheese_error!(
+            "Invalid value found for 'default_names' key: {item:?}"
+        ));
+    };
+
+    table
+        .iter()
+        .map(|(file_type, value)| {
+            let name = value.as_str().ok_or_else(|| {
+                cheese_error!("Invalid name for {file_type} in default_names: {value:?}")
+            })?;
+
+            Ok((file_type.to_string(), name.to_string()))
+        })
+        .collect()
+}
+
+/// Read the patterns for words that spellcheck ignores. The patterns aren't checked here (that's
+/// up to spellcheck), so that one bad pattern doesn't keep the project from loading
+fn read_spellcheck_ignore_patterns(toml_header: &DocumentMut) -> Result<Vec<String>, CheeseError> {
+    let Some(item) = toml_header.get("spellcheck_ignore_patterns") else {
+        return Ok(Vec::new());
+    };
+
+    let Some(array) = item.as_array() else {
+        return Err(cheese_error!(
+            "Invalid value found for 'spellcheck_ignore_patterns' key: {item:?}"
+        ));
+    };
+
+    array
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .map(|pattern| pattern.to_string())
+                .ok_or_else(|| cheese_error!("Invalid spellcheck ignore pattern: {value:?}"))
+        })
+        .collect()
+}
+
 /// Loads a special top level folder (e.g., "project/text/", "project/worldbuilding"), creating it if
-/// it doesn't already exist.
+/// it doesn't already exist. Without `save_changes`, a missing folder is only created in memory.
 ///
 /// Name will be used directly in the metadata name, but will be converted to lowercase for the filename
 fn load_top_level_folder(
@@ -111,11 +381,15 @@ fn load_top_level_folder(
     project_path: &Path,
     name: &str,
     objects: &mut FileObjectStore,
+    save_changes: bool,
 ) -> Result<FileID, CheeseError> {
     let folder_path = &Path::join(project_path, name.to_lowercase());
     if folder_path.exists() {
+        // Reading and parsing every file is the slow part, so do that in parallel first
+        let mut preloaded = preload_headers(folder_path);
+
         let created_object = schema
-            .load_file(folder_path, objects)
+            .load_file_preloaded(folder_path, objects, &mut preloaded, save_changes)
             .map_err(|err| cheese_error!("failed to load top level folder {name}\n{}", err))?;
 
         let created_object_box = objects.get(&created_object).unwrap();
@@ -146,26 +420,97 @@ fn load_top_level_folder(
         }
     } else {
         log::debug!("top level folder {name} does not exist, creating...");
-        let top_level_folder = schema
-            .create_top_level_folder(project_path.to_owned(), name)
-            .map_err(|err| {
-                cheese_error!(
-                    "An error occured while creating the top level folder\n{}",
-                    err
-                )
-            })?;
+        let top_level_folder = match save_changes {
+            true => schema.create_top_level_folder(project_path.to_owned(), name),
+            false => schema.new_top_level_folder(project_path.to_owned(), name),
+        }
+        .map_err(|err| {
+            cheese_error!(
+                "An error occured while creating the top level folder\n{}",
+                err
+            )
+        })?;
         let folder_id = top_level_folder.id().clone();
         objects.insert(folder_id.clone(), RefCell::new(top_level_folder));
         Ok(folder_id)
     }
 }
 
+/// A temporary file from `write_with_temp_file` that was never moved into place, and has content
+/// that the file it was going to replace (`target_path`) doesn't
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoverableFile {
+    pub temp_path: PathBuf,
+    pub target_path: PathBuf,
+}
+
+/// Look through `dir` (and everything inside of it) for temporary files that can be recovered. If
+/// there's more than one for the same file, only the newest is kept
+fn find_recoverable_files(dir: &Path, recoverable_files: &mut Vec<RecoverableFile>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::warn!("Could not check {dir:?} for interrupted writes: {err}");
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            // Hidden folders (e.g., `.git` and `.backups`) aren't part of the project
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                find_recoverable_files(&path, recoverable_files);
+            }
+            continue;
+        }
+
+        let filename = entry.file_name();
+        let Some(target) = temp_file_target(&filename.to_string_lossy()).map(|t| dir.join(t))
+        else {
+            continue;
+        };
+
+        let has_content = entry.metadata().is_ok_and(|metadata| metadata.len() > 0);
+        let target_is_empty =
+            std::fs::metadata(&target).map_or(true, |metadata| metadata.len() == 0);
+        if !has_content || !target_is_empty {
+            continue;
+        }
+
+        let modtime = |path: &Path| {
+            path.metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        };
+
+        match recoverable_files
+            .iter_mut()
+            .find(|recoverable| recoverable.target_path == target)
+        {
+            Some(existing) => {
+                if modtime(&path) > modtime(&existing.temp_path) {
+                    existing.temp_path = path;
+                }
+            }
+            None => recoverable_files.push(RecoverableFile {
+                temp_path: path,
+                target_path: target,
+            }),
+        }
+    }
+}
+
 #[cfg(not(test))]
 const WATCHER_MSEC_DURATION: u64 = 1000;
 
 #[cfg(test)]
 const WATCHER_MSEC_DURATION: u64 = 50;
 
+/// How long after a save events for the saved files are ignored. This only needs to be long enough
+/// for the watcher to send them, since files that change again aren't ignored anyway
+const OWN_SAVE_MSEC_DURATION: u64 = WATCHER_MSEC_DURATION * 5;
+
 fn create_watcher() -> notify::Result<(RecommendedDebouncer, WatcherReceiver)> {
     let (tx, rx) = std::sync::mpsc::channel();
 
@@ -201,16 +546,57 @@ enum ProjectPathKind {
     UnrecognizedLocation,
 }
 
+/// Make sure that a (filename-safe) project name is something we can actually create. Names that
+/// are entirely made of characters that got replaced aren't useful, and a folder that only differs
+/// by case would collide on case-insensitive filesystems.
+fn validate_project_folder_name(dirname: &Path, file_safe_name: &str) -> Result<(), CheeseError> {
+    if file_safe_name.trim_matches(['-', '_']).is_empty() {
+        return Err(cheese_error!(
+            "project name produced an empty or invalid folder name"
+        ));
+    }
+
+    let lowercase_name = file_safe_name.to_lowercase();
+    for entry in std::fs::read_dir(dirname)? {
+        let entry_name = entry?.file_name();
+        if entry_name.to_string_lossy().to_lowercase() == lowercase_name {
+            return Err(cheese_error!(
+                "a folder named {entry_name:?} already exists in {dirname:?}"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 impl Project {
-    /// Create a new project
+    /// Create a new project with the default top level folders
     pub fn new(
         schema: &'static dyn Schema,
         dirname: PathBuf,
         project_name: String,
     ) -> Result<Self, CheeseError> {
-        let canonical_dirname = dirname.canonicalize().unwrap();
+        Self::new_with_top_level_folders(schema, dirname, project_name, &DEFAULT_TOP_LEVEL_FOLDERS)
+    }
+
+    /// Create a new project with the given top level folders. The first one will hold the story
+    /// text
+    pub fn new_with_top_level_folders(
+        schema: &'static dyn Schema,
+        dirname: PathBuf,
+        project_name: String,
+        top_level_folder_names: &[&str],
+    ) -> Result<Self, CheeseError> {
+        if top_level_folder_names.is_empty() {
+            return Err(cheese_error!(
+                "Project must have at least one top level folder"
+            ));
+        }
+
+        let canonical_dirname = dirname.canonicalize()?;
         // Not truncating here (for now)
         let file_safe_name = process_name_for_filename(&project_name);
+        validate_project_folder_name(&canonical_dirname, &file_safe_name)?;
         let project_path = canonical_dirname.join(&file_safe_name);
 
         if project_path.exists() {
@@ -221,16 +607,30 @@ impl Project {
             std::fs::create_dir(&project_path)?;
         }
 
-        let text = schema.create_top_level_folder(project_path.clone(), "Text")?;
-        let characters = schema.create_top_level_folder(project_path.clone(), "Characters")?;
-        let worldbuilding =
-            schema.create_top_level_folder(project_path.clone(), "Worldbuilding")?;
+        let top_level_folder_names: Vec<String> = top_level_folder_names
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let top_level_folder_objects = top_level_folder_names
+            .iter()
+            .map(|name| schema.create_top_level_folder(project_path.clone(), name))
+            .collect::<Result<Vec<_>, _>>()?;
 
         let file = FileInfo {
             dirname: canonical_dirname,
             basename: OsString::from(file_safe_name),
+            converted_to_folder: false,
             modtime: None,
             modified: true, // Newly added files are modified (they don't exist on disk)
+            edited: true,
+            extension: None,
+            index_width: DEFAULT_INDEX_WIDTH,
+            child_index_width: DEFAULT_INDEX_WIDTH,
+            disk_conflict: None,
+            conflict_markers: false,
+            word_count: 0,
+            image_name: OnceCell::new(),
         };
 
         // Create the watcher path by hand since we can't call get_path() yet
@@ -247,32 +647,37 @@ impl Project {
         let mut toml_header = DocumentMut::new();
         toml_header["schema"] = toml_edit::value(schema.get_schema_identifier());
 
-        let top_level_folders = vec![
-            text.id().clone(),
-            characters.id().clone(),
-            worldbuilding.id().clone(),
-        ];
+        let top_level_folders = top_level_folder_objects
+            .iter()
+            .map(|folder| folder.id().clone())
+            .collect();
 
         let mut project = Self {
             schema,
             base_metadata: FileObjectMetadata {
                 name: project_name,
+                created: Some(std::time::SystemTime::now()),
                 ..Default::default()
             },
             metadata: ProjectMetadata::default(),
             top_level_folders,
+            top_level_folder_names,
             file,
             toml_header,
             objects: HashMap::new(),
+            search_index: SearchIndex::default(),
+            crlf_files: Vec::new(),
+            recoverable_files: Vec::new(),
             last_added_event: None,
             event_queue: VecDeque::new(),
             file_event_rx,
+            recent_saves: HashMap::new(),
             _watcher: watcher,
         };
 
-        project.add_object(text);
-        project.add_object(characters);
-        project.add_object(worldbuilding);
+        for folder in top_level_folder_objects {
+            project.add_object(folder);
+        }
 
         project.save()?;
 
@@ -281,6 +686,22 @@ impl Project {
 
     /// Load an existing project from disk
     pub fn load(path: PathBuf) -> Result<Self, CheeseError> {
+        Self::load_inner(path, true)
+    }
+
+    /// Load a project without writing anything back to it (e.g., fixing up missing metadata),
+    /// for when it's only being read from
+    pub fn load_read_only(path: PathBuf) -> Result<Self, CheeseError> {
+        Self::load_inner(path, false)
+    }
+
+    /// Whether `path` has a project in it (a `project.toml` or, for older projects, a `text/`
+    /// folder), as opposed to some other folder
+    pub fn is_project_folder(path: &Path) -> bool {
+        path.join(PROJECT_INFO_NAME).exists() || path.join("text").is_dir()
+    }
+
+    fn load_inner(path: PathBuf, save_changes: bool) -> Result<Self, CheeseError> {
         if !path.exists() {
             return Err(cheese_error!(
                 "attempted to load {path:?}, was not a directory"
@@ -302,8 +723,17 @@ impl Project {
                 }
             }
             .to_owned(),
+            converted_to_folder: false,
             modtime: None,
             modified: false,
+            edited: false,
+            extension: None,
+            index_width: DEFAULT_INDEX_WIDTH,
+            child_index_width: DEFAULT_INDEX_WIDTH,
+            disk_conflict: None,
+            conflict_markers: false,
+            word_count: 0,
+            image_name: OnceCell::new(),
         };
 
         let mut base_metadata = FileObjectMetadata::default();
@@ -315,16 +745,15 @@ impl Project {
         let mut toml_header = if project_info_path.exists() {
             log::debug!("Found `project_info.toml`, loading project");
 
-            let project_info_data =
-                std::fs::read_to_string(project_info_path).expect("could not read file");
+            let project_info_data = std::fs::read_to_string(&project_info_path)?;
 
             project_info_data
                 .parse::<DocumentMut>()
-                .expect("invalid file metadata header")
+                .map_err(|err| cheese_error!("Error parsing {project_info_path:?}: {err}"))?
         } else {
             // If the `project.toml` doesn't exist, check for a `text/` folder so we don't accidentally
             // load and hijack another folder
-            if !Path::join(&path, "text").is_dir() {
+            if !Self::is_project_folder(&path) {
                 log::error!(
                     "Attempted to load a folder without `project_info.toml` or a `text/` folder. \
                      Consider creating a new project instead. If this was intended, please create \
@@ -358,14 +787,30 @@ impl Project {
 
         let schema = resolve_schema(&schema_identifier)?;
 
+        // This has to happen before anything is saved, which could replace an empty file
+        let mut recoverable_files = Vec::new();
+        find_recoverable_files(&path, &mut recoverable_files);
+        recoverable_files.sort_by(|a, b| a.target_path.cmp(&b.target_path));
+
         // Load or create folders
         let mut objects = FileObjectStore::new();
 
-        let top_level_folders = vec![
-            load_top_level_folder(schema, &path, "Text", &mut objects)?,
-            load_top_level_folder(schema, &path, "Characters", &mut objects)?,
-            load_top_level_folder(schema, &path, "Worldbuilding", &mut objects)?,
-        ];
+        // Projects from before the top level folders were configurable just use the defaults
+        let top_level_folder_names = match read_top_level_folder_names(&toml_header)? {
+            Some(names) => names,
+            None => {
+                file_info.modified = true;
+                DEFAULT_TOP_LEVEL_FOLDERS
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect()
+            }
+        };
+
+        let top_level_folders = top_level_folder_names
+            .iter()
+            .map(|name| load_top_level_folder(schema, &path, name, &mut objects, save_changes))
+            .collect::<Result<Vec<_>, _>>()?;
 
         log::debug!("Finished loading all project file objects, continuing");
 
@@ -388,11 +833,16 @@ impl Project {
             base_metadata,
             file: file_info,
             top_level_folders,
+            top_level_folder_names,
             toml_header,
             objects,
+            search_index: SearchIndex::default(),
+            crlf_files: Vec::new(),
+            recoverable_files,
             event_queue: VecDeque::new(),
             last_added_event: None,
             file_event_rx,
+            recent_saves: HashMap::new(),
             _watcher: watcher,
         };
 
@@ -401,20 +851,135 @@ impl Project {
             project.file.modified = true
         }
 
+        project.apply_storage_settings();
+
         project.clean_up_orphaned_objects();
 
         project.resolve_references();
-        project.save()?;
+        if save_changes {
+            project.save()?;
+        }
+
+        // Anything that was just saved is already converted, so only check afterwards
+        project.crlf_files = project.find_crlf_files();
 
         Ok(project)
     }
 
-    pub fn add_object(&mut self, new_object: Box<dyn FileObject>) {
+    pub fn add_object(&mut self, mut new_object: Box<dyn FileObject>) {
+        self.apply_default_name(new_object.as_mut());
+
         let id = new_object.id().clone();
-        self.objects.insert(id, RefCell::new(new_object));
+        self.objects.insert(id.clone(), RefCell::new(new_object));
+        self.apply_file_extension(&id);
+        self.apply_index_width(&id);
+    }
+
+    /// The name that a new object of `file_type` starts with, if the project sets one
+    pub fn default_name(&self, file_type: FileType) -> Option<&str> {
+        self.metadata
+            .default_names
+            .get(file_type.get_identifier())
+            .map(String::as_str)
+    }
+
+    /// Give an object without a name the default name configured for its type (if any)
+    fn apply_default_name(&self, object: &mut dyn FileObject) {
+        if !object.get_base().metadata.name.is_empty() {
+            return;
+        }
+
+        if let Some(name) = self.default_name(object.get_type())
+            && !name.is_empty()
+        {
+            object.get_base_mut().metadata.name = name.to_string();
+            object.get_base_mut().file.modified = true;
+        }
+    }
+
+    /// Make sure an object uses the extension configured for its type, which renames the file the
+    /// next time it's saved if it doesn't
+    pub fn apply_file_extension(&self, file_id: &FileID) {
+        let Some(object) = self.objects.get(file_id) else {
+            return;
+        };
+        let mut object = object.borrow_mut();
+
+        if object.is_folder() {
+            return;
+        }
+
+        let file_type = object.get_type();
+        let extension = self
+            .metadata
+            .file_extensions
+            .get(file_type.get_identifier())
+            .filter(|extension| *extension != file_type.extension())
+            .cloned();
+
+        if object.get_base().file.extension != extension {
+            object.get_base_mut().file.extension = extension;
+            object.get_base_mut().file.modified = true;
+        }
+    }
+
+    /// Make sure a folder pads the indexes of its children to the width configured for the
+    /// project. Any children that need a different width get renamed the next time they're saved
+    pub fn apply_index_width(&self, file_id: &FileID) {
+        let Some(object) = self.objects.get(file_id) else {
+            return;
+        };
+        let mut object = object.borrow_mut();
+
+        if !object.is_folder() {
+            return;
+        }
+
+        let index_width = self
+            .metadata
+            .index_width
+            .map_or(DEFAULT_INDEX_WIDTH, |index_width| index_width as usize);
+
+        if object.get_base().file.child_index_width != index_width {
+            object.get_base_mut().file.child_index_width = index_width;
+            object.update_child_index_widths(&self.objects);
+        }
+    }
+
+    /// Make every object match the project's file extensions and index width, after they've been
+    /// loaded or changed. Files are renamed the next time they're saved
+    pub fn apply_storage_settings(&self) {
+        for file_id in self.objects.keys() {
+            self.apply_file_extension(file_id);
+            self.apply_index_width(file_id);
+        }
+    }
+
+    /// Whether files with `extension` can hold objects in this project
+    fn is_active_extension(&self, extension: &str) -> bool {
+        extension == "toml"
+            || extension == "md"
+            || self
+                .metadata
+                .file_extensions
+                .values()
+                .any(|active| active == extension)
     }
 
     pub fn save(&mut self) -> Result<(), CheeseError> {
+        self.recent_saves.retain(|_path, (_modtime, saved_at)| {
+            saved_at.elapsed().as_millis() < OWN_SAVE_MSEC_DURATION.into()
+        });
+
+        // Remember what's about to be written, so we can recognize our own writes when the
+        // watcher tells us about them
+        let modified_objects: Vec<FileID> = self
+            .objects
+            .iter()
+            .filter(|(_id, object)| object.borrow().get_base().file.modified)
+            .map(|(id, _object)| id.clone())
+            .collect();
+
         // First, try saving the children
 
         let results: Vec<Result<(), CheeseError>> = self
@@ -440,6 +1005,9 @@ impl Project {
 
             write_with_temp_file(self.get_project_info_file(), final_str)?;
 
+            #[cfg(feature = "metrics")]
+            crate::util::write_counters::record_write();
+
             let new_modtime = std::fs::metadata(self.get_project_info_file())
                 .expect("attempted to load file that does not exist")
                 .modified()
@@ -448,6 +1016,26 @@ impl Project {
             // Update modtime based on what we just wrote
             self.file.modtime = Some(new_modtime);
             self.file.modified = false;
+
+            self.recent_saves
+                .insert(self.get_project_info_file(), (new_modtime, Instant::now()));
+        } else {
+            #[cfg(feature = "metrics")]
+            crate::util::write_counters::record_write_avoided();
+        }
+
+        // Anything that isn't modified anymore was written (objects with a conflict or that failed
+        // to save are still modified)
+        for file_id in modified_objects {
+            if let Some(object) = self.objects.get(&file_id) {
+                let object = object.borrow();
+                if !object.get_base().file.modified
+                    && let Some(modtime) = object.get_base().file.modtime
+                {
+                    self.recent_saves
+                        .insert(object.get_file(), (modtime, Instant::now()));
+                }
+            }
         }
 
         for result in results {
@@ -457,6 +1045,94 @@ impl Project {
         Ok(())
     }
 
+    /// Objects with changes that haven't been written to disk yet, sorted by title
+    pub fn modified_objects(&self) -> Vec<FileID> {
+        let mut modified: Vec<(String, FileID)> = self
+            .objects
+            .iter()
+            .filter(|(_id, object)| object.borrow().get_base().file.modified)
+            .map(|(id, object)| (object.borrow().get_title(), id.clone()))
+            .collect();
+
+        modified.sort();
+
+        modified.into_iter().map(|(_title, id)| id).collect()
+    }
+
+    /// Progress toward each folder's word target, for folders in the text that have one, as
+    /// `(id, current, target)` in story order
+    pub fn folder_word_targets(&self) -> Vec<(FileID, u64, u64)> {
+        self.story_word_targets().1
+    }
+
+    /// The words in the whole story, along with `folder_word_targets`, in a single pass
+    pub fn story_word_targets(&self) -> (u64, Vec<(FileID, u64, u64)>) {
+        let mut targets = Vec::new();
+        let (words, _target) = self.add_folder_word_targets(self.text_folder_id(), &mut targets);
+        (words, targets)
+    }
+
+    /// Adds the targets of `file_id` and everything inside of it, returning its own
+    /// `(current, target)` like `aggregate_word_target`. Working from the bottom up means that
+    /// every object is only counted once, no matter how deeply the folders are nested
+    fn add_folder_word_targets(
+        &self,
+        file_id: &FileID,
+        targets: &mut Vec<(FileID, u64, u64)>,
+    ) -> (u64, u64) {
+        let Some(object) = self.objects.get(file_id) else {
+            return (0, 0);
+        };
+        let object = object.borrow();
+
+        // Folders come before the folders inside of them, even though they're finished after
+        let position = targets.len();
+
+        let mut current = object.word_count() as u64;
+        let mut target = 0;
+        for child_id in object.get_base().children.iter() {
+            let (child_current, child_target) = self.add_folder_word_targets(child_id, targets);
+            current += child_current;
+            target += child_target;
+        }
+
+        if object.is_folder()
+            && let Some(own_target) = object.target_word_count()
+        {
+            targets.insert(position, (file_id.clone(), current, own_target));
+        }
+
+        (current, object.target_word_count().unwrap_or(target))
+    }
+
+    /// Whether an event only comes from files that `save` just wrote (and that haven't been
+    /// changed since), so processing it would just reload what we already have
+    fn is_own_save(&self, event: &DebouncedEvent) -> bool {
+        if let EventKind::Remove(_) = event.kind {
+            return false;
+        }
+
+        event
+            .paths
+            .iter()
+            .filter(|path| {
+                matches!(
+                    self.classify_path_position(path),
+                    ProjectPathKind::Contents | ProjectPathKind::ProjectFile
+                )
+            })
+            .all(|path| {
+                self.recent_saves
+                    .get(path)
+                    .is_some_and(|(saved_modtime, saved_at)| {
+                        saved_at.elapsed().as_millis() < OWN_SAVE_MSEC_DURATION.into()
+                            && std::fs::metadata(path)
+                                .and_then(|metadata| metadata.modified())
+                                .is_ok_and(|modtime| modtime == *saved_modtime)
+                    })
+            })
+    }
+
     fn write_metadata(&mut self) {
         assert_eq!(
             self.toml_header["schema"]
@@ -469,6 +1145,9 @@ impl Project {
             toml_edit::value(self.base_metadata.version as i64);
         self.toml_header["name"] = toml_edit::value(&self.base_metadata.name);
         self.toml_header["id"] = toml_edit::value(&*self.base_metadata.id);
+        if let Some(created) = self.base_metadata.created {
+            self.toml_header["created"] = toml_edit::value(system_time_to_unix_seconds(created));
+        }
 
         self.toml_header["summary"] = toml_edit::value(&*self.metadata.summary);
         self.toml_header["notes"] = toml_edit::value(&*self.metadata.notes);
@@ -476,6 +1155,69 @@ impl Project {
         self.toml_header["author"] = toml_edit::value(&self.metadata.author);
         self.toml_header["email"] = toml_edit::value(&self.metadata.email);
 
+        match self.metadata.word_goal {
+            Some(word_goal) => {
+                self.toml_header["word_goal"] = toml_edit::value(u64_to_i64_drop_msb(word_goal));
+            }
+            None => {
+                self.toml_header.remove("word_goal");
+            }
+        }
+
+        match self.metadata.index_width {
+            Some(index_width) => {
+                self.toml_header["index_width"] =
+                    toml_edit::value(u64_to_i64_drop_msb(index_width));
+            }
+            None => {
+                self.toml_header.remove("index_width");
+            }
+        }
+
+        if self.metadata.file_extensions.is_empty() {
+            self.toml_header.remove("file_extensions");
+        } else {
+            let mut file_extensions = toml_edit::InlineTable::new();
+            for (file_type, extension) in &self.metadata.file_extensions {
+                file_extensions.insert(file_type.as_str(), extension.as_str().into());
+            }
+            file_extensions.sort_values();
+            self.toml_header["file_extensions"] = toml_edit::value(file_extensions);
+        }
+
+        if self.metadata.default_names.is_empty() {
+            self.toml_header.remove("default_names");
+        } else {
+            let mut default_names = toml_edit::InlineTable::new();
+            for (file_type, name) in &self.metadata.default_names {
+                default_names.insert(file_type.as_str(), name.as_str().into());
+            }
+            default_names.sort_values();
+            self.toml_header["default_names"] = toml_edit::value(default_names);
+        }
+
+        // Blank lines are left in while editing, but there's no reason to keep them
+        let patterns: toml_edit::Array = self
+            .metadata
+            .spellcheck_ignore_patterns
+            .iter()
+            .filter(|pattern| !pattern.trim().is_empty())
+            .map(|pattern| pattern.as_str())
+            .collect();
+        if patterns.is_empty() {
+            self.toml_header.remove("spellcheck_ignore_patterns");
+        } else {
+            self.toml_header["spellcheck_ignore_patterns"] = toml_edit::value(patterns);
+        }
+
+        self.toml_header["autosave_enabled"] = toml_edit::value(self.metadata.autosave_enabled);
+
+        let mut top_level_folders = toml_edit::Array::new();
+        for name in &self.top_level_folder_names {
+            top_level_folders.push(name.as_str());
+        }
+        self.toml_header["top_level_folders"] = toml_edit::value(top_level_folders);
+
         // If the table doesn't already exist, we create it so we can get it immediately after
         if !self.toml_header.contains_key("export") {
             self.toml_header["export"] = toml_edit::value(toml_edit::InlineTable::new());
@@ -510,6 +1252,39 @@ impl Project {
             "insert_break_at_end",
             self.metadata.export.insert_break_at_end.into(),
         );
+        export_table.insert(
+            "heading_offset",
+            u64_to_i64_drop_msb(self.metadata.export.heading_offset).into(),
+        );
+        export_table.insert(
+            "append_word_count",
+            self.metadata.export.append_word_count.into(),
+        );
+        export_table.insert(
+            "split_filename_template",
+            self.metadata.export.split_filename_template.as_str().into(),
+        );
+        export_table.insert(
+            "folder_title_style",
+            self.metadata.export.folder_title_style.as_str().into(),
+        );
+        export_table.insert(
+            "scene_title_style",
+            self.metadata.export.scene_title_style.as_str().into(),
+        );
+        export_table.insert(
+            "preserve_hard_breaks",
+            self.metadata.export.preserve_hard_breaks.into(),
+        );
+        export_table.insert(
+            "scene_metadata_comments",
+            self.metadata.export.scene_metadata_comments.into(),
+        );
+        export_table.insert(
+            "append_glossary",
+            self.metadata.export.append_glossary.into(),
+        );
+        export_table.insert("strip_markdown", self.metadata.export.strip_markdown.into());
     }
 
     pub fn get_path(&self) -> PathBuf {
@@ -523,6 +1298,27 @@ impl Project {
         path
     }
 
+    pub fn find_cover(&self) -> Option<PathBuf> {
+        find_project_cover(&self.get_path())
+    }
+
+    /// Copies `source` into the project as its cover image, replacing any cover that was already
+    /// there. Returns the path of the newly copied cover
+    pub fn set_cover(&self, source: &Path) -> Result<PathBuf, CheeseError> {
+        let extension = source
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .filter(|extension| IMAGE_EXTENSIONS.contains(&extension.as_str()))
+            .ok_or_else(|| cheese_error!("{source:?} does not look like a supported image"))?;
+
+        let dest = self
+            .get_path()
+            .join(format!("{COVER_FILE_STEM}.{extension}"));
+        replace_with_copy(source, &dest, self.find_cover().as_deref())?;
+
+        Ok(dest)
+    }
+
     fn load_metadata(&mut self) -> Result<bool, CheeseError> {
         let mut modified = false;
 
@@ -551,6 +1347,31 @@ impl Project {
             None => modified = true,
         }
 
+        // Optional, every type uses its normal extension if this is missing
+        self.metadata.file_extensions = read_file_extensions(&self.toml_header)?;
+
+        // Optional, new objects are left blank if this is missing
+        self.metadata.default_names = read_default_names(&self.toml_header)?;
+
+        // Optional, spellcheck only uses the dictionary if this is missing
+        self.metadata.spellcheck_ignore_patterns =
+            read_spellcheck_ignore_patterns(&self.toml_header)?;
+
+        match metadata_extract_bool(self.toml_header.as_table(), "autosave_enabled")? {
+            Some(autosave_enabled) => self.metadata.autosave_enabled = autosave_enabled,
+            None => modified = true,
+        }
+
+        // Optional, every folder uses the default width if this is missing
+        self.metadata.index_width =
+            metadata_extract_u64(self.toml_header.as_table(), "index_width", false)?
+                .filter(|index_width| *index_width > 0);
+
+        // Optional, so it's fine for this to be missing
+        self.metadata.word_goal =
+            metadata_extract_u64(self.toml_header.as_table(), "word_goal", false)?
+                .filter(|word_goal| *word_goal > 0);
+
         match self.toml_header.get("export") {
             Some(export_item) => match export_item.as_table_like() {
                 Some(export_table) => {
@@ -564,43 +1385,259 @@ impl Project {
                         None => modified = true,
                     }
 
-                    match metadata_extract_bool(export_table, "include_all_scene_files")? {
-                        Some(val) => self.metadata.export.include_all_scene_titles = val,
-                        None => modified = true,
-                    }
+                    match metadata_extract_bool(export_table, "include_all_scene_files")? {
+                        Some(val) => self.metadata.export.include_all_scene_titles = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_u64(export_table, "include_scene_title_depth", false)? {
+                        Some(val) => self.metadata.export.include_scene_title_depth = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_bool(export_table, "insert_break_at_end")? {
+                        Some(val) => self.metadata.export.insert_break_at_end = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_u64(export_table, "heading_offset", false)? {
+                        Some(val) => self.metadata.export.heading_offset = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_bool(export_table, "append_word_count")? {
+                        Some(val) => self.metadata.export.append_word_count = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_string(export_table, "split_filename_template")? {
+                        Some(val) => self.metadata.export.split_filename_template = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_string(export_table, "folder_title_style")? {
+                        Some(val) => self.metadata.export.folder_title_style = val.parse()?,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_string(export_table, "scene_title_style")? {
+                        Some(val) => self.metadata.export.scene_title_style = val.parse()?,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_bool(export_table, "preserve_hard_breaks")? {
+                        Some(val) => self.metadata.export.preserve_hard_breaks = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_bool(export_table, "scene_metadata_comments")? {
+                        Some(val) => self.metadata.export.scene_metadata_comments = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_bool(export_table, "append_glossary")? {
+                        Some(val) => self.metadata.export.append_glossary = val,
+                        None => modified = true,
+                    }
+
+                    match metadata_extract_bool(export_table, "strip_markdown")? {
+                        Some(val) => self.metadata.export.strip_markdown = val,
+                        None => modified = true,
+                    }
+                }
+                None => {
+                    return Err(cheese_error!(
+                        "Project Metadata has non-table value for export"
+                    ));
+                }
+            },
+            None => modified = true,
+        }
+
+        Ok(modified)
+    }
+
+    /// Add a new top level folder (after all of the existing ones), creating it on disk
+    pub fn add_top_level_folder(&mut self, name: &str) -> Result<FileID, CheeseError> {
+        let name = name.trim();
+        if !produces_valid_filename(name) {
+            return Err(cheese_error!("invalid top level folder name: {name:?}"));
+        }
+
+        // They're lowercased on disk, so names that only differ by case would be the same folder
+        if self
+            .top_level_folder_names
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(name))
+        {
+            return Err(cheese_error!("top level folder {name:?} already exists"));
+        }
+
+        let folder_id =
+            load_top_level_folder(self.schema, &self.get_path(), name, &mut self.objects, true)?;
+
+        self.top_level_folders.push(folder_id.clone());
+        self.top_level_folder_names.push(name.to_string());
+        self.file.modified = true;
+
+        Ok(folder_id)
+    }
+
+    /// The top level folder that contains the story text
+    pub fn text_folder_id(&self) -> &FileID {
+        &self.top_level_folders[TEXT_FOLDER_POSITION]
+    }
+
+    /// Find a top level folder by its (case-insensitive) name
+    pub fn top_level_folder_id(&self, name: &str) -> Option<&FileID> {
+        self.top_level_folder_names
+            .iter()
+            .position(|folder_name| folder_name.eq_ignore_ascii_case(name))
+            .map(|position| &self.top_level_folders[position])
+    }
+
+    pub fn is_top_level_folder(&self, file_id: &FileID) -> bool {
+        self.top_level_folders.contains(file_id)
+    }
+
+    /// Copy everything in a section (top level folder) of another project into `into`. The other
+    /// project is only read from, never saved. Every copied object gets a new id, so references
+    /// between the copied objects (e.g., a scene's POV) won't point anywhere in this project.
+    /// Returns the ids of the copied objects directly inside of `into`
+    pub fn import_section(
+        &mut self,
+        other: &Path,
+        section_name: &str,
+        into: &FileID,
+    ) -> Result<Vec<FileID>, CheeseError> {
+        let other_project = Project::load_read_only(other.to_path_buf())?;
+
+        if other_project.schema.get_schema_identifier() != self.schema.get_schema_identifier() {
+            return Err(cheese_error!(
+                "can't import from {other:?}: it uses the {} schema, not {}",
+                other_project.schema,
+                self.schema
+            ));
+        }
+
+        let section_id = other_project
+            .top_level_folder_id(section_name)
+            .ok_or_else(|| cheese_error!("{other:?} has no section named {section_name:?}"))?;
+
+        if !self
+            .objects
+            .get(into)
+            .is_some_and(|object| object.borrow().is_folder())
+        {
+            return Err(cheese_error!("can't import into {into}: not a folder"));
+        }
+
+        let source_children = other_project
+            .objects
+            .get(section_id)
+            .unwrap()
+            .borrow()
+            .get_base()
+            .children
+            .clone();
+
+        let mut imported = Vec::new();
+
+        for source_id in source_children {
+            let Some(source) = other_project.objects.get(&source_id) else {
+                continue;
+            };
+
+            let new_id = self.import_object(&**source.borrow(), &other_project.objects, into)?;
+
+            // Only the objects that end up next to existing ones can collide
+            let new_name = self.unused_child_name(into, &new_id);
+            self.objects[&new_id]
+                .borrow_mut()
+                .get_base_mut()
+                .metadata
+                .name = new_name;
+
+            imported.push(new_id);
+        }
+
+        Ok(imported)
+    }
+
+    /// Deep copy `source` (and all of its children) to the end of `parent`
+    fn import_object(
+        &mut self,
+        source: &dyn FileObject,
+        source_objects: &FileObjectStore,
+        parent: &FileID,
+    ) -> Result<FileID, CheeseError> {
+        // Otherwise a body that can't be read would be imported as an empty one
+        source.load_unloaded_body()?;
+
+        let mut new_object = self
+            .objects
+            .get(parent)
+            .unwrap()
+            .borrow_mut()
+            .create_child(source.get_type(), DirPosition::Last, &self.objects)?;
+
+        let mut toml_header = source.get_base().toml_header.clone();
+        toml_header["id"] = toml_edit::value(new_object.id().as_str());
+
+        let base = new_object.get_base_mut();
+        base.metadata
+            .load_base_metadata(toml_header.as_table(), &mut base.file)?;
+        base.toml_header = toml_header;
+        base.file.modified = true;
+        new_object.load_metadata()?;
+
+        if source.has_body() {
+            new_object.load_body(source.get_body());
+        }
+
+        if let Some(image) = source.find_image() {
+            new_object.set_image(&image)?;
+        }
 
-                    match metadata_extract_u64(export_table, "include_scene_title_depth", false)? {
-                        Some(val) => self.metadata.export.include_scene_title_depth = val,
-                        None => modified = true,
-                    }
+        let new_id = new_object.id().clone();
+        self.add_object(new_object);
 
-                    match metadata_extract_bool(export_table, "insert_break_at_end")? {
-                        Some(val) => self.metadata.export.insert_break_at_end = val,
-                        None => modified = true,
-                    }
-                }
-                None => {
-                    return Err(cheese_error!(
-                        "Project Metadata has non-table value for export"
-                    ));
-                }
-            },
-            None => modified = true,
+        for child in source.children(source_objects) {
+            self.import_object(&**child.borrow(), source_objects, &new_id)?;
         }
 
-        Ok(modified)
+        Ok(new_id)
     }
 
-    pub fn is_top_level_folder(&self, file_id: &FileID) -> bool {
-        self.top_level_folders.contains(file_id)
+    /// The name `child_id` should have to be different from every other child of `parent`, adding
+    /// a number to the end if it's already taken
+    pub fn unused_child_name(&self, parent: &FileID, child_id: &FileID) -> String {
+        let sibling_names: HashSet<String> = self.objects[parent]
+            .borrow()
+            .children(&self.objects)
+            .filter(|sibling| sibling.borrow().id() != child_id)
+            .map(|sibling| sibling.borrow().get_title().to_lowercase())
+            .collect();
+
+        let name = self.objects[child_id]
+            .borrow()
+            .get_base()
+            .metadata
+            .name
+            .clone();
+        if name.is_empty() || !sibling_names.contains(&name.to_lowercase()) {
+            return name;
+        }
+
+        (2..)
+            .map(|suffix| format!("{name} ({suffix})"))
+            .find(|candidate| !sibling_names.contains(&candidate.to_lowercase()))
+            .unwrap()
     }
 
     /// Determine if the file should be loaded
     fn should_load(&mut self) -> Result<bool, CheeseError> {
-        let current_modtime = std::fs::metadata(self.get_project_info_file())
-            .expect("attempted to load file that does not exist")
-            .modified()
-            .expect("Modtime not available");
+        let current_modtime = std::fs::metadata(self.get_project_info_file())?.modified()?;
 
         if let Some(old_modtime) = self.file.modtime
             && old_modtime == current_modtime
@@ -617,11 +1654,12 @@ impl Project {
             return Ok(());
         }
 
-        let project_info_data = std::fs::read_to_string(self.get_project_info_file())?;
+        let project_info_file = self.get_project_info_file();
+        let project_info_data = std::fs::read_to_string(&project_info_file)?;
 
         let new_toml_header = project_info_data
             .parse::<DocumentMut>()
-            .expect("invalid file metadata header");
+            .map_err(|err| cheese_error!("Error parsing {project_info_file:?}: {err}"))?;
 
         self.toml_header = new_toml_header;
 
@@ -653,6 +1691,62 @@ impl Project {
         None
     }
 
+    /// Every object with a body whose file uses CRLF line endings
+    fn find_crlf_files(&self) -> Vec<FileID> {
+        let mut crlf_files: Vec<FileID> = self
+            .objects
+            .iter()
+            .filter(|(_, object)| {
+                let object = object.borrow();
+                object.has_body() && has_crlf_line_endings(&object.get_file())
+            })
+            .map(|(file_id, _)| file_id.clone())
+            .collect();
+
+        crlf_files.sort();
+        crlf_files
+    }
+
+    /// Rewrite every file found with CRLF line endings to use LF instead
+    pub fn normalize_line_endings(&mut self) -> Result<(), CheeseError> {
+        for file_id in std::mem::take(&mut self.crlf_files) {
+            if let Some(object) = self.objects.get(&file_id) {
+                object.borrow_mut().normalize_line_endings()?;
+            }
+        }
+
+        self.save()
+    }
+
+    /// Move every recoverable temporary file into the place of the file it was going to replace.
+    /// The watcher picks up the recovered files like any other change on disk
+    pub fn recover_files(&mut self) -> Result<(), CheeseError> {
+        for recoverable in std::mem::take(&mut self.recoverable_files) {
+            log::info!(
+                "Recovering {:?} from {:?}",
+                recoverable.target_path,
+                recoverable.temp_path
+            );
+            std::fs::rename(&recoverable.temp_path, &recoverable.target_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Every object that changed on disk while it had unsaved changes, which need to be resolved
+    /// before they can be saved again
+    pub fn conflicted_objects(&self) -> Vec<FileID> {
+        let mut conflicted: Vec<FileID> = self
+            .objects
+            .iter()
+            .filter(|(_, object)| object.borrow().has_disk_conflict())
+            .map(|(file_id, _)| file_id.clone())
+            .collect();
+
+        conflicted.sort();
+        conflicted
+    }
+
     /// Given a FileID, try to find the FileID of its parent
     pub fn find_object_parent(&self, needle: &FileID) -> Option<FileID> {
         for object in self.objects.values() {
@@ -664,6 +1758,21 @@ impl Project {
         None
     }
 
+    /// If `path` is an image attached to an object, make that object look for its image on disk
+    /// again the next time it's needed
+    fn forget_image_at(&self, path: &Path) {
+        if !is_image_file(path) {
+            return;
+        }
+
+        if let Some(object_id) = path
+            .parent()
+            .and_then(|folder| self.find_object_by_path(folder))
+        {
+            self.objects[&object_id].borrow_mut().forget_image();
+        }
+    }
+
     pub fn remove_path_from_parent(&self, object_path: &Path) -> Option<FileID> {
         let object_id = self.find_object_by_path(object_path)?;
 
@@ -693,11 +1802,11 @@ impl Project {
 
         write_outline_property("Story Summary", &self.metadata.summary, &mut export_string);
 
-        for top_level_folder_id in &self.top_level_folders {
+        for (position, top_level_folder_id) in self.top_level_folders.iter().enumerate() {
             let folder = self.objects.get(top_level_folder_id).unwrap().borrow();
 
             if !folder.get_base().children.is_empty() {
-                if folder.get_base().metadata.name == "Text" {
+                if position == TEXT_FOLDER_POSITION {
                     export_string.push_str("# Scenes\n\n");
                 } else {
                     export_string.push_str(&format!("# {}\n\n", folder.get_base().metadata.name));
@@ -718,36 +1827,352 @@ impl Project {
         export_string
     }
 
+    /// Export every object in a top level folder (e.g., all of the characters) with all of their
+    /// metadata fields, for sharing worldbuilding without the rest of the project. Unlike
+    /// `export_text`, bodies aren't included.
+    pub fn export_dossier(&self, section: &FileID) -> String {
+        let mut export_string = String::new();
+
+        if let Some(folder) = self.objects.get(section) {
+            let folder = folder.borrow();
+
+            export_string.push_str("# ");
+            export_string.push_str(&folder.get_base().metadata.name);
+            export_string.push_str("\n\n");
+
+            for child_id in folder.get_base().children.iter() {
+                self.write_dossier_entry(child_id, 2, &mut export_string);
+            }
+        }
+
+        export_string
+    }
+
+    fn write_dossier_entry(&self, file_id: &FileID, depth: u64, export_string: &mut String) {
+        let Some(object) = self.objects.get(file_id) else {
+            return;
+        };
+        let object = object.borrow();
+
+        object.write_title(depth, export_string);
+
+        let has_body = object.get_type().has_body();
+        object.as_editor().for_each_textbox(&mut |text, name| {
+            // The body is the only text box called "text", everything else is metadata
+            if !(has_body && name == "text") {
+                write_outline_property(name, text, export_string);
+            }
+        });
+        Relationship::write_outline_list(object.relationships(), &self.objects, export_string);
+
+        for child_id in object.get_base().children.iter() {
+            self.write_dossier_entry(child_id, depth + 1, export_string);
+        }
+    }
+
+    /// A glossary of everything outside of the story text that can be in one (e.g., characters and
+    /// places) as a definition list, with a section for each top level folder. Empty if there's
+    /// nothing to put in it
+    pub fn export_glossary(&self, export_options: &ExportOptions) -> String {
+        let mut sections = String::new();
+
+        for (folder_id, folder_name) in self
+            .top_level_folders
+            .iter()
+            .zip(self.top_level_folder_names.iter())
+            .filter(|(id, _name)| *id != self.text_folder_id())
+        {
+            let mut entries = String::new();
+            for child_id in self.objects[folder_id].borrow().get_base().children.iter() {
+                self.write_glossary_entries(child_id, &mut entries);
+            }
+
+            if !entries.is_empty() {
+                sections.push_str(&format!(
+                    "{} {folder_name}\n\n",
+                    "#".repeat(export_options.heading_level(2) as usize)
+                ));
+                sections.push_str(&entries);
+            }
+        }
+
+        if sections.is_empty() {
+            return sections;
+        }
+
+        format!(
+            "{} Glossary\n\n{sections}",
+            "#".repeat(export_options.heading_level(1) as usize)
+        )
+    }
+
+    fn write_glossary_entries(&self, file_id: &FileID, export_string: &mut String) {
+        let Some(object) = self.objects.get(file_id) else {
+            return;
+        };
+        let object = object.borrow();
+
+        if let Some(summary) = object.glossary_summary() {
+            export_string.push_str(&object.get_title());
+            export_string.push('\n');
+
+            let summary = summary.trim();
+            if !summary.is_empty() {
+                // Continuation lines have to be indented to stay part of the definition
+                export_string.push_str(": ");
+                export_string.push_str(&summary.replace('\n', "\n    "));
+                export_string.push('\n');
+            }
+            export_string.push('\n');
+        }
+
+        for child_id in object.get_base().children.iter() {
+            self.write_glossary_entries(child_id, export_string);
+        }
+    }
+
     /// Export the story to a string (which can be written to a file)
     pub fn export_text(&self, export_options: ExportOptions) -> String {
-        let mut export_string = String::new();
+        let mut export_job = self.start_export(export_options);
+
+        while !export_job.is_done() {
+            self.continue_export(&mut export_job);
+        }
+
+        export_job.finish()
+    }
+
+    /// Export only some objects in the story (and everything inside of them), in the order they
+    /// appear in the tree. An object that's inside of another selected object is only exported once
+    pub fn export_selected_text(
+        &self,
+        selected: &[FileID],
+        export_options: ExportOptions,
+    ) -> String {
+        let mut export_job = self.start_export_selected(selected, export_options);
+
+        while !export_job.is_done() {
+            self.continue_export(&mut export_job);
+        }
+
+        export_job.finish()
+    }
+
+    /// Start exporting the story, which is done a piece at a time by `continue_export`
+    pub fn start_export(&self, export_options: ExportOptions) -> ExportJob {
+        self.start_export_selected(&[self.text_folder_id().clone()], export_options)
+    }
+
+    /// Start exporting some objects in the story, see `export_selected_text`
+    pub fn start_export_selected(
+        &self,
+        selected: &[FileID],
+        export_options: ExportOptions,
+    ) -> ExportJob {
+        let selected: HashSet<&FileID> = selected.iter().collect();
+        let mut remaining = VecDeque::new();
+
+        // The text folder itself doesn't get exported (only its contents), so start below it
+        if selected.contains(self.text_folder_id()) {
+            for child_id in self.text_folder_children() {
+                remaining.push_back((child_id, 1));
+            }
+        } else {
+            for child_id in self.text_folder_children() {
+                self.find_export_roots(&child_id, 1, &selected, &mut remaining);
+            }
+        }
+
+        let scenes_total = remaining
+            .iter()
+            .map(|(file_id, _depth)| self.count_scenes(file_id))
+            .sum();
+
+        let glossary = export_options
+            .append_glossary
+            .then(|| self.export_glossary(&export_options));
+
+        ExportJob {
+            export_options,
+            remaining,
+            export_string: String::new(),
+            word_count: 0,
+            include_break: false,
+            scenes_done: 0,
+            scenes_total,
+            glossary,
+        }
+    }
+
+    fn text_folder_children(&self) -> Vec<FileID> {
+        self.objects
+            .get(self.text_folder_id())
+            .unwrap()
+            .borrow()
+            .get_base()
+            .children
+            .clone()
+    }
+
+    /// Walk the tree in order, adding every selected object (without looking inside of it, since
+    /// its children get exported along with it)
+    fn find_export_roots(
+        &self,
+        file_id: &FileID,
+        depth: u64,
+        selected: &HashSet<&FileID>,
+        roots: &mut VecDeque<(FileID, u64)>,
+    ) {
+        if selected.contains(file_id) {
+            roots.push_back((file_id.clone(), depth));
+        } else if let Some(object) = self.objects.get(file_id) {
+            for child_id in object.borrow().get_base().children.iter() {
+                self.find_export_roots(child_id, depth + 1, selected, roots);
+            }
+        }
+    }
+
+    /// Export the next object in the job (along with everything inside of it)
+    pub fn continue_export(&self, export_job: &mut ExportJob) {
+        let Some((child_id, depth)) = export_job.remaining.pop_front() else {
+            return;
+        };
+
+        // The object could have been deleted since the export started
+        if let Some(child) = self.objects.get(&child_id) {
+            export_job.include_break = child.borrow().generate_export(
+                depth,
+                &mut export_job.export_string,
+                &mut export_job.word_count,
+                &self.objects,
+                &export_job.export_options,
+                export_job.include_break,
+            );
+        }
+
+        export_job.scenes_done += self.count_scenes(&child_id);
+    }
 
-        let mut include_break = false;
+    /// Summarize what would be included in an export of the story, so that accidentally excluded
+    /// scenes can be caught before sending it anywhere
+    pub fn export_summary(&self) -> ExportSummary {
+        let mut summary = ExportSummary::default();
 
         for child_id in self
             .objects
-            .get(&self.top_level_folders[TEXT_FOLDER_POSITION])
+            .get(self.text_folder_id())
             .unwrap()
             .borrow()
             .get_base()
             .children
             .iter()
         {
-            include_break = self
-                .objects
-                .get(child_id)
-                .unwrap()
-                .borrow()
-                .generate_export(
-                    1,
-                    &mut export_string,
-                    &self.objects,
-                    &export_options,
-                    include_break,
-                );
+            self.add_to_export_summary(child_id, true, &mut summary);
         }
 
-        export_string
+        summary
+    }
+
+    /// Count the scenes in an object (and its children), along with the words in the included
+    /// ones. Scenes are only included if they and every folder they're in are included
+    fn add_to_export_summary(
+        &self,
+        file_id: &FileID,
+        parent_included: bool,
+        summary: &mut ExportSummary,
+    ) {
+        let Some(object) = self.objects.get(file_id) else {
+            return;
+        };
+        let object = object.borrow();
+
+        let Some(compile_status) = object.compile_status() else {
+            // not something that ever gets exported
+            return;
+        };
+
+        let included = parent_included && compile_status.contains(CompileStatus::INCLUDE);
+
+        if object.has_body() {
+            summary.scenes_total += 1;
+            if included {
+                summary.scenes_included += 1;
+
+                // Counted the same way the export counts them, so that everything adds up
+                let body = strip_comments(&object.get_body());
+                summary.words += count_export_words(&body);
+                let (sentences, paragraphs) = compiled_prose_counts(&body);
+                summary.sentences += sentences;
+                summary.paragraphs += paragraphs;
+
+                if !object.is_folder() && body.trim().is_empty() {
+                    summary.empty_scenes.push(object.get_title());
+                }
+            }
+        }
+
+        for child_id in object.get_base().children.iter() {
+            self.add_to_export_summary(child_id, included, summary);
+        }
+    }
+
+    /// The object with a body (e.g., a scene) whose file was changed most recently
+    pub fn most_recently_modified_scene(&self) -> Option<FileID> {
+        self.objects
+            .iter()
+            .filter_map(|(file_id, object)| {
+                let object = object.borrow();
+                if object.has_body() {
+                    object
+                        .get_base()
+                        .file
+                        .modtime
+                        .map(|modtime| (modtime, file_id.clone()))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(modtime, _file_id)| *modtime)
+            .map(|(_modtime, file_id)| file_id)
+    }
+
+    /// Every scene in the text, in reading order (depth first, with the contents of each folder in
+    /// order), skipping over the folders themselves
+    pub fn linear_scene_order(&self) -> Vec<FileID> {
+        let mut order = Vec::new();
+        self.add_to_scene_order(self.text_folder_id(), &mut order);
+        order
+    }
+
+    fn add_to_scene_order(&self, file_id: &FileID, order: &mut Vec<FileID>) {
+        let Some(object) = self.objects.get(file_id) else {
+            return;
+        };
+        let object = object.borrow();
+
+        if object.has_body() && !object.is_folder() {
+            order.push(file_id.clone());
+        }
+
+        for child_id in object.get_base().children.iter() {
+            self.add_to_scene_order(child_id, order);
+        }
+    }
+
+    /// Count the objects with bodies in (and including) an object
+    fn count_scenes(&self, file_id: &FileID) -> usize {
+        let Some(object) = self.objects.get(file_id) else {
+            return 0;
+        };
+        let object = object.borrow();
+
+        usize::from(object.has_body())
+            + object
+                .get_base()
+                .children
+                .iter()
+                .map(|child_id| self.count_scenes(child_id))
+                .sum::<usize>()
     }
 
     pub fn resolve_references(&mut self) {
@@ -785,6 +2210,11 @@ impl Project {
                                 ProjectPathKind::Contents | ProjectPathKind::ProjectFile
                             )
                         }) {
+                            if self.is_own_save(&event) {
+                                log::debug!("Ignoring event from our own save: {event:?}");
+                                continue;
+                            }
+
                             // We now have an event that seems to be something we care about
                             self.event_queue.push_back(event);
                             self.last_added_event = Some(Instant::now());
@@ -828,6 +2258,12 @@ impl Project {
         // any new elements in a list to scan
         let queued_events: Vec<DebouncedEvent> = self.event_queue.drain(..).collect();
         for event in queued_events {
+            // Attached images aren't objects, but the object they belong to has to look for its
+            // image again
+            for path in &event.paths {
+                self.forget_image_at(path);
+            }
+
             match event.kind {
                 EventKind::Create(_create_kind) => {
                     let modify_path = event.paths.first().unwrap().to_owned();
@@ -1015,6 +2451,7 @@ impl Project {
 
         // 7. Any other steps
         self.resolve_references();
+        self.search_index.refresh_if_built(&self.objects);
 
         true
     }
@@ -1107,10 +2544,9 @@ impl Project {
 
                 // We assume that any files that don't have an extension are folders but this
                 // function doesn't check the disk, so we can't verify if it's actually a folder
-                if modify_path
-                    .extension()
-                    .is_some_and(|extension| extension != "md" && extension != "toml")
-                {
+                if modify_path.extension().is_some_and(|extension| {
+                    !self.is_active_extension(&extension.to_string_lossy())
+                }) {
                     // we write .tmp files and then immediately remove them and other editors can do the same
                     // we also don't care about files that other programs generate
                     return ProjectPathKind::UnrecognizedExtension;
@@ -1138,7 +2574,7 @@ impl Project {
 
     #[cfg(test)]
     pub fn get_text_folder(&self) -> &RefCell<Box<dyn FileObject>> {
-        self.objects.get(&self.top_level_folders[0]).unwrap()
+        self.objects.get(self.text_folder_id()).unwrap()
     }
 }
 
@@ -1159,12 +2595,273 @@ fn u64_to_i64_drop_msb(val: u64) -> i64 {
     (val & MSB_MASK) as i64
 }
 
+#[derive(Debug)]
 pub struct ExportOptions {
     pub folder_title_depth: ExportDepth,
     pub scene_title_depth: ExportDepth,
     pub insert_breaks: bool,
+    /// added to the level of every heading written by the export
+    pub heading_offset: u64,
+    /// end the export with the number of words that were exported
+    pub append_word_count: bool,
+    pub folder_title_style: TitleStyle,
+    pub scene_title_style: TitleStyle,
+    /// keep single line breaks in scenes that don't say otherwise
+    pub preserve_hard_breaks: bool,
+    /// put a `<!-- scene: ... -->` comment before the text of each scene
+    pub scene_metadata_comments: bool,
+    /// end the story with a glossary (see `Project::export_glossary`)
+    pub append_glossary: bool,
+    /// remove markdown syntax from scenes, see `strip_markdown`
+    pub strip_markdown: bool,
+}
+
+/// An export of the story that gets built up one top level object at a time (see
+/// `Project::continue_export`), so that the UI can keep drawing during a long export
+#[derive(Debug)]
+pub struct ExportJob {
+    export_options: ExportOptions,
+    /// Objects that haven't been exported yet, along with their depth in the tree. For a full
+    /// export, these are the objects at the top of the text folder
+    remaining: VecDeque<(FileID, u64)>,
+    export_string: String,
+    /// Words in the bodies that have been exported so far
+    word_count: usize,
+    include_break: bool,
+    scenes_done: usize,
+    scenes_total: usize,
+    /// Added after the story when it's done, if the options ask for it
+    glossary: Option<String>,
+}
+
+impl ExportJob {
+    pub fn is_done(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    /// How much of the export is done (by number of scenes), between 0 and 1
+    pub fn progress(&self) -> f32 {
+        if self.scenes_total == 0 {
+            1.0
+        } else {
+            self.scenes_done as f32 / self.scenes_total as f32
+        }
+    }
+
+    /// Get the exported text, should only be called once `is_done`
+    pub fn finish(mut self) -> String {
+        debug_assert!(self.is_done());
+
+        // Each scene's footnotes were kept separate, so they can be numbered across the whole export
+        self.export_string = renumber_footnotes(&self.export_string);
+
+        if let Some(glossary) = &self.glossary {
+            self.export_string.push_str(glossary);
+        }
+
+        // Only the scene bodies were counted, so the glossary isn't part of the total
+        if self.export_options.append_word_count {
+            self.export_string.push_str(&format!(
+                "Total: {} words\n",
+                format_thousands(self.word_count)
+            ));
+        }
+
+        self.export_string
+    }
+}
+
+/// What an export of the story would contain, see `Project::export_summary`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    pub scenes_included: usize,
+    pub scenes_total: usize,
+    pub words: usize,
+    pub sentences: usize,
+    pub paragraphs: usize,
+    /// Titles of the included scenes that don't have anything to export (their bodies are empty,
+    /// whitespace, or only comments), in story order
+    pub empty_scenes: Vec<String>,
+}
+
+impl ExportSummary {
+    /// The mean number of words in a sentence, or `None` if there aren't any sentences
+    pub fn average_sentence_length(&self) -> Option<f64> {
+        (self.sentences > 0).then(|| self.words as f64 / self.sentences as f64)
+    }
+
+    /// The sentence and paragraph counts, which are too much to fit into the main summary line
+    pub fn prose_details(&self) -> String {
+        let mut details = format!(
+            "{} {} in {} {}",
+            format_thousands(self.sentences),
+            if self.sentences == 1 {
+                "sentence"
+            } else {
+                "sentences"
+            },
+            format_thousands(self.paragraphs),
+            if self.paragraphs == 1 {
+                "paragraph"
+            } else {
+                "paragraphs"
+            }
+        );
+        if let Some(average) = self.average_sentence_length() {
+            details.push_str(&format!(", {average:.1} words per sentence"));
+        }
+        details
+    }
+}
+
+impl std::fmt::Display for ExportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} scenes included, {} words",
+            self.scenes_included,
+            self.scenes_total,
+            format_thousands(self.words)
+        )
+    }
+}
+
+/// Count the sentences and paragraphs in a body that's being exported, skipping headings and
+/// breaks. Paragraphs are separated by blank lines. Sentences end with `.`, `!`, or `?` (a run of
+/// them, like `?!` or `...`, only ends one), and the end of a paragraph ends any sentence that's
+/// still going. This is only a heuristic, so abbreviations like "Mr." will be
+/// counted as the end of a sentence
+fn compiled_prose_counts(export_string: &str) -> (usize, usize) {
+    let mut sentences = 0;
+    let mut paragraphs = 0;
+
+    let mut in_paragraph = false;
+    let mut in_sentence = false;
+
+    for line in export_string.lines() {
+        if line.starts_with('#') || line.trim() == "----" || line.trim().is_empty() {
+            in_paragraph = false;
+            if in_sentence {
+                sentences += 1;
+                in_sentence = false;
+            }
+            continue;
+        }
+
+        if !in_paragraph {
+            paragraphs += 1;
+            in_paragraph = true;
+        }
+
+        for c in line.chars() {
+            if matches!(c, '.' | '!' | '?') {
+                if in_sentence {
+                    sentences += 1;
+                    in_sentence = false;
+                }
+            } else if c.is_alphanumeric() {
+                in_sentence = true;
+            }
+        }
+    }
+
+    if in_sentence {
+        sentences += 1;
+    }
+
+    (sentences, paragraphs)
+}
+
+#[test]
+fn test_compiled_prose_counts() {
+    let text = "One fish. Two fish?! \"Red fish,\" she said...\n\
+        Blue fish\n\
+        \n\
+        # Heading\n\
+        A second paragraph. Version 2.5 counts twice\n\
+        \n\
+        ----\n\
+        \n\
+        \n\
+        Third!\n";
+    assert_eq!(compiled_prose_counts(text), (8, 3));
+    assert_eq!(compiled_prose_counts(""), (0, 0));
+    assert_eq!(compiled_prose_counts("...\n\n"), (0, 1));
+}
+
+/// Format a number with commas between every group of three digits (e.g., `12,345`)
+fn format_thousands(number: usize) -> String {
+    let digits = number.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+#[test]
+fn test_format_thousands() {
+    assert_eq!(format_thousands(0), "0");
+    assert_eq!(format_thousands(999), "999");
+    assert_eq!(format_thousands(1000), "1,000");
+    assert_eq!(format_thousands(12345), "12,345");
+    assert_eq!(format_thousands(1234567), "1,234,567");
+}
+
+/// Markdown doesn't have headings past `######`
+const MAX_HEADING_LEVEL: u64 = 6;
+
+impl ExportOptions {
+    /// The heading level for a title at `depth` in the tree, after applying the offset
+    pub fn heading_level(&self, depth: u64) -> u64 {
+        depth
+            .saturating_add(self.heading_offset)
+            .min(MAX_HEADING_LEVEL)
+    }
+}
+
+/// How a title is written in the export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleStyle {
+    /// A markdown heading at the object's depth (`## Title`)
+    Heading,
+    /// A paragraph of bold text (`**Title**`), which doesn't show up in a table of contents
+    Bold,
+    /// Not written at all, even for objects that always include their title
+    None,
+}
+
+impl TitleStyle {
+    pub const ALL: [TitleStyle; 3] = [TitleStyle::Heading, TitleStyle::Bold, TitleStyle::None];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TitleStyle::Heading => "heading",
+            TitleStyle::Bold => "bold",
+            TitleStyle::None => "none",
+        }
+    }
+}
+
+impl std::str::FromStr for TitleStyle {
+    type Err = CheeseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TitleStyle::ALL
+            .into_iter()
+            .find(|style| style.as_str() == s)
+            .ok_or_else(|| {
+                cheese_error!("Unknown title style {s:?}, expected heading, bold, or none")
+            })
+    }
 }
 
+#[derive(Debug)]
 pub enum ExportDepth {
     All,
     Some(u64),
@@ -0,0 +1,117 @@
+use crate::components::file_objects::utils::get_index_from_name;
+use crate::components::project::Project;
+use crate::components::schema::preload_headers;
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+/// A problem found by `check_project`
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProjectIssue {
+    /// The project couldn't be loaded at all
+    LoadFailed(String),
+    /// A file that looks like it's part of the project, but couldn't be parsed
+    Unparseable { file: PathBuf, reason: String },
+    /// The same id in more than one file, only one of them can be loaded
+    DuplicateId { id: String, files: Vec<PathBuf> },
+    /// A reference from an object to something that doesn't exist
+    BrokenReference { file: PathBuf, reference: String },
+    /// An object whose filename doesn't match its place in its folder
+    Indexing { file: PathBuf, index: usize },
+}
+
+impl Display for ProjectIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LoadFailed(err) => write!(f, "could not load project: {err}"),
+            Self::Unparseable { file, reason } => {
+                write!(f, "{}: could not parse header: {reason}", file.display())
+            }
+            Self::DuplicateId { id, files } => {
+                let files: Vec<_> = files
+                    .iter()
+                    .map(|file| file.display().to_string())
+                    .collect();
+                write!(f, "duplicate id {id} in {}", files.join(", "))
+            }
+            Self::BrokenReference { file, reference } => {
+                write!(f, "{}: broken reference to {reference}", file.display())
+            }
+            Self::Indexing { file, index } => {
+                write!(f, "{}: filename should have index {index}", file.display())
+            }
+        }
+    }
+}
+
+/// Look for problems in a project without changing anything on disk. Orphaned objects are cleaned
+/// up while loading, so anything reported here is something that loading couldn't fix by itself
+pub fn check_project(path: &Path) -> Vec<ProjectIssue> {
+    let mut issues = Vec::new();
+
+    let mut files_by_id: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for (file, preloaded) in preload_headers(path) {
+        match preloaded.header {
+            Ok(header) => {
+                if let Some(id) = header.get("id").and_then(|id| id.as_str()) {
+                    files_by_id.entry(id.to_string()).or_default().push(file);
+                }
+            }
+            Err(reason) => issues.push(ProjectIssue::Unparseable { file, reason }),
+        }
+    }
+
+    for (id, mut files) in files_by_id {
+        if files.len() > 1 {
+            files.sort();
+            issues.push(ProjectIssue::DuplicateId { id, files });
+        }
+    }
+
+    // Loading can't handle duplicate ids (the same object would end up in the tree twice), so
+    // there's no point in continuing until those are fixed
+    if issues
+        .iter()
+        .any(|issue| matches!(issue, ProjectIssue::DuplicateId { .. }))
+    {
+        return issues;
+    }
+
+    let project = match Project::load_read_only(path.to_path_buf()) {
+        Ok(project) => project,
+        Err(err) => {
+            issues.push(ProjectIssue::LoadFailed(err.to_string()));
+            return issues;
+        }
+    };
+
+    let mut object_issues = Vec::new();
+
+    for object in project.objects.values() {
+        let object = object.borrow();
+
+        for reference in object.broken_references() {
+            object_issues.push(ProjectIssue::BrokenReference {
+                file: object.get_path(),
+                reference,
+            });
+        }
+
+        // Indexes get fixed up while loading, but (since nothing is saved) not the filenames
+        if let Some(index) = object.get_base().index
+            && get_index_from_name(&object.get_base().file.basename.to_string_lossy())
+                != Some(index)
+        {
+            object_issues.push(ProjectIssue::Indexing {
+                file: object.get_path(),
+                index,
+            });
+        }
+    }
+
+    object_issues.sort_by_key(|issue| issue.to_string());
+    issues.extend(object_issues);
+
+    issues
+}
@@ -0,0 +1,168 @@
+use crate::cheese_error;
+use crate::components::project::Project;
+use crate::util::CheeseError;
+
+use regex::Regex;
+
+/// A find and replace that's ready to be applied to text. With `use_regex`, the replacement can
+/// refer to groups captured by the pattern (`$1`, `$name`, or `${1}` when followed by something
+/// that could be part of a name), and `$$` is a literal `$`. Otherwise, both are used as is
+#[derive(Debug)]
+pub struct Replacement {
+    regex: Regex,
+    replacement: String,
+}
+
+impl Replacement {
+    pub fn new(find: &str, replacement: &str, use_regex: bool) -> Result<Self, CheeseError> {
+        if find.is_empty() {
+            return Err(cheese_error!("Nothing to find"));
+        }
+
+        let pattern = if use_regex {
+            find.to_string()
+        } else {
+            regex::escape(find)
+        };
+
+        let regex =
+            Regex::new(&pattern).map_err(|err| cheese_error!("Invalid pattern {find:?}: {err}"))?;
+
+        let replacement = if use_regex {
+            check_group_references(&regex, replacement)?;
+            replacement.to_string()
+        } else {
+            replacement.replace('$', "$$")
+        };
+
+        Ok(Self { regex, replacement })
+    }
+
+    /// Replace every match in `text`, or `None` if there weren't any
+    pub fn apply(&self, text: &str) -> Option<String> {
+        if !self.regex.is_match(text) {
+            return None;
+        }
+
+        Some(
+            self.regex
+                .replace_all(text, self.replacement.as_str())
+                .into_owned(),
+        )
+    }
+}
+
+/// Make sure every group that `replacement` refers to exists in `regex`. The `regex` crate quietly
+/// replaces missing groups with nothing, which would delete text without any warning. This follows
+/// the same rules as the crate for what counts as a reference: the longest run of letters, numbers
+/// and underscores after the `$`, or anything in braces
+fn check_group_references(regex: &Regex, replacement: &str) -> Result<(), CheeseError> {
+    let mut rest = replacement;
+
+    while let Some(dollar) = rest.find('$') {
+        rest = &rest[dollar + 1..];
+
+        if let Some(after) = rest.strip_prefix('$') {
+            rest = after;
+            continue;
+        }
+
+        let name = if let Some(braced) = rest.strip_prefix('{')
+            && let Some(end) = braced.find('}')
+        {
+            rest = &braced[end + 1..];
+            &braced[..end]
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            name
+        };
+
+        // A `$` that isn't followed by a name is just a `$`
+        if name.is_empty() {
+            continue;
+        }
+
+        let exists = match name.parse::<usize>() {
+            Ok(index) => index < regex.captures_len(),
+            Err(_) => regex.capture_names().flatten().any(|group| group == name),
+        };
+
+        if !exists {
+            return Err(cheese_error!(
+                "Replacement refers to group ${{{name}}}, but the pattern only has {} group(s){}",
+                regex.captures_len() - 1,
+                if name.starts_with(|c: char| c.is_ascii_digit()) {
+                    " (use ${1} to put text directly after a group)"
+                } else {
+                    ""
+                }
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_replacement() {
+    let replacement = Replacement::new(r"(\w+) said", "$1 whispered", true).unwrap();
+    assert_eq!(
+        replacement.apply("\"Hello,\" Anna said. Then Ben said nothing."),
+        Some("\"Hello,\" Anna whispered. Then Ben whispered nothing.".to_string())
+    );
+    assert_eq!(replacement.apply("no dialogue here"), None);
+
+    let replacement = Replacement::new(r"(?<name>\w+) said", "${name}, $$5", true).unwrap();
+    assert_eq!(replacement.apply("Anna said").unwrap(), "Anna, $5");
+
+    // without regex, everything is literal
+    let replacement = Replacement::new("(a)", "$1", false).unwrap();
+    assert_eq!(replacement.apply("b(a)").unwrap(), "b$1");
+
+    // missing groups are an error instead of quietly deleting text
+    assert!(Replacement::new(r"(\w+) said", "$2 whispered", true).is_err());
+    assert!(Replacement::new(r"(\w+) said", "$1x whispered", true).is_err());
+    assert!(Replacement::new(r"(\w+) said", "${1}x whispered", true).is_ok());
+    assert!(Replacement::new(r"\w+ said", "$ whispered", true).is_ok());
+
+    assert!(Replacement::new(r"(\w+", "", true).is_err());
+    assert!(Replacement::new("", "anything", false).is_err());
+}
+
+impl Project {
+    /// Apply a replacement to the body of every object that has one (unless it's locked), returning
+    /// how many bodies were changed. The changes are saved like any other edit
+    pub fn replace_all(&mut self, replacement: &Replacement) -> usize {
+        let mut changed_count = 0;
+
+        for object in self.objects.values() {
+            let mut object = object.borrow_mut();
+            if !object.has_body() || object.is_locked() {
+                continue;
+            }
+
+            let mut changed = false;
+            object
+                .as_editor_mut()
+                .for_each_textbox_mut(&mut |text, name| {
+                    if name == "text"
+                        && let Some(new_text) = replacement.apply(text)
+                    {
+                        **text = new_text;
+                        changed = true;
+                    }
+                });
+
+            if changed {
+                object.get_base_mut().file.mark_edited();
+                changed_count += 1;
+            }
+        }
+
+        changed_count
+    }
+}
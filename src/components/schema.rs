@@ -8,6 +8,7 @@ use crate::{
 use std::path::Path;
 
 pub use crate::schemas::FileType;
+pub use tools::{PreloadedHeaders, preload_headers};
 
 pub trait Schema {
     /// the id string used in metadata files to identify this schema
@@ -32,6 +33,8 @@ pub trait Schema {
         base: BaseFileObject,
     ) -> Result<Box<dyn FileObject>, CheeseError>;
 
+    /// Create a file object from one that was read from disk. `body` may be `None` even for types
+    /// that have a body, in which case it gets read from disk the first time it's used
     fn load_file_object(
         &self,
         file_type: FileType,
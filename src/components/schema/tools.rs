@@ -1,8 +1,11 @@
-use crate::components::file_objects::utils::{get_index_from_name, read_file_contents};
+use crate::components::file_objects::utils::{
+    ALTERNATE_BODY_EXTENSIONS, BodyScan, get_index_from_name, get_index_width_from_name,
+    is_image_file, read_file_header, scan_file_body, write_with_temp_file,
+};
 use crate::components::file_objects::{FileInfo, FileObjectMetadata};
 use crate::components::schema::{FileType, Schema};
 
-use std::cell::RefCell;
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fs::create_dir;
@@ -13,10 +16,136 @@ use toml_edit::DocumentMut;
 use crate::cheese_error;
 
 use crate::components::file_objects::{
-    BaseFileObject, FOLDER_METADATA_FILE_NAME, FileID, FileObject, FileObjectStore,
+    BaseFileObject, DEFAULT_INDEX_WIDTH, FOLDER_METADATA_FILE_NAME, FileID, FileObject,
+    FileObjectStore,
 };
 use crate::util::CheeseError;
 
+/// File headers that have already been read and parsed, keyed by the path of the file they came
+/// from
+pub type PreloadedHeaders = HashMap<PathBuf, PreloadedFile>;
+
+/// What `preload_headers` found out about a file
+#[derive(Debug)]
+pub struct PreloadedFile {
+    /// Parse errors are kept as strings so that they can be reported when the file is loaded
+    pub header: Result<DocumentMut, String>,
+    pub body_scan: BodyScan,
+}
+
+/// Called when an object is about to be replaced by one of a different type. If the old object
+/// had a body, it gets written next to the new file (as `<file>.recovered`) instead of being
+/// dropped, since the new object has no way to hold onto it
+fn preserve_reclassified_body(
+    object: &dyn FileObject,
+    new_type: FileType,
+    filename: &Path,
+    save_changes: bool,
+) {
+    log::error!(
+        "{object} was a {} but is being reloaded from {filename:?} as a {}",
+        object.type_name(),
+        new_type.type_name()
+    );
+
+    if !object.has_body() || !save_changes {
+        return;
+    }
+
+    let body = object.get_body();
+    if body.trim().is_empty() {
+        return;
+    }
+
+    let mut recovered_path = filename.as_os_str().to_owned();
+    recovered_path.push(".recovered");
+    let mut counter = 1;
+    while Path::new(&recovered_path).exists() {
+        counter += 1;
+        recovered_path = filename.as_os_str().to_owned();
+        recovered_path.push(format!(".{counter}.recovered"));
+    }
+
+    match write_with_temp_file(&recovered_path, body) {
+        Ok(()) => log::error!("The body of {object} was saved to {recovered_path:?}"),
+        Err(err) => log::error!("Failed to save the body of {object} to {recovered_path:?}: {err}"),
+    }
+}
+
+/// Find every file that could be loaded under `folder`
+fn collect_loadable_files(folder: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::debug!("Could not read {folder:?} while preloading: {err}");
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            // Hidden folders (e.g., `.git` and `.backups`) aren't part of the project
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                collect_loadable_files(&path, files);
+            }
+        } else if path.extension().is_some_and(|extension| {
+            extension == "toml"
+                || extension == "md"
+                || ALTERNATE_BODY_EXTENSIONS.contains(&&*extension.to_string_lossy())
+        }) {
+            files.push(path);
+        }
+    }
+}
+
+/// Read and parse the headers of every file under `folder`, spread across a few threads. File
+/// objects themselves can't be sent between threads, so they still need to be created on the main
+/// thread with `load_file_preloaded`, but that's the cheap part.
+///
+/// Files that can't be read are left out, and will be read (and reported) again while loading
+pub fn preload_headers(folder: &Path) -> PreloadedHeaders {
+    let mut files = Vec::new();
+    collect_loadable_files(folder, &mut files);
+
+    if files.is_empty() {
+        return PreloadedHeaders::new();
+    }
+
+    let thread_count = std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1);
+    let chunk_size = files.len().div_ceil(thread_count);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = files
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|path| {
+                            let header = read_file_header(path).ok()?;
+                            let preloaded = PreloadedFile {
+                                header: header
+                                    .parse::<DocumentMut>()
+                                    .map_err(|err| err.to_string()),
+                                body_scan: scan_file_body(path),
+                            };
+                            Some((path.clone(), preloaded))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("preloading thread panicked"))
+            .collect()
+    })
+}
+
 impl PartialEq for dyn Schema {
     fn eq(&self, other: &Self) -> bool {
         self.get_schema_identifier() == other.get_schema_identifier()
@@ -192,6 +321,19 @@ impl dyn Schema {
         &self,
         filename: &Path,
         objects: &mut FileObjectStore,
+    ) -> Result<FileID, CheeseError> {
+        self.load_file_preloaded(filename, objects, &mut PreloadedHeaders::new(), true)
+    }
+
+    /// Load a file object like `load_file`, using (and removing) any headers in `preloaded` instead
+    /// of reading them from disk again. If `save_changes` is false, nothing on disk is changed
+    /// (e.g., old files aren't converted and filenames aren't reindexed), only the objects in memory
+    pub fn load_file_preloaded(
+        &self,
+        filename: &Path,
+        objects: &mut FileObjectStore,
+        preloaded: &mut PreloadedHeaders,
+        save_changes: bool,
     ) -> Result<FileID, CheeseError> {
         if !filename.exists() {
             return Err(cheese_error!(
@@ -199,11 +341,19 @@ impl dyn Schema {
             ));
         }
 
-        // We process every dir, but only `.toml` or `.md` files
-        if !filename.is_dir()
-            && filename
+        let extension = match filename.is_dir() {
+            true => None,
+            false => filename
                 .extension()
-                .is_none_or(|extension| extension != "toml" && extension != "md")
+                .and_then(|extension| extension.to_str()),
+        };
+        let alternate_extension =
+            extension.is_some_and(|extension| ALTERNATE_BODY_EXTENSIONS.contains(&extension));
+
+        // We process every dir, but only `.toml` or `.md` files (or other body extensions)
+        if !filename.is_dir()
+            && !alternate_extension
+            && extension.is_none_or(|extension| extension != "toml" && extension != "md")
         {
             return Err(cheese_error!(
                 "from_file cannot load file {filename:?} with unknown extension"
@@ -233,22 +383,35 @@ impl dyn Schema {
             false => filename.to_path_buf(),
         };
 
-        let (metadata_str, file_body) = read_file_contents(&underlying_file).or_else(|err| {
-            if filename.is_dir() {
-                Ok(("".to_string(), None))
-            } else {
-                Err(cheese_error!(
-                    "Failed to read file {underlying_file:?}: {err}"
-                ))
+        let body_scan = match preloaded.get(&underlying_file) {
+            Some(preloaded_file) => preloaded_file.body_scan,
+            None => scan_file_body(&underlying_file),
+        };
+
+        let toml_header = match preloaded.remove(&underlying_file) {
+            Some(preloaded_file) => preloaded_file
+                .header
+                .map_err(|err| cheese_error!("Error parsing {underlying_file:?}: {err}"))?,
+            None => {
+                // Only the header is read here, bodies are read lazily when they're first needed
+                let metadata_str = read_file_header(&underlying_file).or_else(|err| {
+                    if filename.is_dir() {
+                        Ok("".to_string())
+                    } else {
+                        Err(cheese_error!(
+                            "Failed to read file {underlying_file:?}: {err}"
+                        ))
+                    }
+                })?;
+
+                metadata_str
+                    .parse::<DocumentMut>()
+                    .map_err(|err| cheese_error!("Error parsing {underlying_file:?}: {err}"))?
             }
-        })?;
+        };
 
         let mut metadata = FileObjectMetadata::default();
 
-        let toml_header = metadata_str
-            .parse::<DocumentMut>()
-            .map_err(|err| cheese_error!("Error parsing {underlying_file:?}: {err}"))?;
-
         if !toml_header.contains_key("name") {
             let file_name = PathBuf::from(&basename)
                 .file_stem()
@@ -282,12 +445,27 @@ impl dyn Schema {
             None => None,
         };
 
+        if alternate_extension && file_type_identifier.is_none() {
+            return Err(cheese_error!(
+                "{filename:?} doesn't say what type of object it is, not loading it"
+            ));
+        }
+
         let file_type: FileType = self.resolve_type(filename, file_type_identifier)?;
 
+        // Remember any extension that isn't the default, so that saving doesn't rename the file
+        let extension = extension
+            .filter(|extension| !file_type.is_folder() && *extension != file_type.extension())
+            .map(|extension| extension.to_string());
+
         let mut children = Vec::new();
 
+        // Some types (e.g., characters) are only turned into folders once they have children
+        let converted_to_folder =
+            !file_type.is_folder() && file_type.can_nest() && filename.is_dir();
+
         // Load children of this file object
-        if file_type.is_folder() {
+        if file_type.is_folder() || converted_to_folder {
             if !filename.is_dir() {
                 return Err(cheese_error!(
                     "{filename:?} has a folder-based file_type, but isn't actually a directory",
@@ -309,8 +487,14 @@ impl dyn Schema {
 
                         let file_path = file.path();
 
+                        // Attached images belong to this object, they aren't children
+                        if is_image_file(&file_path) {
+                            continue;
+                        }
+
                         // Just read the children in any order, we'll clean it up later
-                        match self.load_file(&file_path, objects) {
+                        match self.load_file_preloaded(&file_path, objects, preloaded, save_changes)
+                        {
                             Ok(child_id) => children.push(child_id.clone()),
                             Err(err) => log::debug!("Could not load child {file:?}: {err}"),
                         }
@@ -321,6 +505,22 @@ impl dyn Schema {
         }
 
         let index = get_index_from_name(&basename.to_string_lossy());
+        let index_width =
+            get_index_width_from_name(&basename.to_string_lossy()).unwrap_or(DEFAULT_INDEX_WIDTH);
+
+        // An object that changed type (e.g., a scene that's now a folder) can't be updated in
+        // place, so it gets replaced by a new object instead
+        if let Some(existing_file_id) = toml_header
+            .get("id")
+            .and_then(|id_item| id_item.as_str())
+            .map(|id_str| FileID::new(id_str.to_owned()))
+            && objects
+                .get(&existing_file_id)
+                .is_some_and(|object| object.borrow().get_type() != file_type)
+        {
+            let old_object = objects.remove(&existing_file_id).unwrap();
+            preserve_reclassified_body(&**old_object.borrow(), file_type, filename, save_changes);
+        }
 
         // Check if we're loading a file object that we already know about
         if let Some(existing_file_id) = toml_header
@@ -335,6 +535,9 @@ impl dyn Schema {
 
             file_object.get_base_mut().file.dirname = dirname;
             file_object.get_base_mut().file.basename = basename;
+            file_object.get_base_mut().file.converted_to_folder = converted_to_folder;
+            file_object.get_base_mut().file.extension = extension;
+            file_object.get_base_mut().file.index_width = index_width;
 
             file_object.get_base_mut().index = index;
 
@@ -347,8 +550,17 @@ impl dyn Schema {
             let mut file_info = FileInfo {
                 dirname,
                 basename,
+                converted_to_folder,
                 modtime: None,
                 modified,
+                edited: false,
+                extension,
+                index_width,
+                child_index_width: DEFAULT_INDEX_WIDTH,
+                disk_conflict: None,
+                conflict_markers: body_scan.conflict_markers,
+                word_count: body_scan.word_count,
+                image_name: OnceCell::new(),
             };
 
             metadata
@@ -367,9 +579,13 @@ impl dyn Schema {
 
             let file_id = base.metadata.id.clone();
 
-            let mut file_object = self.load_file_object(file_type, base, file_body)?;
+            let mut file_object = self.load_file_object(file_type, base, None)?;
 
-            file_object.rescan_indexing(objects, false);
+            if save_changes {
+                file_object.rescan_indexing(objects, false);
+            } else {
+                file_object.rescan_indexing_in_memory(objects);
+            }
 
             objects.insert(file_id.clone(), RefCell::new(file_object));
 
@@ -405,15 +621,7 @@ impl dyn Schema {
         dirname: PathBuf,
         name: &str,
     ) -> Result<Box<dyn FileObject>, CheeseError> {
-        let file_type = self.get_top_level_folder_type();
-        assert!(file_type.is_folder());
-
-        let mut base = BaseFileObject::new(dirname, None);
-
-        base.metadata.name = name.to_string();
-        base.file.basename = OsString::from(name.to_lowercase());
-
-        let mut file_object = self.init_file_object(file_type, base)?;
+        let mut file_object = self.new_top_level_folder(dirname, name)?;
 
         create_dir(file_object.get_path()).map_err(|err| {
             cheese_error!("Failed to create top-level directory: {}: {err}", name)
@@ -428,4 +636,21 @@ impl dyn Schema {
 
         Ok(file_object)
     }
+
+    /// Creates a top level folder like `create_top_level_folder`, but only in memory
+    pub fn new_top_level_folder(
+        &self,
+        dirname: PathBuf,
+        name: &str,
+    ) -> Result<Box<dyn FileObject>, CheeseError> {
+        let file_type = self.get_top_level_folder_type();
+        assert!(file_type.is_folder());
+
+        let mut base = BaseFileObject::new(dirname, None);
+
+        base.metadata.name = name.to_string();
+        base.file.basename = OsString::from(name.to_lowercase());
+
+        self.init_file_object(file_type, base)
+    }
 }
@@ -0,0 +1,184 @@
+use crate::components::file_objects::{FileID, FileObject, FileObjectStore};
+use crate::components::text::TextUID;
+
+use std::collections::{HashMap, HashSet};
+
+/// An inverted index from the words in every text box to the objects that contain them. This is
+/// only used to shortlist objects before doing the actual (exact) search, so it's fine for it to
+/// return extra candidates, but it must never leave out an object that contains a match.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    postings: HashMap<String, HashSet<FileID>>,
+    objects: HashMap<FileID, IndexedObject>,
+    /// The index isn't built until the first search, since building it reads every text box
+    built: bool,
+}
+
+#[derive(Debug)]
+struct IndexedObject {
+    /// The text boxes (and their versions) that the tokens were computed from. A reloaded object
+    /// gets new text boxes, so this also catches changes from disk
+    text_versions: Vec<(TextUID, usize)>,
+    tokens: HashSet<String>,
+}
+
+/// Split text into maximal runs of alphanumeric characters, along with their byte offsets
+fn token_spans(text: &str) -> Vec<(usize, &str)> {
+    let mut spans = Vec::new();
+    let mut token_start = None;
+
+    for (index, c) in text.char_indices() {
+        match (c.is_alphanumeric(), token_start) {
+            (true, None) => token_start = Some(index),
+            (false, Some(start)) => {
+                spans.push((start, &text[start..index]));
+                token_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = token_start {
+        spans.push((start, &text[start..]));
+    }
+
+    spans
+}
+
+#[test]
+fn test_token_spans() {
+    assert_eq!(
+        token_spans("  Héllo, wörld!x"),
+        vec![(2, "Héllo"), (10, "wörld"), (17, "x")]
+    );
+    assert!(token_spans("... ").is_empty());
+}
+
+fn text_versions(object: &dyn FileObject) -> Vec<(TextUID, usize)> {
+    let mut versions = Vec::new();
+    object
+        .as_editor()
+        .for_each_textbox(&mut |text, _name| versions.push((text.id(), text.version)));
+    versions
+}
+
+impl SearchIndex {
+    /// Throw away everything and index every object from scratch
+    pub fn rebuild(&mut self, objects: &FileObjectStore) {
+        self.postings.clear();
+        self.objects.clear();
+        self.refresh(objects);
+    }
+
+    /// Like `refresh`, but does nothing if the index hasn't been built yet. This keeps background
+    /// updates from reading the body of every object before anything has been searched
+    pub fn refresh_if_built(&mut self, objects: &FileObjectStore) {
+        if self.built {
+            self.refresh(objects);
+        }
+    }
+
+    /// Bring the index up to date with the objects, only re-tokenizing objects whose text has
+    /// changed since they were last indexed
+    pub fn refresh(&mut self, objects: &FileObjectStore) {
+        self.built = true;
+
+        let removed: Vec<FileID> = self
+            .objects
+            .keys()
+            .filter(|file_id| !objects.contains_key(*file_id))
+            .cloned()
+            .collect();
+
+        for file_id in removed {
+            self.remove_object(&file_id);
+        }
+
+        for (file_id, object) in objects {
+            self.update_object(file_id, &**object.borrow());
+        }
+    }
+
+    /// Index an object if it's new or any of its text has changed
+    pub fn update_object(&mut self, file_id: &FileID, object: &dyn FileObject) {
+        let versions = text_versions(object);
+
+        if self
+            .objects
+            .get(file_id)
+            .is_some_and(|indexed| indexed.text_versions == versions)
+        {
+            return;
+        }
+
+        self.remove_object(file_id);
+
+        let mut object_tokens = HashSet::new();
+        object.as_editor().for_each_textbox(&mut |text, _name| {
+            object_tokens.extend(
+                token_spans(text)
+                    .into_iter()
+                    .map(|(_, token)| token.to_owned()),
+            );
+        });
+
+        for token in &object_tokens {
+            self.postings
+                .entry(token.clone())
+                .or_default()
+                .insert(file_id.clone());
+        }
+
+        self.objects.insert(
+            file_id.clone(),
+            IndexedObject {
+                text_versions: versions,
+                tokens: object_tokens,
+            },
+        );
+    }
+
+    pub fn remove_object(&mut self, file_id: &FileID) {
+        if let Some(indexed) = self.objects.remove(file_id) {
+            for token in indexed.tokens {
+                if let Some(posting) = self.postings.get_mut(&token) {
+                    posting.remove(file_id);
+                    if posting.is_empty() {
+                        self.postings.remove(&token);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find every object that might contain `query` (a case-sensitive substring search). Returns
+    /// `None` if the query can't be narrowed down, meaning every object needs to be searched.
+    ///
+    /// A word in the middle of the query has to match a word in the text exactly, but the words at
+    /// either end of the query could be part of a longer word, so those are matched against every
+    /// word in the index that contains them.
+    pub fn candidates(&self, query: &str) -> Option<HashSet<FileID>> {
+        let mut candidates: Option<HashSet<FileID>> = None;
+
+        for (start, token) in token_spans(query) {
+            let bounded = start > 0 && start + token.len() < query.len();
+
+            let matching: HashSet<FileID> = if bounded {
+                self.postings.get(token).cloned().unwrap_or_default()
+            } else {
+                self.postings
+                    .iter()
+                    .filter(|(indexed_token, _)| indexed_token.contains(token))
+                    .flat_map(|(_, file_ids)| file_ids.iter().cloned())
+                    .collect()
+            };
+
+            candidates = Some(match candidates {
+                Some(candidates) => candidates.intersection(&matching).cloned().collect(),
+                None => matching,
+            });
+        }
+
+        candidates
+    }
+}
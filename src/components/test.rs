@@ -1,9 +1,13 @@
 use crate::components::Schema;
+use crate::components::backup::BACKUP_FOLDER_NAME;
 use crate::components::file_objects::FileObjectStore;
+use crate::components::schema::preload_headers;
 
 use crate::components::file_objects::{FileID, FileObject, utils::write_with_temp_file};
 
-use crate::components::project::Project;
+use crate::components::project::{
+    DEFAULT_TOP_LEVEL_FOLDERS, ExportDepth, ExportOptions, Project, TitleStyle, find_project_cover,
+};
 use crate::util::CheeseError;
 use std::collections::HashMap;
 use std::ffi::OsString;
@@ -61,6 +65,24 @@ fn process_updates_after_save(project: &mut Project) {
     process_updates(project);
 }
 
+/// Export options that only include the text (no titles or breaks), so tests can check exactly
+/// what the export contains
+fn plain_export_options() -> ExportOptions {
+    ExportOptions {
+        folder_title_depth: ExportDepth::None,
+        scene_title_depth: ExportDepth::None,
+        insert_breaks: false,
+        heading_offset: 0,
+        append_word_count: false,
+        folder_title_style: TitleStyle::Heading,
+        scene_title_style: TitleStyle::Heading,
+        preserve_hard_breaks: false,
+        scene_metadata_comments: false,
+        append_glossary: false,
+        strip_markdown: false,
+    }
+}
+
 /// Helper to get the file id from a path
 fn get_id_from_file(filename: &Path) -> Option<FileID> {
     use toml_edit::DocumentMut;
@@ -140,6 +162,38 @@ fn test_basic_create_project() {
     );
 }
 
+#[test]
+fn test_create_project_invalid_name() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let err = Project::new(SCHEMA, base_dir.path().to_path_buf(), "///".to_string())
+        .err()
+        .expect("name without any usable characters should be rejected");
+    assert!(
+        err.to_string()
+            .contains("project name produced an empty or invalid folder name")
+    );
+    assert_eq!(read_dir(base_dir.path()).unwrap().count(), 0);
+
+    Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "Test Project".to_string(),
+    )
+    .unwrap();
+
+    // Only differs by case, so it would be the same folder on some filesystems
+    assert!(
+        Project::new(
+            SCHEMA,
+            base_dir.path().to_path_buf(),
+            "test project".to_string(),
+        )
+        .is_err()
+    );
+    assert_eq!(read_dir(base_dir.path()).unwrap().count(), 1);
+}
+
 #[test]
 /// Ensure that file_objects are created properly
 fn test_basic_create_file_object() -> Result<(), CheeseError> {
@@ -791,6 +845,54 @@ fn test_name_from_filename() {
     assert!(folder.get_path().join("000-scene2.md").exists());
 }
 
+/// Reload a scene that has turned into a folder on disk, which shouldn't lose the scene's body
+#[test]
+fn test_reclassified_scene_body_preserved() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let text_path = SCHEMA
+        .create_top_level_folder(base_dir.path().to_path_buf(), "Text")
+        .unwrap()
+        .get_path();
+
+    write_with_temp_file(
+        text_path.join("000-scene.md"),
+        "id = \"1\"\nfile_type = \"scene\"\n++++++++\nold words",
+    )
+    .unwrap();
+
+    let mut objects = FileObjectStore::new();
+    SCHEMA.load_file(&text_path, &mut objects).unwrap();
+
+    // unsaved changes to the body
+    objects
+        .get(&file_id("1"))
+        .unwrap()
+        .borrow_mut()
+        .load_body("precious words".to_string());
+
+    std::fs::remove_file(text_path.join("000-scene.md")).unwrap();
+    std::fs::create_dir(text_path.join("000-scene")).unwrap();
+    write_with_temp_file(
+        text_path.join("000-scene/metadata.toml"),
+        "id = \"1\"\nfile_type = \"folder\"",
+    )
+    .unwrap();
+
+    SCHEMA.load_file(&text_path, &mut objects).unwrap();
+
+    assert_eq!(
+        objects.get(&file_id("1")).unwrap().borrow().get_type(),
+        FOLDER
+    );
+    assert_eq!(
+        read_to_string(text_path.join("000-scene.recovered"))
+            .unwrap()
+            .trim(),
+        "precious words"
+    );
+}
+
 /// Load various files with indexes out of order (and some missing) and verify that they all get indexed correctly
 #[test]
 fn test_fix_indexing_on_load() {
@@ -852,6 +954,45 @@ contents123"#,
     assert_eq!(child.borrow().get_body(), "contents123\n");
 }
 
+/// Indexes should be compared as numbers when loading, not as strings
+#[test]
+fn test_natural_sort_on_load() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let text_path = SCHEMA
+        .create_top_level_folder(base_dir.path().to_path_buf(), "Text")
+        .unwrap()
+        .get_path();
+
+    for (filename, id) in [
+        ("100-scene.md", "100"),
+        ("10-scene.md", "10"),
+        ("2-scene.md", "2"),
+        ("b_unindexed.md", "b"),
+        ("a_unindexed.md", "a"),
+    ] {
+        write_with_temp_file(text_path.join(filename), format!("id = \"{id}\"\n++++++++")).unwrap();
+    }
+
+    let mut objects = FileObjectStore::new();
+
+    let folder_id = SCHEMA.load_file(&text_path, &mut objects).unwrap();
+    let folder = objects.get(&folder_id).unwrap().borrow();
+
+    assert_eq!(
+        folder.get_base().children,
+        vec![
+            file_id("2"),
+            file_id("10"),
+            file_id("100"),
+            file_id("a"),
+            file_id("b")
+        ]
+    );
+    assert!(text_path.join("000-scene.md").exists());
+    assert!(text_path.join("002-scene.md").exists());
+}
+
 /// Try to delete a file object, verifying it gets removed from disk
 #[test]
 fn test_delete() {
@@ -2515,6 +2656,157 @@ fn test_place_nesting() {
     assert_eq!(place2.get_base().index, Some(0));
 }
 
+#[test]
+fn test_can_hold() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut text = SCHEMA
+        .create_top_level_folder(base_dir.path().to_path_buf(), "Text")
+        .unwrap();
+
+    let folder = text.create_child_at_end(FOLDER).unwrap();
+    let place = text.create_child_at_end(PLACE).unwrap();
+    let character = text.create_child_at_end(CHARACTER).unwrap();
+    let scene = text.create_child_at_end(SCENE).unwrap();
+
+    assert!(folder.can_hold(SCENE));
+    assert!(folder.can_hold(PLACE));
+    assert!(place.can_hold(PLACE));
+    assert!(!place.can_hold(SCENE));
+    assert!(!place.can_hold(FOLDER));
+    assert!(!character.can_hold(SCENE));
+    assert!(!scene.can_hold(SCENE));
+}
+
+#[test]
+fn test_character_nesting() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut characters = SCHEMA
+        .create_top_level_folder(base_dir.path().to_path_buf(), "Characters")
+        .unwrap();
+
+    let mut family = characters.create_child_at_end(CHARACTER).unwrap();
+
+    let member = family.create_child_at_end(CHARACTER).unwrap();
+
+    assert!(member.get_file().exists());
+    assert!(member.get_file().starts_with(family.get_path()));
+    assert_eq!(family.get_base().index, Some(0));
+    assert_eq!(member.get_base().index, Some(0));
+}
+
+#[test]
+fn test_character_nesting_export() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let characters_id = project.top_level_folder_id("Characters").unwrap().clone();
+
+    let mut family = project
+        .objects
+        .get(&characters_id)
+        .unwrap()
+        .borrow_mut()
+        .create_child_at_end(CHARACTER)
+        .unwrap();
+    family.get_base_mut().metadata.name = "The Smiths".to_string();
+
+    let mut member = family.create_child_at_end(CHARACTER).unwrap();
+    member.get_base_mut().metadata.name = "Alice Smith".to_string();
+    *member.get_test_field() = "tall".to_string();
+    let member_id = member.id().clone();
+
+    project.add_object(family);
+    project.add_object(member);
+    project.save().unwrap();
+
+    let dossier = project.export_dossier(&characters_id);
+    assert!(dossier.contains("## The Smiths\n\n"));
+    assert!(dossier.contains("### Alice Smith\n\nappearance: tall\n"));
+
+    let mut outline = String::new();
+    project
+        .objects
+        .get(&characters_id)
+        .unwrap()
+        .borrow()
+        .generate_outline(1, &mut outline, &project.objects);
+    assert!(outline.contains("### Alice Smith\n"));
+
+    // The nesting survives reloading
+    let project = Project::load(project.get_path()).unwrap();
+    let member = project.objects.get(&member_id).unwrap().borrow();
+    let family_path = member.get_base().file.dirname.clone();
+    assert!(family_path.ends_with("000-The_Smiths"));
+}
+
+#[test]
+fn test_character_file_converted_to_folder() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    // open and immediately drop the project (just creating the files)
+    Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let characters_path = base_dir.path().join("test_project/characters");
+    write_with_temp_file(
+        characters_path.join("000-alice.toml"),
+        "id = \"1\"\nfile_type = \"character\"\nappearance = \"tall\"",
+    )
+    .unwrap();
+
+    // Loading a character without anything inside of it leaves it as a single file
+    let mut project = Project::load(base_dir.path().join("test_project")).unwrap();
+    {
+        let character = project.objects.get(&file_id("1")).unwrap().borrow();
+        assert_eq!(character.get_type(), CHARACTER);
+        assert!(!character.is_folder());
+        assert_eq!(character.get_path(), characters_path.join("000-alice.toml"));
+    }
+    assert!(!characters_path.join("000-alice").exists());
+
+    // Putting something inside of it is what turns it into a folder
+    let child = project
+        .objects
+        .get(&file_id("1"))
+        .unwrap()
+        .borrow_mut()
+        .create_child_at_end(CHARACTER)
+        .unwrap();
+    let child_id = child.id().clone();
+    project.add_object(child);
+    project.save().unwrap();
+
+    {
+        let character = project.objects.get(&file_id("1")).unwrap().borrow();
+        assert!(character.is_folder());
+        assert_eq!(character.get_path(), characters_path.join("000-alice"));
+    }
+    assert!(!characters_path.join("000-alice.toml").exists());
+    assert!(
+        read_to_string(characters_path.join("000-alice/metadata.toml"))
+            .unwrap()
+            .contains("appearance = \"tall\"")
+    );
+
+    // And it stays one when loaded again
+    let project = Project::load(base_dir.path().join("test_project")).unwrap();
+    let character = project.objects.get(&file_id("1")).unwrap().borrow();
+    assert!(character.is_folder());
+    assert_eq!(character.get_base().children, vec![child_id]);
+}
+
 #[test]
 fn test_place_loading() {
     let base_dir = tempfile::TempDir::new().unwrap();
@@ -2584,6 +2876,117 @@ file_type = "worldbuilding""#;
     );
 }
 
+#[test]
+fn test_place_image() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let worldbuilding_id = project.top_level_folders[2].clone();
+    let place = project
+        .objects
+        .get(&worldbuilding_id)
+        .unwrap()
+        .borrow_mut()
+        .create_child_at_end(PLACE)
+        .unwrap();
+    let place_id = place.id().clone();
+    project.add_object(place);
+    project.save().unwrap();
+
+    let place_path = project.objects.get(&place_id).unwrap().borrow().get_path();
+    assert_eq!(
+        project
+            .objects
+            .get(&place_id)
+            .unwrap()
+            .borrow()
+            .find_image(),
+        None
+    );
+
+    // Not actually a valid image, but we only care about the file being copied
+    let source_image = base_dir.path().join("reference.PNG");
+    std::fs::write(&source_image, "not really a png").unwrap();
+
+    let copied_image = project
+        .objects
+        .get(&place_id)
+        .unwrap()
+        .borrow_mut()
+        .set_image(&source_image)
+        .unwrap();
+
+    assert_eq!(copied_image, place_path.join("image.png"));
+    assert_eq!(read_to_string(&copied_image).unwrap(), "not really a png");
+    assert_eq!(
+        project
+            .objects
+            .get(&place_id)
+            .unwrap()
+            .borrow()
+            .find_image(),
+        Some(copied_image.clone())
+    );
+
+    // Replacing the image keeps the new one, even when it's the same file
+    let other_image = base_dir.path().join("other.jpg");
+    std::fs::write(&other_image, "not really a jpg").unwrap();
+    let place = project.objects.get(&place_id).unwrap();
+    let copied_image = place.borrow_mut().set_image(&other_image).unwrap();
+    assert_eq!(copied_image, place_path.join("image.jpg"));
+    assert!(!place_path.join("image.png").exists());
+
+    place.borrow_mut().set_image(&copied_image).unwrap();
+    assert_eq!(read_to_string(&copied_image).unwrap(), "not really a jpg");
+    assert_eq!(place.borrow().find_image(), Some(copied_image.clone()));
+
+    // Unknown extensions are rejected
+    let not_an_image = base_dir.path().join("notes.txt");
+    std::fs::write(&not_an_image, "text").unwrap();
+    assert!(
+        project
+            .objects
+            .get(&place_id)
+            .unwrap()
+            .borrow_mut()
+            .set_image(&not_an_image)
+            .is_err()
+    );
+
+    // The image should still be found after a reload, and not get picked up as a child
+    drop(project);
+    let mut project = Project::load(base_dir.path().join("test_project")).unwrap();
+
+    assert_eq!(project.objects.len(), 4);
+    assert_eq!(
+        project
+            .objects
+            .get(&place_id)
+            .unwrap()
+            .borrow()
+            .find_image(),
+        Some(copied_image.clone())
+    );
+
+    // The folder is only looked through once, until the image is forgotten (e.g., by the watcher)
+    std::fs::remove_file(&copied_image).unwrap();
+    let mut place = project.objects.get(&place_id).unwrap().borrow_mut();
+    assert_eq!(place.find_image(), Some(copied_image.clone()));
+    place.forget_image();
+    assert_eq!(place.find_image(), None);
+    drop(place);
+
+    // Removing the place should also remove the image (and the folder)
+    <dyn FileObject>::remove_child(&place_id, &worldbuilding_id, &mut project.objects).unwrap();
+    assert!(!place_path.exists());
+}
+
 #[test]
 fn test_tracker_creation_basic() {
     let base_dir = tempfile::TempDir::new().unwrap();
@@ -5238,3 +5641,3532 @@ contents1
         folder_path.canonicalize().unwrap().join("000-scene.md"),
     );
 }
+
+#[test]
+fn test_export_strips_comments() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body(
+        "The door opened %% check the timeline here %% slowly.\n\n\
+        %% this whole paragraph\nis just for me %%\n\n\
+        She walked in."
+            .to_string(),
+    );
+    scene.get_base_mut().file.modified = true;
+    let scene_path = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    let export = project.export_text(plain_export_options());
+
+    assert!(!export.contains("%%"));
+    assert!(!export.contains("timeline"));
+    assert!(!export.contains("just for me"));
+    assert_eq!(export, "The door opened slowly.\n\nShe walked in.\n\n");
+
+    // The comments are still there on disk
+    let scene_text = read_to_string(scene_path).unwrap();
+    assert!(scene_text.contains("%% check the timeline here %%"));
+    assert!(scene_text.contains("is just for me %%"));
+}
+
+#[test]
+fn test_label_color_round_trip() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut colored = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    colored.get_base_mut().metadata.label_color = Some([200, 40, 16]);
+    let colored_id = colored.id().clone();
+    let colored_path = colored.get_file();
+    project.add_object(colored);
+
+    let plain = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    let plain_id = plain.id().clone();
+    let plain_path = plain.get_file();
+    project.add_object(plain);
+
+    project.save().unwrap();
+
+    assert!(read_to_string(&colored_path).unwrap().contains("#c82810"));
+    // Objects without a color shouldn't write anything
+    assert!(!read_to_string(&plain_path).unwrap().contains("label_color"));
+
+    let mut project = Project::load(base_dir.path().join("test_project")).unwrap();
+
+    assert_eq!(
+        project
+            .objects
+            .get(&colored_id)
+            .unwrap()
+            .borrow()
+            .get_base()
+            .metadata
+            .label_color,
+        Some([200, 40, 16])
+    );
+    assert_eq!(
+        project
+            .objects
+            .get(&plain_id)
+            .unwrap()
+            .borrow()
+            .get_base()
+            .metadata
+            .label_color,
+        None
+    );
+
+    // Clearing the color removes it from the file
+    {
+        let mut colored = project.objects.get(&colored_id).unwrap().borrow_mut();
+        colored.get_base_mut().metadata.label_color = None;
+        colored.get_base_mut().file.modified = true;
+    }
+    project.save().unwrap();
+
+    assert!(
+        !read_to_string(&colored_path)
+            .unwrap()
+            .contains("label_color")
+    );
+}
+
+#[test]
+fn test_search_index_matches_linear_scan() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let bodies = [
+        "The lighthouse keeper counted ships.",
+        "Ships passed in the night, one after another.",
+        "A quiet morning. Nobody counted anything.",
+        "Die Straße war leer, und the keeper schlief.",
+        "",
+    ];
+
+    let mut scene_ids = Vec::new();
+    for body in bodies {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.load_body(body.to_string());
+        scene_ids.push(scene.id().clone());
+        project.add_object(scene);
+    }
+
+    fn linear_matches(project: &Project, query: &str) -> Vec<FileID> {
+        let mut matches: Vec<FileID> = project
+            .objects
+            .iter()
+            .filter(|(_, object)| {
+                let mut found = false;
+                object
+                    .borrow()
+                    .as_editor()
+                    .for_each_textbox(&mut |text, _| found |= text.contains(query));
+                found
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        matches.sort();
+        matches
+    }
+
+    fn indexed_matches(project: &Project, query: &str) -> Vec<FileID> {
+        let candidates = project.search_index.candidates(query);
+
+        let mut matches: Vec<FileID> = linear_matches(project, query)
+            .into_iter()
+            .filter(|id| {
+                candidates
+                    .as_ref()
+                    .is_none_or(|candidates| candidates.contains(id))
+            })
+            .collect();
+        matches.sort();
+        matches
+    }
+
+    let queries = [
+        "keeper",
+        "eeper",
+        "ships",
+        "Ships",
+        "counted ships",
+        "ted shi",
+        "night, one",
+        ". Nobody",
+        "Straße",
+        "raß",
+        "nothing like this",
+        "",
+        " ",
+        "...",
+    ];
+
+    project.search_index.rebuild(&project.objects);
+
+    for query in queries {
+        assert_eq!(
+            indexed_matches(&project, query),
+            linear_matches(&project, query),
+            "query: {query:?}"
+        );
+    }
+
+    // Whole words in the middle of a query are narrowed down precisely
+    assert_eq!(
+        project.search_index.candidates("the keeper counted"),
+        Some(std::collections::HashSet::from([scene_ids[0].clone()]))
+    );
+
+    // Editing a scene is picked up by the next refresh
+    project
+        .objects
+        .get(&scene_ids[4])
+        .unwrap()
+        .borrow_mut()
+        .as_editor_mut()
+        .for_each_textbox_mut(&mut |text, _| {
+            if text.is_empty() {
+                text.push_str("A keeper of secrets.");
+            }
+        });
+    project.search_index.refresh(&project.objects);
+
+    for query in queries {
+        assert_eq!(
+            indexed_matches(&project, query),
+            linear_matches(&project, query),
+            "query: {query:?}"
+        );
+    }
+    assert!(
+        project
+            .search_index
+            .candidates("keeper")
+            .unwrap()
+            .contains(&scene_ids[4])
+    );
+}
+
+#[test]
+fn test_lazy_body_loading() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("It was a dark and stormy night.".to_string());
+    scene.get_base_mut().metadata.name = "Storm".to_string();
+    scene.get_base_mut().file.modified = true;
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    let mut project = Project::load(base_dir.path().join("test_project")).unwrap();
+
+    // Only the metadata has been read
+    {
+        let scene = project.objects.get(&scene_id).unwrap().borrow();
+        assert!(!scene.is_body_loaded());
+        assert_eq!(scene.get_base().metadata.name, "Storm");
+    }
+
+    // Saving metadata changes (including a rename) still writes the (unloaded) body back out
+    {
+        let mut scene = project.objects.get(&scene_id).unwrap().borrow_mut();
+        scene.get_base_mut().metadata.name = "Calm".to_string();
+        scene.get_base_mut().file.modified = true;
+    }
+    project.save().unwrap();
+
+    let scene = project.objects.get(&scene_id).unwrap().borrow();
+    assert!(scene.get_file().ends_with("000-Calm.md"));
+    assert!(
+        read_to_string(scene.get_file())
+            .unwrap()
+            .contains("It was a dark and stormy night.")
+    );
+
+    assert_eq!(scene.get_body(), "It was a dark and stormy night.\n");
+    assert!(scene.is_body_loaded());
+}
+
+#[test]
+fn test_unreadable_lazy_body_not_saved() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("It was a dark and stormy night.".to_string());
+    scene.get_base_mut().file.modified = true;
+    let scene_id = scene.id().clone();
+    let scene_path = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    let mut project = Project::load(base_dir.path().join("test_project")).unwrap();
+
+    // The body isn't valid UTF-8 anymore, so it can't be read
+    let original = std::fs::read(&scene_path).unwrap();
+    let mut unreadable = original.clone();
+    unreadable.extend_from_slice(&[0xff, 0xfe]);
+    std::fs::write(&scene_path, &unreadable).unwrap();
+
+    {
+        let scene = project.objects.get(&scene_id).unwrap().borrow();
+        assert!(scene.load_unloaded_body().is_err());
+        assert_eq!(scene.get_body(), "\n");
+        assert!(!scene.is_body_loaded());
+    }
+
+    // Saving a metadata change has to fail instead of writing the empty placeholder
+    {
+        let mut scene = project.objects.get(&scene_id).unwrap().borrow_mut();
+        scene.get_base_mut().metadata.label_color = Some([0xff, 0, 0]);
+        scene.get_base_mut().file.modified = true;
+    }
+    assert!(project.save().is_err());
+    assert_eq!(std::fs::read(&scene_path).unwrap(), unreadable);
+
+    // Once the file can be read again, the read is retried and the save goes through
+    std::fs::write(&scene_path, &original).unwrap();
+    project.save().unwrap();
+
+    let contents = read_to_string(&scene_path).unwrap();
+    assert!(contents.contains("It was a dark and stormy night."));
+    assert!(contents.contains("label_color"));
+}
+
+/// Create a project with `scene_count` scenes spread across a few folders, returning the path of
+/// the text folder
+fn write_synthetic_project(base_dir: &Path, scene_count: usize) -> std::path::PathBuf {
+    let project = Project::new(SCHEMA, base_dir.to_path_buf(), "test project".to_string()).unwrap();
+    let text_path = project.get_text_folder().borrow().get_path();
+
+    for folder_index in 0..10 {
+        let folder_path = text_path.join(format!("{folder_index:03}-part_{folder_index}"));
+        create_dir(&folder_path).unwrap();
+        write_with_temp_file(
+            &folder_path.join("metadata.toml"),
+            format!(
+                "name = \"Part {folder_index}\"\nid = \"part-{folder_index}\"\n\
+                file_type = \"folder\"\n"
+            ),
+        )
+        .unwrap();
+
+        for scene_index in 0..scene_count / 10 {
+            write_with_temp_file(
+                &folder_path.join(format!("{scene_index:03}-scene.md")),
+                format!(
+                    "name = \"Scene {folder_index}-{scene_index}\"\n\
+                    id = \"scene-{folder_index}-{scene_index}\"\nfile_type = \"scene\"\n\
+                    summary = \"\"\nnotes = \"\"\npov = \"\"\ncompile_status = 1\n\
+                    ++++++++\n\n{}",
+                    "All work and no play makes a dull story. ".repeat(200)
+                ),
+            )
+            .unwrap();
+        }
+    }
+
+    text_path
+}
+
+/// Every object's id, name, and index, for comparing loads
+fn summarize_objects(objects: &FileObjectStore) -> Vec<(FileID, String, Option<usize>)> {
+    let mut summary: Vec<_> = objects
+        .iter()
+        .map(|(id, object)| {
+            let object = object.borrow();
+            (
+                id.clone(),
+                object.get_base().metadata.name.clone(),
+                object.get_base().index,
+            )
+        })
+        .collect();
+    summary.sort();
+    summary
+}
+
+#[test]
+fn test_preloaded_load_matches_sequential() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+    let text_path = write_synthetic_project(base_dir.path(), 50);
+
+    let mut sequential_objects = FileObjectStore::new();
+    let sequential_id = SCHEMA
+        .load_file(&text_path, &mut sequential_objects)
+        .unwrap();
+
+    let mut preloaded = preload_headers(&text_path);
+    // 10 folders, 50 scenes, and the text folder itself
+    assert_eq!(preloaded.len(), 61);
+
+    let mut parallel_objects = FileObjectStore::new();
+    let parallel_id = SCHEMA
+        .load_file_preloaded(&text_path, &mut parallel_objects, &mut preloaded, true)
+        .unwrap();
+
+    // everything should have been used
+    assert!(preloaded.is_empty());
+    assert_eq!(sequential_id, parallel_id);
+    assert_eq!(parallel_objects.len(), 61);
+    assert_eq!(
+        summarize_objects(&sequential_objects),
+        summarize_objects(&parallel_objects)
+    );
+}
+
+/// Not a real test, run with `cargo test --release bench_parallel_load -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn bench_parallel_load() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+    let text_path = write_synthetic_project(base_dir.path(), 1000);
+
+    // warm up the filesystem cache so that both runs are reading from memory
+    SCHEMA
+        .load_file(&text_path, &mut FileObjectStore::new())
+        .unwrap();
+
+    let start = time::Instant::now();
+    let mut sequential_objects = FileObjectStore::new();
+    SCHEMA
+        .load_file(&text_path, &mut sequential_objects)
+        .unwrap();
+    let sequential_duration = start.elapsed();
+
+    let start = time::Instant::now();
+    let mut parallel_objects = FileObjectStore::new();
+    SCHEMA
+        .load_file_preloaded(
+            &text_path,
+            &mut parallel_objects,
+            &mut preload_headers(&text_path),
+            true,
+        )
+        .unwrap();
+    let parallel_duration = start.elapsed();
+
+    assert_eq!(
+        summarize_objects(&sequential_objects),
+        summarize_objects(&parallel_objects)
+    );
+
+    println!(
+        "loaded {} objects: sequential {sequential_duration:?}, parallel {parallel_duration:?}",
+        parallel_objects.len()
+    );
+}
+
+#[test]
+fn test_invalid_toml_returns_error() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let text_id = project.top_level_folders[0].clone();
+    let scene = project
+        .objects
+        .get(&text_id)
+        .unwrap()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    let scene_id = scene.get_base().metadata.id.clone();
+    let scene_path = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    // Corrupt the scene header, reloading it should fail without panicking
+    thread::sleep(MTIME_SLEEP_DURATION);
+    write_with_temp_file(&scene_path, "name = \"unterminated\n++++++++\nbody\n").unwrap();
+
+    let reload_result = project
+        .objects
+        .get(&scene_id)
+        .unwrap()
+        .borrow_mut()
+        .reload_file();
+    assert!(reload_result.is_err());
+
+    // Same with the project file
+    thread::sleep(MTIME_SLEEP_DURATION);
+    write_with_temp_file(project.get_project_info_file(), "[[not toml").unwrap();
+    assert!(project.reload_file().is_err());
+
+    let project_path = project.get_path();
+    drop(project);
+
+    assert!(Project::load(project_path).is_err());
+}
+
+#[test]
+fn test_custom_top_level_folder() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    assert_eq!(project.top_level_folder_names, DEFAULT_TOP_LEVEL_FOLDERS);
+
+    let research_id = project.add_top_level_folder("Research").unwrap();
+    assert!(project.add_top_level_folder("research").is_err());
+
+    let note = project
+        .objects
+        .get(&research_id)
+        .unwrap()
+        .borrow_mut()
+        .create_child_at_end(PLACE)
+        .unwrap();
+    let note_id = note.id().clone();
+    project.add_object(note);
+    project.save().unwrap();
+
+    assert!(project.get_path().join("research").is_dir());
+
+    let project_path = project.get_path();
+    drop(project);
+
+    let project = Project::load(project_path).unwrap();
+
+    assert_eq!(
+        project.top_level_folder_names,
+        ["Text", "Characters", "Worldbuilding", "Research"]
+    );
+    assert_eq!(project.top_level_folders.len(), 4);
+    assert_eq!(project.top_level_folders[3], research_id);
+    assert_eq!(
+        project
+            .objects
+            .get(&research_id)
+            .unwrap()
+            .borrow()
+            .get_base()
+            .children,
+        vec![note_id]
+    );
+}
+
+#[test]
+fn test_arbitrary_top_level_folders() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new_with_top_level_folders(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+        &["Manuscript", "Research"],
+    )
+    .unwrap();
+
+    assert_eq!(project.top_level_folders.len(), 2);
+    assert_eq!(
+        project.top_level_folder_id("manuscript"),
+        Some(project.text_folder_id())
+    );
+    let research_id = project.top_level_folder_id("Research").unwrap().clone();
+    assert!(project.top_level_folder_id("Characters").is_none());
+
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    let project_path = project.get_path();
+    drop(project);
+
+    let project = Project::load(project_path).unwrap();
+
+    assert_eq!(project.top_level_folder_names, ["Manuscript", "Research"]);
+    assert_eq!(project.top_level_folder_id("research"), Some(&research_id));
+    assert_eq!(
+        project.get_text_folder().borrow().get_base().children,
+        vec![scene_id]
+    );
+}
+
+#[test]
+fn test_export_dossier() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let characters_id = project.top_level_folder_id("Characters").unwrap().clone();
+
+    for (name, notes) in [
+        ("Alice", "keeps a lighthouse"),
+        ("Bob", "afraid of the dark"),
+    ] {
+        let mut character = project
+            .objects
+            .get(&characters_id)
+            .unwrap()
+            .borrow_mut()
+            .create_child_at_end(CHARACTER)
+            .unwrap();
+        character.get_base_mut().metadata.name = name.to_string();
+        character
+            .as_editor_mut()
+            .for_each_textbox_mut(&mut |text, field_name| {
+                if field_name == "notes" {
+                    text.push_str(notes);
+                }
+            });
+        project.add_object(character);
+    }
+
+    let dossier = project.export_dossier(&characters_id);
+
+    assert!(dossier.starts_with("# Characters\n\n"));
+    assert!(dossier.contains("## Alice\n\nnotes: keeps a lighthouse\n"));
+    assert!(dossier.contains("## Bob\n\nnotes: afraid of the dark\n"));
+    assert!(dossier.find("Alice").unwrap() < dossier.find("Bob").unwrap());
+}
+
+#[test]
+fn test_timeline_order() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    // Reading order is different from the order things happen in
+    let mut scene_ids = Vec::new();
+    for story_date in [
+        Some("1204-06-01"),
+        None,
+        Some("1203-12-24"),
+        Some("1204-01-15"),
+    ] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+
+        if let Some(story_date) = story_date {
+            scene
+                .get_base_mut()
+                .toml_header
+                .insert("story_date", toml_edit::value(story_date));
+            scene.load_metadata().unwrap();
+        }
+
+        scene_ids.push(scene.id().clone());
+        project.add_object(scene);
+    }
+
+    let expected_order = vec![
+        scene_ids[2].clone(),
+        scene_ids[3].clone(),
+        scene_ids[0].clone(),
+        scene_ids[1].clone(),
+    ];
+
+    let timeline_order = |project: &Project| -> Vec<FileID> {
+        project
+            .timeline()
+            .into_iter()
+            .map(|entry| entry.file_id)
+            .collect()
+    };
+
+    assert_eq!(timeline_order(&project), expected_order);
+    assert_eq!(
+        project.timeline()[0].story_date.as_deref(),
+        Some("1203-12-24")
+    );
+
+    // The dates survive a round trip through the disk
+    project.save().unwrap();
+    let project_path = project.get_path();
+    drop(project);
+
+    let project = Project::load(project_path).unwrap();
+    assert_eq!(timeline_order(&project), expected_order);
+}
+
+#[test]
+fn test_word_goal_round_trip() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    assert_eq!(project.metadata.word_goal, None);
+
+    project.metadata.word_goal = Some(80_000);
+    project.file.modified = true;
+    project.save().unwrap();
+
+    let project_path = project.get_path();
+    drop(project);
+
+    let mut project = Project::load(project_path.clone()).unwrap();
+    assert_eq!(project.metadata.word_goal, Some(80_000));
+
+    project.metadata.word_goal = None;
+    project.file.modified = true;
+    project.save().unwrap();
+    drop(project);
+
+    let project = Project::load(project_path).unwrap();
+    assert_eq!(project.metadata.word_goal, None);
+}
+
+#[test]
+fn test_export_heading_offset() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut folder = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    folder.get_base_mut().metadata.name = "Chapter One".to_string();
+
+    let mut scene = folder.create_child_at_end(SCENE).unwrap();
+    scene.get_base_mut().metadata.name = "Arrival".to_string();
+    scene.load_body("It was raining.".to_string());
+
+    project.add_object(folder);
+    project.add_object(scene);
+
+    let export_options = |heading_offset| ExportOptions {
+        folder_title_depth: ExportDepth::All,
+        scene_title_depth: ExportDepth::All,
+        insert_breaks: false,
+        heading_offset,
+        append_word_count: false,
+        folder_title_style: TitleStyle::Heading,
+        scene_title_style: TitleStyle::Heading,
+        preserve_hard_breaks: false,
+        scene_metadata_comments: false,
+        append_glossary: false,
+        strip_markdown: false,
+    };
+
+    let export = project.export_text(export_options(0));
+    assert_eq!(export, "# Chapter One\n\n## Arrival\n\nIt was raining.\n\n");
+
+    let export = project.export_text(export_options(1));
+    assert_eq!(
+        export,
+        "## Chapter One\n\n### Arrival\n\nIt was raining.\n\n"
+    );
+
+    // Levels past 6 aren't valid markdown, so they stop there
+    let export = project.export_text(export_options(5));
+    assert_eq!(
+        export,
+        "###### Chapter One\n\n###### Arrival\n\nIt was raining.\n\n"
+    );
+}
+
+#[test]
+fn test_export_append_word_count() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut included = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    included.get_base_mut().metadata.name = "Included".to_string();
+    included.load_body("Four words right here.".to_string());
+    included.get_base_mut().file.modified = true;
+
+    let mut excluded = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    excluded.load_body("These words should never be counted at all.".to_string());
+    excluded.get_base_mut().file.modified = true;
+    let excluded_path = excluded.get_file();
+
+    project.add_object(included);
+    project.add_object(excluded);
+    project.save().unwrap();
+
+    // Exclude the second scene from the export by editing its compile status on disk
+    let excluded_text = read_to_string(&excluded_path)
+        .unwrap()
+        .replace("compile_status = 1", "compile_status = 0");
+    std::fs::write(&excluded_path, excluded_text).unwrap();
+    let project = Project::load(project.get_path()).unwrap();
+
+    let export_options = ExportOptions {
+        scene_title_depth: ExportDepth::All,
+        append_word_count: true,
+        ..plain_export_options()
+    };
+
+    // The title isn't counted, and neither is anything from the excluded scene
+    let export = project.export_text(export_options);
+    assert_eq!(
+        export,
+        "# Included\n\nFour words right here.\n\nTotal: 4 words\n"
+    );
+}
+
+#[test]
+fn test_export_word_count_with_comments_and_bold_titles() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut folder = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    folder.get_base_mut().metadata.name = "Part One".to_string();
+    let folder_id = folder.id().clone();
+    project.add_object(folder);
+
+    let mut scene = project.objects[&folder_id]
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.get_base_mut().metadata.name = "The Long Opening Scene".to_string();
+    scene.load_body("Four words right here.".to_string());
+    project.add_object(scene);
+
+    let export = project.export_text(ExportOptions {
+        folder_title_depth: ExportDepth::All,
+        scene_title_depth: ExportDepth::All,
+        folder_title_style: TitleStyle::Bold,
+        scene_title_style: TitleStyle::Bold,
+        scene_metadata_comments: true,
+        append_word_count: true,
+        ..plain_export_options()
+    });
+
+    // Neither the titles nor the scene comment are part of the story
+    assert!(export.contains("**Part One**"));
+    assert!(export.contains("**The Long Opening Scene**"));
+    assert!(export.contains("<!-- scene: id="));
+    assert!(export.ends_with("Four words right here.\n\nTotal: 4 words\n"));
+}
+
+#[test]
+fn test_export_word_count_with_headings_and_bold_lines() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("# A Heading\n\n**A bold line**\n\nFour words right here.".to_string());
+    project.add_object(scene);
+
+    let export = project.export_text(ExportOptions {
+        folder_title_style: TitleStyle::Bold,
+        scene_title_style: TitleStyle::Bold,
+        append_word_count: true,
+        ..plain_export_options()
+    });
+
+    // The author wrote the heading and the bold line, so they're part of the story
+    assert!(export.contains("# A Heading\n\n**A bold line**\n\n"));
+    assert!(export.ends_with("Four words right here.\n\nTotal: 9 words\n"));
+}
+
+#[test]
+fn test_created_time_round_trip() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    let scene_id = scene.get_base().metadata.id.clone();
+    let scene_path = scene.get_file();
+    let created = scene
+        .get_base()
+        .metadata
+        .created
+        .expect("new objects should know when they were created");
+
+    project.add_object(scene);
+    project.save().unwrap();
+
+    assert!(read_to_string(&scene_path).unwrap().contains("created = "));
+
+    // Only whole seconds are stored
+    let created_seconds = created
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let project = Project::load(project.get_path()).unwrap();
+    let reloaded_created = project
+        .objects
+        .get(&scene_id)
+        .unwrap()
+        .borrow()
+        .get_base()
+        .metadata
+        .created
+        .unwrap();
+
+    assert_eq!(
+        reloaded_created
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        created_seconds
+    );
+}
+
+#[test]
+fn test_import_section() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut other = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "other project".to_string(),
+    )
+    .unwrap();
+    let other_world_id = other.top_level_folder_id("Worldbuilding").unwrap().clone();
+
+    let mut cities = other.objects[&other_world_id]
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    cities.get_base_mut().metadata.name = "Cities".to_string();
+    let mut harbor = cities.create_child_at_end(PLACE).unwrap();
+    harbor.get_base_mut().metadata.name = "Harbor".to_string();
+    let harbor_id = harbor.id().clone();
+
+    let mut forest = other.objects[&other_world_id]
+        .borrow_mut()
+        .create_child_at_end(PLACE)
+        .unwrap();
+    forest.get_base_mut().metadata.name = "Forest".to_string();
+
+    other.add_object(cities);
+    other.add_object(harbor);
+    other.add_object(forest);
+    other.save().unwrap();
+    let other_path = other.get_path();
+    drop(other);
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    let world_id = project
+        .top_level_folder_id("Worldbuilding")
+        .unwrap()
+        .clone();
+
+    let mut existing_forest = project.objects[&world_id]
+        .borrow_mut()
+        .create_child_at_end(PLACE)
+        .unwrap();
+    existing_forest.get_base_mut().metadata.name = "Forest".to_string();
+    project.add_object(existing_forest);
+
+    let object_count = project.objects.len();
+
+    let imported = project
+        .import_section(&other_path, "worldbuilding", &world_id)
+        .unwrap();
+
+    // Cities (with Harbor inside of it) and Forest
+    assert_eq!(imported.len(), 2);
+    assert_eq!(project.objects.len(), object_count + 3);
+    assert_eq!(
+        project.objects[&world_id]
+            .borrow()
+            .get_base()
+            .children
+            .len(),
+        3
+    );
+    assert!(!project.objects.contains_key(&harbor_id));
+
+    let names: Vec<String> = imported
+        .iter()
+        .map(|file_id| project.objects[file_id].borrow().get_title())
+        .collect();
+    assert_eq!(names, vec!["Cities", "Forest (2)"]);
+
+    let cities_children = project.objects[&imported[0]]
+        .borrow()
+        .get_base()
+        .children
+        .clone();
+    assert_eq!(cities_children.len(), 1);
+    assert_eq!(
+        project.objects[&cities_children[0]].borrow().get_title(),
+        "Harbor"
+    );
+
+    // Everything is still there after a round trip
+    project.save().unwrap();
+    let reloaded = Project::load(project.get_path()).unwrap();
+    assert_eq!(reloaded.objects.len(), object_count + 3);
+
+    // The other project was only read from
+    let other = Project::load(other_path).unwrap();
+    assert!(other.objects.contains_key(&harbor_id));
+}
+
+#[test]
+fn test_external_modification_conflict() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("original text".to_string());
+    scene.get_base_mut().file.modified = true;
+    let scene_id = scene.id().clone();
+    let scene_path = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+    process_updates_after_save(&mut project);
+
+    // Edit in the app without saving, then have something else change the file
+    {
+        let mut scene = project.objects[&scene_id].borrow_mut();
+        scene.load_body("my text".to_string());
+        scene.get_base_mut().file.mark_edited();
+    }
+
+    thread::sleep(MTIME_SLEEP_DURATION);
+    let disk_text = read_to_string(&scene_path)
+        .unwrap()
+        .replace("original text", "their text");
+    std::fs::write(&scene_path, &disk_text).unwrap();
+
+    // This also saves, which can't overwrite their version
+    process_updates(&mut project);
+
+    assert_eq!(project.conflicted_objects(), vec![scene_id.clone()]);
+    assert_eq!(project.objects[&scene_id].borrow().get_body(), "my text\n");
+    assert_eq!(read_to_string(&scene_path).unwrap(), disk_text);
+
+    // Taking the disk version throws away the local changes
+    project.objects[&scene_id]
+        .borrow_mut()
+        .take_disk_version()
+        .unwrap();
+    assert!(project.conflicted_objects().is_empty());
+    assert_eq!(
+        project.objects[&scene_id].borrow().get_body().trim(),
+        "their text"
+    );
+    assert!(!project.objects[&scene_id].borrow().get_base().file.modified);
+
+    // Do it again, but keep the local version this time
+    {
+        let mut scene = project.objects[&scene_id].borrow_mut();
+        scene.load_body("my text again".to_string());
+        scene.get_base_mut().file.mark_edited();
+    }
+
+    thread::sleep(MTIME_SLEEP_DURATION);
+    std::fs::write(
+        &scene_path,
+        disk_text.replace("their text", "their text again"),
+    )
+    .unwrap();
+    process_updates(&mut project);
+
+    assert_eq!(project.conflicted_objects(), vec![scene_id.clone()]);
+
+    project.objects[&scene_id].borrow_mut().keep_local_version();
+    project.save().unwrap();
+
+    assert!(project.conflicted_objects().is_empty());
+    assert!(
+        read_to_string(&scene_path)
+            .unwrap()
+            .contains("my text again")
+    );
+}
+
+#[test]
+fn test_external_modification_without_edits() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("original text".to_string());
+    scene.get_base_mut().file.modified = true;
+    let scene_id = scene.id().clone();
+    let scene_path = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+    process_updates_after_save(&mut project);
+
+    // Something that needs to be written, but isn't a change the user made
+    project.objects[&scene_id]
+        .borrow_mut()
+        .get_base_mut()
+        .file
+        .modified = true;
+
+    thread::sleep(MTIME_SLEEP_DURATION);
+    let disk_text = read_to_string(&scene_path)
+        .unwrap()
+        .replace("original text", "their text");
+    std::fs::write(&scene_path, &disk_text).unwrap();
+
+    process_updates(&mut project);
+
+    // There's nothing of ours to lose, so the disk version is just loaded
+    assert!(project.conflicted_objects().is_empty());
+    assert_eq!(
+        project.objects[&scene_id].borrow().get_body().trim(),
+        "their text"
+    );
+    assert!(read_to_string(&scene_path).unwrap().contains("their text"));
+}
+
+#[test]
+fn test_scene_file_extension() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    project
+        .metadata
+        .file_extensions
+        .insert("scene".to_string(), "txt".to_string());
+    project.file.modified = true;
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.get_base_mut().metadata.name = "scene1".to_string();
+    scene.load_body("first body".to_string());
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+    project.save().unwrap();
+    process_updates_after_save(&mut project);
+
+    let scene_path = project.objects[&scene_id].borrow().get_file();
+    assert_eq!(scene_path.extension().unwrap(), "txt");
+    assert!(read_to_string(&scene_path).unwrap().contains("first body"));
+    let text_path = project.get_text_folder().borrow().get_path();
+    assert!(!text_path.join("000-scene1.md").exists());
+
+    // The tracker picks up changes to the file
+    thread::sleep(MTIME_SLEEP_DURATION);
+    let new_text = read_to_string(&scene_path)
+        .unwrap()
+        .replace("first body", "second body");
+    std::fs::write(&scene_path, new_text).unwrap();
+    process_updates(&mut project);
+
+    assert_eq!(
+        project.objects[&scene_id].borrow().get_body().trim(),
+        "second body"
+    );
+
+    // and new files
+    let new_scene_text = "file_type = \"scene\"\nid = \"2\"\n++++++++\nexternal body";
+    write_with_temp_file(text_path.join("001-scene2.txt"), new_scene_text).unwrap();
+    process_updates(&mut project);
+
+    assert!(project.objects.contains_key(&file_id("2")));
+    assert_eq!(
+        project.objects[&file_id("2")].borrow().get_body().trim(),
+        "external body"
+    );
+
+    // Text files that aren't objects are left alone
+    std::fs::write(text_path.join("todo.txt"), "not a scene").unwrap();
+    process_updates(&mut project);
+    assert_eq!(
+        read_to_string(text_path.join("todo.txt")).unwrap(),
+        "not a scene"
+    );
+
+    // Everything survives a reload without being renamed
+    let project = Project::load(project.get_path()).unwrap();
+    assert_eq!(
+        project.objects[&scene_id]
+            .borrow()
+            .get_file()
+            .extension()
+            .unwrap(),
+        "txt"
+    );
+    assert!(project.objects.contains_key(&file_id("2")));
+}
+
+#[test]
+fn test_index_width_widens() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene_ids = Vec::new();
+    for _ in 0..1001 {
+        let scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene_ids.push(scene.id().clone());
+        project.add_object(scene);
+    }
+
+    project.save().unwrap();
+
+    let basename = |file_id: &FileID| {
+        project.objects[file_id]
+            .borrow()
+            .get_base()
+            .file
+            .basename
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    assert_eq!(basename(&scene_ids[0]), "0000-New_Scene.md");
+    assert_eq!(basename(&scene_ids[999]), "0999-New_Scene.md");
+    assert_eq!(basename(&scene_ids[1000]), "1000-New_Scene.md");
+
+    // Every file sorts in the right order as a string
+    let text_path = project.get_text_folder().borrow().get_path();
+    let mut filenames: Vec<String> = read_dir(&text_path)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|filename| filename != "metadata.toml")
+        .collect();
+    filenames.sort();
+    let expected: Vec<String> = scene_ids.iter().map(basename).collect();
+    assert_eq!(filenames, expected);
+
+    // Reloading keeps the order
+    let project = Project::load(project.get_path()).unwrap();
+    assert_eq!(
+        project.get_text_folder().borrow().get_base().children,
+        scene_ids
+    );
+}
+
+#[test]
+fn test_configured_index_width() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    assert!(
+        project.objects[&scene_id]
+            .borrow()
+            .get_path()
+            .ends_with("000-New_Scene.md")
+    );
+
+    project.metadata.index_width = Some(5);
+    project.file.modified = true;
+    project.save().unwrap();
+
+    // Existing files get renamed once the project is loaded with the new width
+    let project = Project::load(project.get_path()).unwrap();
+    assert!(
+        project.objects[&scene_id]
+            .borrow()
+            .get_path()
+            .ends_with("00000-New_Scene.md")
+    );
+    assert_eq!(project.metadata.index_width, Some(5));
+}
+
+#[test]
+fn test_export_job_progress() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut folder = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    for body in ["first scene", "second scene", "third scene"] {
+        let mut scene = folder.create_child_at_end(SCENE).unwrap();
+        scene.load_body(body.to_string());
+        project.add_object(scene);
+    }
+    project.add_object(folder);
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("fourth scene".to_string());
+    project.add_object(scene);
+
+    let mut export_job = project.start_export(plain_export_options());
+    assert_eq!(export_job.progress(), 0.0);
+
+    // The folder (and its three scenes) gets exported first
+    project.continue_export(&mut export_job);
+    assert_eq!(export_job.progress(), 0.75);
+    assert!(!export_job.is_done());
+
+    project.continue_export(&mut export_job);
+    assert_eq!(export_job.progress(), 1.0);
+    assert!(export_job.is_done());
+
+    assert_eq!(
+        export_job.finish(),
+        project.export_text(plain_export_options())
+    );
+}
+
+#[test]
+fn test_export_summary() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("three words here".to_string());
+    project.add_object(scene);
+
+    let mut folder = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    let folder_path = folder.get_file();
+    for _ in 0..2 {
+        let mut scene = folder.create_child_at_end(SCENE).unwrap();
+        scene.load_body("these words are in an excluded folder".to_string());
+        project.add_object(scene);
+    }
+    project.add_object(folder);
+    project.save().unwrap();
+
+    let summary = project.export_summary();
+    assert_eq!(summary.scenes_included, 3);
+    assert_eq!(summary.scenes_total, 3);
+    assert_eq!(summary.words, 17);
+
+    // Excluding the folder excludes everything in it
+    let folder_text = read_to_string(&folder_path)
+        .unwrap()
+        .replace("compile_status = 1", "compile_status = 0");
+    std::fs::write(&folder_path, folder_text).unwrap();
+    let project = Project::load(project.get_path()).unwrap();
+
+    let summary = project.export_summary();
+    assert_eq!(summary.scenes_included, 1);
+    assert_eq!(summary.scenes_total, 3);
+    assert_eq!(summary.words, 3);
+    assert_eq!(summary.sentences, 1);
+    assert_eq!(summary.paragraphs, 1);
+    assert_eq!(summary.to_string(), "1 of 3 scenes included, 3 words");
+    assert_eq!(
+        summary.prose_details(),
+        "1 sentence in 1 paragraph, 3.0 words per sentence"
+    );
+    assert!(summary.empty_scenes.is_empty());
+}
+
+#[test]
+fn test_export_summary_matches_export_word_count() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("# Part One\n\nShe left. %% some notes to self %%\n\nHe stayed.".to_string());
+    project.add_object(scene);
+
+    // Comments aren't exported, so they aren't counted either
+    let summary = project.export_summary();
+    assert_eq!(summary.words, 6);
+    assert_eq!(summary.sentences, 2);
+    assert_eq!(summary.paragraphs, 2);
+
+    let export = project.export_text(ExportOptions {
+        append_word_count: true,
+        ..plain_export_options()
+    });
+    assert!(export.ends_with("Total: 6 words\n"));
+}
+
+#[test]
+fn test_export_summary_empty_scenes() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let bodies = [
+        ("Written", "Some actual words."),
+        ("Blank", "  \n\n\t"),
+        ("Only Comments", "%% write this later %%\n"),
+        ("Excluded", ""),
+    ];
+    for (name, body) in bodies {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.get_base_mut().metadata.name = name.to_string();
+        scene.load_body(body.to_string());
+        scene.get_base_mut().file.modified = true;
+        project.add_object(scene);
+    }
+    project.save().unwrap();
+
+    // Excluded scenes aren't exported, so it doesn't matter that they're empty
+    let excluded_id = project
+        .objects
+        .iter()
+        .find(|(_, object)| object.borrow().get_base().metadata.name == "Excluded")
+        .map(|(file_id, _)| file_id.clone())
+        .unwrap();
+    let excluded_path = project.objects[&excluded_id].borrow().get_file();
+    let excluded_text = read_to_string(&excluded_path)
+        .unwrap()
+        .replace("compile_status = 1", "compile_status = 0");
+    std::fs::write(&excluded_path, excluded_text).unwrap();
+    let project = Project::load(project.get_path()).unwrap();
+
+    let summary = project.export_summary();
+    assert_eq!(summary.scenes_included, 3);
+    assert_eq!(summary.empty_scenes, vec!["Blank", "Only Comments"]);
+}
+
+#[test]
+fn test_suppress_leading_break() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    for (body, suppress_leading_break) in [
+        ("The first half.", false),
+        ("The second half.", true),
+        ("A new scene.", false),
+    ] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.load_body(body.to_string());
+        if suppress_leading_break {
+            scene.get_base_mut().toml_header["suppress_leading_break"] = toml_edit::value(true);
+            scene.load_metadata().unwrap();
+        }
+        project.add_object(scene);
+    }
+
+    let export_options = || ExportOptions {
+        insert_breaks: true,
+        ..plain_export_options()
+    };
+
+    let expected = "The first half.\n\nThe second half.\n\n----\n\nA new scene.\n\n";
+    assert_eq!(project.export_text(export_options()), expected);
+
+    // The setting should survive a round trip through the disk
+    project.save().unwrap();
+    let project = Project::load(base_dir.path().join("test_project")).unwrap();
+    assert_eq!(project.export_text(export_options()), expected);
+}
+
+#[test]
+fn test_export_selected() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut chapter_one = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    let mut first_scene = chapter_one.create_child_at_end(SCENE).unwrap();
+    first_scene.load_body("first scene".to_string());
+    let mut second_scene = chapter_one.create_child_at_end(SCENE).unwrap();
+    second_scene.load_body("second scene".to_string());
+
+    let mut skipped_scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    skipped_scene.load_body("skipped scene".to_string());
+
+    let mut chapter_two = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    let mut last_scene = chapter_two.create_child_at_end(SCENE).unwrap();
+    last_scene.load_body("last scene".to_string());
+
+    let chapter_one_id = chapter_one.id().clone();
+    let first_scene_id = first_scene.id().clone();
+    let last_scene_id = last_scene.id().clone();
+
+    for object in [
+        chapter_one,
+        first_scene,
+        second_scene,
+        skipped_scene,
+        chapter_two,
+        last_scene,
+    ] {
+        project.add_object(object);
+    }
+
+    // Selected out of order, and with a scene inside of a selected folder
+    let export = project.export_selected_text(
+        &[last_scene_id, first_scene_id, chapter_one_id],
+        plain_export_options(),
+    );
+    assert_eq!(export, "first scene\n\nsecond scene\n\nlast scene\n\n");
+
+    // Selecting the text folder is the same as exporting everything
+    assert_eq!(
+        project.export_selected_text(&[project.text_folder_id().clone()], plain_export_options()),
+        project.export_text(plain_export_options())
+    );
+
+    assert_eq!(
+        project.export_selected_text(&[], plain_export_options()),
+        ""
+    );
+}
+
+#[test]
+fn test_normalize_crlf_line_endings() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.get_test_field().push_str("a summary\nacross lines");
+    scene.load_body("First line.\n\nSecond line.".to_string());
+    let scene_id = scene.id().clone();
+    let scene_file = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+    drop(project);
+
+    // Simulate a checkout with CRLF line endings
+    let contents = read_to_string(&scene_file).unwrap();
+    write_with_temp_file(&scene_file, contents.replace('\n', "\r\n")).unwrap();
+
+    let mut project = Project::load(base_dir.path().join("test_project")).unwrap();
+    assert_eq!(project.crlf_files, vec![scene_id.clone()]);
+
+    // Nothing is converted until it's asked for
+    assert!(read_to_string(&scene_file).unwrap().contains("\r\n"));
+
+    project.normalize_line_endings().unwrap();
+    assert!(project.crlf_files.is_empty());
+
+    let contents = read_to_string(&scene_file).unwrap();
+    assert!(!contents.contains('\r'));
+    assert!(contents.ends_with("First line.\n\nSecond line.\n"));
+
+    // and it stays that way when loaded again
+    drop(project);
+    let project = Project::load(base_dir.path().join("test_project")).unwrap();
+    assert!(project.crlf_files.is_empty());
+    let scene = project.objects.get(&scene_id).unwrap();
+    assert_eq!(
+        scene.borrow_mut().get_test_field().as_str(),
+        "a summary\nacross lines"
+    );
+}
+
+#[test]
+fn test_project_check() {
+    use crate::components::project_check::{ProjectIssue, check_project};
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    project.add_object(scene);
+    project.save().unwrap();
+    drop(project);
+
+    let project_path = base_dir.path().join("test_project");
+    assert_eq!(check_project(&project_path), Vec::new());
+
+    // A scene with a gap in the indexes that points at a character that doesn't exist
+    let extra_scene = "file_type = \"scene\"\nid = \"extra\"\npov = \"[Nobody|missing]\"\n\
+        ++++++++\n\nSome text.\n";
+    write_with_temp_file(project_path.join("text/005-Extra.md"), extra_scene).unwrap();
+
+    let issues = check_project(&project_path);
+    assert_eq!(issues.len(), 2);
+    assert!(matches!(
+        &issues[0],
+        ProjectIssue::BrokenReference { reference, .. } if reference.contains("missing")
+    ));
+    assert!(matches!(issues[1], ProjectIssue::Indexing { index: 1, .. }));
+
+    // Nothing should have been fixed on disk
+    assert!(project_path.join("text/005-Extra.md").exists());
+
+    // A copy of a file (e.g., from a sync conflict) has the same id
+    write_with_temp_file(project_path.join("text/006-Extra_copy.md"), extra_scene).unwrap();
+    assert_eq!(
+        check_project(&project_path),
+        vec![ProjectIssue::DuplicateId {
+            id: "extra".to_string(),
+            files: vec![
+                project_path.join("text/005-Extra.md"),
+                project_path.join("text/006-Extra_copy.md")
+            ]
+        }]
+    );
+}
+
+/// Every file and folder under `dir`, with the contents of the files
+fn snapshot_dir(dir: &Path, snapshot: &mut Vec<(std::path::PathBuf, Option<Vec<u8>>)>) {
+    for entry in read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            snapshot.push((path.clone(), None));
+            snapshot_dir(&path, snapshot);
+        } else {
+            snapshot.push((path.clone(), Some(std::fs::read(&path).unwrap())));
+        }
+    }
+    snapshot.sort();
+}
+
+#[test]
+fn test_project_check_doesnt_write() {
+    use crate::components::project_check::check_project;
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    let project_path = base_dir.path().join("test_project");
+
+    // Everything that a normal load would fix up: a missing top level folder and a gap in the
+    // indexes
+    std::fs::remove_dir_all(project_path.join("worldbuilding")).unwrap();
+    write_with_temp_file(
+        project_path.join("text/005-Extra.md"),
+        "file_type = \"scene\"\nid = \"extra\"\n++++++++\n\nSome text.\n",
+    )
+    .unwrap();
+
+    let mut before = Vec::new();
+    snapshot_dir(&project_path, &mut before);
+
+    assert!(!check_project(&project_path).is_empty());
+
+    let mut after = Vec::new();
+    snapshot_dir(&project_path, &mut after);
+    assert_eq!(before, after);
+}
+
+#[test]
+fn test_project_check_with_backup() {
+    use crate::components::project_check::check_project;
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    // Every object in the backup has the same id as the original, but the backup isn't part of
+    // the project
+    let backup_path = project.create_backup(3).unwrap();
+    assert!(backup_path.join("text").is_dir());
+    let project_path = project.get_path();
+    drop(project);
+
+    assert_eq!(check_project(&project_path), Vec::new());
+}
+
+#[test]
+fn test_header_split_in_text() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene
+        .get_test_field()
+        .push_str("C++++++++ is not a language");
+    let body = "Before the divider.\n\n++++++++\n\nAfter the divider.";
+    scene.load_body(body.to_string());
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+    project.save().unwrap();
+    drop(project);
+
+    let project = Project::load(base_dir.path().join("test_project")).unwrap();
+    let scene = project.objects.get(&scene_id).unwrap();
+    assert_eq!(scene.borrow().get_body().trim(), body);
+    assert_eq!(
+        scene.borrow_mut().get_test_field().as_str(),
+        "C++++++++ is not a language"
+    );
+}
+
+#[test]
+fn test_body_blank_lines_round_trip() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    let scene_id = scene.id().clone();
+    let scene_file = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+    drop(project);
+
+    // leading and trailing blank lines, indentation, and trailing spaces (a markdown line break)
+    let body = "\n\n    An indented first line.  \nAnd the next one.\n\n\n";
+    let header = read_to_string(&scene_file)
+        .unwrap()
+        .split("++++++++")
+        .next()
+        .unwrap()
+        .to_string();
+    write_with_temp_file(&scene_file, format!("{header}++++++++\n\n{body}")).unwrap();
+
+    // Change the metadata, so the scene gets saved without its body being edited
+    let mut project = Project::load(base_dir.path().join("test_project")).unwrap();
+    {
+        let mut scene = project.objects.get(&scene_id).unwrap().borrow_mut();
+        scene.get_base_mut().metadata.name = "Renamed".to_string();
+        scene.get_base_mut().file.modified = true;
+    }
+    project.save().unwrap();
+
+    let scene = project.objects.get(&scene_id).unwrap().borrow();
+    let contents = read_to_string(scene.get_file()).unwrap();
+    let (_header, saved_body) = contents.split_once("++++++++\n\n").unwrap();
+    assert_eq!(saved_body, body);
+}
+
+#[test]
+fn test_default_names() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    project
+        .metadata
+        .default_names
+        .insert("scene".to_string(), "Untitled Chapter".to_string());
+    project.file.modified = true;
+    project.save().unwrap();
+
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+
+    // Types without a default name are still left blank
+    let folder = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    let folder_id = folder.id().clone();
+    project.add_object(folder);
+
+    project.save().unwrap();
+
+    let scene = project.objects[&scene_id].borrow();
+    assert_eq!(scene.get_base().metadata.name, "Untitled Chapter");
+    assert!(
+        scene
+            .get_file()
+            .to_string_lossy()
+            .ends_with("000-Untitled_Chapter.md")
+    );
+    drop(scene);
+
+    let folder = project.objects[&folder_id].borrow();
+    assert!(folder.get_base().metadata.name.is_empty());
+    assert_eq!(folder.get_title(), "New Folder");
+    drop(folder);
+
+    // The setting is kept when the project is reloaded
+    let project = Project::load(base_dir.path().join("test_project")).unwrap();
+    assert_eq!(project.default_name(SCENE), Some("Untitled Chapter"));
+    assert_eq!(project.default_name(FOLDER), None);
+}
+
+#[test]
+fn test_replace_all_capture_groups() {
+    use crate::components::replace::Replacement;
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("\"Wait,\" Anna said. Ben said nothing.".to_string());
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+
+    let mut other_scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    other_scene.load_body("Nobody talks here.".to_string());
+    let other_scene_id = other_scene.id().clone();
+    project.add_object(other_scene);
+
+    project.save().unwrap();
+
+    let replacement = Replacement::new(r"(\w+) said", "$1 whispered", true).unwrap();
+    assert_eq!(project.replace_all(&replacement), 1);
+    assert!(
+        !project.objects[&other_scene_id]
+            .borrow()
+            .get_base()
+            .file
+            .modified
+    );
+
+    project.save().unwrap();
+
+    let scene_path = project.objects[&scene_id].borrow().get_file();
+    assert!(
+        read_to_string(scene_path)
+            .unwrap()
+            .ends_with("\"Wait,\" Anna whispered. Ben whispered nothing.\n")
+    );
+}
+
+#[test]
+fn test_export_footnotes() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    for body in [
+        "The river[^1] floods.\n\n[^1]: Every spring.",
+        "The mountain[^1] stands.\n\n[^1]: Mostly.",
+    ] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.load_body(body.to_string());
+        project.add_object(scene);
+    }
+
+    assert_eq!(
+        project.export_text(plain_export_options()),
+        "The river[^1] floods.\n\n[^1]: Every spring.\n\n\
+        The mountain[^2] stands.\n\n[^2]: Mostly.\n\n"
+    );
+}
+
+#[test]
+fn test_locked_scene() {
+    use crate::components::replace::Replacement;
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("The final draft.".to_string());
+    scene.get_base_mut().toml_header["locked"] = toml_edit::value(true);
+    scene.load_metadata().unwrap();
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    let mut project = Project::load(base_dir.path().join("test_project")).unwrap();
+    assert!(project.objects[&scene_id].borrow().is_locked());
+
+    // Replacing doesn't touch locked scenes
+    let replacement = Replacement::new("final", "first", false).unwrap();
+    assert_eq!(project.replace_all(&replacement), 0);
+    assert_eq!(
+        project.objects[&scene_id].borrow().get_body(),
+        "The final draft.\n"
+    );
+
+    // but they're still exported
+    assert_eq!(
+        project.export_text(plain_export_options()),
+        "The final draft.\n\n"
+    );
+}
+
+#[test]
+fn test_aggregate_word_target() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut chapter = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+
+    let mut scene_ids = Vec::new();
+    for (body, target) in [
+        ("three short words", Some(1000)),
+        ("and then four more", Some(500)),
+        ("untargeted", None),
+    ] {
+        let mut scene = chapter.create_child_at_end(SCENE).unwrap();
+        scene.load_body(body.to_string());
+        if let Some(target) = target {
+            scene.get_base_mut().toml_header["target_word_count"] = toml_edit::value(target);
+            scene.load_metadata().unwrap();
+        }
+        scene_ids.push(scene.id().clone());
+        project.add_object(scene);
+    }
+
+    let chapter_id = chapter.id().clone();
+    project.add_object(chapter);
+
+    assert_eq!(
+        project.objects[&chapter_id]
+            .borrow()
+            .aggregate_word_target(&project.objects),
+        (8, 1500)
+    );
+    assert_eq!(
+        project.objects[&scene_ids[0]]
+            .borrow()
+            .aggregate_word_target(&project.objects),
+        (3, 1000)
+    );
+
+    // The targets are kept when saving
+    project.save().unwrap();
+    let project = Project::load(base_dir.path().join("test_project")).unwrap();
+    assert_eq!(
+        project.objects[&chapter_id]
+            .borrow()
+            .aggregate_word_target(&project.objects),
+        (8, 1500)
+    );
+}
+
+#[test]
+fn test_most_recently_modified_scene() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene_ids = Vec::new();
+    for body in ["first", "second", "third"] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.load_body(body.to_string());
+        scene_ids.push(scene.id().clone());
+        project.add_object(scene);
+    }
+    project.save().unwrap();
+
+    thread::sleep(MTIME_SLEEP_DURATION);
+
+    {
+        let mut scene = project.objects[&scene_ids[1]].borrow_mut();
+        scene.load_body("second, but edited".to_string());
+        scene.get_base_mut().file.modified = true;
+    }
+    project.save().unwrap();
+
+    assert_eq!(
+        project.most_recently_modified_scene(),
+        Some(scene_ids[1].clone())
+    );
+
+    // The modification times come from the files, so this still works after reopening
+    let project = Project::load(base_dir.path().join("test_project")).unwrap();
+    assert_eq!(
+        project.most_recently_modified_scene(),
+        Some(scene_ids[1].clone())
+    );
+}
+
+#[test]
+fn test_export_split() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    for (name, body) in [
+        ("Opening", "It was raining."),
+        ("Second Scene", "It stopped."),
+    ] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.get_base_mut().metadata.name = name.to_string();
+        scene.load_body(body.to_string());
+        project.add_object(scene);
+    }
+
+    let export_dir = base_dir.path().join("export");
+    create_dir(&export_dir).unwrap();
+
+    let exported_names = |written: Vec<std::path::PathBuf>| -> Vec<String> {
+        written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    };
+
+    project.metadata.export.split_filename_template = "{index:03}-{name}".to_string();
+    let written = project.export_split(&export_dir).unwrap();
+    assert_eq!(
+        exported_names(written),
+        vec!["000-Opening.md", "001-Second_Scene.md"]
+    );
+    let first_export = read_to_string(export_dir.join("000-Opening.md")).unwrap();
+    assert!(first_export.starts_with("# Opening\n\nIt was raining."));
+    assert!(!first_export.contains("It stopped."));
+
+    project.metadata.export.split_filename_template = "{number}. {name}".to_string();
+    let written = project.export_split(&export_dir).unwrap();
+    assert_eq!(
+        exported_names(written),
+        vec!["1. Opening.md", "2. Second_Scene.md"]
+    );
+
+    // Templates that would overwrite their own files are caught before anything is written
+    project.metadata.export.split_filename_template = "chapter".to_string();
+    assert!(project.export_split(&export_dir).is_err());
+    assert!(!export_dir.join("chapter.md").exists());
+}
+
+#[test]
+fn test_recover_interrupted_write() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.get_base_mut().metadata.name = "Interrupted".to_string();
+    scene.load_body("the saved version".to_string());
+    let scene_id = scene.id().clone();
+    let scene_path = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    // Simulate a crash after the temporary file was written but before it replaced the scene
+    let scene_contents = read_to_string(&scene_path)
+        .unwrap()
+        .replace("the saved version", "the unsaved version");
+    let scene_filename = scene_path.file_name().unwrap().to_string_lossy();
+    let temp_path = scene_path.with_file_name(format!(".{scene_filename}.a1B2c3.tmp"));
+    std::fs::write(&temp_path, &scene_contents).unwrap();
+    std::fs::remove_file(&scene_path).unwrap();
+
+    // A leftover with nothing in it can't be recovered
+    let empty_temp_path = scene_path.with_file_name(".001-empty.md.d4E5f6.tmp");
+    std::fs::write(&empty_temp_path, "").unwrap();
+
+    let mut project = Project::load(project.get_path()).unwrap();
+    assert!(!project.objects.contains_key(&scene_id));
+    assert_eq!(project.recoverable_files.len(), 1);
+    assert_eq!(project.recoverable_files[0].temp_path, temp_path);
+    assert_eq!(project.recoverable_files[0].target_path, scene_path);
+
+    project.recover_files().unwrap();
+    assert!(project.recoverable_files.is_empty());
+    assert!(!temp_path.exists());
+    assert_eq!(read_to_string(&scene_path).unwrap(), scene_contents);
+
+    let project = Project::load(project.get_path()).unwrap();
+    assert!(project.recoverable_files.is_empty());
+    let scene = project.objects.get(&scene_id).unwrap().borrow();
+    assert_eq!(scene.get_body(), "the unsaved version");
+}
+
+#[test]
+fn test_create_backup() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("backed up text".to_string());
+    let scene_path = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    let relative_scene_path = scene_path.strip_prefix(project.get_path()).unwrap();
+
+    let mut backups = Vec::new();
+    for _ in 0..3 {
+        backups.push(project.create_backup(2).unwrap());
+    }
+
+    // Only the newest backups are kept
+    let backup_root = project.get_path().join(BACKUP_FOLDER_NAME);
+    assert_eq!(read_dir(&backup_root).unwrap().count(), 2);
+    assert!(!backups[0].exists());
+
+    for backup in &backups[1..] {
+        assert!(backup.join("project.toml").exists());
+        assert!(
+            read_to_string(backup.join(relative_scene_path))
+                .unwrap()
+                .contains("backed up text")
+        );
+        // backups don't include other backups
+        assert!(!backup.join(BACKUP_FOLDER_NAME).exists());
+    }
+
+    // The backups aren't loaded as part of the project
+    let object_count = project.objects.len();
+    let project = Project::load(project.get_path()).unwrap();
+    assert_eq!(project.objects.len(), object_count);
+}
+
+#[test]
+fn test_backup_project_before_loading() {
+    use crate::components::backup::backup_project;
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    // open and immediately drop the project (just creating the files)
+    Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    let project_path = base_dir.path().join("test_project");
+
+    // A gap in the indexes, which loading fixes by renaming the file
+    write_with_temp_file(
+        project_path.join("text/005-Extra.md"),
+        "file_type = \"scene\"\nid = \"extra\"\n++++++++\n\nSome text.\n",
+    )
+    .unwrap();
+
+    let backup = backup_project(&project_path, 2).unwrap();
+    Project::load(project_path.clone()).unwrap();
+
+    assert!(!project_path.join("text/005-Extra.md").exists());
+    assert!(backup.join("text/005-Extra.md").exists());
+
+    // Folders that aren't projects aren't backed up
+    assert!(backup_project(base_dir.path(), 2).is_err());
+    assert!(!base_dir.path().join(BACKUP_FOLDER_NAME).exists());
+}
+
+#[test]
+fn test_spellcheck_ignore_patterns_saved() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    project.metadata.spellcheck_ignore_patterns =
+        vec![r"\w+iel".to_string(), "".to_string(), "(bad".to_string()];
+    project.file.modified = true;
+    project.save().unwrap();
+
+    // Blank lines are dropped, but invalid patterns are kept for the user to fix
+    let mut project = Project::load(project.get_path()).unwrap();
+    assert_eq!(
+        project.metadata.spellcheck_ignore_patterns,
+        vec![r"\w+iel".to_string(), "(bad".to_string()]
+    );
+
+    project.metadata.spellcheck_ignore_patterns.clear();
+    project.file.modified = true;
+    project.save().unwrap();
+
+    let project_toml = std::fs::read_to_string(project.get_project_info_file()).unwrap();
+    assert!(!project_toml.contains("spellcheck_ignore_patterns"));
+}
+
+#[test]
+fn test_autosave_enabled_saved() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    assert!(project.metadata.autosave_enabled);
+
+    // Projects from before the setting existed still autosave
+    let project_toml = std::fs::read_to_string(project.get_project_info_file()).unwrap();
+    let project_toml = project_toml
+        .lines()
+        .filter(|line| !line.starts_with("autosave_enabled"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(project.get_project_info_file(), project_toml).unwrap();
+    let mut project = Project::load(project.get_path()).unwrap();
+    assert!(project.metadata.autosave_enabled);
+
+    project.metadata.autosave_enabled = false;
+    project.file.modified = true;
+    project.save().unwrap();
+
+    let project = Project::load(project.get_path()).unwrap();
+    assert!(!project.metadata.autosave_enabled);
+}
+
+#[test]
+fn test_append_to_inbox() {
+    use crate::components::inbox::INBOX_NAME;
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    assert!(project.inbox_id().is_none());
+
+    // The inbox is created the first time it's needed
+    let inbox_id = project.append_to_inbox("  first idea\n").unwrap();
+    assert_eq!(project.inbox_id(), Some(inbox_id.clone()));
+    assert_eq!(
+        project.objects[&inbox_id].borrow().get_base().metadata.name,
+        INBOX_NAME
+    );
+    assert_eq!(
+        project.objects[&inbox_id].borrow().get_body(),
+        "first idea\n"
+    );
+
+    // Later notes go to the same scene as new paragraphs
+    assert_eq!(project.append_to_inbox("second idea").unwrap(), inbox_id);
+    assert!(project.append_to_inbox("   ").is_err());
+    assert_eq!(
+        project.objects[&inbox_id].borrow().get_body(),
+        "first idea\n\nsecond idea\n"
+    );
+
+    project.save().unwrap();
+
+    // and it's still found after reloading
+    let project = Project::load(project.get_path()).unwrap();
+    let inbox_id = project.inbox_id().unwrap();
+    assert_eq!(
+        project.objects[&inbox_id].borrow().get_body(),
+        "first idea\n\nsecond idea\n"
+    );
+    assert!(
+        project
+            .get_text_folder()
+            .borrow()
+            .get_base()
+            .children
+            .contains(&inbox_id)
+    );
+}
+
+#[test]
+fn test_export_title_styles() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut folder = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    folder.get_base_mut().metadata.name = "Chapter One".to_string();
+
+    let mut scene = folder.create_child_at_end(SCENE).unwrap();
+    scene.get_base_mut().metadata.name = "Arrival".to_string();
+    scene.load_body("It was raining.".to_string());
+
+    project.add_object(folder);
+    project.add_object(scene);
+
+    let export_options = |folder_title_style, scene_title_style| ExportOptions {
+        folder_title_depth: ExportDepth::All,
+        scene_title_depth: ExportDepth::All,
+        folder_title_style,
+        scene_title_style,
+        ..plain_export_options()
+    };
+
+    // Scenes as bold paragraphs while folders stay headings
+    let export = project.export_text(export_options(TitleStyle::Heading, TitleStyle::Bold));
+    assert_eq!(
+        export,
+        "# Chapter One\n\n**Arrival**\n\nIt was raining.\n\n"
+    );
+
+    let export = project.export_text(export_options(TitleStyle::Bold, TitleStyle::Heading));
+    assert_eq!(
+        export,
+        "**Chapter One**\n\n## Arrival\n\nIt was raining.\n\n"
+    );
+
+    let export = project.export_text(export_options(TitleStyle::None, TitleStyle::Bold));
+    assert_eq!(export, "**Arrival**\n\nIt was raining.\n\n");
+
+    // The styles are kept when the project is reloaded
+    project.metadata.export.scene_title_style = TitleStyle::Bold;
+    project.file.modified = true;
+    project.save().unwrap();
+
+    let project = Project::load(project.get_path()).unwrap();
+    assert_eq!(
+        project.metadata.export.folder_title_style,
+        TitleStyle::Heading
+    );
+    assert_eq!(project.metadata.export.scene_title_style, TitleStyle::Bold);
+}
+
+#[test]
+fn test_character_relationships() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let characters_id = project.top_level_folder_id("Characters").unwrap().clone();
+
+    let mut character_ids = Vec::new();
+    for name in ["Alice", "Bea"] {
+        let mut character = project
+            .objects
+            .get(&characters_id)
+            .unwrap()
+            .borrow_mut()
+            .create_child_at_end(CHARACTER)
+            .unwrap();
+        character.get_base_mut().metadata.name = name.to_string();
+        character.get_base_mut().file.modified = true;
+        character_ids.push(character.id().clone());
+        project.add_object(character);
+    }
+    project.save().unwrap();
+    let (alice_id, bea_id) = (&character_ids[0], &character_ids[1]);
+
+    // Relationships written by hand (with only a name) get resolved to the character's id
+    let alice_path = project.objects[alice_id].borrow().get_file();
+    let alice_text = read_to_string(&alice_path).unwrap();
+    std::fs::write(
+        &alice_path,
+        format!(
+            "relationships = [{{ relation = \"sibling of\", target = \"[Bea]\" }}]\n{alice_text}"
+        ),
+    )
+    .unwrap();
+
+    let mut project = Project::load(project.get_path()).unwrap();
+    assert!(
+        project.objects[alice_id]
+            .borrow()
+            .broken_references()
+            .is_empty()
+    );
+
+    project.objects[alice_id]
+        .borrow_mut()
+        .get_base_mut()
+        .file
+        .modified = true;
+    project.save().unwrap();
+    assert!(
+        read_to_string(&alice_path)
+            .unwrap()
+            .contains(&format!("target = \"[Bea|{bea_id}]\""))
+    );
+
+    let dossier = project.export_dossier(&characters_id);
+    assert!(dossier.contains("relationships:\n\n- sibling of Bea\n"));
+
+    // If the other character is deleted, the relationship is kept (but reported as broken)
+    let bea_path = project.objects[bea_id].borrow().get_path();
+    std::fs::remove_dir_all(bea_path).unwrap();
+
+    let project = Project::load(project.get_path()).unwrap();
+    let broken_references = project.objects[alice_id].borrow().broken_references();
+    assert_eq!(broken_references.len(), 1);
+    assert!(broken_references[0].contains("sibling of"));
+    assert!(
+        project
+            .export_dossier(&characters_id)
+            .contains("- sibling of Bea\n")
+    );
+}
+
+#[test]
+fn test_export_hard_breaks() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    // compile_status values for "Always" and "Never" keeping line breaks (along with INCLUDE)
+    for (body, compile_status) in [
+        ("Roses are red,\nViolets are blue.", Some(0b110_0001)),
+        ("A sentence that\nwraps by hand.", None),
+        ("Prose that must\nstay joined.", Some(0b010_0001)),
+    ] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.load_body(body.to_string());
+        if let Some(compile_status) = compile_status {
+            scene.get_base_mut().toml_header["compile_status"] = toml_edit::value(compile_status);
+            scene.load_metadata().unwrap();
+        }
+        project.add_object(scene);
+    }
+
+    // Only the verse scene asks for its breaks to be kept
+    assert_eq!(
+        project.export_text(plain_export_options()),
+        "Roses are red,  \nViolets are blue.\n\n\
+        A sentence that\nwraps by hand.\n\n\
+        Prose that must\nstay joined.\n\n"
+    );
+
+    // Scenes that don't say otherwise follow the project setting, which survives a round trip
+    project.metadata.export.preserve_hard_breaks = true;
+    project.save().unwrap();
+    let project = Project::load(project.get_path()).unwrap();
+    assert!(
+        project
+            .metadata
+            .export
+            .export_options()
+            .preserve_hard_breaks
+    );
+
+    assert_eq!(
+        project.export_text(ExportOptions {
+            preserve_hard_breaks: true,
+            ..plain_export_options()
+        }),
+        "Roses are red,  \nViolets are blue.\n\n\
+        A sentence that  \nwraps by hand.\n\n\
+        Prose that must\nstay joined.\n\n"
+    );
+}
+
+#[test]
+fn test_descendant_count() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut part = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    let mut chapter = part.create_child_at_end(FOLDER).unwrap();
+    let first_scene = chapter.create_child_at_end(SCENE).unwrap();
+    let second_scene = chapter.create_child_at_end(SCENE).unwrap();
+    let interlude = part.create_child_at_end(SCENE).unwrap();
+
+    let (part_id, chapter_id, scene_id) = (
+        part.id().clone(),
+        chapter.id().clone(),
+        first_scene.id().clone(),
+    );
+    for object in [part, chapter, first_scene, second_scene, interlude] {
+        project.add_object(object);
+    }
+
+    let descendant_count = |id: &FileID| {
+        project.objects[id]
+            .borrow()
+            .descendant_count(&project.objects)
+    };
+
+    assert_eq!(descendant_count(&part_id), 4);
+    assert_eq!(descendant_count(&chapter_id), 2);
+    assert_eq!(descendant_count(&scene_id), 0);
+}
+
+#[test]
+fn test_export_scene_metadata_comments() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut ids = Vec::new();
+    for (name, body) in [("Opening", "It was a dark night."), ("The \"End\"", "Fin.")] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.get_base_mut().metadata.name = name.to_string();
+        scene.load_body(body.to_string());
+        ids.push(scene.id().clone());
+        project.add_object(scene);
+    }
+
+    assert_eq!(
+        project.export_text(plain_export_options()),
+        "It was a dark night.\n\nFin.\n\n"
+    );
+
+    project.metadata.export.scene_metadata_comments = true;
+    project.save().unwrap();
+    let project = Project::load(project.get_path()).unwrap();
+    let export_options = project.metadata.export.export_options();
+    assert!(export_options.scene_metadata_comments);
+
+    // The comment comes right before each scene's body
+    assert_eq!(
+        project.export_text(ExportOptions {
+            scene_metadata_comments: true,
+            ..plain_export_options()
+        }),
+        format!(
+            "<!-- scene: id={} name=\"Opening\" index=0 -->\n\nIt was a dark night.\n\n\
+            <!-- scene: id={} name=\"The \\\"End\\\"\" index=1 -->\n\nFin.\n\n",
+            ids[0], ids[1]
+        )
+    );
+}
+
+/// Saving from the app shouldn't make the tracker reload what was just written, but changes made
+/// right afterwards by something else still need to be picked up
+#[test]
+fn test_tracker_ignores_own_saves() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("First draft".to_string());
+    let scene_id = scene.id().clone();
+    let scene_file = scene.get_file();
+    project.add_object(scene);
+    project.save().unwrap();
+    process_updates_after_save(&mut project);
+
+    thread::sleep(MTIME_SLEEP_DURATION);
+    {
+        let mut scene = project.objects.get(&scene_id).unwrap().borrow_mut();
+        scene.load_body("Second draft".to_string());
+        scene.get_base_mut().file.modified = true;
+    }
+    project.metadata.genre = "Mystery".to_string();
+    project.file.modified = true;
+    project.save().unwrap();
+
+    for _ in 0..5 {
+        thread::sleep(time::Duration::from_millis(60));
+        project.receive_updates();
+    }
+    assert!(!project.has_updates_queued());
+
+    // Something else editing the file is still noticed
+    thread::sleep(MTIME_SLEEP_DURATION);
+    let contents = read_to_string(&scene_file).unwrap();
+    write_with_temp_file(&scene_file, contents.replace("Second draft", "Third draft")).unwrap();
+    process_updates(&mut project);
+
+    assert_eq!(
+        project.objects.get(&scene_id).unwrap().borrow().get_body(),
+        "Third draft"
+    );
+}
+
+#[test]
+fn test_modified_objects() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    project.save().unwrap();
+    assert!(project.modified_objects().is_empty());
+
+    let mut ids = Vec::new();
+    for name in ["Beta", "Alpha"] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.get_base_mut().metadata.name = name.to_string();
+        ids.push(scene.id().clone());
+        project.add_object(scene);
+    }
+    project.save().unwrap();
+    assert!(project.modified_objects().is_empty());
+
+    for id in &ids {
+        let mut scene = project.objects.get(id).unwrap().borrow_mut();
+        scene.load_body("Some words".to_string());
+        scene.get_base_mut().file.modified = true;
+    }
+
+    // Sorted by title
+    assert_eq!(
+        project.modified_objects(),
+        vec![ids[1].clone(), ids[0].clone()]
+    );
+
+    project.save().unwrap();
+    assert!(project.modified_objects().is_empty());
+}
+
+#[test]
+fn test_import_characters_csv() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let characters_id = project.top_level_folder_id("Characters").unwrap().clone();
+
+    let csv_path = base_dir.path().join("characters.csv");
+    std::fs::write(
+        &csv_path,
+        "Name,Summary,Age\n\
+        Alice,\"A detective, retired\",52\n\
+        Broken row\n\
+        ,No name,1\n\
+        Bob,,30\n",
+    )
+    .unwrap();
+
+    let imported = project
+        .import_characters_csv(&csv_path, &characters_id)
+        .unwrap();
+    assert_eq!(imported.len(), 2);
+    project.save().unwrap();
+
+    assert_eq!(
+        project.objects[&characters_id].borrow().get_base().children,
+        imported
+    );
+
+    let alice_file = project.objects[&imported[0]].borrow().get_file();
+    assert_eq!(project.objects[&imported[0]].borrow().get_title(), "Alice");
+    assert!(
+        read_to_string(alice_file)
+            .unwrap()
+            .contains("summary = \"A detective, retired\"")
+    );
+    assert_eq!(project.objects[&imported[1]].borrow().get_title(), "Bob");
+
+    // There's no way to tell what to name the characters without a name column
+    std::fs::write(&csv_path, "Summary\nA detective\n").unwrap();
+    assert!(
+        project
+            .import_characters_csv(&csv_path, &characters_id)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_export_glossary() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("Alice went home.".to_string());
+    project.add_object(scene);
+
+    // Nothing has been added to the glossary yet
+    assert_eq!(project.export_glossary(&plain_export_options()), "");
+
+    let characters_id = project.top_level_folder_id("Characters").unwrap().clone();
+    let mut character = project.objects[&characters_id]
+        .borrow_mut()
+        .create_child_at_end(CHARACTER)
+        .unwrap();
+    character.get_base_mut().metadata.name = "Alice".to_string();
+    character.get_base_mut().toml_header["summary"] = toml_edit::value("A retired detective");
+    character.load_metadata().unwrap();
+    project.add_object(character);
+
+    let worldbuilding_id = project
+        .top_level_folder_id("Worldbuilding")
+        .unwrap()
+        .clone();
+    let mut place = project.objects[&worldbuilding_id]
+        .borrow_mut()
+        .create_child_at_end(PLACE)
+        .unwrap();
+    place.get_base_mut().metadata.name = "Home".to_string();
+    project.add_object(place);
+
+    assert_eq!(
+        project.export_text(plain_export_options()),
+        "Alice went home.\n\n"
+    );
+
+    // The glossary isn't counted as part of the story
+    assert_eq!(
+        project.export_text(ExportOptions {
+            append_glossary: true,
+            append_word_count: true,
+            ..plain_export_options()
+        }),
+        "Alice went home.\n\n\
+        # Glossary\n\n\
+        ## Characters\n\n\
+        Alice\n: A retired detective\n\n\
+        ## Worldbuilding\n\n\
+        Home\n\n\
+        Total: 3 words\n"
+    );
+
+    // Large offsets are capped at the deepest valid heading
+    assert_eq!(
+        project.export_glossary(&ExportOptions {
+            heading_offset: 10,
+            ..plain_export_options()
+        }),
+        "###### Glossary\n\n\
+        ###### Characters\n\n\
+        Alice\n: A retired detective\n\n\
+        ###### Worldbuilding\n\n\
+        Home\n\n"
+    );
+}
+
+/// Saving to a folder that can't be written to should fail with an error that says so, without
+/// losing any of the changes that weren't saved
+#[cfg(unix)]
+#[test]
+fn test_save_read_only() {
+    use std::fs::{Permissions, set_permissions};
+    use std::os::unix::fs::PermissionsExt;
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("Saved".to_string());
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    let project_path = project.get_path();
+    let text_path = project.get_text_folder().borrow().get_path();
+    for path in [&project_path, &text_path] {
+        set_permissions(path, Permissions::from_mode(0o555)).unwrap();
+    }
+
+    // Permissions don't apply to root, so there's no way to make the write fail
+    if std::fs::write(text_path.join("probe"), "").is_ok() {
+        log::warn!("skipping test_save_read_only, the folder is still writable");
+        return;
+    }
+
+    {
+        let mut scene = project.objects[&scene_id].borrow_mut();
+        scene.load_body("Not saved yet".to_string());
+        scene.get_base_mut().file.modified = true;
+    }
+    project.metadata.genre = "Mystery".to_string();
+    project.file.modified = true;
+
+    let err = project.save().unwrap_err();
+    assert!(err.is_read_only());
+
+    // Everything is still there to be saved later
+    assert!(project.objects[&scene_id].borrow().get_base().file.modified);
+    assert!(project.file.modified);
+
+    for path in [&project_path, &text_path] {
+        set_permissions(path, Permissions::from_mode(0o755)).unwrap();
+    }
+
+    project.save().unwrap();
+    let scene_file = project.objects[&scene_id].borrow().get_file();
+    assert!(
+        read_to_string(scene_file)
+            .unwrap()
+            .ends_with("Not saved yet")
+    );
+}
+
+#[test]
+fn test_export_strip_markdown() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("It was **very** dark, see [the map](map.png).".to_string());
+    project.add_object(scene);
+
+    assert_eq!(
+        project.export_text(plain_export_options()),
+        "It was **very** dark, see [the map](map.png).\n\n"
+    );
+
+    project.metadata.export.strip_markdown = true;
+    project.save().unwrap();
+    let project = Project::load(project.get_path()).unwrap();
+    assert!(project.metadata.export.export_options().strip_markdown);
+
+    assert_eq!(
+        project.export_text(ExportOptions {
+            strip_markdown: true,
+            ..plain_export_options()
+        }),
+        "It was very dark, see the map.\n\n"
+    );
+}
+
+#[test]
+fn test_conflict_markers() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut ids = Vec::new();
+    for body in [
+        "It was\n<<<<<<< HEAD\nraining.\n=======\nsnowing.\n>>>>>>> other-branch",
+        "It was sunny.\n=======",
+    ] {
+        let mut scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene.load_body(body.to_string());
+        ids.push(scene.id().clone());
+        project.add_object(scene);
+    }
+    project.save().unwrap();
+
+    let project = Project::load(project.get_path()).unwrap();
+    let conflicted = project.objects.get(&ids[0]).unwrap();
+    let clean = project.objects.get(&ids[1]).unwrap();
+
+    // Conflicts are found while loading, without having to load the body
+    assert!(!conflicted.borrow().is_body_loaded());
+    assert!(conflicted.borrow().has_conflict_markers());
+    assert!(!clean.borrow().has_conflict_markers());
+
+    conflicted.borrow().get_body();
+    clean.borrow().get_body();
+    assert!(conflicted.borrow().has_conflict_markers());
+    assert!(!clean.borrow().has_conflict_markers());
+}
+
+#[test]
+fn test_export_excluded_folder() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.load_body("Kept.".to_string());
+    project.add_object(scene);
+
+    let mut folder = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+    let folder_path = folder.get_file();
+
+    let mut scene = folder.create_child_at_end(SCENE).unwrap();
+    scene.load_body("Deleted scene.".to_string());
+    project.add_object(scene);
+
+    // Everything under the folder is skipped, even if it's included itself
+    let mut nested_folder = folder.create_child_at_end(FOLDER).unwrap();
+    let mut scene = nested_folder.create_child_at_end(SCENE).unwrap();
+    scene.load_body("Another deleted scene.".to_string());
+    project.add_object(scene);
+    project.add_object(nested_folder);
+    project.add_object(folder);
+    project.save().unwrap();
+
+    assert!(
+        project
+            .export_text(plain_export_options())
+            .contains("Another deleted scene.")
+    );
+
+    let folder_text = read_to_string(&folder_path)
+        .unwrap()
+        .replace("compile_status = 1", "compile_status = 0");
+    std::fs::write(&folder_path, folder_text).unwrap();
+    let project = Project::load(project.get_path()).unwrap();
+
+    assert_eq!(project.export_text(plain_export_options()), "Kept.\n\n");
+}
+
+#[test]
+fn test_project_cover() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+    let project_path = project.get_path();
+    assert_eq!(project.find_cover(), None);
+    assert_eq!(find_project_cover(&project_path), None);
+
+    // Not actually a valid image, but we only care about the file being copied
+    let source_image = base_dir.path().join("cover art.JPG");
+    std::fs::write(&source_image, "not really a jpg").unwrap();
+
+    let cover = project.set_cover(&source_image).unwrap();
+    assert_eq!(cover, project_path.join("cover.jpg"));
+    assert_eq!(read_to_string(&cover).unwrap(), "not really a jpg");
+
+    // Replacing the cover removes the old one
+    let source_image = base_dir.path().join("better cover.png");
+    std::fs::write(&source_image, "not really a png").unwrap();
+    let cover = project.set_cover(&source_image).unwrap();
+    assert!(!project_path.join("cover.jpg").exists());
+
+    // Picking the current cover again keeps it
+    project.set_cover(&cover).unwrap();
+    assert_eq!(read_to_string(&cover).unwrap(), "not really a png");
+
+    // Unknown extensions are rejected
+    let not_an_image = base_dir.path().join("cover.txt");
+    std::fs::write(&not_an_image, "text").unwrap();
+    assert!(project.set_cover(&not_an_image).is_err());
+
+    // The chooser finds it without loading the project, and it isn't picked up as an object
+    drop(project);
+    assert_eq!(find_project_cover(&project_path), Some(cover.clone()));
+
+    let project = Project::load(project_path).unwrap();
+    assert_eq!(project.objects.len(), 3);
+    assert_eq!(project.find_cover(), Some(cover));
+}
+
+#[test]
+fn test_apply_storage_settings() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene.get_base_mut().metadata.name = "scene1".to_string();
+    scene.load_body("first body".to_string());
+    let scene_id = scene.id().clone();
+    project.add_object(scene);
+    project.save().unwrap();
+
+    let text_path = project.get_text_folder().borrow().get_path();
+    assert!(text_path.join("000-scene1.md").exists());
+
+    // Changing the settings on an open project renames the files that are already there
+    project
+        .metadata
+        .file_extensions
+        .insert("scene".to_string(), "txt".to_string());
+    project.metadata.index_width = Some(4);
+    project.file.modified = true;
+    project.apply_storage_settings();
+    project.save().unwrap();
+
+    assert_eq!(
+        project.objects[&scene_id].borrow().get_file(),
+        text_path.join("0000-scene1.txt")
+    );
+    assert!(!text_path.join("000-scene1.md").exists());
+    assert!(
+        read_to_string(text_path.join("0000-scene1.txt"))
+            .unwrap()
+            .contains("first body")
+    );
+
+    let project = Project::load(project.get_path()).unwrap();
+    assert_eq!(project.metadata.index_width, Some(4));
+    assert_eq!(project.metadata.file_extensions["scene"], "txt");
+}
+
+#[test]
+fn test_folder_word_targets() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut chapter_ids = Vec::new();
+    for (body, chapter_target) in [("three short words", Some(1000)), ("untargeted", None)] {
+        let mut chapter = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(FOLDER)
+            .unwrap();
+        if let Some(chapter_target) = chapter_target {
+            chapter.get_base_mut().toml_header["target_word_count"] =
+                toml_edit::value(chapter_target);
+            chapter.load_metadata().unwrap();
+        }
+
+        // The chapter's target replaces the targets of the scenes in it
+        let mut scene = chapter.create_child_at_end(SCENE).unwrap();
+        scene.load_body(body.to_string());
+        scene.get_base_mut().toml_header["target_word_count"] = toml_edit::value(300);
+        scene.load_metadata().unwrap();
+        project.add_object(scene);
+
+        chapter_ids.push(chapter.id().clone());
+        project.add_object(chapter);
+    }
+
+    assert_eq!(
+        project.folder_word_targets(),
+        vec![(chapter_ids[0].clone(), 3, 1000)]
+    );
+    assert_eq!(
+        project.objects[&chapter_ids[1]]
+            .borrow()
+            .aggregate_word_target(&project.objects),
+        (1, 300)
+    );
+
+    // The whole story adds up the chapters
+    assert_eq!(
+        project
+            .get_text_folder()
+            .borrow()
+            .aggregate_word_target(&project.objects),
+        (4, 1300)
+    );
+    assert_eq!(
+        project.story_word_targets(),
+        (4, vec![(chapter_ids[0].clone(), 3, 1000)])
+    );
+
+    // The target is kept when saving
+    project.save().unwrap();
+    let project = Project::load(base_dir.path().join("test_project")).unwrap();
+    assert_eq!(
+        project.folder_word_targets(),
+        vec![(chapter_ids[0].clone(), 3, 1000)]
+    );
+}
+
+#[test]
+fn test_linear_scene_order() {
+    let base_dir = tempfile::TempDir::new().unwrap();
+
+    let mut project = Project::new(
+        SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    // text/
+    //   scene a
+    //   chapter/
+    //     scene b
+    //     part/
+    //       scene c
+    //     scene d
+    //   scene e
+    let mut scene_ids = Vec::new();
+
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene_ids.push(scene.id().clone());
+    project.add_object(scene);
+
+    let mut chapter = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(FOLDER)
+        .unwrap();
+
+    let scene = chapter.create_child_at_end(SCENE).unwrap();
+    scene_ids.push(scene.id().clone());
+    project.add_object(scene);
+
+    let mut part = chapter.create_child_at_end(FOLDER).unwrap();
+    let scene = part.create_child_at_end(SCENE).unwrap();
+    scene_ids.push(scene.id().clone());
+    project.add_object(scene);
+    project.add_object(part);
+
+    let scene = chapter.create_child_at_end(SCENE).unwrap();
+    scene_ids.push(scene.id().clone());
+    project.add_object(scene);
+    project.add_object(chapter);
+
+    let scene = project
+        .get_text_folder()
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    scene_ids.push(scene.id().clone());
+    project.add_object(scene);
+
+    // Scenes outside of the text aren't part of the story
+    let worldbuilding_id = project.top_level_folders[2].clone();
+    let notes = project.objects[&worldbuilding_id]
+        .borrow_mut()
+        .create_child_at_end(SCENE)
+        .unwrap();
+    project.add_object(notes);
+
+    assert_eq!(project.linear_scene_order(), scene_ids);
+}
@@ -1,8 +1,11 @@
 use std::any::TypeId;
+use std::collections::HashMap;
 use std::ops::{Deref, DerefMut, Range};
+use std::sync::LazyLock;
 use std::sync::atomic::AtomicUsize;
 
 use egui::TextBuffer;
+use regex::Regex;
 
 static GLOBAL_ID_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
@@ -98,3 +101,270 @@ impl TextBuffer for Text {
         TypeId::of::<Self>()
     }
 }
+
+/// Finds the byte ranges of every `%% comment %%` in a piece of text (including the markers).
+/// Comments can span multiple lines, an unterminated `%%` is just treated as text
+pub fn comment_ranges(text: &str) -> Vec<Range<usize>> {
+    static COMMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)%%.*?%%").unwrap());
+
+    COMMENT_REGEX
+        .find_iter(text)
+        .map(|comment| comment.range())
+        .collect()
+}
+
+/// Removes all of the comments from a piece of text (for export), trying to not leave behind
+/// any doubled spaces or blank lines where the comments used to be
+pub fn strip_comments(text: &str) -> String {
+    let ranges = comment_ranges(text);
+
+    if ranges.is_empty() {
+        return text.to_string();
+    }
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut last_end = 0;
+
+    for range in ranges {
+        stripped.push_str(&text[last_end..range.start]);
+        last_end = range.end;
+
+        let rest = &text[last_end..];
+        if stripped.ends_with(' ') && (rest.is_empty() || rest.starts_with([' ', '\n'])) {
+            stripped.pop();
+        } else if (stripped.is_empty() || stripped.ends_with('\n')) && rest.starts_with(' ') {
+            last_end += 1;
+        }
+    }
+
+    stripped.push_str(&text[last_end..]);
+
+    // Comments that took up an entire paragraph leave extra blank lines behind
+    static EXTRA_NEWLINES_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\n[ \t]*\n(?:[ \t]*\n)+").unwrap());
+
+    let stripped = EXTRA_NEWLINES_REGEX.replace_all(&stripped, "\n\n");
+
+    if text.starts_with('\n') {
+        stripped.into_owned()
+    } else {
+        stripped.trim_start().to_string()
+    }
+}
+
+/// End every line that continues onto another line of the same paragraph with two spaces, so that
+/// markdown keeps the line break instead of joining the lines together (for poetry and verse).
+/// Headings and fenced code blocks are left alone
+pub fn preserve_hard_breaks(text: &str) -> String {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut result = String::with_capacity(text.len() + lines.len() * 2);
+    let mut in_code_block = false;
+
+    for (line_number, line) in lines.iter().enumerate() {
+        let is_fence = line.trim_start().starts_with("```");
+        if is_fence {
+            in_code_block = !in_code_block;
+        }
+
+        let continues = lines
+            .get(line_number + 1)
+            .is_some_and(|next_line| !next_line.trim().is_empty());
+
+        if continues
+            && !is_fence
+            && !in_code_block
+            && !line.trim().is_empty()
+            && !line.trim_start().starts_with('#')
+            && !line.ends_with('\\')
+        {
+            result.push_str(line.trim_end());
+            result.push_str("  ");
+        } else {
+            result.push_str(line);
+        }
+
+        if line_number + 1 < lines.len() {
+            result.push('\n');
+        }
+    }
+
+    result
+}
+
+#[test]
+fn test_preserve_hard_breaks() {
+    assert_eq!(
+        preserve_hard_breaks("Roses are red,\nViolets are blue.\n\nSecond verse\nhere.\n"),
+        "Roses are red,  \nViolets are blue.\n\nSecond verse  \nhere.\n"
+    );
+
+    // lines that already break, or that end in some extra spaces
+    assert_eq!(
+        preserve_hard_breaks("one  \ntwo \nthree\\\nfour"),
+        "one  \ntwo  \nthree\\\nfour"
+    );
+
+    // headings and code are left alone
+    assert_eq!(
+        preserve_hard_breaks("# Title\nline\n```\ncode\nmore code\n```\nafter"),
+        "# Title\nline  \n```\ncode\nmore code\n```\nafter"
+    );
+}
+
+/// Counts the words in text that's being exported, where markdown markers on their own (like the
+/// `#` of a heading) aren't words
+pub fn count_export_words(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+#[test]
+fn test_count_export_words() {
+    assert_eq!(count_export_words("# A Heading\n\n**bold** words"), 4);
+    assert_eq!(count_export_words("----\n\n* * *"), 0);
+    assert_eq!(count_export_words(""), 0);
+}
+
+#[test]
+fn test_strip_comments() {
+    assert_eq!(strip_comments("no comments here"), "no comments here");
+    assert_eq!(
+        strip_comments("She left %% fix this later %% quickly."),
+        "She left quickly."
+    );
+    assert_eq!(
+        strip_comments("First.\n\n%% a whole\nparagraph of notes %%\n\nSecond."),
+        "First.\n\nSecond."
+    );
+    assert_eq!(strip_comments("%%start%% text"), "text");
+    assert_eq!(strip_comments("text %%end%%"), "text");
+    assert_eq!(strip_comments("100%% sure"), "100%% sure");
+}
+
+/// A markdown task list item (`- [ ] do the thing` or `- [x] did the thing`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskItem {
+    pub checked: bool,
+    /// The text after the checkbox
+    pub label: String,
+    /// The byte offset of the character between the brackets, which is what gets toggled
+    mark: usize,
+}
+
+/// Finds every task list item in a piece of text, in order
+pub fn task_items(text: &str) -> Vec<TaskItem> {
+    static TASK_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?m)^[ \t]*[-*+] \[([ xX])\](?:[ \t]+(.*?))?\r?$").unwrap());
+
+    TASK_REGEX
+        .captures_iter(text)
+        .map(|captures| {
+            let mark = captures.get(1).unwrap();
+            TaskItem {
+                checked: mark.as_str() != " ",
+                label: captures
+                    .get(2)
+                    .map_or(String::new(), |label| label.as_str().to_string()),
+                mark: mark.start(),
+            }
+        })
+        .collect()
+}
+
+impl Text {
+    /// Check or uncheck a task list item, found with `task_items`. Does nothing if the text has
+    /// changed so that the item isn't there anymore
+    pub fn toggle_task(&mut self, task: &TaskItem) {
+        let replacement = if task.checked { " " } else { "x" };
+
+        let current = self.text.get(task.mark..task.mark + 1);
+        if current.is_some_and(|current| (current != " ") == task.checked) {
+            self.replace_range(task.mark..task.mark + 1, replacement);
+        } else {
+            log::warn!(
+                "Task list item at {} has changed, not toggling it",
+                task.mark
+            );
+        }
+    }
+}
+
+#[test]
+fn test_toggle_task() {
+    let mut text = Text::from(
+        "Beats:\n- [ ] she arrives\n  * [x] the storm\n- [] not a task\n- [ ]\n".to_string(),
+    );
+
+    let tasks = task_items(&text);
+    assert_eq!(
+        tasks
+            .iter()
+            .map(|task| (task.checked, task.label.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(false, "she arrives"), (true, "the storm"), (false, "")]
+    );
+
+    let version = text.version;
+    text.toggle_task(&tasks[0]);
+    text.toggle_task(&tasks[1]);
+    assert_eq!(
+        text.as_str(),
+        "Beats:\n- [x] she arrives\n  * [ ] the storm\n- [] not a task\n- [ ]\n"
+    );
+    assert!(text.version > version);
+
+    // Toggling with a stale item doesn't touch the text
+    text.toggle_task(&tasks[0]);
+    assert!(text.as_str().starts_with("Beats:\n- [x] she arrives"));
+}
+
+static FOOTNOTE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[\^([^\]\s]+)\]").unwrap());
+
+/// Prefixes every footnote label (`[^1]`, and its definition `[^1]: ...`) with `scope`, so that
+/// footnotes from different scenes can't collide once they're exported into a single file. The
+/// labels are turned back into numbers by `renumber_footnotes`
+pub fn scope_footnotes(text: &str, scope: &str) -> String {
+    FOOTNOTE_REGEX
+        .replace_all(text, |captures: &regex::Captures| {
+            format!("[^{scope}-{}]", &captures[1])
+        })
+        .into_owned()
+}
+
+/// Renumbers every footnote label to 1, 2, 3, ... in the order that they first appear
+pub fn renumber_footnotes(text: &str) -> String {
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+
+    FOOTNOTE_REGEX
+        .replace_all(text, |captures: &regex::Captures| {
+            let next_number = numbers.len() + 1;
+            let number = numbers
+                .entry(captures[1].to_string())
+                .or_insert(next_number);
+            format!("[^{number}]")
+        })
+        .into_owned()
+}
+
+#[test]
+fn test_renumber_footnotes() {
+    let first = scope_footnotes(
+        "Rivers[^1] and hills[^note].\n\n[^1]: wet\n[^note]: dry\n",
+        "a",
+    );
+    let second = scope_footnotes("Mountains[^1].\n\n[^1]: tall\n", "b");
+
+    assert_eq!(
+        first,
+        "Rivers[^a-1] and hills[^a-note].\n\n[^a-1]: wet\n[^a-note]: dry\n"
+    );
+
+    assert_eq!(
+        renumber_footnotes(&format!("{first}\n{second}")),
+        "Rivers[^1] and hills[^2].\n\n[^1]: wet\n[^2]: dry\n\nMountains[^3].\n\n[^3]: tall\n"
+    );
+
+    // not footnotes
+    assert_eq!(scope_footnotes("[^ 1] [^] [1]", "a"), "[^ 1] [^] [1]");
+}
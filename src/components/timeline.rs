@@ -0,0 +1,57 @@
+use crate::components::file_objects::{FileID, FileObject, FileObjectStore};
+use crate::components::project::Project;
+
+/// A scene (or other object in the story text) placed on the timeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEntry {
+    pub file_id: FileID,
+    pub title: String,
+    pub story_date: Option<String>,
+}
+
+fn collect_entries(file_id: &FileID, objects: &FileObjectStore, entries: &mut Vec<TimelineEntry>) {
+    let Some(object) = objects.get(file_id) else {
+        return;
+    };
+    let object = object.borrow();
+
+    if !object.is_folder() {
+        entries.push(TimelineEntry {
+            file_id: file_id.clone(),
+            title: object.get_title(),
+            story_date: object.story_date().map(|story_date| story_date.to_string()),
+        });
+    }
+
+    for child_id in object.get_base().children.iter() {
+        collect_entries(child_id, objects, entries);
+    }
+}
+
+/// Sort entries by their story date (just comparing the strings). Entries without a date go at
+/// the end, and ties stay in reading order
+pub fn sort_by_story_date(entries: &mut [TimelineEntry]) {
+    entries.sort_by(|a, b| match (&a.story_date, &b.story_date) {
+        (Some(a_date), Some(b_date)) => a_date.cmp(b_date),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+impl Project {
+    /// Every object in the story text, in chronological (rather than reading) order
+    pub fn timeline(&self) -> Vec<TimelineEntry> {
+        let mut entries = Vec::new();
+
+        if let Some(text_folder) = self.objects.get(self.text_folder_id()) {
+            for child_id in text_folder.borrow().get_base().children.iter() {
+                collect_entries(child_id, &self.objects, &mut entries);
+            }
+        }
+
+        sort_by_story_date(&mut entries);
+
+        entries
+    }
+}
@@ -1,19 +1,56 @@
 // hide console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod cli;
 mod components;
 mod schemas;
 mod ui;
 mod util;
 
 use crate::ui::CheesePaperApp;
+use crate::util::AppDirs;
 
 fn main() -> eframe::Result {
     env_logger::init();
 
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        cli::attach_parent_console();
+    }
+    let data_dir = match cli::take_data_dir(&mut args) {
+        Ok(data_dir) => data_dir,
+        Err(err) => {
+            eprintln!("{err}\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // Exports and checks can be run from the command line (e.g., in scripts) without opening the
+    // editor
+    match cli::parse_args(args) {
+        Ok(Some(cli::CliCommand::Export(export_args))) => {
+            if let Err(err) = cli::run_export(&export_args) {
+                eprintln!("export failed: {err}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Ok(Some(cli::CliCommand::Check { project })) => {
+            if !cli::run_check(&project) {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Ok(None) => {}
+        Err(err) => {
+            eprintln!("{err}\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
     eframe::run_native(
         "Cheese Paper Rust Single File",
         Default::default(),
-        Box::new(|cc| Ok(Box::new(CheesePaperApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(CheesePaperApp::new(cc, AppDirs::new(data_dir))))),
     )
 }
@@ -84,7 +84,10 @@ impl Schema for DefaultSchema {
             Character::IDENTIFIER => Ok(Box::new(character::Character::from_base(base)?)),
             Folder::IDENTIFIER => Ok(Box::new(folder::Folder::from_base(base)?)),
             Place::IDENTIFIER => Ok(Box::new(place::Place::from_base(base)?)),
-            Scene::IDENTIFIER => Ok(Box::new(scene::Scene::from_base(base, None)?)),
+            Scene::IDENTIFIER => Ok(Box::new(scene::Scene::from_base(
+                base,
+                Some(String::new()),
+            )?)),
             _ => unreachable!(),
         }
     }
@@ -95,7 +98,7 @@ impl Schema for DefaultSchema {
         base: BaseFileObject,
         body: Option<String>,
     ) -> Result<Box<dyn FileObject>, CheeseError> {
-        assert!(body.is_some() == file_type.has_body());
+        assert!(body.is_none() || file_type.has_body());
 
         match file_type.identifier {
             Character::IDENTIFIER => Ok(Box::new(character::Character::from_base(base)?)),
@@ -5,8 +5,8 @@ use crate::components::text::Text;
 use crate::schemas::FileType;
 use crate::util::CheeseError;
 
-use crate::ui::FileObjectEditor;
 use crate::ui::prelude::*;
+use crate::ui::{FileObjectEditor, image_ui};
 
 use crate::schemas::FileTypeInfo;
 
@@ -34,6 +34,7 @@ impl Place {
     pub const TYPE_INFO: FileTypeInfo = FileTypeInfo {
         identifier: Self::IDENTIFIER,
         is_folder: true,
+        can_nest: true,
         has_body: false,
         type_name: "Place",
         empty_string_name: "New Place",
@@ -120,6 +121,10 @@ impl FileObject for Place {
         &mut self.base
     }
 
+    fn glossary_summary(&self) -> Option<&str> {
+        Some(self.metadata.description.as_str())
+    }
+
     fn write_metadata(&mut self, _objects: &FileObjectStore) {
         self.base.toml_header["connection"] = toml_edit::value(&*self.metadata.connection);
         self.base.toml_header["description"] = toml_edit::value(&*self.metadata.description);
@@ -217,8 +222,13 @@ impl Place {
                         .desired_width(f32::INFINITY),
                 );
                 self.process_response(&response);
+                ctx.focus_name_if_requested(self.id(), &response);
                 ids.push(response.id);
 
+                egui::CollapsingHeader::new("Image")
+                    .default_open(true)
+                    .show(ui, |ui| image_ui(ui, &mut *self));
+
                 ui.label("Notes");
                 let response = ui.add_sized(ui.available_size(), |ui: &'_ mut Ui| {
                     self.metadata.notes.ui(ui, ctx)
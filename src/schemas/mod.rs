@@ -35,6 +35,11 @@ pub struct FileTypeInfo {
 
     is_folder: bool,
 
+    /// Types that only hold more of their own type (e.g., places inside of places). If `is_folder`
+    /// is false (e.g., characters), they're stored in a single file until something is put inside
+    /// of them, at which point they're turned into a folder
+    can_nest: bool,
+
     has_body: bool,
 
     type_name: &'static str,
@@ -82,6 +87,10 @@ impl FileTypeInfo {
         self.is_folder
     }
 
+    pub fn can_nest(&self) -> bool {
+        self.can_nest
+    }
+
     pub fn has_body(&self) -> bool {
         self.has_body
     }
@@ -82,8 +82,8 @@ impl Schema for OverthinkerSchema {
         match file_type.identifier {
             Character::IDENTIFIER => Ok(Box::new(Character::from_base(base)?)),
             Folder::IDENTIFIER => Ok(Box::new(Folder::from_base(base)?)),
-            Scene::IDENTIFIER => Ok(Box::new(Scene::from_base(base, None)?)),
-            Note::IDENTIFIER => Ok(Box::new(Note::from_base(base, None)?)),
+            Scene::IDENTIFIER => Ok(Box::new(Scene::from_base(base, Some(String::new()))?)),
+            Note::IDENTIFIER => Ok(Box::new(Note::from_base(base, Some(String::new()))?)),
             Section::IDENTIFIER => Ok(Box::new(Section::from_base(base)?)),
             _ => unreachable!(),
         }
@@ -95,7 +95,7 @@ impl Schema for OverthinkerSchema {
         base: BaseFileObject,
         body: Option<String>,
     ) -> Result<Box<dyn FileObject>, CheeseError> {
-        assert!(body.is_some() == file_type.has_body());
+        assert!(body.is_none() || file_type.has_body());
 
         match file_type.identifier {
             Character::IDENTIFIER => Ok(Box::new(character::Character::from_base(base)?)),
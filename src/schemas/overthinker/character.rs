@@ -1,12 +1,12 @@
 use crate::components::file_objects::FileObjectStore;
 use crate::components::file_objects::utils::{metadata_extract_string, write_outline_property};
-use crate::components::file_objects::{BaseFileObject, FileObject};
+use crate::components::file_objects::{BaseFileObject, FileObject, Relationship};
 use crate::components::text::Text;
 use crate::schemas::FileType;
 use crate::util::CheeseError;
 
-use crate::ui::FileObjectEditor;
 use crate::ui::prelude::*;
+use crate::ui::{FileObjectEditor, image_ui, relationships_ui};
 
 use crate::schemas::FileTypeInfo;
 
@@ -22,6 +22,8 @@ pub struct CharacterMetadata {
     pub goal: Text,
     pub conflict: Text,
     pub habits: Text,
+    /// How this character is connected to other characters (e.g., "sibling of")
+    pub relationships: Vec<Relationship>,
 }
 
 #[derive(Debug)]
@@ -36,11 +38,12 @@ impl Character {
     pub const TYPE_INFO: FileTypeInfo = FileTypeInfo {
         identifier: Self::IDENTIFIER,
         is_folder: false,
+        can_nest: true,
         has_body: false,
         type_name: "Character",
         empty_string_name: "New Character",
         extension: "toml",
-        description: "An info sheet for characters",
+        description: "An info sheet for characters, which can also group related characters",
     };
 
     pub fn from_base(base: BaseFileObject) -> Result<Self, CheeseError> {
@@ -116,6 +119,13 @@ impl FileObject for Character {
             None => modified = true,
         }
 
+        // Only written when there are some, most characters won't have any
+        self.metadata.relationships = Relationship::load_list(
+            self.base.toml_header.as_table(),
+            "relationships",
+            &Self::TYPE_INFO,
+        )?;
+
         Ok(modified)
     }
 
@@ -132,7 +142,23 @@ impl FileObject for Character {
         &mut self.base
     }
 
-    fn write_metadata(&mut self, _objects: &FileObjectStore) {
+    fn glossary_summary(&self) -> Option<&str> {
+        Some(self.metadata.summary.as_str())
+    }
+
+    fn resolve_references(&mut self, objects: &FileObjectStore) {
+        Relationship::resolve_list(&mut self.metadata.relationships, objects);
+    }
+
+    fn relationships(&self) -> &[Relationship] {
+        &self.metadata.relationships
+    }
+
+    fn broken_references(&self) -> Vec<String> {
+        Relationship::broken_list(&self.metadata.relationships)
+    }
+
+    fn write_metadata(&mut self, objects: &FileObjectStore) {
         self.base.toml_header["summary"] = toml_edit::value(&*self.metadata.summary);
         self.base.toml_header["notes"] = toml_edit::value(&*self.metadata.notes);
         self.base.toml_header["appearance"] = toml_edit::value(&*self.metadata.appearance);
@@ -140,9 +166,16 @@ impl FileObject for Character {
         self.base.toml_header["goal"] = toml_edit::value(&*self.metadata.goal);
         self.base.toml_header["conflict"] = toml_edit::value(&*self.metadata.conflict);
         self.base.toml_header["habits"] = toml_edit::value(&*self.metadata.habits);
+
+        Relationship::write_list(
+            &mut self.base.toml_header,
+            "relationships",
+            &self.metadata.relationships,
+            objects,
+        );
     }
 
-    fn generate_outline(&self, depth: u64, export_string: &mut String, _objects: &FileObjectStore) {
+    fn generate_outline(&self, depth: u64, export_string: &mut String, objects: &FileObjectStore) {
         (self as &dyn FileObject).write_title(depth, export_string);
 
         write_outline_property("summary", &self.metadata.summary, export_string);
@@ -152,6 +185,15 @@ impl FileObject for Character {
         write_outline_property("conflict", &self.metadata.conflict, export_string);
         write_outline_property("habits", &self.metadata.habits, export_string);
         write_outline_property("notes", &self.metadata.notes, export_string);
+        Relationship::write_outline_list(&self.metadata.relationships, objects, export_string);
+
+        for child_id in self.get_base().children.iter() {
+            objects.get(child_id).unwrap().borrow().generate_outline(
+                depth + 1,
+                export_string,
+                objects,
+            );
+        }
     }
 
     fn as_editor(&self) -> &dyn crate::ui::FileObjectEditor {
@@ -228,6 +270,7 @@ impl Character {
                     .desired_width(f32::INFINITY),
             );
             self.process_response(&response);
+            ctx.focus_name_if_requested(self.id(), &response);
             ids.push(response.id);
 
             // Make each text box take up a bit of the screen by default
@@ -256,6 +299,27 @@ impl Character {
                     self.process_response(&response);
                     ids.push(response.id);
                 });
+
+            egui::CollapsingHeader::new("Relationships")
+                .default_open(!self.metadata.relationships.is_empty())
+                .show(ui, |ui| {
+                    let own_id = self.id().clone();
+                    let changed = relationships_ui(
+                        ui,
+                        ctx,
+                        &mut self.metadata.relationships,
+                        &own_id,
+                        &Self::TYPE_INFO,
+                        &mut ids,
+                    );
+                    if changed {
+                        self.get_base_mut().file.mark_edited();
+                    }
+                });
+
+            egui::CollapsingHeader::new("Image")
+                .default_open(true)
+                .show(ui, |ui| image_ui(ui, &mut *self));
         });
 
         ids
@@ -4,13 +4,13 @@ use crate::components::file_objects::utils::{
 };
 use crate::components::file_objects::{BaseFileObject, FileObject};
 use crate::components::file_objects::{CompileStatus, IncludeOptions};
-use crate::components::project::ExportOptions;
+use crate::components::project::{ExportOptions, TitleStyle};
 use crate::components::text::Text;
 use crate::schemas::FileType;
 use crate::util::CheeseError;
 
-use crate::ui::FileObjectEditor;
 use crate::ui::prelude::*;
+use crate::ui::{FileObjectEditor, word_target_ui};
 
 use crate::ford_get;
 use crate::schemas::FileTypeInfo;
@@ -23,6 +23,8 @@ pub struct FolderMetadata {
     pub summary: Text,
     pub notes: Text,
     pub compile_status: CompileStatus,
+    /// How many words everything inside should add up to, replacing the targets inside of it
+    pub target_word_count: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -37,6 +39,7 @@ impl Folder {
     pub const TYPE_INFO: FileTypeInfo = FileTypeInfo {
         identifier: Self::IDENTIFIER,
         is_folder: true,
+        can_nest: false,
         has_body: false,
         type_name: "Folder",
         empty_string_name: "New Folder",
@@ -95,6 +98,11 @@ impl FileObject for Folder {
             None => modified = true,
         }
 
+        // Optional, most folders won't have a target
+        self.metadata.target_word_count =
+            metadata_extract_u64(self.base.toml_header.as_table(), "target_word_count", false)?
+                .filter(|target| *target > 0);
+
         Ok(modified)
     }
 
@@ -116,6 +124,19 @@ impl FileObject for Folder {
         self.base.toml_header["notes"] = toml_edit::value(&*self.metadata.notes);
         self.base.toml_header["compile_status"] =
             toml_edit::value(self.metadata.compile_status.bits() as i64);
+
+        match self.metadata.target_word_count {
+            Some(target) => {
+                self.base.toml_header["target_word_count"] = toml_edit::value(target as i64);
+            }
+            None => {
+                self.base.toml_header.remove("target_word_count");
+            }
+        }
+    }
+
+    fn target_word_count(&self) -> Option<u64> {
+        self.metadata.target_word_count
     }
 
     fn generate_outline(&self, depth: u64, export_string: &mut String, objects: &FileObjectStore) {
@@ -133,10 +154,15 @@ impl FileObject for Folder {
         }
     }
 
+    fn compile_status(&self) -> Option<&CompileStatus> {
+        Some(&self.metadata.compile_status)
+    }
+
     fn generate_export(
         &self,
         depth: u64,
         export_string: &mut String,
+        word_count: &mut usize,
         objects: &FileObjectStore,
         export_options: &ExportOptions,
         include_break: bool,
@@ -150,14 +176,18 @@ impl FileObject for Folder {
                 IncludeOptions::Always => true,
                 IncludeOptions::Default => export_options.folder_title_depth.should_display(depth),
                 IncludeOptions::Never => false,
-            };
+            } && export_options.folder_title_style != TitleStyle::None;
 
             // Keep track of whether the next scene will start with a break, which only ever gets
             // rendered in scenes
             let mut include_break_next = include_break;
 
             if display_title {
-                (self as &dyn FileObject).write_title(depth, export_string);
+                (self as &dyn FileObject).write_export_title(
+                    export_options.folder_title_style,
+                    export_options.heading_level(depth),
+                    export_string,
+                );
                 // We've written a title, so the requested break has been taken care of
                 include_break_next = false;
             }
@@ -173,6 +203,7 @@ impl FileObject for Folder {
                 include_break_next = objects.get(child_id).unwrap().borrow().generate_export(
                     depth + 1,
                     export_string,
+                    word_count,
                     objects,
                     export_options,
                     include_break_next,
@@ -260,10 +291,16 @@ impl Folder {
                     .desired_width(f32::INFINITY),
             );
             self.process_response(&response);
+            ctx.focus_name_if_requested(self.id(), &response);
             ids.push(response.id);
 
             match folder_data.tab {
                 Tab::Notes => {
+                    let response =
+                        word_target_ui(ui, &mut self.metadata.target_word_count, 100.0);
+                    self.process_response(&response);
+                    ids.push(response.id);
+
                     egui::CollapsingHeader::new("Summary")
                         .default_open(true)
                         .show(ui, |ui| {
@@ -341,7 +378,7 @@ impl Folder {
                             self.metadata
                                 .compile_status
                                 .set_include_title(include_title);
-                            self.get_base_mut().file.modified = true;
+                            self.get_base_mut().file.mark_edited();
                         }
 
                         // same thing but for the break
@@ -385,7 +422,7 @@ impl Folder {
                         // We don't have an actual response here so we have to manually process
                         if break_at_end != break_at_end_before {
                             self.metadata.compile_status.set_break_at_end(break_at_end);
-                            self.get_base_mut().file.modified = true;
+                            self.get_base_mut().file.mark_edited();
                         }
                     });
                 }
@@ -1,6 +1,6 @@
 use crate::components::file_objects::FileObjectStore;
-use crate::components::file_objects::utils::metadata_extract_string;
-use crate::components::file_objects::{BaseFileObject, FileObject};
+use crate::components::file_objects::utils::{format_relative_time, metadata_extract_string};
+use crate::components::file_objects::{BaseFileObject, FileObject, LazyBody};
 use crate::components::text::Text;
 use crate::schemas::FileType;
 use crate::util::CheeseError;
@@ -23,7 +23,7 @@ pub struct NoteMetadata {
 pub struct Note {
     base: BaseFileObject,
     pub metadata: NoteMetadata,
-    pub text: Text,
+    text: LazyBody,
 }
 
 impl Note {
@@ -32,6 +32,7 @@ impl Note {
     pub const TYPE_INFO: FileTypeInfo = FileTypeInfo {
         identifier: Self::IDENTIFIER,
         is_folder: false,
+        can_nest: false,
         has_body: true,
         type_name: "Note",
         empty_string_name: "New Note",
@@ -43,7 +44,7 @@ impl Note {
         let mut scene = Self {
             base,
             metadata: Default::default(),
-            text: body.map(|s| s.into()).unwrap_or_default(),
+            text: LazyBody::new(body),
         };
 
         match scene.load_metadata() {
@@ -64,6 +65,16 @@ impl Note {
 
         Ok(scene)
     }
+
+    /// The body text, read from disk the first time it's needed
+    pub fn text(&self) -> &Text {
+        self.text.get(|| (self as &dyn FileObject).get_file())
+    }
+
+    pub fn text_mut(&mut self) -> &mut Text {
+        let file = (self as &dyn FileObject).get_file();
+        self.text.get_mut(|| file)
+    }
 }
 
 impl FileObject for Note {
@@ -92,7 +103,7 @@ impl FileObject for Note {
     }
 
     fn load_body(&mut self, data: String) {
-        self.text = data.trim().to_string().into();
+        self.text.set(data.trim().to_string());
     }
 
     fn get_base(&self) -> &BaseFileObject {
@@ -106,7 +117,7 @@ impl FileObject for Note {
     fn get_body(&self) -> String {
         let mut full_text = String::new();
 
-        for line in self.text.split('\n') {
+        for line in self.text().split('\n') {
             full_text.push_str(line.trim());
             full_text.push('\n');
         }
@@ -114,6 +125,24 @@ impl FileObject for Note {
         full_text
     }
 
+    fn is_body_loaded(&self) -> bool {
+        self.text.is_loaded()
+    }
+
+    fn load_unloaded_body(&self) -> Result<(), CheeseError> {
+        self.text
+            .load(|| (self as &dyn FileObject).get_file())
+            .map(|_| ())
+    }
+
+    fn word_count(&self) -> usize {
+        if self.is_body_loaded() {
+            self.text().split_whitespace().count()
+        } else {
+            self.base.file.word_count
+        }
+    }
+
     fn write_metadata(&mut self, _objects: &FileObjectStore) {
         self.base.toml_header["subject"] = toml_edit::value(&*self.metadata.subject);
         self.base.toml_header["commentary"] = toml_edit::value(&*self.metadata.commentary);
@@ -153,26 +182,37 @@ impl FileObjectEditor for Note {
     fn for_each_textbox<'a>(&'a self, f: &mut dyn FnMut(&Text, &'static str)) {
         f(&self.metadata.subject, "subject");
         f(&self.metadata.commentary, "commentary");
-        f(&self.text, "text");
+        f(self.text(), "text");
     }
 
     fn for_each_textbox_mut<'a>(&'a mut self, f: &mut dyn FnMut(&mut Text, &'static str)) {
         f(&mut self.metadata.subject, "Subject");
         f(&mut self.metadata.commentary, "Commentary");
-        f(&mut self.text, "text");
+        f(self.text_mut(), "text");
     }
 }
 
 impl Note {
     fn show_text_editor(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext) -> Vec<Id> {
+        // Editing the placeholder would look like it worked, but none of it could be saved
+        if let Err(err) = self.load_unloaded_body() {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("The text of this note couldn't be read: {err}"),
+            );
+            return Vec::new();
+        }
+
         ScrollArea::vertical()
             .id_salt("text")
             .auto_shrink(egui::Vec2b { x: false, y: false })
             .show(ui, |ui| {
-                let response =
-                    ui.add_sized(ui.available_size(), |ui: &'_ mut Ui| self.text.ui(ui, ctx));
+                let response = ui.add_sized(ui.available_size(), |ui: &'_ mut Ui| {
+                    self.text_mut().ui(ui, ctx)
+                });
 
                 self.process_response(&response);
+                ctx.focus_body_end_if_requested(self.id(), ui, &response, self.text());
                 vec![response.id]
             })
             .inner
@@ -183,10 +223,28 @@ impl Note {
 
         egui::TopBottomPanel::bottom("word_count").show_inside(ui, |ui| {
             ui.add_space(4.0);
-            let words = self.text.word_count(ctx);
+            let words = self.text().word_count(ctx);
             let text = format!("{words} Words");
             ui.vertical_centered(|ui| {
                 ui.label(text);
+
+                if let Some(elapsed) = self
+                    .get_base()
+                    .file
+                    .modtime
+                    .and_then(|modtime| modtime.elapsed().ok())
+                {
+                    ui.weak(format!("edited {}", format_relative_time(elapsed)));
+                }
+
+                if let Some(elapsed) = self
+                    .get_base()
+                    .metadata
+                    .created
+                    .and_then(|created| created.elapsed().ok())
+                {
+                    ui.weak(format!("created {}", format_relative_time(elapsed)));
+                }
             });
         });
 
@@ -199,6 +257,7 @@ impl Note {
                     .desired_width(f32::INFINITY),
             );
             self.process_response(&response);
+            ctx.focus_name_if_requested(self.id(), &response);
             ids.push(response.id);
 
             let text_box_height = response.rect.height().abs();
@@ -2,19 +2,23 @@ use regex::Regex;
 
 use crate::components::file_objects::FileObjectStore;
 use crate::components::file_objects::utils::{
-    metadata_extract_string, metadata_extract_u64, write_outline_property,
+    format_relative_time, metadata_extract_bool, metadata_extract_string, metadata_extract_u64,
+    trim_final_newline, write_outline_property,
 };
-use crate::components::file_objects::{BaseFileObject, FileObject};
+use crate::components::file_objects::{BaseFileObject, FileObject, LazyBody};
 use crate::components::file_objects::{CompileStatus, IncludeOptions, ObjectReference};
-use crate::components::project::ExportOptions;
-use crate::components::text::Text;
+use crate::components::markdown::{MarkdownDoc, ParsedText, strip_markdown};
+use crate::components::project::{ExportOptions, TitleStyle};
+use crate::components::text::{
+    Text, count_export_words, preserve_hard_breaks, scope_footnotes, strip_comments,
+};
 use crate::schemas::FileType;
 use crate::util::CheeseError;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::ui::FileObjectEditor;
 use crate::ui::prelude::*;
+use crate::ui::{FileObjectEditor, word_target_ui};
 
 use crate::ford_get;
 use crate::schemas::FileTypeInfo;
@@ -28,13 +32,23 @@ pub struct SceneMetadata {
     pub notes: Text,
     pub pov: Rc<RefCell<ObjectReference>>,
     pub compile_status: CompileStatus,
+    /// When the scene happens in the story. This is free-form, it only needs to sort correctly
+    pub story_date: Option<String>,
+    /// Leave out the break before this scene when exporting, even if the previous scene asked for
+    /// one. This is for scenes that continue the one before them
+    pub suppress_leading_break: bool,
+    /// Keep the text from being edited (e.g., once the scene is finished)
+    pub locked: bool,
+    /// How long the author wants the scene to be, counted towards the targets of its folders
+    pub target_word_count: Option<u64>,
 }
 
 #[derive(Debug)]
 pub struct Scene {
     base: BaseFileObject,
     pub metadata: SceneMetadata,
-    pub text: Text,
+    text: LazyBody,
+    parsed_body: ParsedText,
 }
 
 impl Scene {
@@ -43,6 +57,7 @@ impl Scene {
     pub const TYPE_INFO: FileTypeInfo = FileTypeInfo {
         identifier: Self::IDENTIFIER,
         is_folder: false,
+        can_nest: false,
         has_body: true,
         type_name: "Scene",
         empty_string_name: "New Scene",
@@ -54,7 +69,8 @@ impl Scene {
         let mut scene = Self {
             base,
             metadata: Default::default(),
-            text: body.map(|s| s.into()).unwrap_or_default(),
+            text: LazyBody::new(body),
+            parsed_body: ParsedText::default(),
         };
 
         match scene.load_metadata() {
@@ -75,6 +91,22 @@ impl Scene {
 
         Ok(scene)
     }
+
+    /// The body text, read from disk the first time it's needed
+    pub fn text(&self) -> &Text {
+        self.text.get(|| (self as &dyn FileObject).get_file())
+    }
+
+    pub fn text_mut(&mut self) -> &mut Text {
+        let file = (self as &dyn FileObject).get_file();
+        self.text.get_mut(|| file)
+    }
+
+    /// The structure of the body (headings, links, and paragraphs), which is only parsed again
+    /// once the body changes
+    pub fn parse_body(&self) -> Rc<MarkdownDoc> {
+        self.parsed_body.get(self.text())
+    }
 }
 
 impl FileObject for Scene {
@@ -116,11 +148,30 @@ impl FileObject for Scene {
             None => modified = true,
         }
 
+        // Most scenes won't have a date, so it's fine for it to be missing
+        self.metadata.story_date =
+            metadata_extract_string(self.base.toml_header.as_table(), "story_date")?
+                .filter(|story_date| !story_date.is_empty());
+
+        // Only written when it's set, so a missing value means the usual break
+        self.metadata.suppress_leading_break =
+            metadata_extract_bool(self.base.toml_header.as_table(), "suppress_leading_break")?
+                .unwrap_or(false);
+
+        // Only written when it's set, most scenes aren't locked
+        self.metadata.locked =
+            metadata_extract_bool(self.base.toml_header.as_table(), "locked")?.unwrap_or(false);
+
+        // Optional, most scenes won't have a target
+        self.metadata.target_word_count =
+            metadata_extract_u64(self.base.toml_header.as_table(), "target_word_count", false)?
+                .filter(|target| *target > 0);
+
         Ok(modified)
     }
 
     fn load_body(&mut self, data: String) {
-        self.text = data.trim().to_string().into();
+        self.text.set(trim_final_newline(&data).to_string());
     }
 
     fn get_base(&self) -> &BaseFileObject {
@@ -141,14 +192,18 @@ impl FileObject for Scene {
     }
 
     fn get_body(&self) -> String {
-        let mut full_text = String::new();
+        // The text is written exactly as it is, so that blank lines and indentation are kept
+        format!("{}\n", self.text().as_str())
+    }
 
-        for line in self.text.split('\n') {
-            full_text.push_str(line.trim());
-            full_text.push('\n');
-        }
+    fn is_body_loaded(&self) -> bool {
+        self.text.is_loaded()
+    }
 
-        full_text
+    fn load_unloaded_body(&self) -> Result<(), CheeseError> {
+        self.text
+            .load(|| (self as &dyn FileObject).get_file())
+            .map(|_| ())
     }
 
     fn write_metadata(&mut self, objects: &FileObjectStore) {
@@ -158,6 +213,65 @@ impl FileObject for Scene {
             toml_edit::value(self.metadata.pov.borrow().to_string(objects));
         self.base.toml_header["compile_status"] =
             toml_edit::value(self.metadata.compile_status.bits() as i64);
+
+        match &self.metadata.story_date {
+            Some(story_date) => {
+                self.base.toml_header["story_date"] = toml_edit::value(story_date.trim());
+            }
+            None => {
+                self.base.toml_header.remove("story_date");
+            }
+        }
+
+        if self.metadata.suppress_leading_break {
+            self.base.toml_header["suppress_leading_break"] = toml_edit::value(true);
+        } else {
+            self.base.toml_header.remove("suppress_leading_break");
+        }
+
+        if self.metadata.locked {
+            self.base.toml_header["locked"] = toml_edit::value(true);
+        } else {
+            self.base.toml_header.remove("locked");
+        }
+
+        match self.metadata.target_word_count {
+            Some(target) => {
+                self.base.toml_header["target_word_count"] = toml_edit::value(target as i64);
+            }
+            None => {
+                self.base.toml_header.remove("target_word_count");
+            }
+        }
+    }
+
+    fn story_date(&self) -> Option<&str> {
+        self.metadata.story_date.as_deref().map(str::trim)
+    }
+
+    fn is_locked(&self) -> bool {
+        self.metadata.locked
+    }
+
+    fn has_conflict_markers(&self) -> bool {
+        // Until the body is needed, go by what was found when the file was read
+        if self.is_body_loaded() {
+            !self.parse_body().conflict_markers.is_empty()
+        } else {
+            self.base.file.conflict_markers
+        }
+    }
+
+    fn word_count(&self) -> usize {
+        if self.is_body_loaded() {
+            self.parse_body().word_count
+        } else {
+            self.base.file.word_count
+        }
+    }
+
+    fn target_word_count(&self) -> Option<u64> {
+        self.metadata.target_word_count
     }
 
     fn generate_outline(&self, depth: u64, export_string: &mut String, objects: &FileObjectStore) {
@@ -175,10 +289,25 @@ impl FileObject for Scene {
         write_outline_property("notes", &self.metadata.notes, export_string);
     }
 
+    fn compile_status(&self) -> Option<&CompileStatus> {
+        Some(&self.metadata.compile_status)
+    }
+
+    fn broken_references(&self) -> Vec<String> {
+        match &*self.metadata.pov.borrow() {
+            ObjectReference::Unknown(unknown_reference) => vec![format!(
+                "pov \"{}\" (id \"{}\")",
+                unknown_reference.name, unknown_reference.id
+            )],
+            _ => Vec::new(),
+        }
+    }
+
     fn generate_export(
         &self,
         depth: u64,
         export_string: &mut String,
+        word_count: &mut usize,
         _objects: &FileObjectStore,
         export_options: &ExportOptions,
         include_break: bool,
@@ -192,17 +321,37 @@ impl FileObject for Scene {
                 IncludeOptions::Always => true,
                 IncludeOptions::Default => export_options.scene_title_depth.should_display(depth),
                 IncludeOptions::Never => false,
-            };
+            } && export_options.scene_title_style != TitleStyle::None;
 
             if display_title {
-                (self as &dyn FileObject).write_title(depth, export_string);
-            } else if include_break {
+                (self as &dyn FileObject).write_export_title(
+                    export_options.scene_title_style,
+                    export_options.heading_level(depth),
+                    export_string,
+                );
+            } else if include_break && !self.metadata.suppress_leading_break {
                 // We only include a break if the previous scene/document requested it *and* we
-                // didn't already include a heading (title)
+                // didn't already include a heading (title), unless this scene continues the
+                // previous one
                 export_string.push_str("----\n\n");
             }
 
-            let body_text_unprocessed = &self.get_body();
+            if export_options.scene_metadata_comments {
+                (self as &dyn FileObject).write_export_comment(export_string);
+            }
+
+            // comments are only for the author, they never make it into the export
+            let body_text_unprocessed = &strip_comments(&self.get_body());
+
+            // footnotes are numbered within each scene, keep them apart until the export is done
+            let body_text_unprocessed = &scope_footnotes(body_text_unprocessed, self.id());
+
+            // plain text exports (e.g., for text to speech) only keep the words themselves
+            let body_text_unprocessed = &if export_options.strip_markdown {
+                strip_markdown(body_text_unprocessed)
+            } else {
+                body_text_unprocessed.to_string()
+            };
 
             // add in smart quotes, other platforms will insert some and it's easier to be consistent here
             // regexes from https://webapps.stackexchange.com/questions/166314/how-to-replace-dumb-quotes-with-smart-quotes-in-google-docs/169065#169065
@@ -220,9 +369,20 @@ impl FileObject for Scene {
             let body_text = opening_single_quote.replace_all(&body_text, "$1‘");
             let body_text = closing_single_quote.replace_all(&body_text, "’");
 
-            // This should probably eventually be split into a `get_body_export` and `get_body_save`
-            // function once those are different (probably for in-text-notes)
-            export_string.push_str(&body_text);
+            // verse needs every line break kept, where markdown would join the lines together
+            let keep_line_breaks = match self.metadata.compile_status.hard_breaks() {
+                IncludeOptions::Always => true,
+                IncludeOptions::Default => export_options.preserve_hard_breaks,
+                IncludeOptions::Never => false,
+            };
+
+            *word_count += count_export_words(&body_text);
+
+            if keep_line_breaks {
+                export_string.push_str(&preserve_hard_breaks(&body_text));
+            } else {
+                export_string.push_str(&body_text);
+            }
 
             while !export_string.ends_with("\n\n") {
                 export_string.push('\n');
@@ -258,6 +418,7 @@ impl FileObject for Scene {
 pub enum SidebarTab {
     #[default]
     Notes,
+    Outline,
     Export,
 }
 
@@ -288,26 +449,66 @@ impl FileObjectEditor for Scene {
     fn for_each_textbox<'a>(&'a self, f: &mut dyn FnMut(&Text, &'static str)) {
         f(&self.metadata.summary, "Summary");
         f(&self.metadata.notes, "Notes");
-        f(&self.text, "text");
+        f(self.text(), "text");
     }
 
     fn for_each_textbox_mut<'a>(&'a mut self, f: &mut dyn FnMut(&mut Text, &'static str)) {
         f(&mut self.metadata.summary, "Summary");
         f(&mut self.metadata.notes, "Notes");
-        f(&mut self.text, "text");
+        f(self.text_mut(), "text");
     }
 }
 
 impl Scene {
     fn show_text_editor(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext) -> Vec<Id> {
+        // Editing the placeholder would look like it worked, but none of it could be saved
+        if let Err(err) = self.load_unloaded_body() {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("The text of this scene couldn't be read: {err}"),
+            );
+            return Vec::new();
+        }
+
+        if self.metadata.locked {
+            ui.horizontal(|ui| {
+                ui.weak("This scene is locked");
+                if ui.button("Unlock").clicked() {
+                    self.metadata.locked = false;
+                    self.get_base_mut().file.mark_edited();
+                }
+            });
+        }
+
+        let first_conflict = self.parse_body().conflict_markers.first().copied();
+        if let Some(offset) = first_conflict {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "⚠ This scene has git conflict markers",
+                );
+                if ui.button("Go to Conflict").clicked() {
+                    ctx.go_to_text = Some((self.text().struct_uid, offset));
+                }
+            });
+        }
+
+        let locked = self.metadata.locked;
+
         ScrollArea::vertical()
             .id_salt("text")
             .auto_shrink(egui::Vec2b { x: false, y: false })
             .show(ui, |ui| {
-                let response =
-                    ui.add_sized(ui.available_size(), |ui: &'_ mut Ui| self.text.ui(ui, ctx));
+                let response = ui.add_sized(ui.available_size(), |ui: &'_ mut Ui| {
+                    if locked {
+                        self.text_mut().read_only_ui(ui, ctx)
+                    } else {
+                        self.text_mut().ui(ui, ctx)
+                    }
+                });
 
                 self.process_response(&response);
+                ctx.focus_body_end_if_requested(self.id(), ui, &response, self.text());
                 vec![response.id]
             })
             .inner
@@ -320,10 +521,34 @@ impl Scene {
 
         egui::TopBottomPanel::bottom("word_count").show_inside(ui, |ui| {
             ui.add_space(4.0);
-            let words = self.text.word_count(ctx);
+            let words = self.text().word_count(ctx);
+            let words_at_open = *ctx.words_at_open.entry(self.id().clone()).or_insert(words);
             let text = format!("{words} Words");
             ui.vertical_centered(|ui| {
                 ui.label(text);
+
+                let change = words as i64 - words_at_open as i64;
+                if change != 0 {
+                    ui.weak(format!("{change:+} since opened"));
+                }
+
+                if let Some(elapsed) = self
+                    .get_base()
+                    .file
+                    .modtime
+                    .and_then(|modtime| modtime.elapsed().ok())
+                {
+                    ui.weak(format!("edited {}", format_relative_time(elapsed)));
+                }
+
+                if let Some(elapsed) = self
+                    .get_base()
+                    .metadata
+                    .created
+                    .and_then(|created| created.elapsed().ok())
+                {
+                    ui.weak(format!("created {}", format_relative_time(elapsed)));
+                }
             });
         });
 
@@ -336,6 +561,7 @@ impl Scene {
                     .desired_width(f32::INFINITY),
             );
             self.process_response(&response);
+            ctx.focus_name_if_requested(self.id(), &response);
             ids.push(response.id);
 
             let text_box_height = response.rect.height().abs();
@@ -347,6 +573,7 @@ impl Scene {
                     SidebarTab::Notes,
                     "Summary/Notes",
                 );
+                ui.selectable_value(&mut scene_data.sidebar_tab, SidebarTab::Outline, "Outline");
                 ui.selectable_value(&mut scene_data.sidebar_tab, SidebarTab::Export, "Export");
             });
 
@@ -354,6 +581,7 @@ impl Scene {
 
             let sidebar_other_ids = match scene_data.sidebar_tab {
                 SidebarTab::Notes => self.show_sidebar_metadata(ui, ctx, text_box_height),
+                SidebarTab::Outline => self.show_sidebar_outline(ui, ctx),
                 SidebarTab::Export => self.show_sidebar_export(ui),
             };
 
@@ -370,6 +598,16 @@ impl Scene {
     ) -> Vec<Id> {
         let mut ids = Vec::new();
 
+        let response = ui
+            .checkbox(&mut self.metadata.locked, "Locked")
+            .on_hover_text("Keep the text of this scene from being edited");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        let response = word_target_ui(ui, &mut self.metadata.target_word_count, 10.0);
+        self.process_response(&response);
+        ids.push(response.id);
+
         // I am doing horrible things here but the borrow checker must be satisifed
         let changed = {
             let mut object_pov = self.metadata.pov.borrow_mut();
@@ -423,9 +661,32 @@ impl Scene {
         };
 
         if changed {
-            self.get_base_mut().file.modified = true;
+            self.get_base_mut().file.mark_edited();
         }
 
+        ui.horizontal(|ui| {
+            ui.label("Story Date ");
+            let mut story_date = self.metadata.story_date.clone().unwrap_or_default();
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut story_date)
+                    .id_salt("story date")
+                    .hint_text("e.g., 1204-03-15")
+                    .desired_width(f32::INFINITY),
+            );
+            // Spaces at the end are kept while typing (e.g., on the way from "Spring" to
+            // "Spring 1204"), and only trimmed once the field loses focus
+            if response.lost_focus() {
+                story_date = story_date.trim().to_string();
+            }
+            let story_date = (!story_date.trim().is_empty()).then_some(story_date);
+            if story_date != self.metadata.story_date {
+                self.metadata.story_date = story_date;
+                self.get_base_mut().file.mark_edited();
+            }
+            self.process_response(&response);
+            ids.push(response.id);
+        });
+
         // half of the available height should go to each widget
         let widget_space = ui.available_height() / 2.0;
 
@@ -455,7 +716,34 @@ impl Scene {
                 );
                 self.process_response(&response);
                 ids.push(response.id);
+
+                if let Some(response) = self.metadata.notes.task_list_ui(ui) {
+                    self.process_response(&response);
+                }
+            });
+        ids
+    }
+
+    /// The headings in the text, which move the cursor to that heading when clicked
+    fn show_sidebar_outline(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext) -> Vec<Id> {
+        let mut ids = Vec::new();
+
+        let body = self.parse_body();
+        if body.headings.is_empty() {
+            ui.weak("Add markdown headings (e.g., `## Section`) to the text to outline it");
+        }
+
+        for heading in &body.headings {
+            ui.horizontal(|ui| {
+                ui.add_space((heading.level - 1) as f32 * 12.0);
+                let response = ui.link(&heading.title);
+                if response.clicked() {
+                    ctx.go_to_text = Some((self.text().struct_uid, heading.offset));
+                }
+                ids.push(response.id);
             });
+        }
+
         ids
     }
 
@@ -508,7 +796,7 @@ impl Scene {
                 self.metadata
                     .compile_status
                     .set_include_title(include_title);
-                self.get_base_mut().file.modified = true;
+                self.get_base_mut().file.mark_edited();
             }
 
             // same thing but for the break
@@ -541,8 +829,50 @@ impl Scene {
             // We don't have an actual response here so we have to manually process
             if break_at_end != break_at_end_before {
                 self.metadata.compile_status.set_break_at_end(break_at_end);
-                self.get_base_mut().file.modified = true;
+                self.get_base_mut().file.mark_edited();
+            }
+
+            // and again for keeping line breaks
+            let mut hard_breaks = self.metadata.compile_status.hard_breaks();
+            let hard_breaks_before = hard_breaks;
+
+            ui.horizontal(|ui| {
+                const HARD_BREAKS_MESSAGE: &str =
+                    "Should every line break in this scene be kept (e.g., for poetry)?
+                    default - this will come from the settings in the export tab
+                    always - keep the line breaks, even if the project export settings differ
+                    never - join lines into paragraphs as usual, even if the export settings differ";
+
+                ui.label("Keep Line Breaks  ℹ")
+                    .on_hover_text(HARD_BREAKS_MESSAGE);
+
+                let hard_breaks_combobox_response = egui::ComboBox::from_id_salt("Keep Line Breaks")
+                    .selected_text(format!("{hard_breaks:?}"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut hard_breaks, IncludeOptions::Default, "Default");
+                        ui.selectable_value(&mut hard_breaks, IncludeOptions::Always, "Always");
+                        ui.selectable_value(&mut hard_breaks, IncludeOptions::Never, "Never");
+                    });
+
+                // We want to be able to tab to the box, but it doesn't get a process_response
+                // call because that needs to be handled below
+                ids.push(hard_breaks_combobox_response.response.id);
+            });
+
+            // We don't have an actual response here so we have to manually process
+            if hard_breaks != hard_breaks_before {
+                self.metadata.compile_status.set_hard_breaks(hard_breaks);
+                self.get_base_mut().file.mark_edited();
             }
+
+            let response = ui
+                .checkbox(
+                    &mut self.metadata.suppress_leading_break,
+                    "Continues previous scene",
+                )
+                .on_hover_text("Never put a break before this scene, so it joins the one before it");
+            self.process_response(&response);
+            ids.push(response.id);
         });
 
         ids
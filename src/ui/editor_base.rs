@@ -1,24 +1,38 @@
 use crate::{schemas::DEFAULT_SCHEMA, schemas::SCHEMA_LIST, ui::prelude::*};
 use spellbook::Dictionary;
 
-use crate::components::file_objects::utils::{create_dir_if_missing, write_with_temp_file};
-use directories::ProjectDirs;
+use crate::components::backup::backup_project;
+use crate::components::file_objects::utils::{
+    create_dir_if_missing, preserve_invalid_file, write_with_temp_file,
+};
+use crate::components::project::find_project_cover;
+use crate::ui::idle::{IdleTracker, IdleTransition};
+use crate::util::AppDirs;
 use egui::{FontFamily, FontId, ScrollArea, TextStyle};
 use rfd::FileDialog;
 use toml_edit::{DocumentMut, value};
 
 use std::{
     fs::read_to_string,
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
 
 #[cfg(feature = "metrics")]
 use super::metrics::Metrics;
 
+/// The minimum time between full saves (project, app data, and settings). Changes made in between
+/// are coalesced into the next save
+const MIN_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Debug)]
 pub struct Data {
     pub recent_projects: Vec<PathBuf>,
+
+    /// Projects that are always shown at the top of the project chooser, no matter how long ago
+    /// they were opened
+    pub pinned_projects: Vec<PathBuf>,
+
     pub last_project_parent_folder: PathBuf,
     pub last_export_folder: PathBuf,
     pub last_open_file_ids: HashMap<String, Vec<String>>,
@@ -26,22 +40,114 @@ pub struct Data {
     /// Words that have been ignored by the user. Maybe should be in a separate file, but they're here for
     /// now
     pub custom_dictionary: Vec<String>,
+
+    /// Where the window was the last time the app was open, restored on startup
+    pub window_geometry: Option<WindowGeometry>,
+}
+
+/// The size and position of the app window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowGeometry {
+    /// The position of the outside of the window (including decorations)
+    pub position: egui::Pos2,
+    /// The size of the inside of the window
+    pub size: egui::Vec2,
+    /// If the window was maximized, `position` and `size` are from before it was
+    pub maximized: bool,
+}
+
+/// The smallest window that will be restored, anything smaller probably isn't intentional
+const MIN_WINDOW_SIZE: egui::Vec2 = egui::vec2(300.0, 200.0);
+
+/// The size of the covers shown next to each project in the project chooser
+const COVER_THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(32.0, 48.0);
+
+impl WindowGeometry {
+    /// Fit the window onto a monitor, so that a window from a monitor that's been disconnected (or
+    /// has changed resolution) doesn't end up somewhere it can't be seen. We only know the size of
+    /// the current monitor, so the window is moved onto it
+    fn clamped_to(self, monitor_size: egui::Vec2) -> Self {
+        let size = self.size.max(MIN_WINDOW_SIZE).min(monitor_size);
+
+        let max_position = (monitor_size - size).max(egui::Vec2::ZERO);
+        let position = egui::pos2(
+            self.position.x.clamp(0.0, max_position.x),
+            self.position.y.clamp(0.0, max_position.y),
+        );
+
+        Self {
+            position,
+            size,
+            maximized: self.maximized,
+        }
+    }
+}
+
+#[test]
+fn test_window_geometry_clamped_to() {
+    let monitor_size = egui::vec2(1920.0, 1080.0);
+    let geometry = |x, y, width, height| WindowGeometry {
+        position: egui::pos2(x, y),
+        size: egui::vec2(width, height),
+        maximized: false,
+    };
+
+    // already visible
+    assert_eq!(
+        geometry(100.0, 50.0, 800.0, 600.0).clamped_to(monitor_size),
+        geometry(100.0, 50.0, 800.0, 600.0)
+    );
+
+    // from a monitor that isn't there anymore
+    assert_eq!(
+        geometry(5000.0, -300.0, 800.0, 600.0).clamped_to(monitor_size),
+        geometry(1120.0, 0.0, 800.0, 600.0)
+    );
+
+    // bigger than the monitor, or too small to use
+    assert_eq!(
+        geometry(10.0, 10.0, 3000.0, 2000.0).clamped_to(monitor_size),
+        geometry(0.0, 0.0, 1920.0, 1080.0)
+    );
+    assert_eq!(
+        geometry(10.0, 10.0, 5.0, 5.0).clamped_to(monitor_size),
+        geometry(10.0, 10.0, 300.0, 200.0)
+    );
 }
 
 impl Default for Data {
     fn default() -> Self {
         Self {
             recent_projects: Vec::new(),
-            last_project_parent_folder: directories::UserDirs::new()
-                .unwrap()
-                .home_dir()
-                .to_path_buf(),
-            last_export_folder: directories::UserDirs::new()
-                .unwrap()
-                .home_dir()
-                .to_path_buf(),
+            pinned_projects: Vec::new(),
+            last_project_parent_folder: default_folder(),
+            last_export_folder: default_folder(),
             last_open_file_ids: HashMap::new(),
             custom_dictionary: Vec::new(),
+            window_geometry: None,
+        }
+    }
+}
+
+/// The folder to start file dialogs in before the user has picked one. This is normally the home
+/// directory, but that isn't always available (e.g., some sandboxes and CI environments)
+fn default_folder() -> PathBuf {
+    if let Some(user_dirs) = directories::UserDirs::new() {
+        return user_dirs.home_dir().to_path_buf();
+    }
+
+    match std::env::current_dir() {
+        Ok(current_dir) => {
+            log::warn!("Could not find home directory, defaulting to {current_dir:?}");
+            current_dir
+        }
+        Err(err) => {
+            let temp_dir = std::env::temp_dir();
+            log::warn!(
+                "Could not find home directory or current directory ({err}), defaulting to \
+                {temp_dir:?}"
+            );
+            temp_dir
         }
     }
 }
@@ -69,6 +175,17 @@ impl Data {
             self.recent_projects = recent_projects;
         }
 
+        if let Some(pinned_projects_array) =
+            table.get("pinned_projects").and_then(|val| val.as_array())
+        {
+            self.pinned_projects = pinned_projects_array
+                .iter()
+                .filter_map(|val| val.as_str())
+                .map(PathBuf::from)
+                .filter(|project_path| project_path.exists())
+                .collect();
+        }
+
         if let Some(last_project_parent_folder_value) = table.get("last_project_parent_folder")
             && let Some(last_export_folder) = last_project_parent_folder_value.as_str()
         {
@@ -109,15 +226,46 @@ impl Data {
                 }
             }
         }
+
+        if let Some(window) = table.get("window").and_then(|val| val.as_table_like()) {
+            let number = |key| window.get(key).and_then(|val| val.as_float());
+            if let (Some(x), Some(y), Some(width), Some(height)) =
+                (number("x"), number("y"), number("width"), number("height"))
+            {
+                self.window_geometry = Some(WindowGeometry {
+                    position: egui::pos2(x as f32, y as f32),
+                    size: egui::vec2(width as f32, height as f32),
+                    maximized: window
+                        .get("maximized")
+                        .and_then(|val| val.as_bool())
+                        .unwrap_or(false),
+                });
+            }
+        }
     }
 
     fn save(&self, table: &mut DocumentMut) {
         let mut recent_projects = toml_edit::Array::new();
-        for project in self.recent_projects.iter() {
+        // projects can be deleted or moved while we're running, there's no point in remembering them
+        for project in self
+            .recent_projects
+            .iter()
+            .filter(|project| project.exists())
+        {
             recent_projects.push(project.to_string_lossy().to_string());
         }
         table.insert("recent_projects", value(recent_projects));
 
+        let mut pinned_projects = toml_edit::Array::new();
+        for project in self
+            .pinned_projects
+            .iter()
+            .filter(|project| project.exists())
+        {
+            pinned_projects.push(project.to_string_lossy().to_string());
+        }
+        table.insert("pinned_projects", value(pinned_projects));
+
         table.insert(
             "last_project_parent_folder",
             value(
@@ -146,10 +294,20 @@ impl Data {
             "custom_dictionary",
             value(toml_edit::Array::from_iter(self.custom_dictionary.iter())),
         );
+
+        if let Some(window_geometry) = &self.window_geometry {
+            let mut window = toml_edit::InlineTable::new();
+            window.insert("x", f64::from(window_geometry.position.x).into());
+            window.insert("y", f64::from(window_geometry.position.y).into());
+            window.insert("width", f64::from(window_geometry.size.x).into());
+            window.insert("height", f64::from(window_geometry.size.y).into());
+            window.insert("maximized", window_geometry.maximized.into());
+            table.insert("window", value(window));
+        }
     }
 
-    fn get_path(project_dirs: &ProjectDirs) -> PathBuf {
-        project_dirs.data_dir().join("data.toml")
+    fn get_path(app_dirs: &AppDirs) -> PathBuf {
+        app_dirs.data_dir().join("data.toml")
     }
 }
 
@@ -158,7 +316,7 @@ pub struct EditorState {
     pub data: Data,
     data_toml: DocumentMut,
     data_modified: bool,
-    project_dirs: ProjectDirs,
+    app_dirs: AppDirs,
     error_message: Option<(String, Instant)>,
     new_project_dir: Option<PathBuf>,
     new_project_name: String,
@@ -174,34 +332,43 @@ impl std::fmt::Debug for EditorState {
             .field("settings", &self.settings)
             .field("data", &self.data)
             .field("data_modified", &self.data_modified)
-            .field("project_dirs", &self.project_dirs)
+            .field("app_dirs", &self.app_dirs)
             .finish()
     }
 }
 
-impl Default for EditorState {
-    fn default() -> Self {
-        let project_dirs = ProjectDirs::from("", "", "cheese-paper")
-            .expect("it should be possible to write to system dirs");
+impl EditorState {
+    pub fn new(app_dirs: AppDirs) -> Self {
+        let mut settings = Settings::new(&app_dirs);
 
-        let mut settings = Settings::new(&project_dirs);
+        // Problems with the settings or data files shouldn't keep the app from starting, so we
+        // fall back to the defaults and tell the user about it
+        let mut startup_errors = Vec::new();
 
-        settings.load().unwrap_or_else(|err| {
-            log::error!("{err}");
-            panic!("{err}");
-        });
+        if let Err(err) = settings.load() {
+            log::error!("Could not load settings, using defaults: {err}");
+            startup_errors.push(format!("Could not load settings: {err}"));
+        }
 
         let mut data = Data::default();
 
-        let data_toml = match read_to_string(Data::get_path(&project_dirs)) {
-            Ok(config) => config
-                .parse::<DocumentMut>()
-                .expect("invalid toml data file"),
+        let data_path = Data::get_path(&app_dirs);
+        let data_toml = match read_to_string(&data_path) {
+            Ok(config) => match config.parse::<DocumentMut>() {
+                Ok(data_toml) => data_toml,
+                Err(err) => {
+                    log::error!("Invalid toml in app data file {data_path:?}: {err}");
+                    preserve_invalid_file(&data_path);
+                    startup_errors.push(format!("Could not load app data: {err}"));
+                    DocumentMut::new()
+                }
+            },
             Err(err) => match err.kind() {
                 std::io::ErrorKind::NotFound => DocumentMut::new(),
                 _ => {
-                    log::error!("Unknown error while reading editor settings: {err}");
-                    panic!("Unknown error while reading editor settings: {err}");
+                    log::error!("Unknown error while reading app data: {err}");
+                    startup_errors.push(format!("Could not read app data: {err}"));
+                    DocumentMut::new()
                 }
             },
         };
@@ -213,8 +380,9 @@ impl Default for EditorState {
             data,
             data_toml,
             data_modified: false,
-            project_dirs,
-            error_message: None,
+            app_dirs,
+            error_message: (!startup_errors.is_empty())
+                .then(|| (startup_errors.join("\n"), Instant::now())),
             new_project_dir: None,
             new_project_name: String::new(),
             new_project_schema: &DEFAULT_SCHEMA,
@@ -222,27 +390,69 @@ impl Default for EditorState {
             next_project: None,
         }
     }
-}
 
-impl EditorState {
+    /// Move a project to the front of the recent projects (adding it if it's new), forgetting the
+    /// oldest ones if there are more than the configured maximum
+    fn add_recent_project(&mut self, project_path: PathBuf) {
+        let recent_projects = &mut self.data.recent_projects;
+
+        if recent_projects.first() != Some(&project_path) {
+            recent_projects.retain(|path| path != &project_path);
+            recent_projects.insert(0, project_path);
+            self.data_modified = true;
+        }
+
+        let max_recent_projects = self.settings.max_recent_projects();
+        if recent_projects.len() > max_recent_projects {
+            recent_projects.truncate(max_recent_projects);
+            self.data_modified = true;
+        }
+    }
+
+    pub fn clear_recent_projects(&mut self) {
+        self.data.recent_projects.clear();
+        self.data_modified = true;
+    }
+
+    /// Write the app data and settings, but only the ones that have actually changed
     fn save(&mut self) -> Result<(), CheeseError> {
         if self.data_modified {
             self.data.save(&mut self.data_toml);
             write_with_temp_file(
-                create_dir_if_missing(&Data::get_path(&self.project_dirs))?,
+                create_dir_if_missing(&Data::get_path(&self.app_dirs))?,
                 self.data_toml.to_string(),
             )
             .map_err(|err| cheese_error!("Error while saving app data\n{}", err))?;
+            self.data_modified = false;
+
+            #[cfg(feature = "metrics")]
+            crate::util::write_counters::record_write();
+        } else {
+            #[cfg(feature = "metrics")]
+            crate::util::write_counters::record_write_avoided();
         }
 
         if self.settings.modified() {
             self.settings.save()?;
+
+            #[cfg(feature = "metrics")]
+            crate::util::write_counters::record_write();
+        } else {
+            #[cfg(feature = "metrics")]
+            crate::util::write_counters::record_write_avoided();
         }
 
         Ok(())
     }
 }
 
+#[derive(Debug)]
+struct LoadingProject {
+    path: PathBuf,
+    /// Whether the loading screen has been drawn yet
+    shown: bool,
+}
+
 pub struct CheesePaperApp {
     pub project_editor: Option<ProjectEditor>,
 
@@ -258,9 +468,28 @@ pub struct CheesePaperApp {
     /// We want to keep track of this separately from the save logic (probably?)
     last_dictionary_update: Instant,
 
+    /// If the window had focus last frame, so that losing focus can trigger a save
+    window_focused: bool,
+
+    /// Whether anyone has been using the app recently. Autosaves and dictionary updates are
+    /// paused while it's idle
+    idle: IdleTracker,
+
     /// Dictionary for spellchecking, if we managed to load it
     dictionary: Option<Dictionary>,
 
+    /// A project that's waiting to be loaded. Loading a large project can take a noticeable amount
+    /// of time (and blocks the UI), so we draw one frame saying that it's loading before we start
+    loading_project: Option<LoadingProject>,
+
+    /// Whether the window has been moved to where it was last time. This has to wait for the first
+    /// frame, since that's the first time we know how big the monitor is
+    window_restored: bool,
+
+    /// The cover of each project in the project chooser, looked up the first time it's shown
+    /// instead of every frame. Forgotten when a project is closed, in case its cover changed
+    project_covers: HashMap<PathBuf, Option<PathBuf>>,
+
     #[cfg(feature = "metrics")]
     metrics: Metrics,
 }
@@ -270,23 +499,70 @@ impl eframe::App for CheesePaperApp {
         #[cfg(feature = "metrics")]
         self.metrics.frame_start();
 
+        if self.window_restored {
+            self.track_window_geometry(ctx);
+        } else {
+            self.restore_window_geometry(ctx);
+            self.window_restored = true;
+        }
+
         if self.state.closing_project {
             self.project_editor = None;
             self.state.closing_project = false;
+            self.project_covers.clear();
             ctx.send_viewport_cmd(egui::ViewportCommand::Title("Cheese Paper".to_string()));
-            if let Some(new_project_path) = self.state.next_project.take()
-                && let Err(err) = self.load_project(new_project_path)
-            {
-                log::error!("Could not load project: {err}");
+            if let Some(new_project_path) = self.state.next_project.take() {
+                self.start_loading_project(new_project_path);
             }
         }
 
+        if self
+            .loading_project
+            .as_ref()
+            .is_some_and(|loading_project| loading_project.shown)
+            && let Some(loading_project) = self.loading_project.take()
+            && let Err(err) = self.load_project(loading_project.path)
+        {
+            log::error!("Could not load project: {err}");
+        }
+
+        let mut save_app_state = false;
+
+        // The focus is unknown on some platforms, which is treated as always being focused
+        let window_focused = ctx.input(|i| i.viewport().focused.unwrap_or(true));
+        let lost_focus = self.window_focused && !window_focused;
+        self.window_focused = window_focused;
+
+        let now = Instant::now();
+        let idle_timeout = self.state.settings.idle_timeout();
+        let had_input = ctx.input(|i| !i.events.is_empty());
+        let idle_transition = self.idle.update(now, had_input, idle_timeout);
+        // make sure there's a frame when it becomes idle, so the last save actually happens
+        if let Some(until_idle) = self.idle.until_idle(now, idle_timeout) {
+            ctx.request_repaint_after(until_idle);
+        }
+        match idle_transition {
+            Some(IdleTransition::BecameIdle) => log::debug!("No recent input, pausing autosaves"),
+            Some(IdleTransition::BecameActive) => log::debug!("Input again, resuming autosaves"),
+            None => {}
+        }
+
         match &mut self.project_editor {
             Some(project_editor) => {
                 project_editor.panels(ctx, &mut self.state);
 
+                let save_for_focus_loss =
+                    lost_focus && project_editor.editor_context.settings.save_on_focus_loss();
+
+                // Save one last time before pausing, so nothing waits for the user to come back
+                let save_for_idle = idle_transition == Some(IdleTransition::BecameIdle);
+
                 let current_time = Instant::now();
-                if current_time.duration_since(self.last_save) > Duration::from_secs(5) {
+                if save_for_focus_loss
+                    || save_for_idle
+                    || (!self.idle.is_idle()
+                        && current_time.duration_since(self.last_save) > MIN_SAVE_INTERVAL)
+                {
                     // Slightly hacky, but write the data back into the editor state with every
                     // autosave. The settings object was put into a refcell and actually included in
                     // the ctx, but this is easy and good enough for now
@@ -297,14 +573,21 @@ impl eframe::App for CheesePaperApp {
                             project_editor.editor_context.last_export_folder.clone()
                     }
 
-                    project_editor.save();
+                    // Projects can turn off autosave, in which case they're only written when
+                    // explicitly saved or closed
+                    if project_editor.project.metadata.autosave_enabled {
+                        project_editor.save();
+                    }
+                    project_editor.recount_story_words();
                     self.last_save = current_time;
+                    save_app_state = true;
                 }
                 // is it better to have a potential lag spike happen during a save (making the lag worse,
                 // or separately, making it smaller but separate)? not sure if this will even be an issue
                 // so I'm not thinking too hard about it right now
-                if current_time.duration_since(self.last_dictionary_update)
-                    > Duration::from_secs(20)
+                if !self.idle.is_idle()
+                    && current_time.duration_since(self.last_dictionary_update)
+                        > Duration::from_secs(20)
                 {
                     project_editor.update_spellcheck_file_object_names();
                     project_editor
@@ -316,17 +599,29 @@ impl eframe::App for CheesePaperApp {
                     self.last_dictionary_update = current_time;
                 }
             }
+            None if self.loading_project.is_some() => self.loading_project_ui(ctx),
             None => match self.state.new_project_dir.is_none() {
                 true => self.choose_project_ui(ctx),
                 false => self.new_project_name_ui(ctx),
             },
         }
 
+        // app data and settings get saved on the same schedule as the project (only writing if they
+        // changed), rather than every time they're modified
+        if save_app_state {
+            self.save();
+        }
+
         #[cfg(feature = "metrics")]
         {
             let next_refresh = self.metrics.frame_stop();
             ctx.request_repaint_after(next_refresh);
 
+            if self.metrics.has_new_report() {
+                let project = self.project_editor.as_ref().map(|editor| &editor.project);
+                log::debug!("metrics: {:?}", self.metrics.snapshot(project));
+            }
+
             if let Some(report) = &self.metrics.report {
                 egui::Area::new(egui::Id::new("metrics"))
                     .anchor(egui::Align2::LEFT_BOTTOM, [0.0, 0.0])
@@ -382,10 +677,11 @@ fn configure_text_styles(ctx: &egui::Context, font_size: f32) {
 }
 
 impl CheesePaperApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let state = EditorState::default();
+    pub fn new(cc: &eframe::CreationContext<'_>, app_dirs: AppDirs) -> Self {
+        let state = EditorState::new(app_dirs);
 
         configure_text_styles(&cc.egui_ctx, state.settings.font_size());
+        egui_extras::install_image_loaders(&cc.egui_ctx);
 
         let mut dictionary = None;
 
@@ -433,7 +729,12 @@ impl CheesePaperApp {
             state,
             last_save: Instant::now(),
             last_dictionary_update: Instant::now(),
+            idle: IdleTracker::new(Instant::now()),
+            window_focused: true,
             dictionary,
+            loading_project: None,
+            window_restored: false,
+            project_covers: HashMap::new(),
 
             #[cfg(feature = "metrics")]
             metrics: Metrics::default(),
@@ -443,16 +744,84 @@ impl CheesePaperApp {
             && let Some(last_open_project) = app.state.data.recent_projects.first()
         {
             let last_open_project = last_open_project.clone();
-            if let Err(err) = app.load_project(PathBuf::from(&last_open_project)) {
-                log::error!(
-                    "error while trying to open most recent project: {last_open_project:?}: {err}"
-                );
-            }
+            app.start_loading_project(last_open_project);
         }
 
         app
     }
 
+    /// Move and resize the window to where it was the last time the app was closed
+    fn restore_window_geometry(&self, ctx: &egui::Context) {
+        let Some(window_geometry) = self.state.data.window_geometry else {
+            return;
+        };
+
+        // Without the monitor size, we can't tell if the old position is still on screen, so it's
+        // safer to leave the position up to the window manager
+        let monitor_size = ctx.input(|i| i.viewport().monitor_size);
+        if let Some(monitor_size) = monitor_size {
+            let window_geometry = window_geometry.clamped_to(monitor_size);
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(
+                window_geometry.position,
+            ));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(window_geometry.size));
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(
+                window_geometry.size.max(MIN_WINDOW_SIZE),
+            ));
+        }
+
+        if window_geometry.maximized {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Maximized(true));
+        }
+    }
+
+    /// Keep track of where the window is, so that it can be restored next time
+    fn track_window_geometry(&mut self, ctx: &egui::Context) {
+        let Some((position, size, maximized)) = ctx.input(|i| {
+            let viewport = i.viewport();
+            if viewport.minimized == Some(true) || viewport.fullscreen == Some(true) {
+                return None;
+            }
+            Some((
+                viewport.outer_rect?.min,
+                viewport.inner_rect?.size(),
+                viewport.maximized == Some(true),
+            ))
+        }) else {
+            return;
+        };
+
+        let window_geometry = &mut self.state.data.window_geometry;
+
+        // A maximized window fills the screen, so keep the size it had before so that it goes
+        // back to that when it's restored
+        let new_geometry = match window_geometry {
+            Some(old_geometry) if maximized => WindowGeometry {
+                maximized: true,
+                ..*old_geometry
+            },
+            _ => WindowGeometry {
+                position,
+                size,
+                maximized,
+            },
+        };
+
+        if *window_geometry != Some(new_geometry) {
+            *window_geometry = Some(new_geometry);
+            self.state.data_modified = true;
+        }
+    }
+
+    /// The cover of a project in the chooser, see `project_covers`
+    fn project_cover(&mut self, project: &Path) -> Option<PathBuf> {
+        self.project_covers
+            .entry(project.to_path_buf())
+            .or_insert_with(|| find_project_cover(project))
+            .clone()
+    }
+
     fn choose_project_ui(&mut self, ctx: &egui::Context) {
         if let Some((_message, time)) = &self.state.error_message
             && time.elapsed().as_secs() > 7
@@ -466,15 +835,55 @@ impl CheesePaperApp {
                     .id_salt("recent projects")
                     .show(ui, |ui| {
                         ui.vertical_centered(|ui| {
+                            let mut chosen_action = None;
+
+                            let pinned_projects = self.state.data.pinned_projects.clone();
+                            if !pinned_projects.is_empty() {
+                                ui.heading("Pinned");
+                                for project in pinned_projects {
+                                    let cover = self.project_cover(&project);
+                                    if let Some(action) =
+                                        project_list_entry(ui, &project, cover.as_deref(), true)
+                                    {
+                                        chosen_action = Some((action, project));
+                                    }
+                                }
+                                ui.separator();
+                            }
+
                             let projects = self.state.data.recent_projects.clone();
                             for project in projects {
-                                if ui.button(project.to_string_lossy().to_string()).clicked()
-                                    && let Err(err) = self.load_project(project.clone())
+                                if self.state.data.pinned_projects.contains(&project) {
+                                    continue;
+                                }
+                                let cover = self.project_cover(&project);
+                                if let Some(action) =
+                                    project_list_entry(ui, &project, cover.as_deref(), false)
                                 {
-                                    log::error!(
-                                        "Error while attempting to load {project:?}: {err}"
-                                    );
+                                    chosen_action = Some((action, project));
+                                }
+                            }
+
+                            match chosen_action {
+                                Some((ProjectListAction::Load, project)) => {
+                                    self.start_loading_project(project);
+                                }
+                                Some((ProjectListAction::Remove, project)) => {
+                                    let data = &mut self.state.data;
+                                    data.recent_projects.retain(|path| path != &project);
+                                    data.pinned_projects.retain(|path| path != &project);
+                                    self.state.data_modified = true;
+                                }
+                                Some((ProjectListAction::TogglePin, project)) => {
+                                    let pinned_projects = &mut self.state.data.pinned_projects;
+                                    if pinned_projects.contains(&project) {
+                                        pinned_projects.retain(|path| path != &project);
+                                    } else {
+                                        pinned_projects.push(project);
+                                    }
+                                    self.state.data_modified = true;
                                 }
+                                None => {}
                             }
                         })
                     });
@@ -514,12 +923,8 @@ impl CheesePaperApp {
                                 .set_directory(&self.state.data.last_project_parent_folder)
                                 .pick_folder();
 
-                            if let Some(project_dir) = project_dir
-                                && let Err(err) = self.load_project(project_dir.clone())
-                            {
-                                log::error!(
-                                    "Error while attempting to load {project_dir:?}: {err}"
-                                );
+                            if let Some(project_dir) = project_dir {
+                                self.start_loading_project(project_dir);
                             }
                         }
                     });
@@ -563,6 +968,10 @@ impl CheesePaperApp {
                     }
                 });
 
+                if let Some((message, _time)) = &self.state.error_message {
+                    ui.colored_label(ui.visuals().error_fg_color, message);
+                }
+
                 egui::Sides::new().show(
                     ui,
                     |_ui| {},
@@ -576,10 +985,7 @@ impl CheesePaperApp {
                                 Ok(project) => {
                                     self.state.data.last_project_parent_folder =
                                         owned_folder_dir.clone();
-                                    self.state
-                                        .data
-                                        .recent_projects
-                                        .insert(0, project.get_path());
+                                    self.state.add_recent_project(project.get_path());
                                     self.state.data_modified = true;
                                     self.project_editor = Some(ProjectEditor::new(
                                         project,
@@ -611,7 +1017,42 @@ impl CheesePaperApp {
         });
     }
 
+    /// Load a project, after showing the loading screen for a frame (see `loading_project`)
+    fn start_loading_project(&mut self, project_path: PathBuf) {
+        self.loading_project = Some(LoadingProject {
+            path: project_path,
+            shown: false,
+        });
+    }
+
+    fn loading_project_ui(&mut self, ctx: &egui::Context) {
+        if let Some(loading_project) = &mut self.loading_project {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(ui.available_height() / 3.0);
+                    ui.spinner();
+                    ui.label(format!(
+                        "Loading {}…",
+                        loading_project.path.to_string_lossy()
+                    ));
+                });
+            });
+
+            loading_project.shown = true;
+            // make sure there's another frame to actually do the loading in
+            ctx.request_repaint();
+        }
+    }
+
     fn load_project(&mut self, project_path: PathBuf) -> Result<(), CheeseError> {
+        // Loading already writes its fixes (e.g., to the indexes), so the backup has to come first.
+        // A failed backup shouldn't keep the project from opening
+        if self.state.settings.backup_on_open()
+            && let Err(err) = backup_project(&project_path, self.state.settings.backups_to_keep())
+        {
+            log::error!("Could not back up project: {err}");
+        }
+
         match Project::load(project_path) {
             Ok(project) => {
                 // open the project
@@ -626,29 +1067,7 @@ impl CheesePaperApp {
                     self.state.data_modified = true;
                 }
 
-                let project_path_position = self
-                    .state
-                    .data
-                    .recent_projects
-                    .iter()
-                    .position(|id| id == &project_path);
-
-                match project_path_position {
-                    Some(position) => {
-                        if position != 0 {
-                            let project_pathbuf = self.state.data.recent_projects.remove(position);
-                            self.state.data.recent_projects.insert(0, project_pathbuf);
-                            self.state.data_modified = true;
-                        }
-                    }
-                    None => {
-                        self.state
-                            .data
-                            .recent_projects
-                            .insert(0, project_path.clone());
-                        self.state.data_modified = true;
-                    }
-                };
+                self.state.add_recent_project(project_path);
 
                 // load tabs
                 let open_tabs = self
@@ -681,11 +1100,7 @@ impl CheesePaperApp {
 
     fn update_open_tabs(&mut self) {
         if let Some(project_editor) = &self.project_editor {
-            let open_tabs_ids = project_editor
-                .get_open_tabs()
-                .iter()
-                .map(|tab| tab.page.get_id().to_owned())
-                .collect();
+            let open_tabs_ids = project_editor.open_tab_ids();
 
             if Some(&open_tabs_ids)
                 != self
@@ -727,3 +1142,73 @@ impl CheesePaperApp {
         }
     }
 }
+
+/// Something the user asked to do with one of the projects listed in the project chooser
+enum ProjectListAction {
+    Load,
+    Remove,
+    TogglePin,
+}
+
+/// Draw a single project in the project chooser, returning what (if anything) the user wants to do
+/// with it
+fn project_list_entry(
+    ui: &mut Ui,
+    project: &Path,
+    cover: Option<&Path>,
+    pinned: bool,
+) -> Option<ProjectListAction> {
+    let mut action = None;
+
+    ui.horizontal(|ui| {
+        match cover {
+            Some(cover) => {
+                ui.add(
+                    egui::Image::new(format!("file://{}", cover.display()))
+                        .fit_to_exact_size(COVER_THUMBNAIL_SIZE)
+                        .maintain_aspect_ratio(true),
+                );
+            }
+            None => {
+                // Keep the projects without a cover lined up with the rest
+                let (rect, _response) =
+                    ui.allocate_exact_size(COVER_THUMBNAIL_SIZE, egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(rect, 2.0, ui.visuals().faint_bg_color);
+                ui.painter().text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    "📖",
+                    FontId::proportional(16.0),
+                    ui.visuals().weak_text_color(),
+                );
+            }
+        }
+
+        let response = ui.button(project.to_string_lossy().to_string());
+        if response.clicked() {
+            action = Some(ProjectListAction::Load);
+        }
+
+        let pin_text = if pinned { "Unpin" } else { "Pin" };
+
+        response.context_menu(|ui| {
+            if ui.button(pin_text).clicked() {
+                action = Some(ProjectListAction::TogglePin);
+            }
+            if ui.button("Remove from list").clicked() {
+                action = Some(ProjectListAction::Remove);
+            }
+        });
+
+        if ui.small_button(pin_text).clicked() {
+            action = Some(ProjectListAction::TogglePin);
+        }
+
+        if ui.small_button("Remove from list").clicked() {
+            action = Some(ProjectListAction::Remove);
+        }
+    });
+
+    action
+}
@@ -0,0 +1,141 @@
+use std::time::{Duration, Instant};
+
+/// What changed when the idle state was updated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleTransition {
+    /// There hasn't been any input for the timeout. Anything that's waiting (e.g., unsaved
+    /// changes) should be taken care of now, since the work that would normally do it is paused
+    BecameIdle,
+    /// There was input after being idle, so the paused work can start again
+    BecameActive,
+}
+
+/// Tracks whether anyone is using the app, so that background work (autosaves and updating the
+/// spellcheck dictionary) can stop while nobody is around.
+///
+/// The tracker is either active or idle. It goes from active to idle once there hasn't been any
+/// input for the timeout, and back to active with the first input after that. Without a timeout
+/// (idle detection turned off), it's always active
+#[derive(Debug)]
+pub struct IdleTracker {
+    /// The last time there was any input
+    last_input: Instant,
+    idle: bool,
+}
+
+impl IdleTracker {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            last_input: now,
+            idle: false,
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// How long until it becomes idle if there isn't any more input, `None` if it's already idle
+    /// (or never will be)
+    pub fn until_idle(&self, now: Instant, timeout: Option<Duration>) -> Option<Duration> {
+        if self.idle {
+            return None;
+        }
+        timeout.map(|timeout| timeout.saturating_sub(now.duration_since(self.last_input)))
+    }
+
+    /// Record whether there was input since the last update, moving between active and idle if
+    /// that changes anything
+    pub fn update(
+        &mut self,
+        now: Instant,
+        had_input: bool,
+        timeout: Option<Duration>,
+    ) -> Option<IdleTransition> {
+        if had_input {
+            self.last_input = now;
+        }
+
+        let idle = timeout.is_some_and(|timeout| now.duration_since(self.last_input) >= timeout);
+
+        match (self.idle, idle) {
+            (false, true) => {
+                self.idle = true;
+                Some(IdleTransition::BecameIdle)
+            }
+            (true, false) => {
+                self.idle = false;
+                Some(IdleTransition::BecameActive)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_idle_tracker() {
+    let timeout = Some(Duration::from_secs(5 * 60));
+    let start = Instant::now();
+
+    let mut tracker = IdleTracker::new(start);
+    assert!(!tracker.is_idle());
+    assert_eq!(tracker.update(start, false, timeout), None);
+
+    // input keeps it active
+    let typing = start + Duration::from_secs(4 * 60);
+    assert_eq!(tracker.update(typing, true, timeout), None);
+    assert_eq!(
+        tracker.until_idle(typing + Duration::from_secs(60), timeout),
+        Some(Duration::from_secs(4 * 60))
+    );
+    assert_eq!(
+        tracker.update(start + Duration::from_secs(6 * 60), false, timeout),
+        None
+    );
+
+    // it only becomes idle once, no matter how many updates there are after that
+    let later = typing + Duration::from_secs(5 * 60);
+    assert_eq!(
+        tracker.update(later, false, timeout),
+        Some(IdleTransition::BecameIdle)
+    );
+    assert!(tracker.is_idle());
+    assert_eq!(tracker.until_idle(later, timeout), None);
+    assert_eq!(
+        tracker.update(later + Duration::from_secs(60), false, timeout),
+        None
+    );
+
+    // the first input after that wakes it back up
+    let back = later + Duration::from_secs(30 * 60);
+    assert_eq!(
+        tracker.update(back, true, timeout),
+        Some(IdleTransition::BecameActive)
+    );
+    assert!(!tracker.is_idle());
+    assert_eq!(tracker.update(back, true, timeout), None);
+}
+
+#[test]
+fn test_idle_tracker_disabled() {
+    let start = Instant::now();
+
+    let mut tracker = IdleTracker::new(start);
+    assert_eq!(
+        tracker.update(start + Duration::from_secs(24 * 60 * 60), false, None),
+        None
+    );
+    assert!(!tracker.is_idle());
+
+    // turning it off while idle counts as becoming active
+    let timeout = Some(Duration::from_secs(60));
+    let later = start + Duration::from_secs(2 * 60);
+    assert_eq!(
+        tracker.update(later, false, timeout),
+        Some(IdleTransition::BecameIdle)
+    );
+    assert_eq!(
+        tracker.update(later, false, None),
+        Some(IdleTransition::BecameActive)
+    );
+}
@@ -1,12 +1,22 @@
+use crate::components::project::Project;
+use crate::util::write_counters::write_counts;
+
+use std::collections::VecDeque;
 use std::fmt::Display;
 use std::time::{Duration, SystemTime};
 
+/// How many of the most recent frame times are kept around for computing percentiles
+const FRAME_HISTORY_LENGTH: usize = 600;
+
 #[derive(Debug)]
 pub struct Metrics {
     frame_start_time: Option<SystemTime>,
     last_update: SystemTime,
     frame_times: Vec<Duration>,
+    recent_frame_times: VecDeque<Duration>,
     pub report: Option<Report>,
+    /// Set when `report` was regenerated by the last `frame_stop`
+    report_is_new: bool,
 }
 
 impl Default for Metrics {
@@ -15,7 +25,9 @@ impl Default for Metrics {
             frame_start_time: None,
             last_update: SystemTime::now(),
             frame_times: Vec::new(),
+            recent_frame_times: VecDeque::with_capacity(FRAME_HISTORY_LENGTH),
             report: None,
+            report_is_new: false,
         }
     }
 }
@@ -24,19 +36,51 @@ impl Default for Metrics {
 pub struct Report {
     pub avg_frame_duration: Duration,
     pub frames_since_last_update: usize,
+    /// Files written to disk since startup
+    pub writes: usize,
+    /// Saves that were skipped because nothing had changed
+    pub writes_avoided: usize,
 }
 
 impl Display for Report {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(
             f,
-            "{} µs | {} frames",
+            "{} µs | {} frames | {} writes ({} avoided)",
             self.avg_frame_duration.as_micros(),
-            self.frames_since_last_update
+            self.frames_since_last_update,
+            self.writes,
+            self.writes_avoided
         )
     }
 }
 
+/// Structured performance data, for logging or showing somewhere other than the overlay
+#[derive(Debug, Clone)]
+pub struct MetricsSnapshot {
+    pub frame_time_p50: Duration,
+    pub frame_time_p90: Duration,
+    pub frame_time_p99: Duration,
+    pub frame_time_max: Duration,
+    /// Number of file objects in the open project (0 if there isn't one)
+    pub object_count: usize,
+    /// Rough estimate (in bytes) of the project text currently held in memory. Bodies that haven't
+    /// been loaded yet aren't counted
+    pub text_memory_estimate: usize,
+    pub writes: usize,
+    pub writes_avoided: usize,
+}
+
+/// The value at `percentile` (0.0 - 1.0) of an already sorted list
+fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let index = ((sorted.len() - 1) as f64 * percentile).round() as usize;
+    sorted[index.min(sorted.len() - 1)]
+}
+
 const REFRESH_PERIOD: Duration = Duration::from_secs(1);
 
 impl Metrics {
@@ -47,24 +91,88 @@ impl Metrics {
     }
 
     pub fn frame_stop(&mut self) -> Duration {
-        self.frame_times
-            .push(self.frame_start_time.unwrap().elapsed().unwrap());
+        let frame_time = self.frame_start_time.unwrap().elapsed().unwrap();
+        self.frame_times.push(frame_time);
         self.frame_start_time = None;
 
+        if self.recent_frame_times.len() == FRAME_HISTORY_LENGTH {
+            self.recent_frame_times.pop_front();
+        }
+        self.recent_frame_times.push_back(frame_time);
+        self.report_is_new = false;
+
         let mut since_last_update = self.last_update.elapsed().unwrap();
         if since_last_update >= REFRESH_PERIOD {
             let frames_since_last_update = self.frame_times.len();
             let avg_frame_duration: Duration =
                 self.frame_times.drain(..).sum::<Duration>() / (frames_since_last_update as u32);
+            let (writes, writes_avoided) = write_counts();
             self.report = Some(Report {
                 frames_since_last_update,
                 avg_frame_duration,
+                writes,
+                writes_avoided,
             });
 
+            self.report_is_new = true;
+
             self.last_update = SystemTime::now();
             since_last_update = Duration::ZERO;
         }
 
         REFRESH_PERIOD - since_last_update
     }
+
+    /// If the report was regenerated during the last frame
+    pub fn has_new_report(&self) -> bool {
+        self.report_is_new
+    }
+
+    /// Collect the current metrics, including some information about the open project (if any)
+    pub fn snapshot(&self, project: Option<&Project>) -> MetricsSnapshot {
+        let mut sorted_frame_times: Vec<Duration> =
+            self.recent_frame_times.iter().copied().collect();
+        sorted_frame_times.sort();
+
+        let (object_count, text_memory_estimate) = match project {
+            Some(project) => {
+                let mut text_memory_estimate = 0;
+                for object in project.objects.values() {
+                    let object = object.borrow();
+                    // Don't count (and load) bodies that haven't been read yet
+                    if object.is_body_loaded() {
+                        object.as_editor().for_each_textbox(&mut |text, _name| {
+                            text_memory_estimate += text.capacity();
+                        });
+                    }
+                }
+                (project.objects.len(), text_memory_estimate)
+            }
+            None => (0, 0),
+        };
+
+        let (writes, writes_avoided) = write_counts();
+
+        MetricsSnapshot {
+            frame_time_p50: percentile(&sorted_frame_times, 0.5),
+            frame_time_p90: percentile(&sorted_frame_times, 0.9),
+            frame_time_p99: percentile(&sorted_frame_times, 0.99),
+            frame_time_max: sorted_frame_times.last().copied().unwrap_or_default(),
+            object_count,
+            text_memory_estimate,
+            writes,
+            writes_avoided,
+        }
+    }
+}
+
+#[test]
+fn test_percentile() {
+    let frame_times: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+
+    assert_eq!(percentile(&frame_times, 0.0), Duration::from_millis(1));
+    assert_eq!(percentile(&frame_times, 0.5), Duration::from_millis(51));
+    assert_eq!(percentile(&frame_times, 0.99), Duration::from_millis(99));
+    assert_eq!(percentile(&frame_times, 1.0), Duration::from_millis(100));
+    assert_eq!(percentile(&[], 0.5), Duration::ZERO);
 }
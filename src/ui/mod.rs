@@ -1,6 +1,7 @@
 mod render_data;
 
 mod editor_base;
+mod idle;
 mod settings;
 mod text_box;
 
@@ -11,6 +12,7 @@ pub mod prelude;
 
 pub use editor_base::CheesePaperApp;
 pub use project_editor::page::FileObjectEditor;
+pub use project_editor::page::file_object_editor::{image_ui, relationships_ui, word_target_ui};
 
 #[cfg(feature = "metrics")]
 mod metrics;
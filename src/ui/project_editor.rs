@@ -1,6 +1,12 @@
 pub mod action;
+mod conflict;
+mod delete_confirmation;
 mod file_tree;
+mod line_endings;
 pub mod page;
+mod pomodoro;
+mod quick_capture;
+mod recovery;
 pub mod search;
 mod util;
 
@@ -11,6 +17,7 @@ use crate::components::file_objects::utils::process_name_for_filename;
 use crate::ui::editor_base::EditorState;
 use crate::ui::project_editor::search::global_search;
 use crate::ui::project_tracker::ProjectTracker;
+use crate::ui::text_box::IgnorePatterns;
 
 use action::Actions;
 
@@ -18,10 +25,11 @@ use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::ops::Range;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use egui::{Key, Modifiers};
 use egui_dock::{DockArea, DockState};
-use egui_ltreeview::TreeViewState;
+use egui_ltreeview::{DirPosition, TreeViewState};
 use rfd::FileDialog;
 use spellbook::Dictionary;
 
@@ -52,8 +60,31 @@ pub struct ProjectEditor {
     /// We need to keep track of the tree state to set selection
     tree_state: TreeViewState<Page>,
 
+    /// Folders that are displayed in something other than their manual order
+    tree_sort_modes: HashMap<FileID, file_tree::TreeSortMode>,
+
+    /// The object being renamed from the tree, along with the name being typed
+    tree_rename: Option<(FileID, String)>,
+
     /// Set by the tab viewer, used to sync the file tree
     current_open_tab: Option<OpenPage>,
+
+    word_goal_tracker: util::WordGoalTracker,
+
+    /// Words in the story text, for the status bar. Counted when it's first shown, and again after
+    /// every autosave (see `recount_story_words`)
+    story_words: Option<usize>,
+
+    pomodoro: pomodoro::Pomodoro,
+
+    /// A message (about reaching the word goal or the pomodoro timer), and when it was shown
+    toast: Option<(String, Instant)>,
+
+    /// The note being written in the quick capture box, if it's open
+    quick_capture: Option<quick_capture::QuickCapture>,
+
+    /// An object that will be deleted once the user confirms it
+    pending_delete: Option<delete_confirmation::PendingDelete>,
 }
 
 impl Debug for ProjectEditor {
@@ -78,6 +109,8 @@ pub struct DictionaryState {
     old_characters_and_places: HashSet<String>,
     added_file_object_names: HashSet<String>,
     pub ignore_list_updated: bool,
+    /// Words matching these (from the project metadata) are never misspelled
+    pub ignore_patterns: IgnorePatterns,
 }
 
 impl DictionaryState {
@@ -90,6 +123,7 @@ impl DictionaryState {
             old_characters_and_places: HashSet::new(),
             added_file_object_names: HashSet::new(),
             ignore_list_updated: false,
+            ignore_patterns: IgnorePatterns::default(),
         }
     }
 
@@ -257,6 +291,74 @@ pub struct EditorContext {
 
     /// version number. increment to trigger a project-wide formatting refresh
     pub version: usize,
+
+    /// An object whose name field should be focused the next time it's drawn (e.g., because it
+    /// was just created)
+    pub focus_name: Option<FileID>,
+
+    /// An object whose body should be focused, with the cursor at the end, the next time it's
+    /// drawn (e.g., to continue writing)
+    pub focus_body_end: Option<FileID>,
+
+    /// The multiline text box that had focus the last time one was drawn, which gets to use Tab
+    /// for indenting (if that's enabled) instead of it moving focus
+    pub focused_text_box: Option<egui::Id>,
+
+    /// A position (byte offset) in a text box to move the cursor to and scroll to the next time
+    /// it's drawn (e.g., from the outline of a scene)
+    pub go_to_text: Option<(TextUID, usize)>,
+
+    /// The word count of each open scene when its tab was opened, for showing how much it's
+    /// changed since. Scenes add themselves when they're first drawn
+    pub words_at_open: HashMap<FileID, usize>,
+
+    /// Incremented whenever word counts may have changed (after every autosave or when a page is
+    /// opened), so that anything worked out from them (e.g., progress toward word targets) knows
+    /// to do it again
+    pub word_count_version: usize,
+
+    /// Set when the project couldn't be saved because it's read-only, which turns off editing
+    /// text until a save works again (anything that was already changed is kept in memory)
+    pub read_only: bool,
+}
+
+impl EditorContext {
+    /// Focus the name field of an object if it was requested by `focus_name`
+    pub fn focus_name_if_requested(&mut self, file_id: &FileID, response: &Response) {
+        if self.focus_name.as_ref() == Some(file_id) {
+            response.request_focus();
+            self.focus_name = None;
+        }
+    }
+
+    /// Focus the body of an object and put the cursor at the end if it was requested by
+    /// `focus_body_end`
+    pub fn focus_body_end_if_requested(
+        &mut self,
+        file_id: &FileID,
+        ui: &Ui,
+        response: &Response,
+        text: &str,
+    ) {
+        if self.focus_body_end.as_ref() != Some(file_id) {
+            return;
+        }
+        self.focus_body_end = None;
+
+        // The text box won't have any state yet if it was just opened
+        let mut state = egui::TextEdit::load_state(ui.ctx(), response.id).unwrap_or_default();
+        let end = egui::text::CCursor::new(text.chars().count());
+        state
+            .cursor
+            .set_char_range(Some(egui::text::CCursorRange::one(end)));
+        state.store(ui.ctx(), response.id);
+
+        response.request_focus();
+        ui.scroll_to_rect(
+            egui::Rect::from_min_size(response.rect.left_bottom(), egui::Vec2::ZERO),
+            Some(egui::Align::Max),
+        );
+    }
 }
 
 #[derive(Debug, Default)]
@@ -271,6 +373,11 @@ pub enum TabMove {
     Next,
 }
 
+pub enum TabSplit {
+    Right,
+    Down,
+}
+
 pub struct TabViewer<'a> {
     pub project: &'a mut Project,
     pub editor_context: &'a mut EditorContext,
@@ -330,6 +437,51 @@ impl egui_dock::TabViewer for TabViewer<'_> {
         }
     }
 
+    fn context_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        tab: &mut Self::Tab,
+        _surface: egui_dock::SurfaceIndex,
+        _node: egui_dock::NodeIndex,
+    ) {
+        if ui.button("Split right").clicked() {
+            let page = tab.page.clone();
+            self.editor_context
+                .actions
+                .schedule(move |project_editor, _ctx| {
+                    project_editor.split_tab(&page, TabSplit::Right)
+                });
+            ui.close();
+        }
+
+        if ui.button("Split down").clicked() {
+            let page = tab.page.clone();
+            self.editor_context
+                .actions
+                .schedule(move |project_editor, _ctx| {
+                    project_editor.split_tab(&page, TabSplit::Down)
+                });
+            ui.close();
+        }
+
+        ui.separator();
+
+        if ui.button("Close others").clicked() {
+            let page = tab.page.clone();
+            self.editor_context
+                .actions
+                .schedule(move |project_editor, _ctx| project_editor.close_other_tabs(&page));
+            ui.close();
+        }
+
+        if ui.button("Close all").clicked() {
+            self.editor_context
+                .actions
+                .schedule(|project_editor, _ctx| project_editor.close_all_tabs());
+            ui.close();
+        }
+    }
+
     fn allowed_in_windows(&self, _tab: &mut Self::Tab) -> bool {
         // disable moving tabs into windows (untested, could maybe be supported later)
         false
@@ -350,6 +502,80 @@ impl ProjectEditor {
 
         self.draw_menu(ctx, state);
 
+        if self.editor_context.read_only {
+            let mut retry = false;
+            egui::TopBottomPanel::top("read only banner").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "The project is read-only; changes are not being saved",
+                    );
+                    if ui.button("Try Again").clicked() {
+                        retry = true;
+                    }
+                });
+            });
+
+            if retry {
+                self.save();
+            }
+        }
+
+        let word_goal = self.project.metadata.word_goal;
+        if word_goal.is_none() {
+            // so that setting a goal that's already been passed doesn't count as reaching it
+            self.word_goal_tracker = Default::default();
+        }
+
+        let pomodoro_enabled = self.editor_context.settings.pomodoro();
+        if !pomodoro_enabled {
+            self.pomodoro.stop();
+        }
+
+        if word_goal.is_some() || pomodoro_enabled {
+            let mut goal_reached = None;
+            let mut pomodoro_transition = None;
+
+            egui::TopBottomPanel::bottom("status bar").show(ctx, |ui| {
+                let words = *self
+                    .story_words
+                    .get_or_insert_with(|| util::story_word_count(&self.project));
+                ui.horizontal(|ui| {
+                    if let Some(word_goal) = word_goal {
+                        if self.word_goal_tracker.update(words, word_goal) {
+                            goal_reached = Some(word_goal);
+                        }
+                        util::word_goal_ring(ui, words, word_goal);
+                    }
+
+                    if pomodoro_enabled {
+                        if word_goal.is_some() {
+                            ui.separator();
+                        }
+                        pomodoro_transition = pomodoro::status_ui(
+                            &mut self.pomodoro,
+                            ui,
+                            words,
+                            &self.editor_context.settings,
+                        );
+                    }
+                });
+            });
+
+            if let Some(word_goal) = goal_reached {
+                self.notify(
+                    "Word goal reached",
+                    format!("You reached your goal of {word_goal} words!"),
+                );
+            }
+
+            if let Some(transition) = pomodoro_transition {
+                self.notify_pomodoro(transition);
+            }
+        }
+
+        self.toast_ui(ctx);
+
         egui::SidePanel::left("project tree panel").show(ctx, |ui| {
             self.side_panel(ui);
         });
@@ -359,12 +585,31 @@ impl ProjectEditor {
             Page::ProjectMetadata => true,
             Page::Export => true,
             Page::Settings => true,
+            Page::LinkCheck => true,
+            Page::Timeline => true,
+            Page::Unsaved => true,
+            Page::Goals => true,
             Page::FileObject(tab_id) => self.project.objects.contains_key(tab_id),
         });
 
-        // render the tab view
+        // Forget the starting word counts of closed scenes, so reopening one starts counting again
+        let open_file_ids: HashSet<&FileID> = self
+            .dock_state
+            .iter_all_tabs()
+            .filter_map(|(_, tab)| match &tab.page {
+                Page::FileObject(file_id) => Some(file_id),
+                _ => None,
+            })
+            .collect();
+        self.editor_context
+            .words_at_open
+            .retain(|file_id, _| open_file_ids.contains(file_id));
+
+        // render the tab view. Tabs can be reordered by dragging (which is kept when the open
+        // tabs are saved), or dragged to the side of another tab to split the view
         DockArea::new(&mut self.dock_state)
-            .allowed_splits(egui_dock::AllowedSplits::None)
+            .draggable_tabs(true)
+            .allowed_splits(egui_dock::AllowedSplits::All)
             .show_leaf_collapse_buttons(false)
             .show_leaf_close_all_buttons(false)
             .show(
@@ -390,10 +635,91 @@ impl ProjectEditor {
         {
             self.tree_state.set_one_selected(open_tab.page.clone());
         }
+
+        conflict::ui(self, ctx);
+        line_endings::ui(self, ctx);
+        recovery::ui(self, ctx);
+        quick_capture::ui(self, ctx);
+        delete_confirmation::ui(self, ctx);
+    }
+
+    /// Show a message as a toast (and a desktop notification, if they're enabled)
+    #[cfg_attr(not(feature = "desktop-notifications"), allow(unused_variables))]
+    fn notify(&mut self, summary: &str, message: String) {
+        #[cfg(feature = "desktop-notifications")]
+        if let Err(err) = notify_rust::Notification::new()
+            .summary(summary)
+            .body(&message)
+            .show()
+        {
+            log::warn!("could not show notification: {err}");
+        }
+
+        self.toast = Some((message, Instant::now()));
+    }
+
+    fn notify_pomodoro(&mut self, transition: pomodoro::PomodoroTransition) {
+        match transition {
+            pomodoro::PomodoroTransition::WritingDone { words_written } => {
+                log::info!(
+                    "Pomodoro session {} finished: {words_written} words written",
+                    self.pomodoro.sessions_completed
+                );
+
+                let message = if words_written >= 0 {
+                    format!("Time for a break! You wrote {words_written} words this session")
+                } else {
+                    format!(
+                        "Time for a break! The story is {} words shorter than when the session \
+                        started",
+                        -words_written
+                    )
+                };
+                self.notify("Writing session over", message);
+            }
+            pomodoro::PomodoroTransition::BreakDone => {
+                self.notify("Break over", "Back to writing!".to_string());
+            }
+        }
+    }
+
+    /// Show the latest notification in the corner of the window until it times out
+    fn toast_ui(&mut self, ctx: &egui::Context) {
+        const TOAST_DURATION: Duration = Duration::from_secs(7);
+
+        let Some((message, time)) = &self.toast else {
+            return;
+        };
+
+        let Some(remaining) = TOAST_DURATION.checked_sub(time.elapsed()) else {
+            self.toast = None;
+            return;
+        };
+
+        egui::Area::new(egui::Id::new("toast"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -48.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| ui.label(message));
+            });
+
+        // make sure it goes away even if nothing else is happening
+        ctx.request_repaint_after(remaining);
     }
 
     /// Get input that the project editor itself will read (hotkeys to switch or close tabs)
     fn process_input(&mut self, ctx: &egui::Context) {
+        // save immediately if ctrl-s is pressed (the only way to save before closing when
+        // autosave is turned off for the project)
+        if ctx.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut {
+                modifiers: Modifiers::CTRL,
+                logical_key: Key::S,
+            })
+        }) {
+            self.save();
+        }
+
         // close current tab if ctrl-w is pressed
         if ctx.input_mut(|i| {
             i.consume_shortcut(&egui::KeyboardShortcut {
@@ -426,6 +752,107 @@ impl ProjectEditor {
             // ctrl-tab was pressed, move fowards
             self.move_tab(TabMove::Next)
         }
+
+        // Open the next or previous scene in reading order (ctrl-pagedown or ctrl-pageup)
+        if ctx.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut {
+                modifiers: Modifiers::CTRL,
+                logical_key: Key::PageDown,
+            })
+        }) {
+            self.move_scene(TabMove::Next);
+        } else if ctx.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut {
+                modifiers: Modifiers::CTRL,
+                logical_key: Key::PageUp,
+            })
+        }) {
+            self.move_scene(TabMove::Previous);
+        }
+
+        // Jot down a note in the inbox without leaving the current scene (ctrl-shift-space)
+        if ctx.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut {
+                modifiers: Modifiers::CTRL | Modifiers::SHIFT,
+                logical_key: Key::Space,
+            })
+        }) && self.quick_capture.is_none()
+        {
+            self.quick_capture = Some(quick_capture::QuickCapture::default());
+        }
+
+        // Create new objects without the mouse (ctrl-n for a scene, ctrl-shift-n for a folder)
+        if ctx.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut {
+                modifiers: Modifiers::CTRL | Modifiers::SHIFT,
+                logical_key: Key::N,
+            })
+        }) {
+            self.create_object_in_selected_folder(self.project.schema.get_top_level_folder_type());
+        } else if ctx.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut {
+                modifiers: Modifiers::CTRL,
+                logical_key: Key::N,
+            })
+        }) && let Some(scene_type) = self
+            .project
+            .schema
+            .get_all_file_types()
+            .iter()
+            .find(|file_type| file_type.has_body() && !file_type.is_folder())
+        {
+            self.create_object_in_selected_folder(*scene_type);
+        }
+    }
+
+    /// Add a new object at the end of the selected folder (or the closest folder above the
+    /// selected object that can hold it, or the text folder if there isn't one), then open it so
+    /// it can be named
+    fn create_object_in_selected_folder(&mut self, file_type: FileType) {
+        let mut parent_id = match self.tree_state.selected().first() {
+            Some(Page::FileObject(file_id)) => Some(file_id.clone()),
+            _ => None,
+        };
+
+        // e.g., a new scene shouldn't end up inside of the place that happens to be selected
+        while let Some(candidate) = &parent_id
+            && !self
+                .project
+                .objects
+                .get(candidate)
+                .is_some_and(|object| object.borrow().can_hold(file_type))
+        {
+            parent_id = self.project.find_object_parent(candidate);
+        }
+
+        let parent_id = parent_id.unwrap_or_else(|| self.project.text_folder_id().clone());
+
+        let Some(parent) = self.project.objects.get(&parent_id) else {
+            return;
+        };
+
+        let result =
+            parent
+                .borrow_mut()
+                .create_child(file_type, DirPosition::Last, &self.project.objects);
+
+        match result {
+            Ok(new_child) => {
+                let new_id = new_child.id().clone();
+                self.project.add_object(new_child);
+                self.set_editor_tab(&Page::FileObject(new_id.clone()), true);
+                self.editor_context.focus_name = Some(new_id);
+            }
+            Err(err) => log::error!("Encountered error while trying to add child: {err}"),
+        }
+    }
+
+    /// Open the scene that was edited most recently, with the cursor at the end of the text
+    fn continue_writing(&mut self) {
+        if let Some(scene_id) = self.project.most_recently_modified_scene() {
+            self.set_editor_tab(&Page::FileObject(scene_id.clone()), true);
+            self.editor_context.focus_body_end = Some(scene_id);
+        }
     }
 
     fn move_tab(&mut self, tab_move: TabMove) {
@@ -459,6 +886,34 @@ impl ProjectEditor {
         }
     }
 
+    /// Open the scene before or after the focused one, as they'd be read in the story
+    fn move_scene(&mut self, scene_move: TabMove) {
+        let current_id = match self.dock_state.find_active_focused() {
+            Some((_, Page::FileObject(current_id))) => current_id.clone(),
+            _ => return,
+        };
+
+        let scene_order = self.project.linear_scene_order();
+        let Some(current_pos) = scene_order.iter().position(|id| *id == current_id) else {
+            return;
+        };
+
+        let wrap = self.editor_context.settings.wrap_scene_navigation();
+        let new_pos = match scene_move {
+            TabMove::Next if current_pos + 1 < scene_order.len() => Some(current_pos + 1),
+            TabMove::Next => wrap.then_some(0),
+            TabMove::Previous => current_pos
+                .checked_sub(1)
+                .or(wrap.then_some(scene_order.len() - 1)),
+        };
+
+        if let Some(new_pos) = new_pos
+            && new_pos != current_pos
+        {
+            self.set_editor_tab(&Page::FileObject(scene_order[new_pos].clone()), true);
+        }
+    }
+
     fn draw_menu(&mut self, ctx: &egui::Context, state: &mut EditorState) {
         egui::TopBottomPanel::top("menu_bar_panel")
             .show_separator_line(false)
@@ -476,12 +931,25 @@ impl ProjectEditor {
                                     state.next_project = Some(project.clone());
                                 }
                             }
+
+                            ui.separator();
+
+                            if ui.button("Clear Recent Projects").clicked() {
+                                state.clear_recent_projects();
+                            }
                         });
 
                         if ui.button("Export Story Text").clicked() {
                             self.set_editor_tab(&Page::Export, true);
                         }
 
+                        if ui.button("Copy Story Text to Clipboard").clicked() {
+                            let export_contents = self
+                                .project
+                                .export_text(self.project.metadata.export.export_options());
+                            ui.ctx().copy_text(export_contents);
+                        }
+
                         if ui.button("Export Outline").clicked() {
                             let project_title = &self.project.base_metadata.name;
                             let suggested_title =
@@ -506,16 +974,169 @@ impl ProjectEditor {
                             }
                         }
 
+                        ui.menu_button("Export Dossier", |ui| {
+                            let sections: Vec<(FileID, String)> = self
+                                .project
+                                .top_level_folders
+                                .iter()
+                                .zip(self.project.top_level_folder_names.iter())
+                                .filter(|(id, _name)| *id != self.project.text_folder_id())
+                                .map(|(id, name)| (id.clone(), name.clone()))
+                                .collect();
+
+                            for (section_id, section_name) in sections {
+                                if ui.button(&section_name).clicked() {
+                                    let project_title = &self.project.base_metadata.name;
+                                    let suggested_title = format!(
+                                        "{}_{}.md",
+                                        process_name_for_filename(project_title),
+                                        process_name_for_filename(&section_name).to_lowercase()
+                                    );
+                                    let export_location_option = FileDialog::new()
+                                        .set_title(format!("Export {project_title} {section_name}"))
+                                        .set_directory(&state.data.last_export_folder)
+                                        .set_file_name(suggested_title)
+                                        .save_file();
+
+                                    if let Some(export_location) = export_location_option {
+                                        let dossier_contents =
+                                            self.project.export_dossier(&section_id);
+                                        if let Err(err) =
+                                            std::fs::write(&export_location, dossier_contents)
+                                        {
+                                            log::error!(
+                                                "Error while attempting to write dossier: {err}"
+                                            );
+                                        }
+
+                                        state.data.last_export_folder = export_location
+                                            .parent()
+                                            .map(|val| val.to_path_buf())
+                                            .unwrap_or_default();
+                                    }
+                                }
+                            }
+                        });
+
+                        ui.menu_button("Import Section", |ui| {
+                            let sections: Vec<(FileID, String)> = self
+                                .project
+                                .top_level_folders
+                                .iter()
+                                .zip(self.project.top_level_folder_names.iter())
+                                .map(|(id, name)| (id.clone(), name.clone()))
+                                .collect();
+
+                            // Copies the section with the same name from the chosen project
+                            for (section_id, section_name) in sections {
+                                if ui.button(&section_name).clicked() {
+                                    let import_location_option = FileDialog::new()
+                                        .set_title(format!("Import {section_name} From Project"))
+                                        .pick_folder();
+
+                                    if let Some(import_location) = import_location_option
+                                        && let Err(err) = self.project.import_section(
+                                            &import_location,
+                                            &section_name,
+                                            &section_id,
+                                        )
+                                    {
+                                        log::error!(
+                                            "Error while importing {section_name} from \
+                                            {import_location:?}: {err}"
+                                        );
+                                    }
+                                    ui.close();
+                                }
+                            }
+                        });
+
+                        ui.menu_button("Import Characters", |ui| {
+                            let sections: Vec<(FileID, String)> = self
+                                .project
+                                .top_level_folders
+                                .iter()
+                                .zip(self.project.top_level_folder_names.iter())
+                                .filter(|(id, _name)| *id != self.project.text_folder_id())
+                                .map(|(id, name)| (id.clone(), name.clone()))
+                                .collect();
+
+                            // Adds a character to the end of the section for each row
+                            for (section_id, section_name) in sections {
+                                if ui.button(&section_name).clicked() {
+                                    let import_location_option = FileDialog::new()
+                                        .set_title(format!("Import Characters to {section_name}"))
+                                        .add_filter("CSV", &["csv"])
+                                        .pick_file();
+
+                                    if let Some(import_location) = import_location_option
+                                        && let Err(err) = self
+                                            .project
+                                            .import_characters_csv(&import_location, &section_id)
+                                    {
+                                        log::error!(
+                                            "Error while importing characters from \
+                                            {import_location:?}: {err}"
+                                        );
+                                    }
+                                    ui.close();
+                                }
+                            }
+                        });
+
                         if ui.button("Quit").clicked() {
                             ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
                         }
                     });
 
                     ui.menu_button("Edit", |ui| {
+                        if ui.button("Continue Writing").clicked() {
+                            self.continue_writing();
+                        }
+
                         if ui.button("Find (Global)").clicked() {
                             self.editor_context.search.show();
                         }
 
+                        let show_comments = self.editor_context.settings.show_comments();
+                        let comments_label = match show_comments {
+                            true => "Hide Comments",
+                            false => "Show Comments",
+                        };
+                        if ui.button(comments_label).clicked() {
+                            self.editor_context
+                                .settings
+                                .set_show_comments(!show_comments);
+                            self.editor_context.version += 1;
+                        }
+
+                        let show_invisibles = self.editor_context.settings.show_invisibles();
+                        let invisibles_label = match show_invisibles {
+                            true => "Hide Invisibles",
+                            false => "Show Invisibles",
+                        };
+                        if ui.button(invisibles_label).clicked() {
+                            self.editor_context
+                                .settings
+                                .set_show_invisibles(!show_invisibles);
+                        }
+
+                        if ui.button("Check Links").clicked() {
+                            self.set_editor_tab(&Page::LinkCheck, true);
+                        }
+
+                        if ui.button("Timeline").clicked() {
+                            self.set_editor_tab(&Page::Timeline, true);
+                        }
+
+                        if ui.button("Unsaved Changes").clicked() {
+                            self.set_editor_tab(&Page::Unsaved, true);
+                        }
+
+                        if ui.button("Word Goals").clicked() {
+                            self.set_editor_tab(&Page::Goals, true);
+                        }
+
                         if ui.button("Settings").clicked() {
                             self.set_editor_tab(&Page::Settings, true);
                         }
@@ -546,6 +1167,9 @@ impl ProjectEditor {
                     global_search::ui(ui, &self.project, &mut self.editor_context);
                 });
         } else {
+            file_tree::toolbar_ui(self, ui);
+            ui.separator();
+
             egui::ScrollArea::both()
                 .id_salt("tree scroll")
                 .max_height(ui.available_height())
@@ -601,7 +1225,7 @@ impl ProjectEditor {
         }
     }
 
-    fn set_editor_tab(&mut self, page: &Page, keep: bool) {
+    pub fn set_editor_tab(&mut self, page: &Page, keep: bool) {
         // We don't want to open these, so just exit early
         if let Page::FileObject(id) = page
             && self.project.is_top_level_folder(id)
@@ -620,8 +1244,14 @@ impl ProjectEditor {
                 // there's a tab open in browsing mode, close it
                 self.dock_state.remove_tab(tab_position);
             }
-            // New file object, open it for editing
-            self.dock_state.push_to_first_leaf(page.clone().open(keep));
+            // New file object, open it for editing (next to whatever is being worked on if the
+            // view is split)
+            self.dock_state
+                .push_to_focused_leaf(page.clone().open(keep));
+
+            // Pages keep their data around after being closed, make sure this one doesn't show
+            // word counts from back then
+            self.editor_context.word_count_version += 1;
         }
     }
 
@@ -634,6 +1264,44 @@ impl ProjectEditor {
         }
     }
 
+    /// Close every tab except the one showing `page`. Everything is saved continuously, so
+    /// nothing is lost by closing tabs
+    fn close_other_tabs(&mut self, page: &Page) {
+        self.dock_state.retain_tabs(|tab| &tab.page == page);
+    }
+
+    fn close_all_tabs(&mut self) {
+        self.dock_state.retain_tabs(|_tab| false);
+    }
+
+    /// Move the tab showing `page` into a new pane next to the one it's in. This does nothing if
+    /// it's the only tab in its pane, since there would be nothing left to show beside it
+    fn split_tab(&mut self, page: &Page, split: TabSplit) {
+        let Some(tab_position) = self.dock_state.find_tab_from(|tab| &tab.page == page) else {
+            return;
+        };
+        let (surface, node, _) = tab_position;
+
+        let tabs_in_pane = self
+            .dock_state
+            .iter_all_tabs()
+            .filter(|((tab_surface, tab_node), _)| *tab_surface == surface && *tab_node == node)
+            .count();
+
+        // Moving tabs into windows is disabled, so everything should be in the main surface
+        if tabs_in_pane < 2 || surface != egui_dock::SurfaceIndex::main() {
+            return;
+        }
+
+        if let Some(tab) = self.dock_state.remove_tab(tab_position) {
+            let surface = self.dock_state.main_surface_mut();
+            match split {
+                TabSplit::Right => surface.split_right(node, 0.5, vec![tab]),
+                TabSplit::Down => surface.split_below(node, 0.5, vec![tab]),
+            };
+        }
+    }
+
     // last_export_folder probably should be wrapped in another object but I don't have a good object
     // to wrap it in, so it's here for now
     pub fn new(
@@ -663,17 +1331,16 @@ impl ProjectEditor {
         for ignored_word in ignored_words.into_iter() {
             dictionary_state.add_ignored(ignored_word.as_ref());
         }
+        dictionary_state.ignore_patterns =
+            IgnorePatterns::new(&project.metadata.spellcheck_ignore_patterns);
 
-        let open_tabs = open_tab_ids
-            .iter()
-            .map(|tab_id| Page::from_id(tab_id).open(true))
-            .collect();
+        let dock_state = dock_state_from_tab_ids(&project, &open_tab_ids);
 
         let references = References::new(&project);
 
         let mut project_editor = Self {
             project,
-            dock_state: DockState::new(open_tabs),
+            dock_state,
             editor_context: EditorContext {
                 settings,
                 dictionary_state,
@@ -685,10 +1352,25 @@ impl ProjectEditor {
                 references,
                 last_export_folder,
                 version: 0,
+                focus_name: None,
+                focus_body_end: None,
+                focused_text_box: None,
+                go_to_text: None,
+                words_at_open: HashMap::new(),
+                word_count_version: 0,
+                read_only: false,
             },
             tracker,
             tree_state: Default::default(),
+            tree_sort_modes: HashMap::new(),
+            tree_rename: None,
             current_open_tab: None,
+            word_goal_tracker: Default::default(),
+            story_words: None,
+            pomodoro: Default::default(),
+            toast: None,
+            quick_capture: None,
+            pending_delete: None,
         };
 
         project_editor.update_spellcheck_file_object_names();
@@ -717,6 +1399,11 @@ impl ProjectEditor {
         }
     }
 
+    /// The ids of the open tabs in the order they're shown, for reopening them next time
+    pub fn open_tab_ids(&self) -> Vec<String> {
+        tab_ids(&self.dock_state)
+    }
+
     pub fn get_open_tabs(&self) -> Vec<OpenPage> {
         // the indexes provided to use are meaningless (I think), just put all the tabs in the
         // order it gave us.
@@ -726,11 +1413,133 @@ impl ProjectEditor {
             .collect()
     }
 
+    /// Count the story's words again the next time they're shown. Counting walks the whole tree,
+    /// so it's only done this often instead of every frame
+    pub fn recount_story_words(&mut self) {
+        self.story_words = None;
+        self.editor_context.word_count_version += 1;
+    }
+
     /// Process any queued events and then do the actual save
     pub fn save(&mut self) {
         self.project.process_updates();
-        if let Err(err) = self.project.save() {
-            log::error!("encountered error while saving project: {err}");
+        match self.project.save() {
+            Ok(()) => {
+                if self.editor_context.read_only {
+                    self.editor_context.read_only = false;
+                    self.notify(
+                        "Project saved",
+                        "The project was saved, so editing is turned back on".to_string(),
+                    );
+                }
+            }
+            Err(err) if err.is_read_only() => {
+                log::warn!("project is read-only, changes not saved: {err}");
+                if !self.editor_context.read_only {
+                    self.editor_context.read_only = true;
+                    self.notify(
+                        "Project is read-only",
+                        "The project is read-only, so changes were not saved. Editing is turned \
+                        off until it can be saved again"
+                            .to_string(),
+                    );
+                }
+            }
+            Err(err) => log::error!("encountered error while saving project: {err}"),
         }
     }
 }
+
+fn tab_ids(dock_state: &DockState<OpenPage>) -> Vec<String> {
+    // Splits aren't saved, so the tabs from every pane are reopened together, pane by pane
+    dock_state
+        .iter_all_tabs()
+        .map(|(_, tab)| tab.page.get_id().to_owned())
+        .collect()
+}
+
+/// Reopen tabs from their ids (in the same order), skipping any objects that don't exist anymore
+fn dock_state_from_tab_ids(project: &Project, open_tab_ids: &[String]) -> DockState<OpenPage> {
+    let mut open_tabs: Vec<OpenPage> = Vec::new();
+
+    for page in open_tab_ids.iter().map(|tab_id| Page::from_id(tab_id)) {
+        let exists = match &page {
+            Page::FileObject(file_id) => project.objects.contains_key(file_id),
+            _ => true,
+        };
+
+        if exists && !open_tabs.iter().any(|open_tab| open_tab.page == page) {
+            open_tabs.push(page.open(true));
+        }
+    }
+
+    DockState::new(open_tabs)
+}
+
+#[test]
+fn test_tab_order_round_trip() {
+    use crate::schemas::export_file_types::SCENE;
+
+    let base_dir = tempfile::TempDir::new().unwrap();
+    let mut project = Project::new(
+        &crate::schemas::DEFAULT_SCHEMA,
+        base_dir.path().to_path_buf(),
+        "test project".to_string(),
+    )
+    .unwrap();
+
+    let mut scene_ids = Vec::new();
+    for _ in 0..2 {
+        let scene = project
+            .get_text_folder()
+            .borrow_mut()
+            .create_child_at_end(SCENE)
+            .unwrap();
+        scene_ids.push(scene.id().to_string());
+        project.add_object(scene);
+    }
+
+    let saved_ids = vec![
+        scene_ids[0].clone(),
+        "export".to_string(),
+        scene_ids[1].clone(),
+        "settings".to_string(),
+        "deleted-object".to_string(),
+        scene_ids[0].clone(),
+    ];
+
+    let mut dock_state = dock_state_from_tab_ids(&project, &saved_ids);
+    assert_eq!(
+        tab_ids(&dock_state),
+        vec![
+            scene_ids[0].clone(),
+            "export".to_string(),
+            scene_ids[1].clone(),
+            "settings".to_string()
+        ]
+    );
+
+    // Move the first tab to the end, like dragging it would
+    let first_tab = dock_state
+        .find_tab_from(|tab| tab.page.get_id() == scene_ids[0])
+        .unwrap();
+    let tab = dock_state.remove_tab(first_tab).unwrap();
+    dock_state.push_to_first_leaf(tab);
+
+    let reordered_ids = tab_ids(&dock_state);
+    assert_eq!(
+        reordered_ids,
+        vec![
+            "export".to_string(),
+            scene_ids[1].clone(),
+            "settings".to_string(),
+            scene_ids[0].clone()
+        ]
+    );
+
+    // and the new order is what gets reopened
+    assert_eq!(
+        tab_ids(&dock_state_from_tab_ids(&project, &reordered_ids)),
+        reordered_ids
+    );
+}
@@ -0,0 +1,111 @@
+use super::ProjectEditor;
+
+use crate::components::file_objects::utils::{DiffLine, diff_lines};
+use crate::ui::prelude::*;
+
+use egui::RichText;
+
+enum Resolution {
+    KeepLocal,
+    TakeDisk,
+}
+
+/// Ask what to do about an object that changed on disk while it had unsaved changes. Only one
+/// conflict is shown at a time, the next one shows up once it's resolved
+pub fn ui(editor: &mut ProjectEditor, ctx: &egui::Context) {
+    let Some(file_id) = editor.project.conflicted_objects().into_iter().next() else {
+        return;
+    };
+    let Some(object) = editor.project.objects.get(&file_id) else {
+        return;
+    };
+
+    let mut resolution = None;
+
+    egui::Modal::new(egui::Id::new("disk conflict")).show(ctx, |ui| {
+        let object = object.borrow();
+        let Some(conflict) = &object.get_base().file.disk_conflict else {
+            return;
+        };
+
+        ui.heading("Changed on Disk");
+        ui.label(format!(
+            "\"{}\" was changed outside of Cheese Paper while it had unsaved changes.",
+            object.get_title()
+        ));
+
+        egui::CollapsingHeader::new("View Diff").show(ui, |ui| {
+            // Objects without a body can only differ in their metadata
+            let (disk, local) = if object.has_body() {
+                (conflict.body.clone().unwrap_or_default(), object.get_body())
+            } else {
+                (
+                    conflict.metadata.clone(),
+                    object.get_base().toml_header.to_string(),
+                )
+            };
+
+            ui.weak("- only on disk, + only in Cheese Paper");
+
+            egui::ScrollArea::vertical()
+                .id_salt("conflict diff")
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    for line in diff_lines(&disk, &local) {
+                        match line {
+                            DiffLine::Same(line) => {
+                                ui.label(RichText::new(format!("  {line}")).monospace());
+                            }
+                            DiffLine::Removed(line) => {
+                                ui.label(
+                                    RichText::new(format!("- {line}"))
+                                        .monospace()
+                                        .color(ui.visuals().error_fg_color),
+                                );
+                            }
+                            DiffLine::Added(line) => {
+                                ui.label(
+                                    RichText::new(format!("+ {line}"))
+                                        .monospace()
+                                        .color(ui.visuals().warn_fg_color),
+                                );
+                            }
+                        }
+                    }
+                });
+        });
+
+        egui::Sides::new().show(
+            ui,
+            |_ui| {},
+            |ui| {
+                if ui
+                    .button("Take Disk")
+                    .on_hover_text("Throw away the unsaved changes and load the file from disk")
+                    .clicked()
+                {
+                    resolution = Some(Resolution::TakeDisk);
+                }
+                if ui
+                    .button("Keep Mine")
+                    .on_hover_text("Overwrite the file on disk with the unsaved changes")
+                    .clicked()
+                {
+                    resolution = Some(Resolution::KeepLocal);
+                }
+            },
+        );
+    });
+
+    match resolution {
+        Some(Resolution::KeepLocal) => object.borrow_mut().keep_local_version(),
+        Some(Resolution::TakeDisk) => {
+            if let Err(err) = object.borrow_mut().take_disk_version() {
+                log::error!("Failed to reload {file_id} from disk: {err}");
+            }
+            // the text boxes need to pick up the new contents
+            editor.editor_context.version += 1;
+        }
+        None => {}
+    }
+}
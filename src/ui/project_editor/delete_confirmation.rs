@@ -0,0 +1,99 @@
+use super::ProjectEditor;
+
+use crate::ui::prelude::*;
+
+use egui::Id;
+
+/// An object that's waiting for the user to confirm that it should be deleted
+#[derive(Debug)]
+pub struct PendingDelete {
+    parent: FileID,
+    deleting: FileID,
+    dont_ask_again: bool,
+}
+
+/// Delete an object from the tree, asking first unless that's been turned off in the settings
+pub fn request_delete(editor: &mut ProjectEditor, parent: FileID, deleting: FileID) {
+    if editor.editor_context.settings.confirm_delete() {
+        editor.pending_delete = Some(PendingDelete {
+            parent,
+            deleting,
+            dont_ask_again: false,
+        });
+    } else {
+        delete(editor, &parent, &deleting);
+    }
+}
+
+fn delete(editor: &mut ProjectEditor, parent: &FileID, deleting: &FileID) {
+    // Delete the actual file object (removes from other objects and file on disk)
+    if let Err(err) = <dyn FileObject>::remove_child(deleting, parent, &mut editor.project.objects)
+    {
+        log::error!("Encountered error while trying to delete element: {deleting:?}: {err}");
+    }
+}
+
+/// Ask whether the pending object should really be deleted, if there is one
+pub fn ui(editor: &mut ProjectEditor, ctx: &egui::Context) {
+    let Some(pending) = &editor.pending_delete else {
+        return;
+    };
+
+    // The object could have been removed some other way (e.g., on disk) while we were asking
+    let Some((title, descendants)) = editor.project.objects.get(&pending.deleting).map(|object| {
+        let object = object.borrow();
+        (
+            object.get_title(),
+            object.descendant_count(&editor.project.objects),
+        )
+    }) else {
+        editor.pending_delete = None;
+        return;
+    };
+
+    let mut dont_ask_again = pending.dont_ask_again;
+    let mut confirm = false;
+    let mut cancel = false;
+
+    let modal = egui::Modal::new(Id::new("confirm delete")).show(ctx, |ui| {
+        ui.heading(format!("Delete \"{title}\"?"));
+
+        match descendants {
+            0 => ui.label("This can't be undone."),
+            1 => ui.label("The 1 object inside of it will also be deleted. This can't be undone."),
+            _ => ui.label(format!(
+                "The {descendants} objects inside of it will also be deleted. This can't be undone."
+            )),
+        };
+
+        ui.checkbox(&mut dont_ask_again, "Don't ask again")
+            .on_hover_text("This can be turned back on in the settings");
+
+        egui::Sides::new().show(
+            ui,
+            |_ui| {},
+            |ui| {
+                if ui.button("Delete").clicked() {
+                    confirm = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            },
+        );
+    });
+
+    if confirm {
+        if dont_ask_again {
+            editor.editor_context.settings.set_confirm_delete(false);
+        }
+
+        if let Some(pending) = editor.pending_delete.take() {
+            delete(editor, &pending.parent, &pending.deleting);
+        }
+    } else if cancel || modal.should_close() {
+        editor.pending_delete = None;
+    } else if let Some(pending) = &mut editor.pending_delete {
+        pending.dont_ask_again = dont_ask_again;
+    }
+}
@@ -1,13 +1,68 @@
 use super::ProjectEditor;
+use super::delete_confirmation;
 
+use crate::components::file_objects::utils::{add_index_to_name, produces_valid_filename};
 use crate::ui::prelude::*;
 
+use egui::{Key, Modifiers};
 use egui_ltreeview::{Action, DirPosition, NodeBuilder, TreeView};
 
 /// Temporary solution. Point to the schema statically here.
 /// Eventually, a solution for loading the schema when opening the project will be needed
 const SCHEMA: &'static dyn Schema = &crate::schemas::DEFAULT_SCHEMA;
 
+/// How the children of a folder are ordered in the tree. This only changes what's displayed, the
+/// order on disk (and in exports) is always the manual order
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TreeSortMode {
+    #[default]
+    Manual,
+    Name,
+    /// Least recently modified first, so stale objects show up at the top
+    Modified,
+    /// Oldest first
+    Created,
+    /// Shortest first
+    WordCount,
+}
+
+impl TreeSortMode {
+    const ALL: [TreeSortMode; 5] = [
+        TreeSortMode::Manual,
+        TreeSortMode::Name,
+        TreeSortMode::Modified,
+        TreeSortMode::Created,
+        TreeSortMode::WordCount,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            TreeSortMode::Manual => "Manual",
+            TreeSortMode::Name => "Name",
+            TreeSortMode::Modified => "Modified",
+            TreeSortMode::Created => "Created",
+            TreeSortMode::WordCount => "Word Count",
+        }
+    }
+
+    /// Put children in the order they should be displayed in
+    fn sort(&self, children: &mut [&RefCell<Box<dyn FileObject>>]) {
+        match self {
+            TreeSortMode::Manual => {}
+            TreeSortMode::Name => children.sort_by_cached_key(|child| child.borrow().get_title()),
+            TreeSortMode::Modified => {
+                children.sort_by_cached_key(|child| child.borrow().get_base().file.modtime)
+            }
+            TreeSortMode::Created => {
+                children.sort_by_cached_key(|child| child.borrow().get_base().metadata.created)
+            }
+            TreeSortMode::WordCount => {
+                children.sort_by_cached_key(|child| child.borrow().word_count())
+            }
+        }
+    }
+}
+
 /// Context menu actions for file objects, should only be constructed by file objects
 enum ContextMenuActions {
     Delete {
@@ -19,6 +74,20 @@ enum ContextMenuActions {
         position: DirPosition<FileID>,
         file_type: FileType,
     },
+    SetColor {
+        object: FileID,
+        color: Option<[u8; 3]>,
+    },
+    SetSortMode {
+        folder: FileID,
+        sort_mode: TreeSortMode,
+    },
+    Rename {
+        object: FileID,
+    },
+    CopyExport {
+        object: FileID,
+    },
 }
 
 impl dyn FileObject {
@@ -27,8 +96,10 @@ impl dyn FileObject {
         objects: &FileObjectStore,
         builder: &mut egui_ltreeview::TreeViewBuilder<'_, Page>,
         actions: &mut Vec<ContextMenuActions>,
+        sort_modes: &HashMap<FileID, TreeSortMode>,
         parent_id: Option<FileID>,
         node_height: f32,
+        show_indices: bool,
     ) {
         let node_name = if self.get_base().metadata.name.is_empty() {
             self.empty_string_name().to_string()
@@ -36,6 +107,20 @@ impl dyn FileObject {
             self.get_base().metadata.name.clone()
         };
 
+        // Top level folders don't have an index
+        let node_name = match self.get_base().index {
+            Some(index) if show_indices => {
+                add_index_to_name(&node_name, index, self.get_base().file.index_width)
+            }
+            _ => node_name,
+        };
+
+        let node_name = if self.has_conflict_markers() {
+            format!("{node_name} ⚠")
+        } else {
+            node_name
+        };
+
         // first, construct the node. we avoid a lot of duplication by putting it into a variable
         // before sticking it in the nodebuilder
         let base_node_id: Page = self.id().clone().into();
@@ -52,6 +137,9 @@ impl dyn FileObject {
             (parent_id.clone(), DirPosition::After(self.id().clone()))
         };
 
+        let label_color = self.get_base().metadata.label_color;
+        let sort_mode = sort_modes.get(self.id()).copied().unwrap_or_default();
+
         let node = base_node_builder
             .height(node_height)
             .label(node_name)
@@ -69,8 +157,73 @@ impl dyn FileObject {
                     }
                 }
 
+                // Objects like characters are turned into a folder once something is put inside
+                if !self.is_folder() && self.get_type().can_nest() {
+                    let label = format!("New {} Inside", self.type_name());
+                    if ui.button(label).clicked() {
+                        actions.push(ContextMenuActions::Add {
+                            parent: self.id().clone(),
+                            position: DirPosition::Last,
+                            file_type: self.get_type(),
+                        });
+                        ui.close();
+                    }
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Color:");
+                    let mut color = label_color.unwrap_or([128, 128, 128]);
+                    if ui.color_edit_button_srgb(&mut color).changed() {
+                        actions.push(ContextMenuActions::SetColor {
+                            object: self.id().clone(),
+                            color: Some(color),
+                        });
+                    }
+                    if label_color.is_some() && ui.button("Clear").clicked() {
+                        actions.push(ContextMenuActions::SetColor {
+                            object: self.id().clone(),
+                            color: None,
+                        });
+                        ui.close();
+                    }
+                });
+
                 ui.separator();
 
+                if self.is_folder() {
+                    ui.menu_button(format!("Sort: {}", sort_mode.label()), |ui| {
+                        for mode in TreeSortMode::ALL {
+                            if ui.radio(sort_mode == mode, mode.label()).clicked() {
+                                actions.push(ContextMenuActions::SetSortMode {
+                                    folder: self.id().clone(),
+                                    sort_mode: mode,
+                                });
+                                ui.close();
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                }
+
+                if self.compile_status().is_some() && ui.button("Copy Export of Selected").clicked()
+                {
+                    actions.push(ContextMenuActions::CopyExport {
+                        object: self.id().clone(),
+                    });
+                    ui.close();
+                }
+
+                // top level folders are named by the project file, so they can't be renamed here
+                if parent_id.is_some() && ui.button("Rename").clicked() {
+                    actions.push(ContextMenuActions::Rename {
+                        object: self.id().clone(),
+                    });
+                    ui.close();
+                }
+
                 if let Some(parent) = parent_id.clone()
                     && ui.button("Delete").clicked()
                 {
@@ -81,16 +234,34 @@ impl dyn FileObject {
                 }
             });
 
+        // draw a small bar of the label color in front of the name
+        let node = match label_color {
+            Some([r, g, b]) => node.icon(move |ui| {
+                let rect = ui.max_rect();
+                ui.painter().rect_filled(
+                    egui::Rect::from_center_size(rect.center(), egui::vec2(4.0, rect.height())),
+                    1.0,
+                    egui::Color32::from_rgb(r, g, b),
+                );
+            }),
+            None => node,
+        };
+
         builder.node(node);
 
         if self.is_folder() {
-            for child in self.children(objects) {
+            let mut children: Vec<_> = self.children(objects).collect();
+            sort_mode.sort(&mut children);
+
+            for child in children {
                 child.borrow_mut().build_tree(
                     objects,
                     builder,
                     actions,
+                    sort_modes,
                     Some(self.id().clone()),
                     node_height,
+                    show_indices,
                 );
             }
 
@@ -104,7 +275,9 @@ impl Project {
         &mut self,
         builder: &mut egui_ltreeview::TreeViewBuilder<'_, Page>,
         actions: &mut Vec<ContextMenuActions>,
+        sort_modes: &HashMap<FileID, TreeSortMode>,
         node_height: f32,
+        show_indices: bool,
     ) {
         // Add special project metadata to the tree
         builder.node(
@@ -119,8 +292,74 @@ impl Project {
                 .get(top_level_folder)
                 .unwrap()
                 .borrow_mut()
-                .build_tree(&self.objects, builder, actions, None, node_height);
+                .build_tree(
+                    &self.objects,
+                    builder,
+                    actions,
+                    sort_modes,
+                    None,
+                    node_height,
+                    show_indices,
+                );
+        }
+    }
+}
+
+/// Buttons above the tree for opening and closing folders in bulk. These only change what's shown
+pub fn toolbar_ui(editor: &mut ProjectEditor, ui: &mut egui::Ui) {
+    ui.horizontal_wrapped(|ui| {
+        if ui.small_button("Expand All").clicked() {
+            set_all_folders_open(editor, true);
+        }
+        if ui.small_button("Collapse All").clicked() {
+            set_all_folders_open(editor, false);
+        }
+        if ui
+            .small_button("Collapse to Current")
+            .on_hover_text("Collapse everything except the folders containing the open object")
+            .clicked()
+        {
+            collapse_to_current(editor);
         }
+    });
+}
+
+fn set_all_folders_open(editor: &mut ProjectEditor, open: bool) {
+    let folders: Vec<FileID> = editor
+        .project
+        .objects
+        .iter()
+        .filter(|(_, object)| object.borrow().is_folder())
+        .map(|(file_id, _)| file_id.clone())
+        .collect();
+
+    for folder in folders {
+        editor
+            .tree_state
+            .set_openness(Page::FileObject(folder), open);
+    }
+}
+
+fn collapse_to_current(editor: &mut ProjectEditor) {
+    let current = match &editor.current_open_tab {
+        Some(OpenPage {
+            page: Page::FileObject(file_id),
+            ..
+        }) => Some(file_id.clone()),
+        _ => match editor.tree_state.selected().first() {
+            Some(Page::FileObject(file_id)) => Some(file_id.clone()),
+            _ => None,
+        },
+    };
+
+    set_all_folders_open(editor, false);
+
+    let mut ancestor = current.and_then(|file_id| editor.project.find_object_parent(&file_id));
+    while let Some(folder) = ancestor {
+        ancestor = editor.project.find_object_parent(&folder);
+        editor
+            .tree_state
+            .set_openness(Page::FileObject(folder), true);
     }
 }
 
@@ -132,14 +371,19 @@ pub fn ui(editor: &mut ProjectEditor, ui: &mut egui::Ui) {
         .unwrap()
         .size;
     let node_height = (font_size * 1.1).ceil();
+    let show_indices = editor.editor_context.settings.show_tree_indices();
     let mut context_menu_actions: Vec<ContextMenuActions> = Vec::new();
 
     let (_response, actions) = TreeView::new(ui.make_persistent_id("project tree"))
         .allow_multi_selection(false)
         .show_state(ui, &mut editor.tree_state, |builder| {
-            editor
-                .project
-                .build_tree(builder, &mut context_menu_actions, node_height);
+            editor.project.build_tree(
+                builder,
+                &mut context_menu_actions,
+                &editor.tree_sort_modes,
+                node_height,
+                show_indices,
+            );
         });
 
     for action in actions {
@@ -243,17 +487,19 @@ pub fn ui(editor: &mut ProjectEditor, ui: &mut egui::Ui) {
         }
     }
 
+    // F2 renames the selected object, as long as nothing else (like a text box) has focus
+    if editor.tree_rename.is_none()
+        && ui.memory(|mem| mem.focused().is_none())
+        && ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::F2))
+        && let Some(Page::FileObject(file_id)) = editor.tree_state.selected().first().cloned()
+    {
+        start_rename(editor, &file_id);
+    }
+
     for action in context_menu_actions {
         match action {
             ContextMenuActions::Delete { parent, deleting } => {
-                // Delete the actual file object (removes from other objects and file on disk)
-                if let Err(err) =
-                    <dyn FileObject>::remove_child(&deleting, &parent, &mut editor.project.objects)
-                {
-                    log::error!(
-                        "Encountered error while trying to delete element: {deleting:?}: {err}"
-                    );
-                }
+                delete_confirmation::request_delete(editor, parent, deleting);
             }
             ContextMenuActions::Add {
                 parent,
@@ -275,6 +521,112 @@ pub fn ui(editor: &mut ProjectEditor, ui: &mut egui::Ui) {
                     }
                 }
             }
+            ContextMenuActions::SetSortMode { folder, sort_mode } => {
+                if sort_mode == TreeSortMode::Manual {
+                    editor.tree_sort_modes.remove(&folder);
+                } else {
+                    editor.tree_sort_modes.insert(folder, sort_mode);
+                }
+            }
+            ContextMenuActions::SetColor { object, color } => {
+                if let Some(object) = editor.project.objects.get(&object) {
+                    let mut object = object.borrow_mut();
+                    object.get_base_mut().metadata.label_color = color;
+                    object.get_base_mut().file.mark_edited();
+                }
+            }
+            ContextMenuActions::Rename { object } => start_rename(editor, &object),
+            ContextMenuActions::CopyExport { object } => {
+                // Export everything that's selected, as long as the object that was clicked on is
+                // part of that selection
+                let selected: Vec<FileID> = editor
+                    .tree_state
+                    .selected()
+                    .iter()
+                    .filter_map(|page| match page {
+                        Page::FileObject(file_id) => Some(file_id.clone()),
+                        _ => None,
+                    })
+                    .collect();
+
+                let roots = if selected.contains(&object) {
+                    selected
+                } else {
+                    vec![object]
+                };
+
+                let export_options = editor.project.metadata.export.export_options();
+                ui.ctx()
+                    .copy_text(editor.project.export_selected_text(&roots, export_options));
+            }
         }
     }
+
+    rename_ui(editor, ui);
+}
+
+fn start_rename(editor: &mut ProjectEditor, file_id: &FileID) {
+    if editor.project.is_top_level_folder(file_id) {
+        return;
+    }
+
+    if let Some(object) = editor.project.objects.get(file_id) {
+        let name = object.borrow().get_base().metadata.name.clone();
+        editor.tree_rename = Some((file_id.clone(), name));
+    }
+}
+
+/// Draw the rename box if a rename is in progress. The new name is only applied once it's
+/// confirmed, and the file itself gets renamed the next time the object is saved
+fn rename_ui(editor: &mut ProjectEditor, ui: &mut egui::Ui) {
+    let Some((file_id, new_name)) = &mut editor.tree_rename else {
+        return;
+    };
+
+    let mut apply = false;
+    let mut cancel = false;
+
+    let modal = egui::Modal::new(egui::Id::new("rename object")).show(ui.ctx(), |ui| {
+        ui.heading("Rename");
+
+        let response = ui.text_edit_singleline(new_name);
+        if ui.memory(|mem| mem.focused().is_none()) {
+            response.request_focus();
+        }
+
+        let valid = produces_valid_filename(new_name);
+        if !valid {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                "Names need at least one letter or number",
+            );
+        }
+
+        if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+            apply = valid;
+        }
+
+        egui::Sides::new().show(
+            ui,
+            |_ui| {},
+            |ui| {
+                if ui.add_enabled(valid, egui::Button::new("Rename")).clicked() {
+                    apply = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            },
+        );
+    });
+
+    if apply && let Some(object) = editor.project.objects.get(file_id) {
+        let mut object = object.borrow_mut();
+        object.get_base_mut().metadata.name = new_name.trim().to_string();
+        object.get_base_mut().file.mark_edited();
+    }
+
+    if apply || cancel || modal.should_close() {
+        editor.tree_rename = None;
+    }
 }
@@ -0,0 +1,64 @@
+use super::ProjectEditor;
+
+use egui::Id;
+
+/// Offer to convert files with CRLF line endings (e.g., from a collaborator on Windows) to LF.
+/// Nothing is changed without asking, since it rewrites every one of those files
+pub fn ui(editor: &mut ProjectEditor, ctx: &egui::Context) {
+    if editor.project.crlf_files.is_empty() {
+        return;
+    }
+
+    let mut normalize = false;
+    let mut dismiss = false;
+
+    egui::Modal::new(Id::new("crlf line endings")).show(ctx, |ui| {
+        ui.heading("Mixed Line Endings");
+
+        let file_count = editor.project.crlf_files.len();
+        ui.label(format!(
+            "{file_count} {} Windows (CRLF) line endings. Converting them to LF will keep \
+             diffs from changing every line.",
+            if file_count == 1 {
+                "file uses"
+            } else {
+                "files use"
+            }
+        ));
+
+        egui::CollapsingHeader::new("Files").show(ui, |ui| {
+            for file_id in &editor.project.crlf_files {
+                if let Some(object) = editor.project.objects.get(file_id) {
+                    ui.label(object.borrow().get_title());
+                }
+            }
+        });
+
+        egui::Sides::new().show(
+            ui,
+            |_ui| {},
+            |ui| {
+                if ui.button("Convert to LF").clicked() {
+                    normalize = true;
+                }
+                if ui
+                    .button("Leave As Is")
+                    .on_hover_text("Don't change anything (you'll be asked again next time)")
+                    .clicked()
+                {
+                    dismiss = true;
+                }
+            },
+        );
+    });
+
+    if normalize {
+        if let Err(err) = editor.project.normalize_line_endings() {
+            log::error!("Failed to convert line endings: {err}");
+        }
+        // the text boxes need to pick up the new contents
+        editor.editor_context.version += 1;
+    } else if dismiss {
+        editor.project.crlf_files.clear();
+    }
+}
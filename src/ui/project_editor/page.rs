@@ -1,6 +1,10 @@
 mod export_selection;
 pub mod file_object_editor;
+mod goals;
+mod link_check;
 mod project_metadata_editor;
+mod timeline;
+mod unsaved;
 
 use crate::ui::prelude::*;
 
@@ -21,12 +25,20 @@ pub enum Page {
     FileObject(FileID),
     Settings,
     Export,
+    LinkCheck,
+    Timeline,
+    Unsaved,
+    Goals,
 }
 
 impl Page {
     const PROJECT_METADATA_ID: &str = "project_metadata";
     const EXPORT_ID: &str = "export";
     const SETTINGS_ID: &str = "settings";
+    const LINK_CHECK_ID: &str = "link_check";
+    const TIMELINE_ID: &str = "timeline";
+    const UNSAVED_ID: &str = "unsaved";
+    const GOALS_ID: &str = "goals";
 
     /// Get an id from a string. This (and its reverse, `get_id`) could be replaced by `From`
     /// (and `Into`), but this seems like it might be more explicit?
@@ -34,6 +46,11 @@ impl Page {
         match id {
             Self::PROJECT_METADATA_ID => Self::ProjectMetadata,
             Self::EXPORT_ID => Self::Export,
+            Self::SETTINGS_ID => Self::Settings,
+            Self::LINK_CHECK_ID => Self::LinkCheck,
+            Self::TIMELINE_ID => Self::Timeline,
+            Self::UNSAVED_ID => Self::Unsaved,
+            Self::GOALS_ID => Self::Goals,
             _ => Self::FileObject(FileID::new(id.to_owned())),
         }
     }
@@ -43,6 +60,10 @@ impl Page {
             Self::ProjectMetadata => Self::PROJECT_METADATA_ID,
             Self::Export => Self::EXPORT_ID,
             Self::Settings => Self::SETTINGS_ID,
+            Self::LinkCheck => Self::LINK_CHECK_ID,
+            Self::Timeline => Self::TIMELINE_ID,
+            Self::Unsaved => Self::UNSAVED_ID,
+            Self::Goals => Self::GOALS_ID,
             Self::FileObject(id) => id,
         }
     }
@@ -59,6 +80,10 @@ impl Page {
         match self {
             Self::Export => false,
             Self::Settings => false,
+            Self::LinkCheck => false,
+            Self::Timeline => false,
+            Self::Unsaved => false,
+            Self::Goals => false,
             Self::FileObject(_) => true,
             Self::ProjectMetadata => true,
         }
@@ -84,6 +109,17 @@ pub struct PageData {
     last_selected_id: Option<Id>,
 
     settings_page: Option<SettingsPage>,
+
+    link_check: link_check::LinkCheckState,
+
+    export: export_selection::ExportState,
+
+    unsaved: unsaved::UnsavedState,
+
+    goals: goals::GoalsState,
+
+    /// A folder's `(current, target)` words, along with the `word_count_version` they're from
+    word_target: Option<(usize, (u64, u64))>,
 }
 
 pub type Store = RenderDataStore<Page, PageData>;
@@ -118,6 +154,10 @@ impl OpenPage {
             }
             Page::Export => "Export".into(),
             Page::Settings => "Settings".into(),
+            Page::LinkCheck => "Links".into(),
+            Page::Timeline => "Timeline".into(),
+            Page::Unsaved => "Unsaved".into(),
+            Page::Goals => "Goals".into(),
         };
 
         let text = if self.keep { text } else { text.italics() };
@@ -129,7 +169,14 @@ impl OpenPage {
         let rdata = ctx.stores.page.get(&self.page);
         let page_data: &mut PageData = &mut rdata.borrow_mut();
 
-        let focus_shift_option = if ui.input_mut(|i| i.consume_key(Modifiers::SHIFT, Key::Tab)) {
+        // Tab indents in text boxes if that's enabled, so leave it for the text box to handle
+        let text_box_uses_tab = ctx.settings.tab_indents()
+            && ctx.focused_text_box.is_some()
+            && ui.memory(|mem| mem.focused()) == ctx.focused_text_box;
+
+        let focus_shift_option = if text_box_uses_tab {
+            None
+        } else if ui.input_mut(|i| i.consume_key(Modifiers::SHIFT, Key::Tab)) {
             Some(FocusShiftDirection::Previous)
         } else if ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Tab)) {
             Some(FocusShiftDirection::Next)
@@ -148,12 +195,34 @@ impl OpenPage {
             Page::ProjectMetadata => project.metadata_ui(ui, ctx),
             Page::FileObject(file_object_id) => {
                 if let Some(file_object) = project.objects.get(file_object_id) {
+                    // Folders show how close everything inside of them is to its targets, which
+                    // needs the other objects, so it can't be done by the folder itself
+                    if file_object.borrow().is_folder() {
+                        let (words, target) = match page_data.word_target {
+                            Some((version, word_target)) if version == ctx.word_count_version => {
+                                word_target
+                            }
+                            _ => {
+                                let word_target =
+                                    file_object.borrow().aggregate_word_target(&project.objects);
+                                page_data.word_target = Some((ctx.word_count_version, word_target));
+                                word_target
+                            }
+                        };
+                        if target > 0 {
+                            ui.add(
+                                egui::ProgressBar::new(words as f32 / target as f32)
+                                    .text(format!("{words} / {target} words")),
+                            );
+                        }
+                    }
+
                     file_object.borrow_mut().as_editor_mut().ui(ui, ctx)
                 } else {
                     Vec::new()
                 }
             }
-            Page::Export => project.export_ui(ui, ctx),
+            Page::Export => project.export_ui(ui, ctx, &mut page_data.export),
             Page::Settings => {
                 if page_data.settings_page.is_none() {
                     page_data.settings_page = Some(SettingsPage::load(ctx));
@@ -161,6 +230,10 @@ impl OpenPage {
                 let settings_page = page_data.settings_page.as_mut().unwrap();
                 settings_page.ui(ui, ctx)
             }
+            Page::LinkCheck => link_check::ui(ui, project, ctx, &mut page_data.link_check),
+            Page::Timeline => timeline::ui(ui, project, ctx),
+            Page::Unsaved => unsaved::ui(ui, project, ctx, &mut page_data.unsaved),
+            Page::Goals => goals::ui(ui, project, ctx, &mut page_data.goals),
         };
 
         if let Some(focus_shift) = focus_shift_option {
@@ -296,6 +369,11 @@ impl From<Rc<String>> for Page {
         match id.as_str() {
             Self::PROJECT_METADATA_ID => Self::ProjectMetadata,
             Self::EXPORT_ID => Self::Export,
+            Self::SETTINGS_ID => Self::Settings,
+            Self::LINK_CHECK_ID => Self::LinkCheck,
+            Self::TIMELINE_ID => Self::Timeline,
+            Self::UNSAVED_ID => Self::Unsaved,
+            Self::GOALS_ID => Self::Goals,
             _ => Self::FileObject(id),
         }
     }
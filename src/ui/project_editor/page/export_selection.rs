@@ -2,23 +2,147 @@ use egui::Id;
 use egui::Vec2;
 use rfd::FileDialog;
 
-use crate::{
-    components::{
-        file_objects::utils::process_name_for_filename,
-        project::{ExportDepth, ExportOptions},
-    },
-    ui::prelude::*,
-};
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use crate::components::export_command::run_post_command;
+use crate::components::export_split::DEFAULT_SPLIT_FILENAME_TEMPLATE;
+use crate::components::file_objects::utils::{process_name_for_filename, write_with_temp_file};
+use crate::components::project::{ExportJob, ExportSummary, TitleStyle};
+use crate::ui::prelude::*;
+
+/// How long to spend exporting each frame, so the UI stays responsive during big exports
+const EXPORT_FRAME_BUDGET: Duration = Duration::from_millis(15);
+
+/// Where the text goes once an export is done
+#[derive(Debug)]
+enum ExportDestination {
+    File(PathBuf),
+    Clipboard,
+}
+
+/// Which button started an export, kept while the summary is shown
+#[derive(Debug)]
+enum ExportTarget {
+    File,
+    Clipboard,
+    /// each top level object gets its own file, see `Project::export_split`
+    SplitFiles,
+}
+
+/// State of the export page, stored with the rest of the page data
+#[derive(Debug, Default)]
+pub struct ExportState {
+    /// Shown before an export starts, so that missing scenes can be caught
+    confirmation: Option<(ExportSummary, ExportTarget)>,
+
+    /// The export that's currently being compiled, if any
+    job: Option<(ExportJob, ExportDestination)>,
+
+    /// Set while a finished export is being written (and post-processed) on a background thread
+    write_receiver: Option<Receiver<Result<PathBuf, String>>>,
+
+    /// Message about how the last export went, `Err` if it failed
+    status: Option<Result<String, String>>,
+}
+
+impl ExportState {
+    fn is_running(&self) -> bool {
+        self.job.is_some() || self.write_receiver.is_some()
+    }
+
+    fn receive_write_result(&mut self, ui: &Ui) {
+        if let Some(receiver) = &self.write_receiver {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.status =
+                        Some(result.map(|path| format!("Exported to {}", path.display())));
+                    self.write_receiver = None;
+                }
+                Err(TryRecvError::Empty) => {
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                }
+                Err(TryRecvError::Disconnected) => {
+                    self.write_receiver = None;
+                }
+            }
+        }
+    }
+}
 
 //This probably shouldn't be a part of Project but it's easy enough right now
 impl Project {
-    pub fn export_ui(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext) -> Vec<Id> {
+    pub fn export_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &mut EditorContext,
+        state: &mut ExportState,
+    ) -> Vec<Id> {
+        self.run_export(ui, ctx, state);
+
         egui::CentralPanel::default()
-            .show_inside(ui, |ui| self.show_export_selection(ui, ctx))
+            .show_inside(ui, |ui| self.show_export_selection(ui, ctx, state))
             .inner
     }
 
-    fn show_export_selection(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext) -> Vec<Id> {
+    /// Do as much of the current export as fits in a frame, and hand it off once it's done
+    fn run_export(&self, ui: &Ui, ctx: &EditorContext, state: &mut ExportState) {
+        state.receive_write_result(ui);
+
+        let Some((export_job, _destination)) = &mut state.job else {
+            return;
+        };
+
+        let start = Instant::now();
+        while !export_job.is_done() && start.elapsed() < EXPORT_FRAME_BUDGET {
+            self.continue_export(export_job);
+        }
+
+        if !export_job.is_done() {
+            ui.ctx().request_repaint();
+            return;
+        }
+
+        let (export_job, destination) = state.job.take().unwrap();
+        let export_contents = export_job.finish();
+
+        match destination {
+            ExportDestination::Clipboard => {
+                ui.ctx().copy_text(export_contents);
+                state.status = Some(Ok("Copied story text to the clipboard".to_string()));
+            }
+            ExportDestination::File(export_location) => {
+                let post_command = ctx.settings.export_post_command();
+
+                // Written to a temporary file first, so there's never a partial export on disk
+                let (tx, rx) = std::sync::mpsc::channel();
+                std::thread::spawn(move || {
+                    let result = write_with_temp_file(&export_location, export_contents)
+                        .map_err(|err| format!("Failed to write export: {err}"))
+                        .and_then(|()| match post_command {
+                            Some(post_command) => run_post_command(&post_command, &export_location)
+                                .map_err(|err| format!("Export post-processing failed: {err}")),
+                            None => Ok(()),
+                        })
+                        .map(|()| export_location);
+
+                    if let Err(err) = &result {
+                        log::error!("{err}");
+                    }
+                    let _ = tx.send(result);
+                });
+                state.write_receiver = Some(rx);
+            }
+        }
+    }
+
+    fn show_export_selection(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &mut EditorContext,
+        state: &mut ExportState,
+    ) -> Vec<Id> {
         let mut ids = Vec::new();
         ui.label("Project Export Selection");
 
@@ -94,6 +218,30 @@ impl Project {
                 });
                 ui.end_row();
 
+                ui.label("Folder Title Style");
+                let folder_title_style = title_style_combo_box(
+                    ui,
+                    "folder title style",
+                    &mut self.metadata.export.folder_title_style,
+                );
+                ids.push(folder_title_style.response.id);
+                if folder_title_style.inner == Some(true) {
+                    self.file.modified = true;
+                }
+                ui.end_row();
+
+                ui.label("Scene Title Style");
+                let scene_title_style = title_style_combo_box(
+                    ui,
+                    "scene title style",
+                    &mut self.metadata.export.scene_title_style,
+                );
+                ids.push(scene_title_style.response.id);
+                if scene_title_style.inner == Some(true) {
+                    self.file.modified = true;
+                }
+                ui.end_row();
+
                 let response = ui.checkbox(
                     &mut self.metadata.export.insert_break_at_end,
                     "Insert break between consecutive scenes",
@@ -101,58 +249,329 @@ impl Project {
                     not set, two consecutive scenes will only have a newline in the final export");
                 self.process_response(&response);
                 ids.push(response.id);
+                ui.end_row();
+
+                ui.label("Heading Offset  ℹ").on_hover_text(
+                    "Added to the level of every heading in the export. For example, 1 turns \
+                    top level titles into `##` headings, for pasting under an existing title",
+                );
+                let response = ui.add(
+                    egui::DragValue::new(&mut self.metadata.export.heading_offset).range(0..=5),
+                );
+                self.process_response(&response);
+                ids.push(response.id);
+                ui.end_row();
+
+                let response = ui.checkbox(
+                    &mut self.metadata.export.preserve_hard_breaks,
+                    "Keep line breaks in scenes",
+                ).on_hover_text("If checked, every line break in a scene is kept (for poetry or verse) \
+                    instead of lines being joined into paragraphs. Scenes can override this in \
+                    their export settings");
+                self.process_response(&response);
+                ids.push(response.id);
+                ui.end_row();
+
+                let response = ui.checkbox(
+                    &mut self.metadata.export.scene_metadata_comments,
+                    "Add scene comments",
+                ).on_hover_text("If checked, put an html comment with the id, name, and index of \
+                    each scene before its text, so that other tools can tell where it came from");
+                self.process_response(&response);
+                ids.push(response.id);
+                ui.end_row();
+
+                let response = ui.checkbox(
+                    &mut self.metadata.export.append_word_count,
+                    "Append word count",
+                ).on_hover_text("If checked, end the export with the total number of words that \
+                    were exported (not counting titles or excluded scenes)");
+                self.process_response(&response);
+                ids.push(response.id);
+                ui.end_row();
+
+                let response = ui.checkbox(
+                    &mut self.metadata.export.strip_markdown,
+                    "Remove formatting",
+                ).on_hover_text("If checked, remove markdown formatting (emphasis, heading \
+                    markers, and link syntax) from scenes, for a plain text export");
+                self.process_response(&response);
+                ids.push(response.id);
+                ui.end_row();
+
+                let response = ui.checkbox(
+                    &mut self.metadata.export.append_glossary,
+                    "Append glossary",
+                ).on_hover_text("If checked, add a list of the characters and places (with their \
+                    summaries) after the story");
+                self.process_response(&response);
+                ids.push(response.id);
+                ui.end_row();
+
+                ui.label("Chapter Filenames  ℹ").on_hover_text(
+                    "How files are named when exporting chapters separately. {index} is the \
+                    position of the chapter (starting at 0), {number} starts at 1, and {name} is \
+                    its title. Add a width to pad with zeros, like {index:03}",
+                );
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.metadata.export.split_filename_template)
+                        .hint_text(DEFAULT_SPLIT_FILENAME_TEMPLATE),
+                );
+                self.process_response(&response);
+                ids.push(response.id);
             });
 
         ui.add_space(40.0);
 
+        if state.is_running() {
+            self.show_export_progress(ui, state, &mut ids);
+        } else {
+            self.show_export_buttons(ui, state, &mut ids);
+        }
+
+        self.show_export_confirmation(ui, ctx, state);
+
+        match &state.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(message)) => {
+                ui.colored_label(ui.visuals().error_fg_color, message);
+            }
+            None => {}
+        }
+
+        ids
+    }
+
+    fn show_export_buttons(&self, ui: &mut egui::Ui, state: &mut ExportState, ids: &mut Vec<Id>) {
         let export_story_button_response = ui.button("Export Story Text");
 
         if export_story_button_response.clicked() {
-            let project_title = &self.base_metadata.name;
-            let suggested_title = format!("{}.md", process_name_for_filename(project_title));
-            let export_location_option = FileDialog::new()
-                .set_title(format!("Export {project_title}"))
-                .set_directory(&ctx.last_export_folder)
-                .set_file_name(suggested_title)
-                .save_file();
-
-            let folder_title_depth = if self.metadata.export.include_all_folder_titles {
-                ExportDepth::All
-            } else if self.metadata.export.include_folder_title_depth == 0 {
-                ExportDepth::None
-            } else {
-                ExportDepth::Some(self.metadata.export.include_folder_title_depth)
-            };
-
-            let scene_title_depth = if self.metadata.export.include_all_scene_titles {
-                ExportDepth::All
-            } else if self.metadata.export.include_scene_title_depth == 0 {
-                ExportDepth::None
-            } else {
-                ExportDepth::Some(self.metadata.export.include_scene_title_depth)
-            };
-
-            let export_options = ExportOptions {
-                folder_title_depth,
-                scene_title_depth,
-                insert_breaks: self.metadata.export.insert_break_at_end,
-            };
-
-            if let Some(export_location) = export_location_option {
-                let export_contents = self.export_text(export_options);
-                if let Err(err) = std::fs::write(&export_location, export_contents) {
-                    log::error!("Error while attempting to write outline: {err}");
+            state.confirmation = Some((self.export_summary(), ExportTarget::File));
+        }
+
+        ids.push(export_story_button_response.id);
+
+        // Not every export needs a file (e.g., pasting into a submission form)
+        let copy_button_response = ui.button("Copy Story Text to Clipboard");
+
+        if copy_button_response.clicked() {
+            state.confirmation = Some((self.export_summary(), ExportTarget::Clipboard));
+        }
+
+        ids.push(copy_button_response.id);
+
+        let split_button_response = ui.button("Export Chapters to Separate Files");
+
+        if split_button_response.clicked() {
+            state.confirmation = Some((self.export_summary(), ExportTarget::SplitFiles));
+        }
+
+        ids.push(split_button_response.id);
+    }
+
+    /// Show what's about to be exported, starting the export if it's confirmed
+    fn show_export_confirmation(
+        &self,
+        ui: &mut egui::Ui,
+        ctx: &mut EditorContext,
+        state: &mut ExportState,
+    ) {
+        let Some((summary, target)) = &state.confirmation else {
+            return;
+        };
+
+        let mut confirmed = None;
+
+        egui::Modal::new(Id::new("export summary")).show(ui.ctx(), |ui| {
+            ui.heading("Export Summary");
+            ui.label(summary.to_string());
+            ui.label(summary.prose_details());
+
+            let excluded = summary.scenes_total - summary.scenes_included;
+            if excluded > 0 {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "{excluded} {} excluded from the export",
+                        if excluded == 1 {
+                            "scene is"
+                        } else {
+                            "scenes are"
+                        }
+                    ),
+                );
+            }
+
+            let empty_count = summary.empty_scenes.len();
+            if empty_count > 0 {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "{empty_count} included {} nothing to export",
+                        if empty_count == 1 {
+                            "scene has"
+                        } else {
+                            "scenes have"
+                        }
+                    ),
+                );
+                egui::CollapsingHeader::new("Empty Scenes")
+                    .default_open(empty_count <= 5)
+                    .show(ui, |ui| {
+                        for title in &summary.empty_scenes {
+                            ui.label(title);
+                        }
+                    });
+            }
+
+            egui::Sides::new().show(
+                ui,
+                |_ui| {},
+                |ui| {
+                    if ui.button("Cancel").clicked() {
+                        confirmed = Some(false);
+                    }
+                    let confirm_text = match (target, empty_count > 0) {
+                        (ExportTarget::File | ExportTarget::SplitFiles, false) => "Export",
+                        (ExportTarget::File | ExportTarget::SplitFiles, true) => "Export Anyway",
+                        (ExportTarget::Clipboard, false) => "Copy",
+                        (ExportTarget::Clipboard, true) => "Copy Anyway",
+                    };
+                    if ui.button(confirm_text).clicked() {
+                        confirmed = Some(true);
+                    }
+                },
+            );
+        });
+
+        match confirmed {
+            Some(true) => {
+                let (_summary, target) = state.confirmation.take().unwrap();
+                match target {
+                    ExportTarget::File => self.start_file_export(ctx, state),
+                    ExportTarget::SplitFiles => self.split_export(ctx, state),
+                    ExportTarget::Clipboard => {
+                        state.status = None;
+                        state.job = Some((
+                            self.start_export(self.metadata.export.export_options()),
+                            ExportDestination::Clipboard,
+                        ));
+                    }
                 }
+            }
+            Some(false) => state.confirmation = None,
+            None => {}
+        }
+    }
+
+    fn start_file_export(&self, ctx: &mut EditorContext, state: &mut ExportState) {
+        let project_title = &self.base_metadata.name;
+        let suggested_title = format!("{}.md", process_name_for_filename(project_title));
+        let export_location_option = FileDialog::new()
+            .set_title(format!("Export {project_title}"))
+            .set_directory(&ctx.last_export_folder)
+            .set_file_name(suggested_title)
+            .save_file();
 
-                ctx.last_export_folder = export_location
-                    .parent()
-                    .map(|val| val.to_path_buf())
-                    .unwrap_or_default();
+        let export_options = self.metadata.export.export_options();
+
+        if let Some(export_location) = export_location_option {
+            ctx.last_export_folder = export_location
+                .parent()
+                .map(|val| val.to_path_buf())
+                .unwrap_or_default();
+
+            state.status = None;
+            state.job = Some((
+                self.start_export(export_options),
+                ExportDestination::File(export_location),
+            ));
+        }
+    }
+
+    /// Export every chapter to its own file. This isn't done a piece at a time like other exports,
+    /// since each chapter is only a small part of the story
+    fn split_export(&self, ctx: &mut EditorContext, state: &mut ExportState) {
+        let Some(export_dir) = FileDialog::new()
+            .set_title(format!("Export {} by Chapter", self.base_metadata.name))
+            .set_directory(&ctx.last_export_folder)
+            .pick_folder()
+        else {
+            return;
+        };
+
+        state.status = Some(match self.export_split(&export_dir) {
+            Ok(written) => Ok(format!(
+                "Exported {} files to {}",
+                written.len(),
+                export_dir.display()
+            )),
+            Err(err) => {
+                log::error!("Failed to export chapters: {err}");
+                Err(format!("Failed to export chapters: {err}"))
+            }
+        });
+
+        ctx.last_export_folder = export_dir;
+    }
+
+    fn show_export_progress(&self, ui: &mut egui::Ui, state: &mut ExportState, ids: &mut Vec<Id>) {
+        match state
+            .job
+            .as_ref()
+            .map(|(export_job, _)| export_job.progress())
+        {
+            Some(progress) => {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::ProgressBar::new(progress)
+                            .show_percentage()
+                            .desired_width(300.0),
+                    );
+
+                    let cancel_response = ui.button("Cancel");
+                    if cancel_response.clicked() {
+                        // nothing has been written yet, so there's nothing to clean up
+                        state.job = None;
+                        state.status = Some(Ok("Export cancelled".to_string()));
+                    }
+                    ids.push(cancel_response.id);
+                });
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Writing export");
+                });
             }
         }
+    }
+}
 
-        ids.push(export_story_button_response.id);
+/// A dropdown for choosing how titles are written, whose inner value is whether it was changed
+fn title_style_combo_box(
+    ui: &mut Ui,
+    id_salt: &str,
+    title_style: &mut TitleStyle,
+) -> egui::InnerResponse<Option<bool>> {
+    let original_style = *title_style;
 
-        ids
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(title_style_name(*title_style))
+        .show_ui(ui, |ui| {
+            for style in TitleStyle::ALL {
+                ui.selectable_value(title_style, style, title_style_name(style));
+            }
+            *title_style != original_style
+        })
+}
+
+fn title_style_name(title_style: TitleStyle) -> &'static str {
+    match title_style {
+        TitleStyle::Heading => "Heading",
+        TitleStyle::Bold => "Bold",
+        TitleStyle::None => "None",
     }
 }
@@ -1,5 +1,9 @@
+use crate::components::file_objects::utils::IMAGE_EXTENSIONS;
+use crate::components::file_objects::{ObjectReference, Relationship};
 use crate::ui::prelude::*;
 
+use rfd::FileDialog;
+
 pub trait FileObjectEditor: FileObject {
     fn ui<'a>(&'a mut self, ui: &'a mut egui::Ui, ctx: &'a mut EditorContext) -> Vec<egui::Id>;
 
@@ -7,7 +11,6 @@ pub trait FileObjectEditor: FileObject {
     // note to Brie: in any other situation please use `impl FnMut` and not `&mut dyn FnMut``
     fn for_each_textbox<'a>(&'a self, f: &mut dyn FnMut(&Text, &'static str));
 
-    #[allow(dead_code)] // included for the sake of completeness
     fn for_each_textbox_mut<'a>(&'a mut self, f: &mut dyn FnMut(&mut Text, &'static str));
 
     /// provide a list of words that this fileobject wants to automatically add to the spellcheck dictionary
@@ -17,7 +20,153 @@ pub trait FileObjectEditor: FileObject {
 
     fn process_response(&mut self, response: &egui::Response) {
         if response.changed() {
-            self.get_base_mut().file.modified = true;
+            self.get_base_mut().file.mark_edited();
+        }
+    }
+}
+
+/// Shows an editor for an object's word target, where 0 means that it doesn't have one. `speed`
+/// is how quickly dragging changes the target (folders have bigger targets than scenes)
+pub fn word_target_ui(
+    ui: &mut egui::Ui,
+    target_word_count: &mut Option<u64>,
+    speed: f64,
+) -> egui::Response {
+    ui.horizontal(|ui| {
+        ui.label("Word Target (0 for none)");
+        let mut target = target_word_count.unwrap_or(0);
+        let response = ui.add(egui::DragValue::new(&mut target).speed(speed));
+        if response.changed() {
+            *target_word_count = (target > 0).then_some(target);
+        }
+        response
+    })
+    .inner
+}
+
+/// Shows an editable list of relationships to other objects of `file_type` (other than the object
+/// with `own_id`), pushing the ids of its widgets onto `ids`. Returns whether anything changed
+pub fn relationships_ui(
+    ui: &mut egui::Ui,
+    ctx: &mut EditorContext,
+    relationships: &mut Vec<Relationship>,
+    own_id: &FileID,
+    file_type: FileType,
+    ids: &mut Vec<egui::Id>,
+) -> bool {
+    let mut changed = false;
+    let mut remove_index = None;
+
+    for (index, relationship) in relationships.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut relationship.relation)
+                    .id_salt(("relation", index))
+                    .hint_text("sibling of")
+                    .desired_width(100.0),
+            );
+            changed |= response.changed();
+            ids.push(response.id);
+
+            let targets = ctx.references.for_type(file_type);
+            let mut target = relationship.target.clone();
+            let combo_box = egui::ComboBox::from_id_salt(("relationship target", index))
+                .selected_text(match &target {
+                    ObjectReference::Known(file_id) => targets
+                        .get(file_id)
+                        .cloned()
+                        .unwrap_or_else(|| format!("Ref: {file_id}")),
+                    ObjectReference::Unknown(unknown_reference) => {
+                        format!("Ref: {}|{}", unknown_reference.name, unknown_reference.id)
+                    }
+                    ObjectReference::None => "None".to_string(),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut target, ObjectReference::None, "None");
+                    for (target_id, name) in targets.iter() {
+                        if target_id != own_id {
+                            ui.selectable_value(
+                                &mut target,
+                                ObjectReference::Known(target_id.clone()),
+                                name,
+                            );
+                        }
+                    }
+                });
+            ids.push(combo_box.response.id);
+
+            // We don't have an actual response here so we have to manually process
+            if target != relationship.target {
+                relationship.target = target;
+                changed = true;
+            }
+
+            if let ObjectReference::Known(file_id) = &relationship.target
+                && targets.contains_key(file_id)
+                && ui.link("Open").clicked()
+            {
+                let page = Page::FileObject(file_id.clone());
+                ctx.actions.schedule(move |project_editor, _ctx| {
+                    project_editor.set_editor_tab(&page, true)
+                });
+            }
+
+            if ui.button("Remove").clicked() {
+                remove_index = Some(index);
+            }
+        });
+    }
+
+    if let Some(index) = remove_index {
+        relationships.remove(index);
+        changed = true;
+    }
+
+    let response = ui.button("Add Relationship");
+    if response.clicked() {
+        relationships.push(Relationship::default());
+        changed = true;
+    }
+    ids.push(response.id);
+
+    changed
+}
+
+/// Shows the image attached to a (folder-based) file object, along with a button to set or
+/// replace it
+pub fn image_ui(ui: &mut egui::Ui, file_object: &mut dyn FileObject) {
+    let image = file_object.find_image();
+
+    if let Some(image_path) = &image {
+        ui.add(
+            egui::Image::new(format!("file://{}", image_path.display()))
+                .max_width(ui.available_width())
+                .maintain_aspect_ratio(true),
+        );
+    }
+
+    let button_text = match image {
+        Some(_) => "Change image…",
+        None => "Set image…",
+    };
+
+    if ui.button(button_text).clicked()
+        && let Some(source) = FileDialog::new()
+            .set_title("Choose an image")
+            .add_filter("Images", IMAGE_EXTENSIONS)
+            .pick_file()
+    {
+        match file_object.set_image(&source) {
+            Ok(new_image) => {
+                // egui caches images by uri, so make sure that we don't keep showing the old one
+                if let Some(old_image) = &image {
+                    ui.ctx()
+                        .forget_image(&format!("file://{}", old_image.display()));
+                }
+                ui.ctx()
+                    .forget_image(&format!("file://{}", new_image.display()));
+            }
+            Err(err) => log::error!("Could not set image for {file_object}: {err}"),
         }
     }
 }
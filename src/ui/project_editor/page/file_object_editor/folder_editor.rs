@@ -147,7 +147,7 @@ impl Folder {
                             self.metadata
                                 .compile_status
                                 .set_include_title(include_title);
-                            self.get_base_mut().file.modified = true;
+                            self.get_base_mut().file.mark_edited();
                         }
 
                         // same thing but for the break
@@ -191,7 +191,7 @@ impl Folder {
                         // We don't have an actual response here so we have to manually process
                         if break_at_end != break_at_end_before {
                             self.metadata.compile_status.set_break_at_end(break_at_end);
-                            self.get_base_mut().file.modified = true;
+                            self.get_base_mut().file.mark_edited();
                         }
                     });
                 }
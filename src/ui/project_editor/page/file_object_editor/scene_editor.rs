@@ -173,7 +173,7 @@ impl Scene {
         };
 
         if changed {
-            self.get_base_mut().file.modified = true;
+            self.get_base_mut().file.mark_edited();
         }
 
         // half of the available height should go to each widget
@@ -258,7 +258,7 @@ impl Scene {
                 self.metadata
                     .compile_status
                     .set_include_title(include_title);
-                self.get_base_mut().file.modified = true;
+                self.get_base_mut().file.mark_edited();
             }
 
             // same thing but for the break
@@ -291,7 +291,7 @@ impl Scene {
             // We don't have an actual response here so we have to manually process
             if break_at_end != break_at_end_before {
                 self.metadata.compile_status.set_break_at_end(break_at_end);
-                self.get_base_mut().file.modified = true;
+                self.get_base_mut().file.mark_edited();
             }
         });
 
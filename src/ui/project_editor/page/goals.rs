@@ -0,0 +1,76 @@
+use crate::ui::prelude::*;
+
+use egui::Id;
+
+/// The story's words and the progress of every folder with a target, along with the
+/// `word_count_version` they were counted at
+#[derive(Debug, Default)]
+pub struct GoalsState {
+    counted: Option<(usize, u64, Vec<(FileID, u64, u64)>)>,
+}
+
+/// Progress toward the word goal of the whole story and each folder that has its own target
+pub fn ui(
+    ui: &mut Ui,
+    project: &Project,
+    ctx: &mut EditorContext,
+    state: &mut GoalsState,
+) -> Vec<Id> {
+    if state
+        .counted
+        .as_ref()
+        .is_none_or(|(version, _, _)| *version != ctx.word_count_version)
+    {
+        let (words, targets) = project.story_word_targets();
+        state.counted = Some((ctx.word_count_version, words, targets));
+    }
+    let (_, words, targets) = state.counted.as_ref().unwrap();
+    let words = *words;
+
+    egui::CentralPanel::default().show_inside(ui, |ui| {
+        if let Some(word_goal) = project.metadata.word_goal {
+            ui.label("Story");
+            ui.add(
+                egui::ProgressBar::new(words as f32 / word_goal as f32)
+                    .text(format!("{words} / {word_goal} words")),
+            );
+            ui.separator();
+        }
+
+        if targets.is_empty() {
+            ui.weak("Set a word target on a folder (e.g., a chapter) to see its progress here");
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("goals")
+            .show(ui, |ui| {
+                egui::Grid::new("goal list")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (file_id, words, target) in targets {
+                            let Some(object) = project.objects.get(file_id) else {
+                                continue;
+                            };
+
+                            if ui.link(object.borrow().get_title()).clicked() {
+                                let page = Page::FileObject(file_id.clone());
+                                ctx.actions.schedule(move |project_editor, _ctx| {
+                                    project_editor.set_editor_tab(&page, true)
+                                });
+                            }
+
+                            ui.add(
+                                egui::ProgressBar::new(*words as f32 / *target as f32)
+                                    .text(format!("{words} / {target} words")),
+                            );
+
+                            ui.end_row();
+                        }
+                    });
+            });
+    });
+
+    Vec::new()
+}
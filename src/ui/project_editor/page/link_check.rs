@@ -0,0 +1,135 @@
+use crate::components::link_check::FoundLink;
+#[cfg(feature = "link-check")]
+use crate::components::link_check::LinkCheckResult;
+use crate::ui::prelude::*;
+
+use egui::Id;
+
+/// State of the link checking page, stored with the rest of the page data
+#[derive(Debug, Default)]
+pub struct LinkCheckState {
+    links: Option<Vec<FoundLink>>,
+
+    /// results of the most recent check, keyed by url
+    #[cfg(feature = "link-check")]
+    results: HashMap<String, Result<(), String>>,
+
+    /// Set while a check is running on a background thread
+    #[cfg(feature = "link-check")]
+    receiver: Option<std::sync::mpsc::Receiver<LinkCheckResult>>,
+}
+
+impl LinkCheckState {
+    #[cfg(feature = "link-check")]
+    fn receive_results(&mut self, ui: &Ui) {
+        if let Some(receiver) = &self.receiver {
+            loop {
+                match receiver.try_recv() {
+                    Ok(result) => {
+                        self.results.insert(result.url, result.status);
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Empty) => {
+                        // still waiting on more results, make sure we check again soon
+                        ui.ctx()
+                            .request_repaint_after(std::time::Duration::from_millis(200));
+                        break;
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                        self.receiver = None;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn ui(
+    ui: &mut Ui,
+    project: &Project,
+    ctx: &mut EditorContext,
+    state: &mut LinkCheckState,
+) -> Vec<Id> {
+    let mut ids = Vec::new();
+
+    #[cfg(feature = "link-check")]
+    state.receive_results(ui);
+
+    egui::CentralPanel::default().show_inside(ui, |ui| {
+        ui.horizontal(|ui| {
+            let response = ui.button("Find Links");
+            if response.clicked() {
+                state.links = Some(project.find_links());
+            }
+            ids.push(response.id);
+
+            #[cfg(feature = "link-check")]
+            {
+                let checking = state.receiver.is_some();
+                let response = ui.add_enabled(!checking, egui::Button::new("Check Links"));
+                if response.clicked() {
+                    state.links = Some(project.find_links());
+                    state.results.clear();
+                    state.receiver = Some(project.check_links());
+                }
+                ids.push(response.id);
+
+                if checking {
+                    ui.spinner();
+                }
+            }
+
+            #[cfg(not(feature = "link-check"))]
+            ui.label("(built without link checking, links can only be listed)");
+        });
+
+        ui.separator();
+
+        let Some(links) = &state.links else {
+            return;
+        };
+
+        if links.is_empty() {
+            ui.label("No links found");
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("links")
+            .show(ui, |ui| {
+                egui::Grid::new("link list")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for link in links {
+                            if ui.link(&link.object_title).clicked() {
+                                let page = Page::FileObject(FileID::new(link.object_id.clone()));
+                                ctx.actions.schedule(move |project_editor, _ctx| {
+                                    project_editor.set_editor_tab(&page, true)
+                                });
+                            }
+
+                            ui.hyperlink(&link.url);
+
+                            #[cfg(feature = "link-check")]
+                            match state.results.get(&link.url) {
+                                Some(Ok(())) => {
+                                    ui.label("ok");
+                                }
+                                Some(Err(err)) => {
+                                    ui.colored_label(ui.visuals().error_fg_color, "unreachable")
+                                        .on_hover_text(err);
+                                }
+                                None => {
+                                    ui.label("");
+                                }
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+    });
+
+    ids
+}
@@ -1,7 +1,10 @@
+use crate::components::file_objects::utils::{ALTERNATE_BODY_EXTENSIONS, IMAGE_EXTENSIONS};
+use crate::ui::text_box::IgnorePatterns;
 use crate::ui::{prelude::*, project_editor::update_title};
 
 use egui::Id;
 use egui::ScrollArea;
+use rfd::FileDialog;
 
 impl Project {
     pub fn metadata_ui(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext) -> Vec<Id> {
@@ -66,6 +69,167 @@ impl Project {
                 ids.push(response.id);
             });
 
+            ui.horizontal(|ui| {
+                ui.label("Word Goal (0 for none)");
+                let mut word_goal = self.metadata.word_goal.unwrap_or(0);
+                let response = ui.add(egui::DragValue::new(&mut word_goal).speed(100));
+                if response.changed() {
+                    self.metadata.word_goal = (word_goal > 0).then_some(word_goal);
+                }
+                self.process_response(&response);
+                ids.push(response.id);
+            });
+
+            let response = ui
+                .checkbox(&mut self.metadata.autosave_enabled, "Autosave")
+                .on_hover_text("When this is off, changes are only saved with Ctrl+S or on close");
+            self.process_response(&response);
+            ids.push(response.id);
+
+            egui::CollapsingHeader::new("Cover")
+                .default_open(false)
+                .show(ui, |ui| self.cover_ui(ui));
+
+            egui::CollapsingHeader::new("Names for New Objects")
+                .default_open(false)
+                .show(ui, |ui| {
+                    for file_type in self.schema.get_all_file_types() {
+                        ui.horizontal(|ui| {
+                            ui.label(file_type.type_name());
+
+                            let identifier = file_type.get_identifier();
+                            let mut name = self
+                                .metadata
+                                .default_names
+                                .get(identifier)
+                                .cloned()
+                                .unwrap_or_default();
+
+                            let response = ui.add(
+                                egui::TextEdit::singleline(&mut name)
+                                    .id_salt(("default_name", identifier))
+                                    .hint_text(file_type.empty_string_name())
+                                    .desired_width(f32::INFINITY),
+                            );
+                            if response.changed() {
+                                if name.is_empty() {
+                                    self.metadata.default_names.remove(identifier);
+                                } else {
+                                    self.metadata
+                                        .default_names
+                                        .insert(identifier.to_string(), name);
+                                }
+                            }
+                            self.process_response(&response);
+                            ids.push(response.id);
+                        });
+                    }
+                });
+
+            egui::CollapsingHeader::new("File Storage")
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.label(
+                        "Changing these renames files the next time they're saved, which other \
+                        programs (e.g., git) might see as a lot of changes",
+                    );
+
+                    ui.horizontal(|ui| {
+                        ui.label("Index Width (0 for default)")
+                            .on_hover_text("The smallest number of digits in file name indexes");
+                        let mut index_width = self.metadata.index_width.unwrap_or(0);
+                        let response = ui.add(egui::DragValue::new(&mut index_width).range(0..=9));
+                        if response.changed() {
+                            self.metadata.index_width = (index_width > 0).then_some(index_width);
+                            self.apply_storage_settings();
+                        }
+                        self.process_response(&response);
+                        ids.push(response.id);
+                    });
+
+                    for file_type in self.schema.get_all_file_types() {
+                        if file_type.is_folder() || !file_type.has_body() {
+                            continue;
+                        }
+
+                        let identifier = file_type.get_identifier();
+                        let current = self
+                            .metadata
+                            .file_extensions
+                            .get(identifier)
+                            .cloned()
+                            .unwrap_or_else(|| file_type.extension().to_string());
+
+                        let mut extensions = vec![file_type.extension()];
+                        for extension in
+                            std::iter::once("md").chain(ALTERNATE_BODY_EXTENSIONS.iter().copied())
+                        {
+                            if !extensions.contains(&extension) {
+                                extensions.push(extension);
+                            }
+                        }
+
+                        let mut selected = current.clone();
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} Extension", file_type.type_name()));
+                            let response =
+                                egui::ComboBox::from_id_salt(("file_extension", identifier))
+                                    .selected_text(format!(".{selected}"))
+                                    .show_ui(ui, |ui| {
+                                        for extension in extensions {
+                                            ui.selectable_value(
+                                                &mut selected,
+                                                extension.to_string(),
+                                                format!(".{extension}"),
+                                            );
+                                        }
+                                    })
+                                    .response;
+                            ids.push(response.id);
+                        });
+
+                        if selected != current {
+                            if selected == file_type.extension() {
+                                self.metadata.file_extensions.remove(identifier);
+                            } else {
+                                self.metadata
+                                    .file_extensions
+                                    .insert(identifier.to_string(), selected);
+                            }
+                            self.apply_storage_settings();
+                            self.file.modified = true;
+                        }
+                    }
+                });
+
+            egui::CollapsingHeader::new("Spellcheck Ignore Patterns")
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.label(
+                        "Words matching any of these regular expressions (one per line) aren't \
+                        marked as misspelled",
+                    );
+
+                    let mut patterns = self.metadata.spellcheck_ignore_patterns.join("\n");
+                    let response = ui.add(
+                        egui::TextEdit::multiline(&mut patterns)
+                            .id_salt("spellcheck_ignore_patterns")
+                            .hint_text(r"\w+iel")
+                            .code_editor()
+                            .desired_rows(3)
+                            .desired_width(f32::INFINITY),
+                    );
+                    if response.changed() {
+                        self.metadata.spellcheck_ignore_patterns =
+                            patterns.split('\n').map(|line| line.to_string()).collect();
+                        ctx.dictionary_state.ignore_patterns =
+                            IgnorePatterns::new(&self.metadata.spellcheck_ignore_patterns);
+                        ctx.version += 1;
+                    }
+                    self.process_response(&response);
+                    ids.push(response.id);
+                });
+
             // extract the height from some arbitrary text box, it shouldn't matter much
             let text_box_height = response.rect.height().abs();
 
@@ -107,4 +271,48 @@ impl Project {
             self.file.modified = true;
         }
     }
+
+    /// Shows the project's cover image (which is also shown in the project chooser), along with
+    /// a button to set or replace it
+    fn cover_ui(&self, ui: &mut egui::Ui) {
+        let cover = self.find_cover();
+
+        match &cover {
+            Some(cover_path) => {
+                ui.add(
+                    egui::Image::new(format!("file://{}", cover_path.display()))
+                        .max_height(300.0)
+                        .maintain_aspect_ratio(true),
+                );
+            }
+            None => {
+                ui.weak("This project doesn't have a cover");
+            }
+        }
+
+        let button_text = match cover {
+            Some(_) => "Change cover…",
+            None => "Set cover…",
+        };
+
+        if ui.button(button_text).clicked()
+            && let Some(source) = FileDialog::new()
+                .set_title("Choose a cover image")
+                .add_filter("Images", IMAGE_EXTENSIONS)
+                .pick_file()
+        {
+            match self.set_cover(&source) {
+                Ok(new_cover) => {
+                    // egui caches images by uri, so make sure that we don't keep showing the old one
+                    if let Some(old_cover) = &cover {
+                        ui.ctx()
+                            .forget_image(&format!("file://{}", old_cover.display()));
+                    }
+                    ui.ctx()
+                        .forget_image(&format!("file://{}", new_cover.display()));
+                }
+                Err(err) => log::error!("Could not set the project cover: {err}"),
+            }
+        }
+    }
 }
@@ -0,0 +1,42 @@
+use crate::ui::prelude::*;
+
+use egui::Id;
+
+/// A read-only view of the story in chronological order, as set by each scene's story date
+pub fn ui(ui: &mut Ui, project: &Project, ctx: &mut EditorContext) -> Vec<Id> {
+    egui::CentralPanel::default().show_inside(ui, |ui| {
+        let timeline = project.timeline();
+
+        if timeline.is_empty() {
+            ui.label("No scenes yet");
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("timeline")
+            .show(ui, |ui| {
+                egui::Grid::new("timeline list")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for entry in timeline {
+                            match &entry.story_date {
+                                Some(story_date) => ui.label(story_date),
+                                None => ui.weak("(no date)"),
+                            };
+
+                            if ui.link(&entry.title).clicked() {
+                                let page = Page::FileObject(entry.file_id.clone());
+                                ctx.actions.schedule(move |project_editor, _ctx| {
+                                    project_editor.set_editor_tab(&page, true)
+                                });
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+    });
+
+    Vec::new()
+}
@@ -0,0 +1,91 @@
+use crate::ui::prelude::*;
+
+use egui::Id;
+use std::time::{Duration, Instant};
+
+/// How often the list is rebuilt while the page is open. Objects are only saved every so often, so
+/// there's no need to look through all of them every frame
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// State of the unsaved changes page, stored with the rest of the page data
+#[derive(Debug, Default)]
+pub struct UnsavedState {
+    /// The unsaved objects and their titles, as of `refreshed`
+    objects: Vec<(FileID, String)>,
+    /// If the project metadata (from the project file) has unsaved changes
+    metadata_modified: bool,
+    /// When the list was last built, `None` if it needs to be rebuilt right away
+    refreshed: Option<Instant>,
+}
+
+impl UnsavedState {
+    fn refresh(&mut self, project: &Project) {
+        self.objects = project
+            .modified_objects()
+            .into_iter()
+            .filter_map(|file_id| {
+                let title = project.objects.get(&file_id)?.borrow().get_title();
+                Some((file_id, title))
+            })
+            .collect();
+        self.metadata_modified = project.file.modified;
+        self.refreshed = Some(Instant::now());
+    }
+}
+
+/// A list of everything that hasn't been written to disk yet
+pub fn ui(
+    ui: &mut Ui,
+    project: &Project,
+    ctx: &mut EditorContext,
+    state: &mut UnsavedState,
+) -> Vec<Id> {
+    let mut ids = Vec::new();
+
+    if state
+        .refreshed
+        .is_none_or(|refreshed| refreshed.elapsed() >= REFRESH_INTERVAL)
+    {
+        state.refresh(project);
+    }
+    ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+
+    egui::CentralPanel::default().show_inside(ui, |ui| {
+        let response = ui.button("Save Now");
+        if response.clicked() {
+            ctx.actions.schedule(|project_editor, _ctx| {
+                project_editor.save();
+            });
+            state.refreshed = None;
+        }
+        ids.push(response.id);
+
+        ui.separator();
+
+        if state.objects.is_empty() && !state.metadata_modified {
+            ui.label("Everything has been saved");
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("unsaved")
+            .show(ui, |ui| {
+                if state.metadata_modified && ui.link("Project Metadata").clicked() {
+                    ctx.actions.schedule(|project_editor, _ctx| {
+                        project_editor.set_editor_tab(&Page::ProjectMetadata, true)
+                    });
+                }
+
+                for (file_id, title) in &state.objects {
+                    if ui.link(title).clicked() {
+                        let page = Page::FileObject(file_id.clone());
+                        ctx.actions.schedule(move |project_editor, _ctx| {
+                            project_editor.set_editor_tab(&page, true)
+                        });
+                    }
+                }
+            });
+    });
+
+    ids
+}
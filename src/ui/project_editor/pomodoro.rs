@@ -0,0 +1,202 @@
+use crate::ui::prelude::*;
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroPhase {
+    Writing,
+    Break,
+}
+
+/// What happened when the timer moved on to the next phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroTransition {
+    /// A writing session ended, with the change in the story's word count over the session
+    WritingDone {
+        words_written: i64,
+    },
+    BreakDone,
+}
+
+/// A pomodoro timer that alternates between writing sessions and breaks until it's stopped
+#[derive(Debug, Default)]
+pub struct Pomodoro {
+    /// The current phase and when it started, `None` when the timer isn't running
+    phase: Option<(PomodoroPhase, Instant)>,
+    /// The story's word count when the current writing session started
+    session_start_words: usize,
+    /// Writing sessions finished since the project was opened
+    pub sessions_completed: usize,
+    /// Words written over all of the finished writing sessions
+    pub total_words_written: i64,
+}
+
+impl Pomodoro {
+    /// Start a new writing session (restarting the current one if it's already running)
+    pub fn start(&mut self, now: Instant, words: usize) {
+        self.phase = Some((PomodoroPhase::Writing, now));
+        self.session_start_words = words;
+    }
+
+    pub fn stop(&mut self) {
+        self.phase = None;
+    }
+
+    /// The current phase, and how long is left in it
+    pub fn remaining(
+        &self,
+        now: Instant,
+        work_length: Duration,
+        break_length: Duration,
+    ) -> Option<(PomodoroPhase, Duration)> {
+        let (phase, started) = self.phase?;
+        let length = match phase {
+            PomodoroPhase::Writing => work_length,
+            PomodoroPhase::Break => break_length,
+        };
+        Some((
+            phase,
+            length.saturating_sub(now.saturating_duration_since(started)),
+        ))
+    }
+
+    /// Move to the next phase if the current one is over. The next phase starts when the last one
+    /// was supposed to end, so the timer doesn't drift if frames are slow
+    pub fn update(
+        &mut self,
+        now: Instant,
+        words: usize,
+        work_length: Duration,
+        break_length: Duration,
+    ) -> Option<PomodoroTransition> {
+        let (phase, started) = self.phase?;
+
+        match phase {
+            PomodoroPhase::Writing if now >= started + work_length => {
+                let words_written = words as i64 - self.session_start_words as i64;
+                self.sessions_completed += 1;
+                self.total_words_written += words_written;
+                self.phase = Some((PomodoroPhase::Break, started + work_length));
+                Some(PomodoroTransition::WritingDone { words_written })
+            }
+            PomodoroPhase::Break if now >= started + break_length => {
+                self.phase = Some((PomodoroPhase::Writing, started + break_length));
+                self.session_start_words = words;
+                Some(PomodoroTransition::BreakDone)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Format a duration as a countdown clock (e.g., `24:59`)
+pub fn format_remaining(remaining: Duration) -> String {
+    // round up so that the clock says 0:00 right as the phase ends instead of a second early
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Show the timer (or the button to start it) in the status bar, moving on to the next phase if
+/// the current one is over
+pub fn status_ui(
+    pomodoro: &mut Pomodoro,
+    ui: &mut Ui,
+    words: usize,
+    settings: &Settings,
+) -> Option<PomodoroTransition> {
+    let now = Instant::now();
+    let work_length = settings.pomodoro_work_length();
+    let break_length = settings.pomodoro_break_length();
+
+    let transition = pomodoro.update(now, words, work_length, break_length);
+
+    match pomodoro.remaining(now, work_length, break_length) {
+        Some((phase, remaining)) => {
+            let phase_name = match phase {
+                PomodoroPhase::Writing => "Writing",
+                PomodoroPhase::Break => "Break",
+            };
+
+            ui.label(format!("{phase_name} {}", format_remaining(remaining)))
+                .on_hover_text(format!(
+                    "{} writing sessions finished, {} words written",
+                    pomodoro.sessions_completed, pomodoro.total_words_written
+                ));
+
+            if ui.button("Stop").clicked() {
+                pomodoro.stop();
+            } else {
+                // redraw when the clock goes down to the next second, even if nothing else happens
+                let until_tick = match remaining.subsec_nanos() {
+                    0 => Duration::from_secs(1),
+                    nanos => Duration::from_nanos(nanos as u64),
+                };
+                ui.ctx().request_repaint_after(until_tick);
+            }
+        }
+        None => {
+            if ui.button("Start Pomodoro").clicked() {
+                pomodoro.start(now, words);
+            }
+        }
+    }
+
+    transition
+}
+
+#[test]
+fn test_pomodoro() {
+    let work = Duration::from_secs(25 * 60);
+    let rest = Duration::from_secs(5 * 60);
+    let start = Instant::now();
+
+    let mut pomodoro = Pomodoro::default();
+    assert_eq!(pomodoro.update(start + work, 0, work, rest), None);
+    assert_eq!(pomodoro.remaining(start, work, rest), None);
+
+    pomodoro.start(start, 1000);
+    assert_eq!(
+        pomodoro.remaining(start + Duration::from_secs(60), work, rest),
+        Some((PomodoroPhase::Writing, Duration::from_secs(24 * 60)))
+    );
+    assert_eq!(
+        pomodoro.update(start + Duration::from_secs(60), 1100, work, rest),
+        None
+    );
+
+    // the writing session ends and counts the words written
+    let later = start + work + Duration::from_secs(2);
+    assert_eq!(
+        pomodoro.update(later, 1350, work, rest),
+        Some(PomodoroTransition::WritingDone { words_written: 350 })
+    );
+    assert_eq!(
+        pomodoro.remaining(later, work, rest),
+        Some((PomodoroPhase::Break, rest - Duration::from_secs(2)))
+    );
+
+    // the break ends and the next session starts counting from there
+    let later = start + work + rest;
+    assert_eq!(
+        pomodoro.update(later, 1360, work, rest),
+        Some(PomodoroTransition::BreakDone)
+    );
+    assert_eq!(
+        pomodoro.update(later + work, 1300, work, rest),
+        Some(PomodoroTransition::WritingDone { words_written: -60 })
+    );
+    assert_eq!(pomodoro.sessions_completed, 2);
+    assert_eq!(pomodoro.total_words_written, 290);
+
+    pomodoro.stop();
+    assert_eq!(pomodoro.remaining(later + work, work, rest), None);
+    assert_eq!(pomodoro.update(later + work + rest, 1300, work, rest), None);
+}
+
+#[test]
+fn test_format_remaining() {
+    assert_eq!(format_remaining(Duration::from_secs(25 * 60)), "25:00");
+    assert_eq!(format_remaining(Duration::from_millis(59_500)), "1:00");
+    assert_eq!(format_remaining(Duration::from_secs(61)), "1:01");
+    assert_eq!(format_remaining(Duration::ZERO), "0:00");
+}
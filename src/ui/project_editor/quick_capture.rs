@@ -0,0 +1,88 @@
+use super::ProjectEditor;
+
+use crate::components::inbox::INBOX_NAME;
+
+use egui::{Id, Key, KeyboardShortcut, Modifiers};
+
+/// A note being typed into the quick capture box
+#[derive(Debug)]
+pub struct QuickCapture {
+    text: String,
+    /// Set until the text box has been focused, which is done once so that the buttons still work
+    needs_focus: bool,
+}
+
+impl Default for QuickCapture {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            needs_focus: true,
+        }
+    }
+}
+
+/// Show the quick capture box if it's open. Notes are added to the end of the inbox scene without
+/// changing the open tabs, so that writing can pick up right where it left off
+pub fn ui(editor: &mut ProjectEditor, ctx: &egui::Context) {
+    let Some(quick_capture) = &mut editor.quick_capture else {
+        return;
+    };
+
+    let mut submit = false;
+    let mut cancel = false;
+
+    let modal = egui::Modal::new(Id::new("quick capture")).show(ctx, |ui| {
+        ui.heading("Quick Capture");
+        ui.weak(format!(
+            "Added to the end of the \"{INBOX_NAME}\" scene (Shift+Enter for a new line)"
+        ));
+
+        let response = ui.add(
+            egui::TextEdit::multiline(&mut quick_capture.text)
+                .id_salt("quick capture text")
+                .hint_text("Idea")
+                .desired_rows(3)
+                .desired_width(400.0)
+                .return_key(KeyboardShortcut::new(Modifiers::SHIFT, Key::Enter)),
+        );
+
+        if quick_capture.needs_focus {
+            response.request_focus();
+            quick_capture.needs_focus = false;
+        }
+
+        if response.has_focus() && ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Enter)) {
+            submit = true;
+        }
+
+        egui::Sides::new().show(
+            ui,
+            |_ui| {},
+            |ui| {
+                if ui.button("Add").clicked() {
+                    submit = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            },
+        );
+    });
+
+    if modal.should_close() {
+        cancel = true;
+    }
+
+    if submit && !quick_capture.text.trim().is_empty() {
+        match editor.project.append_to_inbox(&quick_capture.text) {
+            Ok(_) => {
+                editor.quick_capture = None;
+                // The inbox might be open in a tab, which needs to show the new note
+                editor.editor_context.version += 1;
+            }
+            Err(err) => log::error!("Failed to add note to the inbox: {err}"),
+        }
+    } else if cancel {
+        editor.quick_capture = None;
+    }
+}
@@ -0,0 +1,68 @@
+use super::ProjectEditor;
+
+use egui::Id;
+
+/// Offer to recover files from temporary files that were left behind when a write was interrupted
+/// (e.g., by a crash). Nothing is changed without asking, since the temporary files could be stale
+pub fn ui(editor: &mut ProjectEditor, ctx: &egui::Context) {
+    if editor.project.recoverable_files.is_empty() {
+        return;
+    }
+
+    let mut recover = false;
+    let mut dismiss = false;
+
+    egui::Modal::new(Id::new("recoverable files")).show(ctx, |ui| {
+        ui.heading("Interrupted Saves");
+
+        let file_count = editor.project.recoverable_files.len();
+        ui.label(format!(
+            "{file_count} {} while being saved. The unsaved contents were found and can be \
+             put back.",
+            if file_count == 1 {
+                "file was missing or emptied"
+            } else {
+                "files were missing or emptied"
+            }
+        ));
+
+        egui::CollapsingHeader::new("Files").show(ui, |ui| {
+            let project_path = editor.project.get_path();
+            for recoverable in &editor.project.recoverable_files {
+                let target_path = &recoverable.target_path;
+                ui.label(
+                    target_path
+                        .strip_prefix(&project_path)
+                        .unwrap_or(target_path)
+                        .display()
+                        .to_string(),
+                );
+            }
+        });
+
+        egui::Sides::new().show(
+            ui,
+            |_ui| {},
+            |ui| {
+                if ui.button("Recover").clicked() {
+                    recover = true;
+                }
+                if ui
+                    .button("Leave As Is")
+                    .on_hover_text("Don't change anything (you'll be asked again next time)")
+                    .clicked()
+                {
+                    dismiss = true;
+                }
+            },
+        );
+    });
+
+    if recover {
+        if let Err(err) = editor.project.recover_files() {
+            log::error!("Failed to recover files: {err}");
+        }
+    } else if dismiss {
+        editor.project.recoverable_files.clear();
+    }
+}
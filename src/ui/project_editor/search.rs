@@ -14,6 +14,14 @@ pub struct Search {
 
     pub find_text: String,
 
+    pub replace_text: String,
+
+    /// Treat `find_text` as a regex when replacing, which lets `replace_text` use capture groups
+    pub use_regex: bool,
+
+    /// The outcome of the last replace, shown until the next one
+    pub replace_status: Option<String>,
+
     pub redo_search: bool,
 
     pub search_results: Option<HashMap<TextUID, TextBoxSearchResult>>,
@@ -97,26 +105,59 @@ impl Searchable<'_> {
             }
         }
     }
+
+    /// Record that there are no matches without searching the text. This still needs an entry for
+    /// every text box so that the results get updated if the text is edited while searching
+    pub fn skip(&self, page: &Page, search: &mut Search) {
+        let mut skip_function = |text: &'_ Text, box_name: &'_ str| {
+            let search_result = textbox_search::empty_result(text, page, box_name);
+            search
+                .search_results
+                .as_mut()
+                .unwrap()
+                .insert(text.id(), search_result);
+        };
+
+        match self {
+            Searchable::FileObject(file_object) => {
+                file_object
+                    .borrow()
+                    .as_editor()
+                    .for_each_textbox(&mut skip_function);
+            }
+            Searchable::ProjectMetadata(metadata) => metadata.for_each_textbox(&mut skip_function),
+        }
+    }
 }
 
 impl ProjectEditor {
     pub fn search(&mut self) {
         self.editor_context.search.search_results = Some(HashMap::new());
 
-        let object_iter =
-            self.project.objects.iter().map(|(id, file_object)| {
-                (Page::from_file_id(id), Searchable::FileObject(file_object))
-            });
-
-        let metadata_iter = std::iter::once((
-            Page::ProjectMetadata,
-            Searchable::ProjectMetadata(&self.project.metadata),
-        ));
-
-        for (key, object) in object_iter.chain(metadata_iter) {
-            object.search(&key, &mut self.editor_context.search);
+        // Use the index to narrow down which objects could possibly match before actually searching
+        self.project.search_index.refresh(&self.project.objects);
+        let candidates = self
+            .project
+            .search_index
+            .candidates(&self.editor_context.search.find_text);
+
+        for (id, file_object) in self.project.objects.iter() {
+            let searchable = Searchable::FileObject(file_object);
+            let page = Page::from_file_id(id);
+            if candidates
+                .as_ref()
+                .is_none_or(|candidates| candidates.contains(id))
+            {
+                searchable.search(&page, &mut self.editor_context.search);
+            } else {
+                searchable.skip(&page, &mut self.editor_context.search);
+            }
         }
 
+        // The project metadata isn't indexed, it's always small enough to just search
+        Searchable::ProjectMetadata(&self.project.metadata)
+            .search(&Page::ProjectMetadata, &mut self.editor_context.search);
+
         self.editor_context.search.clear_focus();
 
         // trigger a formatting refresh
@@ -133,6 +174,10 @@ impl Project {
             Page::ProjectMetadata => Some(Searchable::ProjectMetadata(&self.metadata)),
             Page::Export => None,
             Page::Settings => None,
+            Page::LinkCheck => None,
+            Page::Timeline => None,
+            Page::Unsaved => None,
+            Page::Goals => None,
         }
     }
 }
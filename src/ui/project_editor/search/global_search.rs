@@ -1,11 +1,13 @@
 use egui::{Color32, Response};
 
+use crate::components::replace::Replacement;
 use crate::ui::prelude::*;
 
 use super::textbox_search::TextBoxSearchResult;
 
 /// Global search ui, returns the search box response output
 pub fn ui(ui: &mut Ui, project: &Project, ctx: &mut EditorContext) -> Response {
+    let read_only = ctx.read_only;
     let gs = &mut ctx.search;
 
     // Take up the entire area horizontally
@@ -25,6 +27,52 @@ pub fn ui(ui: &mut Ui, project: &Project, ctx: &mut EditorContext) -> Response {
         gs.redo_search = true;
     }
 
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut gs.replace_text)
+                .hint_text("replace")
+                .desired_width(ui.available_width() / 2.0),
+        );
+        ui.checkbox(&mut gs.use_regex, "regex")
+            .on_hover_text("Find with a regex, so the replacement can use groups like $1");
+
+        if ui
+            .add_enabled(!read_only, egui::Button::new("replace all"))
+            .on_disabled_hover_text("The project is read-only")
+            .clicked()
+        {
+            let find_text = gs.find_text.clone();
+            let replace_text = gs.replace_text.clone();
+            let use_regex = gs.use_regex;
+
+            ctx.actions.schedule(move |project_editor, _ctx| {
+                // the project could have become read-only since the button was clicked
+                if project_editor.editor_context.read_only {
+                    return;
+                }
+
+                let search = &mut project_editor.editor_context.search;
+                search.replace_status = Some(
+                    match Replacement::new(&find_text, &replace_text, use_regex) {
+                        Ok(replacement) => {
+                            let count = project_editor.project.replace_all(&replacement);
+                            search.redo_search = true;
+                            format!("Replaced in {count} file(s)")
+                        }
+                        Err(err) => {
+                            log::warn!("Could not replace: {err}");
+                            err.to_string()
+                        }
+                    },
+                );
+            });
+        }
+    });
+
+    if let Some(status) = &ctx.search.replace_status {
+        ui.label(status);
+    }
+
     if let Some(search_results) = &mut ctx.search.search_results {
         let mut items: Vec<(TextUID, String, &TextBoxSearchResult)> = search_results
             .iter()
@@ -36,6 +84,10 @@ pub fn ui(ui: &mut Ui, project: &Project, ctx: &mut EditorContext) -> Response {
                 Page::ProjectMetadata => Some((*id, String::from("Project Metadata"), tbsr)),
                 Page::Export => unreachable!(),
                 Page::Settings => unreachable!(),
+                Page::LinkCheck => unreachable!(),
+                Page::Timeline => unreachable!(),
+                Page::Unsaved => unreachable!(),
+                Page::Goals => unreachable!(),
             })
             .filter(|(_, _, tbsr)| !tbsr.finds.is_empty())
             .collect();
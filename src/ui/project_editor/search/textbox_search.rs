@@ -125,3 +125,13 @@ pub fn search(text: &Text, page: &Page, box_name: &str, search_term: &str) -> Te
         text_version: text.version,
     }
 }
+
+/// A result with no matches, for text that's known not to contain the search term
+pub fn empty_result(text: &Text, page: &Page, box_name: &str) -> TextBoxSearchResult {
+    TextBoxSearchResult {
+        page: page.clone(),
+        box_name: box_name.to_string(),
+        finds: Vec::new(),
+        text_version: text.version,
+    }
+}
@@ -1,5 +1,26 @@
 use crate::ui::prelude::*;
 
+/// Count the words in the bodies of everything in the story text (ignoring summaries, notes, etc.).
+/// This uses the counts the objects keep, so bodies that haven't been loaded aren't read
+pub fn story_word_count(project: &Project) -> usize {
+    fn count_object(file_id: &FileID, project: &Project) -> usize {
+        let Some(object) = project.objects.get(file_id) else {
+            return 0;
+        };
+        let object = object.borrow();
+
+        let mut word_count = object.word_count();
+
+        for child_id in object.get_base().children.iter() {
+            word_count += count_object(child_id, project);
+        }
+
+        word_count
+    }
+
+    count_object(project.text_folder_id(), project)
+}
+
 #[allow(dead_code)]
 pub fn project_word_count(project: &Project, ctx: &mut EditorContext) -> usize {
     let mut word_count = 0;
@@ -15,3 +36,79 @@ pub fn project_word_count(project: &Project, ctx: &mut EditorContext) -> usize {
 
     word_count
 }
+
+/// Notices when the word count crosses the goal, so it can be celebrated once per crossing
+#[derive(Debug, Default)]
+pub struct WordGoalTracker {
+    /// If the goal was reached the last time the words were counted (`None` before the first count)
+    reached: Option<bool>,
+}
+
+impl WordGoalTracker {
+    /// Returns true if the goal was just reached. Already being past the goal on the first count
+    /// (e.g., when opening the project) doesn't count, and dropping back under the goal means it
+    /// can be reached again
+    pub fn update(&mut self, words: usize, word_goal: u64) -> bool {
+        let reached = words as u64 >= word_goal;
+        let just_reached = reached && self.reached == Some(false);
+        self.reached = Some(reached);
+        just_reached
+    }
+}
+
+#[test]
+fn test_word_goal_tracker() {
+    let mut tracker = WordGoalTracker::default();
+    assert!(!tracker.update(150, 100));
+    assert!(!tracker.update(160, 100));
+
+    assert!(!tracker.update(90, 100));
+    assert!(tracker.update(100, 100));
+    assert!(!tracker.update(101, 100));
+}
+
+/// Draw a ring that fills up as the story gets closer to the word goal, along with the counts.
+/// Once the goal is reached, the ring stays full and the extra words are shown instead
+pub fn word_goal_ring(ui: &mut Ui, words: usize, word_goal: u64) {
+    let size = ui.spacing().interact_size.y;
+    let (rect, _response) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
+
+    let stroke_width = 3.0;
+    let center = rect.center();
+    let radius = size / 2.0 - stroke_width;
+    let progress = (words as f32 / word_goal as f32).min(1.0);
+
+    let painter = ui.painter();
+    painter.circle_stroke(
+        center,
+        radius,
+        egui::Stroke::new(stroke_width, ui.visuals().widgets.inactive.bg_fill),
+    );
+
+    if progress > 0.0 {
+        let segments = ((64.0 * progress).ceil() as usize).max(1);
+        let points = (0..=segments)
+            .map(|segment| {
+                // start at the top and go clockwise
+                let angle = -std::f32::consts::FRAC_PI_2
+                    + std::f32::consts::TAU * progress * segment as f32 / segments as f32;
+                center + radius * egui::Vec2::angled(angle)
+            })
+            .collect();
+
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(stroke_width, ui.visuals().selection.bg_fill),
+        ));
+    }
+
+    let words = words as u64;
+    if words > word_goal {
+        ui.label(format!(
+            "{words} / {word_goal} words (+{})",
+            words - word_goal
+        ));
+    } else {
+        ui.label(format!("{words} / {word_goal} words"));
+    }
+}
@@ -4,13 +4,14 @@ pub mod theme;
 use crate::ui::prelude::*;
 
 use crate::components::file_objects::utils::{
-    create_dir_if_missing, process_name_for_filename, write_with_temp_file,
+    create_dir_if_missing, preserve_invalid_file, process_name_for_filename, write_with_temp_file,
 };
+use crate::util::AppDirs;
 
 use std::fs::read_dir;
+use std::time::Duration;
 use std::{fs::read_to_string, path::PathBuf};
 
-use directories::ProjectDirs;
 use toml_edit::{DocumentMut, value};
 
 pub use theme::Theme;
@@ -30,12 +31,64 @@ struct SettingsData {
     /// visual indentation at the start of lines (buggy)
     indent_line_start: bool,
 
+    /// pressing Tab in a text box indents (and Shift+Tab dedents) instead of moving focus
+    tab_indents: bool,
+
+    /// capitalize the first letter of sentences and paragraphs while typing
+    auto_capitalize: bool,
+
+    /// show `%% comments %%` in the editor, if unset they're shrunk down to be nearly invisible
+    show_comments: bool,
+
+    /// mark spaces, tabs, and line breaks in the editor so that stray whitespace can be seen
+    show_invisibles: bool,
+
+    /// show the index (as used in the filename, e.g., `003-`) in front of each name in the tree
+    show_tree_indices: bool,
+
+    /// save as soon as the window loses focus (e.g., switching to another app), in addition to
+    /// the regular autosaves
+    save_on_focus_loss: bool,
+
+    /// how long (in minutes) without any input before autosaves and dictionary updates are
+    /// paused, 0 to never pause them
+    idle_minutes: usize,
+
+    /// ask before deleting objects from the tree
+    confirm_delete: bool,
+
+    /// go back around to the first scene after the last one (and the reverse) when moving between
+    /// scenes with ctrl-pageup and ctrl-pagedown
+    wrap_scene_navigation: bool,
+
+    /// show a pomodoro timer in the status bar
+    pomodoro: bool,
+
+    /// how long (in minutes) each pomodoro writing session lasts
+    pomodoro_work_minutes: usize,
+
+    /// how long (in minutes) the break after each writing session lasts
+    pomodoro_break_minutes: usize,
+
     /// re-open the last project when launching the app
     reopen_last: bool,
 
+    /// how many projects to remember in the recent projects list
+    max_recent_projects: usize,
+
+    /// copy the project into its `.backups/` folder every time it's opened
+    backup_on_open: bool,
+
+    /// how many backups to keep for each project, older ones are deleted
+    backups_to_keep: usize,
+
     /// Location of the Dictionary
     dictionary_location: PathBuf,
 
+    /// command run on every exported file (with `{input}` and `{output}` filled in), empty if
+    /// exports shouldn't be post-processed
+    export_post_command: String,
+
     /// theming for visuals.
     theme: Theme,
 
@@ -44,22 +97,38 @@ struct SettingsData {
     // theme_selection: ThemeSelection,
     available_themes: Rc<Vec<(String, Theme)>>,
 
-    project_dirs: Rc<ProjectDirs>,
+    app_dirs: Rc<AppDirs>,
 
     modified: bool,
 }
 
 impl SettingsData {
-    pub fn new(project_dirs: &ProjectDirs) -> Self {
+    pub fn new(app_dirs: &AppDirs) -> Self {
         Self {
             font_size: 18.0,
             reopen_last: true,
+            max_recent_projects: 10,
+            backup_on_open: false,
+            backups_to_keep: 5,
             indent_line_start: false,
+            tab_indents: false,
+            auto_capitalize: false,
+            show_comments: true,
+            show_invisibles: false,
+            show_tree_indices: false,
+            save_on_focus_loss: false,
+            idle_minutes: 10,
+            confirm_delete: true,
+            wrap_scene_navigation: false,
+            pomodoro: false,
+            pomodoro_work_minutes: 25,
+            pomodoro_break_minutes: 5,
             dictionary_location: PathBuf::from("/usr/share/hunspell/en_US"),
+            export_post_command: String::new(),
             theme: Theme::default(),
             selected_theme: ThemeSelection::Default,
             available_themes: Rc::new(Vec::new()),
-            project_dirs: Rc::new(project_dirs.clone()),
+            app_dirs: Rc::new(app_dirs.clone()),
             modified: false,
         }
     }
@@ -83,11 +152,106 @@ impl SettingsData {
             None => self.modified = true,
         }
 
+        match table
+            .get("max_recent_projects")
+            .and_then(|val| val.as_integer())
+        {
+            Some(max_recent_projects) => {
+                self.max_recent_projects = max_recent_projects.max(1) as usize
+            }
+            None => self.modified = true,
+        }
+
+        match table.get("backup_on_open").and_then(|val| val.as_bool()) {
+            Some(backup_on_open) => self.backup_on_open = backup_on_open,
+            None => self.modified = true,
+        }
+
+        match table
+            .get("backups_to_keep")
+            .and_then(|val| val.as_integer())
+        {
+            Some(backups_to_keep) => self.backups_to_keep = backups_to_keep.max(1) as usize,
+            None => self.modified = true,
+        }
+
         match table.get("indent_line_start").and_then(|val| val.as_bool()) {
             Some(indent_line_start) => self.indent_line_start = indent_line_start,
             None => self.modified = true,
         }
 
+        match table.get("tab_indents").and_then(|val| val.as_bool()) {
+            Some(tab_indents) => self.tab_indents = tab_indents,
+            None => self.modified = true,
+        }
+
+        match table.get("auto_capitalize").and_then(|val| val.as_bool()) {
+            Some(auto_capitalize) => self.auto_capitalize = auto_capitalize,
+            None => self.modified = true,
+        }
+
+        match table.get("show_comments").and_then(|val| val.as_bool()) {
+            Some(show_comments) => self.show_comments = show_comments,
+            None => self.modified = true,
+        }
+
+        match table.get("show_invisibles").and_then(|val| val.as_bool()) {
+            Some(show_invisibles) => self.show_invisibles = show_invisibles,
+            None => self.modified = true,
+        }
+
+        match table.get("show_tree_indices").and_then(|val| val.as_bool()) {
+            Some(show_tree_indices) => self.show_tree_indices = show_tree_indices,
+            None => self.modified = true,
+        }
+
+        match table
+            .get("save_on_focus_loss")
+            .and_then(|val| val.as_bool())
+        {
+            Some(save_on_focus_loss) => self.save_on_focus_loss = save_on_focus_loss,
+            None => self.modified = true,
+        }
+
+        match table.get("idle_minutes").and_then(|val| val.as_integer()) {
+            Some(idle_minutes) => self.idle_minutes = idle_minutes.max(0) as usize,
+            None => self.modified = true,
+        }
+
+        match table.get("confirm_delete").and_then(|val| val.as_bool()) {
+            Some(confirm_delete) => self.confirm_delete = confirm_delete,
+            None => self.modified = true,
+        }
+
+        match table
+            .get("wrap_scene_navigation")
+            .and_then(|val| val.as_bool())
+        {
+            Some(wrap_scene_navigation) => self.wrap_scene_navigation = wrap_scene_navigation,
+            None => self.modified = true,
+        }
+
+        match table.get("pomodoro").and_then(|val| val.as_bool()) {
+            Some(pomodoro) => self.pomodoro = pomodoro,
+            None => self.modified = true,
+        }
+
+        match table
+            .get("pomodoro_work_minutes")
+            .and_then(|val| val.as_integer())
+        {
+            Some(work_minutes) => self.pomodoro_work_minutes = work_minutes.max(1) as usize,
+            None => self.modified = true,
+        }
+
+        match table
+            .get("pomodoro_break_minutes")
+            .and_then(|val| val.as_integer())
+        {
+            Some(break_minutes) => self.pomodoro_break_minutes = break_minutes.max(1) as usize,
+            None => self.modified = true,
+        }
+
         if let Some(dictionary_location) = table
             .get("dictionary_location")
             .and_then(|location| location.as_str())
@@ -95,6 +259,13 @@ impl SettingsData {
             self.dictionary_location = PathBuf::from(dictionary_location);
         }
 
+        if let Some(export_post_command) = table
+            .get("export_post_command")
+            .and_then(|command| command.as_str())
+        {
+            self.export_post_command = export_post_command.to_string();
+        }
+
         if let Some(theme_table) = table
             .get("theme")
             .and_then(|theme_item| theme_item.as_table_like())
@@ -106,15 +277,42 @@ impl SettingsData {
     pub fn save(&self, table: &mut DocumentMut) {
         table.insert("font_size", value(self.font_size as f64));
         table.insert("reopen_last", value(self.reopen_last));
+        table.insert(
+            "max_recent_projects",
+            value(self.max_recent_projects as i64),
+        );
+        table.insert("backup_on_open", value(self.backup_on_open));
+        table.insert("backups_to_keep", value(self.backups_to_keep as i64));
         table.insert("indent_line_start", value(self.indent_line_start));
+        table.insert("tab_indents", value(self.tab_indents));
+        table.insert("auto_capitalize", value(self.auto_capitalize));
+        table.insert("show_comments", value(self.show_comments));
+        table.insert("show_invisibles", value(self.show_invisibles));
+        table.insert("show_tree_indices", value(self.show_tree_indices));
+        table.insert("save_on_focus_loss", value(self.save_on_focus_loss));
+        table.insert("idle_minutes", value(self.idle_minutes as i64));
+        table.insert("confirm_delete", value(self.confirm_delete));
+        table.insert("wrap_scene_navigation", value(self.wrap_scene_navigation));
+        table.insert("pomodoro", value(self.pomodoro));
+        table.insert(
+            "pomodoro_work_minutes",
+            value(self.pomodoro_work_minutes as i64),
+        );
+        table.insert(
+            "pomodoro_break_minutes",
+            value(self.pomodoro_break_minutes as i64),
+        );
+        if !self.export_post_command.trim().is_empty() {
+            table.insert("export_post_command", value(&self.export_post_command));
+        }
     }
 
     fn config_file_path(&self) -> PathBuf {
-        self.project_dirs.config_dir().join("settings.toml")
+        self.app_dirs.config_dir().join("settings.toml")
     }
 
     fn themes_path(&self) -> PathBuf {
-        self.project_dirs.config_dir().join("themes")
+        self.app_dirs.config_dir().join("themes")
     }
 }
 
@@ -122,17 +320,18 @@ impl SettingsData {
 pub struct Settings(Rc<RefCell<SettingsData>>);
 
 impl Settings {
-    pub fn new(project_dirs: &ProjectDirs) -> Self {
-        Self(Rc::new(RefCell::new(SettingsData::new(project_dirs))))
+    pub fn new(app_dirs: &AppDirs) -> Self {
+        Self(Rc::new(RefCell::new(SettingsData::new(app_dirs))))
     }
 
     pub fn load(&mut self) -> Result<(), CheeseError> {
         let mut data = self.0.borrow_mut();
 
         let settings_toml = match read_to_string(data.config_file_path()) {
-            Ok(config) => config
-                .parse::<DocumentMut>()
-                .map_err(|err| cheese_error!("invalid toml settings file: {err}"))?,
+            Ok(config) => config.parse::<DocumentMut>().map_err(|err| {
+                preserve_invalid_file(&data.config_file_path());
+                cheese_error!("invalid toml settings file: {err}")
+            })?,
             Err(err) => match err.kind() {
                 // It's perfectly normal for there not to be a file, but any other IO error is a problem
                 std::io::ErrorKind::NotFound => DocumentMut::new(),
@@ -224,14 +423,103 @@ impl Settings {
         self.0.borrow_mut().reopen_last = reopen_last;
     }
 
+    pub fn max_recent_projects(&self) -> usize {
+        self.0.borrow().max_recent_projects
+    }
+
+    pub fn backup_on_open(&self) -> bool {
+        self.0.borrow().backup_on_open
+    }
+
+    pub fn backups_to_keep(&self) -> usize {
+        self.0.borrow().backups_to_keep
+    }
+
     pub fn indent_line_start(&self) -> bool {
         self.0.borrow().indent_line_start
     }
 
+    pub fn tab_indents(&self) -> bool {
+        self.0.borrow().tab_indents
+    }
+
+    pub fn auto_capitalize(&self) -> bool {
+        self.0.borrow().auto_capitalize
+    }
+
+    pub fn show_comments(&self) -> bool {
+        self.0.borrow().show_comments
+    }
+
+    pub fn set_show_comments(&self, show_comments: bool) {
+        let mut data = self.0.borrow_mut();
+        data.show_comments = show_comments;
+        data.modified = true;
+    }
+
+    pub fn show_invisibles(&self) -> bool {
+        self.0.borrow().show_invisibles
+    }
+
+    pub fn set_show_invisibles(&self, show_invisibles: bool) {
+        let mut data = self.0.borrow_mut();
+        data.show_invisibles = show_invisibles;
+        data.modified = true;
+    }
+
+    pub fn show_tree_indices(&self) -> bool {
+        self.0.borrow().show_tree_indices
+    }
+
+    pub fn save_on_focus_loss(&self) -> bool {
+        self.0.borrow().save_on_focus_loss
+    }
+
+    /// How long without input before background work is paused, `None` if it never is
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        match self.0.borrow().idle_minutes {
+            0 => None,
+            minutes => Some(Duration::from_secs(minutes as u64 * 60)),
+        }
+    }
+
+    pub fn confirm_delete(&self) -> bool {
+        self.0.borrow().confirm_delete
+    }
+
+    pub fn set_confirm_delete(&self, confirm_delete: bool) {
+        let mut data = self.0.borrow_mut();
+        data.confirm_delete = confirm_delete;
+        data.modified = true;
+    }
+
+    pub fn wrap_scene_navigation(&self) -> bool {
+        self.0.borrow().wrap_scene_navigation
+    }
+
+    pub fn pomodoro(&self) -> bool {
+        self.0.borrow().pomodoro
+    }
+
+    pub fn pomodoro_work_length(&self) -> Duration {
+        Duration::from_secs(self.0.borrow().pomodoro_work_minutes as u64 * 60)
+    }
+
+    pub fn pomodoro_break_length(&self) -> Duration {
+        Duration::from_secs(self.0.borrow().pomodoro_break_minutes as u64 * 60)
+    }
+
     pub fn dictionary_location(&self) -> PathBuf {
         self.0.borrow().dictionary_location.clone()
     }
 
+    /// The command to run after exporting, if one is set
+    pub fn export_post_command(&self) -> Option<String> {
+        let data = self.0.borrow();
+        let command = data.export_post_command.trim();
+        (!command.is_empty()).then(|| command.to_string())
+    }
+
     pub fn theme(&self) -> Theme {
         self.0.borrow().theme.clone()
     }
@@ -15,12 +15,54 @@ pub struct SettingsPage {
 
     indent_line_start_config: bool,
 
+    tab_indents_config: bool,
+
+    auto_capitalize_config: bool,
+
+    show_comments_config: bool,
+
+    show_invisibles_config: bool,
+
+    show_tree_indices_config: bool,
+
+    save_on_focus_loss_config: bool,
+
+    idle_minutes_config: String,
+
+    idle_minutes_error: Option<String>,
+
+    confirm_delete_config: bool,
+
+    wrap_scene_navigation_config: bool,
+
+    pomodoro_config: bool,
+
+    pomodoro_work_minutes_config: String,
+
+    pomodoro_work_minutes_error: Option<String>,
+
+    pomodoro_break_minutes_config: String,
+
+    pomodoro_break_minutes_error: Option<String>,
+
     reopen_last_config: bool,
 
+    max_recent_projects_config: String,
+
+    max_recent_projects_error: Option<String>,
+
+    backup_on_open_config: bool,
+
+    backups_to_keep_config: String,
+
+    backups_to_keep_error: Option<String>,
+
     dictionary_location_config: String,
 
     dictionary_location_error: Option<String>,
 
+    export_post_command_config: String,
+
     random_theme_name: String,
 
     random_theme_save_error: Option<CheeseError>,
@@ -38,20 +80,73 @@ impl SettingsPage {
 
         let indent_line_start_config = data.indent_line_start;
 
+        let tab_indents_config = data.tab_indents;
+
+        let auto_capitalize_config = data.auto_capitalize;
+
+        let show_comments_config = data.show_comments;
+
+        let show_invisibles_config = data.show_invisibles;
+
+        let show_tree_indices_config = data.show_tree_indices;
+
+        let save_on_focus_loss_config = data.save_on_focus_loss;
+
+        let idle_minutes_config = format!("{}", data.idle_minutes);
+
+        let confirm_delete_config = data.confirm_delete;
+
+        let wrap_scene_navigation_config = data.wrap_scene_navigation;
+
+        let pomodoro_config = data.pomodoro;
+
+        let pomodoro_work_minutes_config = format!("{}", data.pomodoro_work_minutes);
+
+        let pomodoro_break_minutes_config = format!("{}", data.pomodoro_break_minutes);
+
         let reopen_last_config = data.reopen_last;
 
+        let max_recent_projects_config = format!("{}", data.max_recent_projects);
+
+        let backup_on_open_config = data.backup_on_open;
+
+        let backups_to_keep_config = format!("{}", data.backups_to_keep);
+
         let dictionary_location_config = match data.dictionary_location.to_str() {
             Some(s) => s.into(),
             None => String::new(),
         };
 
+        let export_post_command_config = data.export_post_command.clone();
+
         Self {
             font_size_config,
             font_size_error: None,
             indent_line_start_config,
+            tab_indents_config,
+            auto_capitalize_config,
+            show_comments_config,
+            show_invisibles_config,
+            show_tree_indices_config,
+            save_on_focus_loss_config,
+            idle_minutes_config,
+            idle_minutes_error: None,
+            confirm_delete_config,
+            wrap_scene_navigation_config,
+            pomodoro_config,
+            pomodoro_work_minutes_config,
+            pomodoro_work_minutes_error: None,
+            pomodoro_break_minutes_config,
+            pomodoro_break_minutes_error: None,
             reopen_last_config,
+            max_recent_projects_config,
+            max_recent_projects_error: None,
+            backup_on_open_config,
+            backups_to_keep_config,
+            backups_to_keep_error: None,
             dictionary_location_config,
             dictionary_location_error: None,
+            export_post_command_config,
             random_theme_name: String::new(),
             random_theme_save_error: None,
             next_update: None,
@@ -74,8 +169,88 @@ impl SettingsPage {
         }
 
         settings_data.indent_line_start = self.indent_line_start_config;
+
+        settings_data.tab_indents = self.tab_indents_config;
+
+        settings_data.auto_capitalize = self.auto_capitalize_config;
+
+        if settings_data.show_comments != self.show_comments_config {
+            settings_data.show_comments = self.show_comments_config;
+            // comments are drawn as part of the layout, so it needs to be redone everywhere
+            ctx.version += 1;
+        }
+
+        settings_data.show_invisibles = self.show_invisibles_config;
+
+        settings_data.show_tree_indices = self.show_tree_indices_config;
+
+        settings_data.save_on_focus_loss = self.save_on_focus_loss_config;
+
+        match self.idle_minutes_config.parse::<usize>() {
+            Ok(val) => {
+                settings_data.idle_minutes = val;
+                self.idle_minutes_error = None;
+            }
+            Err(_) => {
+                self.idle_minutes_error =
+                    Some("Pause When Idle must be a whole number of minutes".to_string());
+            }
+        }
+
+        settings_data.confirm_delete = self.confirm_delete_config;
+
+        settings_data.wrap_scene_navigation = self.wrap_scene_navigation_config;
+
+        settings_data.pomodoro = self.pomodoro_config;
+
+        match self.pomodoro_work_minutes_config.parse::<usize>() {
+            Ok(val) if val > 0 => {
+                settings_data.pomodoro_work_minutes = val;
+                self.pomodoro_work_minutes_error = None;
+            }
+            _ => {
+                self.pomodoro_work_minutes_error =
+                    Some("Writing Session Length must be a positive integer".to_string());
+            }
+        }
+
+        match self.pomodoro_break_minutes_config.parse::<usize>() {
+            Ok(val) if val > 0 => {
+                settings_data.pomodoro_break_minutes = val;
+                self.pomodoro_break_minutes_error = None;
+            }
+            _ => {
+                self.pomodoro_break_minutes_error =
+                    Some("Break Length must be a positive integer".to_string());
+            }
+        }
+
         settings_data.reopen_last = self.reopen_last_config;
 
+        match self.max_recent_projects_config.parse::<usize>() {
+            Ok(val) if val > 0 => {
+                settings_data.max_recent_projects = val;
+                self.max_recent_projects_error = None;
+            }
+            _ => {
+                self.max_recent_projects_error =
+                    Some("Recent Projects must be a positive integer".to_string());
+            }
+        }
+
+        settings_data.backup_on_open = self.backup_on_open_config;
+
+        match self.backups_to_keep_config.parse::<usize>() {
+            Ok(val) if val > 0 => {
+                settings_data.backups_to_keep = val;
+                self.backups_to_keep_error = None;
+            }
+            _ => {
+                self.backups_to_keep_error =
+                    Some("Backups to Keep must be a positive integer".to_string());
+            }
+        }
+
         match self.dictionary_location_config.parse::<PathBuf>() {
             Ok(val) => {
                 // todo! check range
@@ -88,6 +263,8 @@ impl SettingsPage {
             }
         }
 
+        settings_data.export_post_command = self.export_post_command_config.clone();
+
         settings_data.modified = true;
     }
 
@@ -126,12 +303,152 @@ impl SettingsPage {
         self.process_response(&response);
         ids.push(response.id);
 
+        ui.label("Tab Key Indents Text  ℹ").on_hover_text(
+            "Tab inserts a tab and Shift+Tab removes one in the text boxes, instead of moving \
+            between fields",
+        );
+
+        let response = ui.checkbox(&mut self.tab_indents_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Capitalize Sentences  ℹ").on_hover_text(
+            "Capitalize the first letter of each sentence and paragraph while typing. \
+            Abbreviations like \"e.g.\" are skipped, and a letter that's changed back to \
+            lowercase is left alone",
+        );
+
+        let response = ui.checkbox(&mut self.auto_capitalize_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Show Comments");
+
+        let response = ui.checkbox(&mut self.show_comments_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Show Invisibles  ℹ").on_hover_text(
+            "Mark spaces with a dot, tabs with an arrow, and line breaks with ¶ in the editor, to \
+            make double spaces and stray tabs easy to spot",
+        );
+
+        let response = ui.checkbox(&mut self.show_invisibles_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Show Indices in Tree  ℹ").on_hover_text(
+            "Show the number that's used to order each object on disk (the start of its filename) \
+            in front of its name in the tree",
+        );
+
+        let response = ui.checkbox(&mut self.show_tree_indices_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Save When Switching Away  ℹ").on_hover_text(
+            "Save the project as soon as the window loses focus (e.g., switching to another app), \
+            in addition to the regular autosaves. Projects with autosave turned off are still only \
+            saved with Ctrl+S",
+        );
+
+        let response = ui.checkbox(&mut self.save_on_focus_loss_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Pause When Idle (Minutes)  ℹ").on_hover_text(
+            "After this long without any typing or mouse movement, save once and then stop \
+            autosaving and updating the spellcheck dictionary until you're back, to save battery. \
+            0 never pauses",
+        );
+
+        let response = ui.text_edit_singleline(&mut self.idle_minutes_config);
+        self.process_response(&response);
+        ids.push(response.id);
+
+        if let Some(err) = &self.idle_minutes_error {
+            ui.label(RichText::new(err).color(Color32::RED));
+        }
+
+        ui.label("Confirm Before Deleting");
+
+        let response = ui.checkbox(&mut self.confirm_delete_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Wrap Scene Navigation  ℹ").on_hover_text(
+            "Go from the last scene back to the first one (and the other way around) when moving \
+            between scenes with Ctrl+PageDown and Ctrl+PageUp",
+        );
+
+        let response = ui.checkbox(&mut self.wrap_scene_navigation_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Pomodoro Timer  ℹ").on_hover_text(
+            "Show a timer in the status bar that alternates between writing sessions and breaks, \
+            with a message (and the words written) at the end of each session",
+        );
+
+        let response = ui.checkbox(&mut self.pomodoro_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Writing Session Length (Minutes)");
+
+        let response = ui.text_edit_singleline(&mut self.pomodoro_work_minutes_config);
+        self.process_response(&response);
+        ids.push(response.id);
+
+        if let Some(err) = &self.pomodoro_work_minutes_error {
+            ui.label(RichText::new(err).color(Color32::RED));
+        }
+
+        ui.label("Break Length (Minutes)");
+
+        let response = ui.text_edit_singleline(&mut self.pomodoro_break_minutes_config);
+        self.process_response(&response);
+        ids.push(response.id);
+
+        if let Some(err) = &self.pomodoro_break_minutes_error {
+            ui.label(RichText::new(err).color(Color32::RED));
+        }
+
         ui.label("Reopen Last Project on Launch");
 
         let response = ui.checkbox(&mut self.reopen_last_config, "");
         self.process_response(&response);
         ids.push(response.id);
 
+        ui.label("Number of Recent Projects");
+
+        let response = ui.text_edit_singleline(&mut self.max_recent_projects_config);
+        self.process_response(&response);
+        ids.push(response.id);
+
+        if let Some(err) = &self.max_recent_projects_error {
+            ui.label(RichText::new(err).color(Color32::RED));
+        }
+
+        ui.label("Create Backup When Opening a Project  ℹ").on_hover_text(
+            "Copy the whole project into its .backups folder every time it's opened, so there's \
+            always a version from before the current session",
+        );
+
+        let response = ui.checkbox(&mut self.backup_on_open_config, "");
+        self.process_response(&response);
+        ids.push(response.id);
+
+        ui.label("Backups to Keep");
+
+        let response = ui.text_edit_singleline(&mut self.backups_to_keep_config);
+        self.process_response(&response);
+        ids.push(response.id);
+
+        if let Some(err) = &self.backups_to_keep_error {
+            ui.label(RichText::new(err).color(Color32::RED));
+        }
+
         ui.label("Dictionary Location");
 
         let response = ui.text_edit_singleline(&mut self.dictionary_location_config);
@@ -142,6 +459,18 @@ impl SettingsPage {
             ui.label(RichText::new(err).color(Color32::RED));
         }
 
+        ui.label("Export Post-Processing Command  ℹ").on_hover_text(
+            "Run after every export is written, e.g., `pandoc {input} -o {output}.docx`. \
+            {input} is the exported file and {output} is the same path without the extension. \
+            Leave this empty to just write the export",
+        );
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.export_post_command_config).hint_text("disabled"),
+        );
+        self.process_response(&response);
+        ids.push(response.id);
+
         if let Some(next_update) = self.next_update {
             let now = SystemTime::now();
             if now >= next_update {
@@ -1,8 +1,14 @@
+mod auto_capitalize;
 mod format;
 mod spellcheck;
+mod undo;
+
+pub use spellcheck::IgnorePatterns;
 
 use std::ops::Range;
+use std::time::Instant;
 
+use crate::components::text::task_items;
 use crate::ui::prelude::*;
 use crate::ui::project_editor::search::textbox_search;
 use egui::text::{CCursorRange, LayoutJob};
@@ -10,6 +16,44 @@ use egui::{Key, KeyboardShortcut, Modifiers, TextBuffer};
 
 pub type Store = RenderDataStore<usize, TextBox>;
 
+/// Select a range of characters in a text box that was just drawn, focusing it and scrolling so
+/// that the start of the range is visible (at `align`)
+fn select_and_scroll_to(
+    ui: &egui::Ui,
+    output: &egui::text_edit::TextEditOutput,
+    start_pos: usize,
+    end_pos: usize,
+    align: egui::Align,
+) {
+    let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), output.response.id) else {
+        return;
+    };
+
+    let ccursor = egui::text::CCursorRange::two(
+        egui::text::CCursor::new(start_pos),
+        egui::text::CCursor::new(end_pos),
+    );
+
+    // Set the positition of the cursor in the text
+    state.cursor.set_char_range(Some(ccursor));
+    state.store(ui.ctx(), output.response.id);
+    ui.ctx()
+        .memory_mut(|mem| mem.request_focus(output.response.id));
+
+    // Find the position of the cursor position in the rendered text output
+    let cursor_pos_in_galley = output
+        .galley
+        .pos_from_cursor(egui::text::CCursor::new(start_pos));
+
+    let text_edit_pos = output.response.rect;
+
+    // Add the minimum of the text edit widget to the galley position to get the
+    // absolute rectangle
+    let cursor_absolute_pos = cursor_pos_in_galley.translate(text_edit_pos.min.to_vec2());
+
+    ui.scroll_to_rect(cursor_absolute_pos, Some(align));
+}
+
 #[derive(Debug, Default)]
 pub struct TextBox {
     // highlighter: MemoizedMarkdownHighlighter,
@@ -22,6 +66,12 @@ pub struct TextBox {
     // manually force the layout to be redone
     redo_layout: bool,
 
+    // the character position of the last letter that was capitalized automatically, so that it
+    // isn't capitalized again if it gets deleted and retyped in lowercase
+    auto_capitalized_at: Option<usize>,
+
+    undo_history: undo::UndoHistory,
+
     // formatting information that the highlight job was for
     // used to know when highlight needs to be redone
     text_signature: (usize, usize),
@@ -107,12 +157,42 @@ impl TextBox {
 
 pub const SHORTCUT_BOLD: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::B);
 pub const SHORTCUT_ITALICS: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::I);
+pub const SHORTCUT_UNDO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+pub const SHORTCUT_REDO: KeyboardShortcut = KeyboardShortcut::new(
+    Modifiers {
+        shift: true,
+        ..Modifiers::COMMAND
+    },
+    Key::Z,
+);
+pub const SHORTCUT_REDO_ALT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Y);
 
 impl Text {
     pub fn ui(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext) -> Response {
+        // Nothing can be saved while the project is read-only, so don't let it be changed either
+        let read_only = ctx.read_only;
+        self.show(ui, ctx, read_only)
+    }
+
+    /// Draw the text without allowing it to be changed (e.g., for a locked scene)
+    pub fn read_only_ui(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext) -> Response {
+        self.show(ui, ctx, true)
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui, ctx: &mut EditorContext, read_only: bool) -> Response {
         let rdata = ctx.stores.text_box.get(&self.struct_uid);
         let text_box: &mut TextBox = &mut rdata.borrow_mut();
 
+        let text_edit_id = ui.make_persistent_id(self.struct_uid);
+
+        if !read_only && ctx.settings.auto_capitalize() {
+            self.auto_capitalize_input(ui, text_box, text_edit_id);
+        }
+
+        // Undo is handled here instead of by the text edit, so that the history covers every
+        // change to the text (and isn't lost when the text box loses focus)
+        let undone = !read_only && self.undo_input(ui, text_box, text_edit_id);
+
         let mut layouter = |ui: &egui::Ui, text: &dyn TextBuffer, wrap_width: f32| {
             let mut layout_job = text_box.get_layout(ui, text, ctx);
             layout_job.wrap.max_width = wrap_width;
@@ -126,9 +206,29 @@ impl Text {
             .layouter(&mut layouter)
             .min_size(egui::Vec2 { x: 50.0, y: 100.0 })
             .lock_focus(true)
+            .interactive(!read_only)
             .id_salt(text_box_id)
             .show(ui);
 
+        if output.response.has_focus() && !read_only {
+            ctx.focused_text_box = Some(output.response.id);
+        }
+
+        if undone {
+            output.response.mark_changed();
+        }
+
+        if ctx.settings.show_invisibles() {
+            format::paint_invisibles(ui, &output.galley, output.galley_pos);
+        }
+
+        if !read_only {
+            let cursor = output
+                .cursor_range
+                .map_or(0, |cursor_range| cursor_range.primary.index);
+            text_box.undo_history.record(self, cursor, Instant::now());
+        }
+
         // Select the cursor text and scroll to it if requried
         if ctx.search.active
             && ctx.search.goto_focus
@@ -158,38 +258,28 @@ impl Text {
 
             if let Some(start_pos) = start_char_pos
                 && let Some(end_pos) = end_char_pos
-                && let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), output.response.id)
             {
-                let ccursor = egui::text::CCursorRange::two(
-                    egui::text::CCursor::new(start_pos),
-                    egui::text::CCursor::new(end_pos),
-                );
-
-                // Set the positition of the cursor in the text
-                state.cursor.set_char_range(Some(ccursor));
-                state.store(ui.ctx(), output.response.id);
-                ui.ctx()
-                    .memory_mut(|mem| mem.request_focus(output.response.id));
-
-                // Find the position of the cursor position in the rendered text output
-                let cursor_pos_in_galley = output
-                    .galley
-                    .pos_from_cursor(egui::text::CCursor::new(start_pos));
-
-                let text_edit_pos = output.response.rect;
-
-                // Add the minimum of the text edit widget to the galley position to get the
-                // absolute rectangle
-                let cursor_absolute_pos =
-                    cursor_pos_in_galley.translate(text_edit_pos.min.to_vec2());
-
-                ui.scroll_to_rect(cursor_absolute_pos, Some(egui::Align::Center));
+                select_and_scroll_to(ui, &output, start_pos, end_pos, egui::Align::Center);
             }
 
             // We've gone to our focus (or made our best effort), we're done
             ctx.search.goto_focus = false;
         }
 
+        if let Some((uid, offset)) = ctx.go_to_text
+            && uid == self.struct_uid
+        {
+            // The text could have changed since the offset was found, so it might not be on a
+            // character boundary anymore
+            let char_pos = self
+                .text
+                .char_indices()
+                .take_while(|(char_offset, _)| *char_offset < offset)
+                .count();
+            select_and_scroll_to(ui, &output, char_pos, char_pos, egui::Align::Min);
+            ctx.go_to_text = None;
+        }
+
         // Keep track of where we're typing and if it's new, used in spellcheck logic later on
         if let Some(cursor_range) = output.cursor_range {
             // Primary cursor tells us where we're at in the text edit box
@@ -226,6 +316,7 @@ impl Text {
         ui.input_mut(|i| {
             for event in &i.events {
                 if let egui::Event::Paste(contents) = event
+                    && !read_only
                     && contents.contains(['“', '”', '‘', '’'])
                 {
                     self.clean_up_quotes();
@@ -287,7 +378,7 @@ impl Text {
                     .memory_mut(|mem| mem.request_focus(output.response.id));
             }
 
-            if !ctx.spellcheck_status.correct {
+            if !read_only && !ctx.spellcheck_status.correct {
                 ui.separator();
                 for suggestion in ctx.spellcheck_status.suggestions.iter() {
                     if ui.button(suggestion).clicked() {
@@ -324,7 +415,8 @@ impl Text {
         });
 
         // process hotkeys like ctrl-b and ctrl-i:
-        if let Some(focused_window) = ui.ctx().memory(|i| i.focused())
+        if !read_only
+            && let Some(focused_window) = ui.ctx().memory(|i| i.focused())
             && focused_window == output.response.id
             && let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), output.response.id)
             && let Some(output_cursor_range) = state.cursor.char_range()
@@ -352,6 +444,34 @@ impl Text {
         output.response
     }
 
+    /// Draws a checkbox for every task list item (`- [ ]`) in the text, which check the items off
+    /// in the text itself when clicked. Returns `None` if there aren't any items
+    pub fn task_list_ui(&mut self, ui: &mut egui::Ui) -> Option<Response> {
+        let tasks = task_items(&self.text);
+        if tasks.is_empty() {
+            return None;
+        }
+
+        let mut toggled = None;
+        let mut response = ui
+            .vertical(|ui| {
+                for task in &tasks {
+                    let mut checked = task.checked;
+                    if ui.checkbox(&mut checked, &task.label).changed() {
+                        toggled = Some(task);
+                    }
+                }
+            })
+            .response;
+
+        if let Some(task) = toggled {
+            self.toggle_task(task);
+            response.mark_changed();
+        }
+
+        Some(response)
+    }
+
     /// Toggles formatting like italic or bold
     fn toggle_formatting(&mut self, cursor_range: &mut CCursorRange, pattern: &str) {
         let current_working_range = self.get_selection_range_trimmed(cursor_range);
@@ -514,6 +634,103 @@ impl Text {
         text_box.word_count
     }
 
+    /// Undo or redo if the shortcut was pressed in this text box, moving the cursor to where the
+    /// change was. Returns true if the text changed
+    fn undo_input(
+        &mut self,
+        ui: &mut egui::Ui,
+        text_box: &mut TextBox,
+        text_edit_id: egui::Id,
+    ) -> bool {
+        if !ui.memory(|mem| mem.has_focus(text_edit_id)) {
+            return false;
+        }
+
+        let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), text_edit_id) else {
+            return false;
+        };
+
+        let cursor = state
+            .cursor
+            .char_range()
+            .map_or(0, |cursor_range| cursor_range.primary.index);
+
+        // Check for redo first, since the undo shortcut would also match ctrl-shift-z
+        let new_cursor = ui.input_mut(|i| {
+            if i.consume_shortcut(&SHORTCUT_REDO) || i.consume_shortcut(&SHORTCUT_REDO_ALT) {
+                text_box.undo_history.redo(self)
+            } else if i.consume_shortcut(&SHORTCUT_UNDO) {
+                text_box.undo_history.undo(self, cursor)
+            } else {
+                None
+            }
+        });
+
+        let Some(new_cursor) = new_cursor else {
+            return false;
+        };
+
+        state
+            .cursor
+            .set_char_range(Some(CCursorRange::one(egui::text::CCursor::new(
+                new_cursor,
+            ))));
+        state.store(ui.ctx(), text_edit_id);
+        true
+    }
+
+    /// Capitalize a letter that's about to be typed if it starts a sentence. This changes the input
+    /// event before the text edit sees it, so that the cursor and undo work as usual
+    fn auto_capitalize_input(
+        &self,
+        ui: &mut egui::Ui,
+        text_box: &mut TextBox,
+        text_edit_id: egui::Id,
+    ) {
+        if !ui.memory(|mem| mem.has_focus(text_edit_id)) {
+            return;
+        }
+
+        let Some(cursor_range) = egui::TextEdit::load_state(ui.ctx(), text_edit_id)
+            .and_then(|state| state.cursor.char_range())
+        else {
+            return;
+        };
+
+        // Typing replaces the selection, so the letter goes where the selection starts
+        let cursor_pos = cursor_range.primary.index.min(cursor_range.secondary.index);
+        let byte_pos = self
+            .text
+            .char_indices()
+            .nth(cursor_pos)
+            .map_or(self.text.len(), |(byte_pos, _)| byte_pos);
+
+        ui.input_mut(|i| {
+            // Only the first text in the frame can be right after the cursor
+            let Some(typed) = i.events.iter_mut().find_map(|event| match event {
+                egui::Event::Text(typed) => Some(typed),
+                _ => None,
+            }) else {
+                return;
+            };
+
+            if !auto_capitalize::starts_sentence(&self.text[..byte_pos]) {
+                return;
+            }
+
+            // The capital letter was deleted and retyped in lowercase, so it's deliberate
+            if text_box.auto_capitalized_at == Some(cursor_pos) {
+                text_box.auto_capitalized_at = None;
+                return;
+            }
+
+            if let Some(capitalized) = auto_capitalize::capitalize_first(typed) {
+                *typed = capitalized;
+                text_box.auto_capitalized_at = Some(cursor_pos);
+            }
+        });
+    }
+
     /// Remove *all* smart quotes from text that was just pasted into. This could probably be made
     /// more efficient (e.g., we technically don't need to do this in a separate pass from formatting),
     /// but this works.
@@ -0,0 +1,108 @@
+/// Abbreviations (lowercased, without their final `.`) that usually aren't the end of a sentence.
+/// Abbreviations with a `.` in the middle (e.g., "e.g.") are caught without being listed
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "st", "jr", "sr", "prof", "vs", "cf", "etc", "approx", "no", "vol",
+];
+
+/// Whether a letter typed after `before` (the text up to the cursor) starts a new sentence or
+/// paragraph, and so should be capitalized
+pub fn starts_sentence(before: &str) -> bool {
+    let current_line = before.rsplit('\n').next().unwrap_or(before);
+
+    // Nothing but whitespace or markdown markers (list items, headings, quotes) on the line so far
+    if current_line
+        .chars()
+        .all(|c| c.is_whitespace() || matches!(c, '#' | '-' | '*' | '>'))
+    {
+        return true;
+    }
+
+    // The sentence has to actually be over, which means a space after the punctuation
+    if !before.ends_with(char::is_whitespace) {
+        return false;
+    }
+
+    // Quotes, brackets, and formatting can close after the punctuation
+    let sentence = before
+        .trim_end()
+        .trim_end_matches(['"', '\'', '”', '’', ')', ']', '*', '_']);
+
+    // An ellipsis usually trails off rather than ending the sentence
+    if sentence.ends_with("..") || sentence.ends_with('…') {
+        return false;
+    }
+
+    if sentence.ends_with(['!', '?']) {
+        return true;
+    }
+
+    let Some(without_period) = sentence.strip_suffix('.') else {
+        return false;
+    };
+
+    let last_word = without_period
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or(without_period)
+        .trim_start_matches(['"', '\'', '“', '‘', '(', '[', '*', '_']);
+
+    // "e.g.", "U.S.", and initials like "J." don't end sentences
+    if last_word.contains('.') || last_word.chars().count() == 1 {
+        return false;
+    }
+
+    !ABBREVIATIONS.contains(&last_word.to_lowercase().as_str())
+}
+
+/// Capitalize the first character of typed text, or `None` if it isn't a lowercase letter
+pub fn capitalize_first(typed: &str) -> Option<String> {
+    let mut chars = typed.chars();
+    let first = chars.next().filter(|c| c.is_lowercase())?;
+
+    let mut capitalized: String = first.to_uppercase().collect();
+    capitalized.push_str(chars.as_str());
+    Some(capitalized)
+}
+
+#[test]
+fn test_starts_sentence() {
+    assert!(starts_sentence("It rained. "));
+    assert!(starts_sentence("Really? "));
+    assert!(starts_sentence("He shouted, \"Stop!\" "));
+    assert!(starts_sentence("(It was late.) "));
+
+    // paragraph starts
+    assert!(starts_sentence(""));
+    assert!(starts_sentence("The end.\n\n"));
+    assert!(starts_sentence("A list:\n- "));
+    assert!(starts_sentence("## "));
+
+    // the middle of a sentence
+    assert!(!starts_sentence("It was "));
+    assert!(!starts_sentence("It rained."));
+    assert!(!starts_sentence("It rained, "));
+
+    // abbreviations and trailing off
+    assert!(!starts_sentence("Fruit, e.g. "));
+    assert!(!starts_sentence("She met Dr. "));
+    assert!(!starts_sentence("Written by J. "));
+    assert!(!starts_sentence("Apples vs. "));
+    assert!(!starts_sentence("Well... "));
+    assert!(!starts_sentence("Well… "));
+}
+
+#[test]
+fn test_capitalize_first() {
+    // `. a` becomes `. A`
+    let text = "It rained. ";
+    assert!(starts_sentence(text));
+    assert_eq!(
+        format!("{text}{}", capitalize_first("a").unwrap()),
+        "It rained. A"
+    );
+
+    assert_eq!(capitalize_first("émile"), Some("Émile".to_string()));
+    assert_eq!(capitalize_first("A"), None);
+    assert_eq!(capitalize_first("\""), None);
+    assert_eq!(capitalize_first(""), None);
+}
@@ -1,10 +1,11 @@
+use crate::components::text::comment_ranges;
 use crate::ui::prelude::*;
 use crate::ui::{
     project_editor::search::textbox_search::{TextBoxSearchResult, WordFind},
     text_box::spellcheck::find_misspelled_words,
 };
 
-use egui::{Color32, Stroke, TextFormat, text::LayoutJob};
+use egui::{Align2, Color32, Galley, Pos2, Stroke, TextFormat, text::LayoutJob};
 
 #[derive(Debug, Clone, Copy)]
 enum StyleOption {
@@ -14,6 +15,7 @@ enum StyleOption {
     NewLine,
     SearchHighlight,
     SearchHighlightFocus,
+    Comment,
     None,
 }
 
@@ -32,6 +34,7 @@ struct Style {
     search_highlight: bool,
     search_highlight_focus: bool,
     newline: bool,
+    comment: bool,
 }
 
 impl Style {
@@ -43,12 +46,17 @@ impl Style {
             StyleOption::NewLine => self.newline = marker.on,
             StyleOption::SearchHighlight => self.search_highlight = marker.on,
             StyleOption::SearchHighlightFocus => self.search_highlight_focus = marker.on,
+            StyleOption::Comment => self.comment = marker.on,
             _ => (),
         }
     }
 }
 
-fn format_from_style(egui_style: &egui::Style, text_style: &Style) -> egui::text::TextFormat {
+fn format_from_style(
+    egui_style: &egui::Style,
+    text_style: &Style,
+    show_comments: bool,
+) -> egui::text::TextFormat {
     let Style {
         strong,
         italic: italics,
@@ -56,6 +64,7 @@ fn format_from_style(egui_style: &egui::Style, text_style: &Style) -> egui::text
         search_highlight,
         search_highlight_focus,
         newline: _newline,
+        comment,
     } = *text_style;
 
     let font_id = egui_style
@@ -70,7 +79,16 @@ fn format_from_style(egui_style: &egui::Style, text_style: &Style) -> egui::text
         ..Default::default()
     };
 
-    if strong {
+    if comment {
+        format.color = egui_style.visuals.weak_text_color();
+
+        if !show_comments {
+            // we can't actually remove the text from the text box, so shrink it down to (almost)
+            // nothing instead
+            format.font_id.size = 1.0;
+            format.color = Color32::TRANSPARENT;
+        }
+    } else if strong {
         format.color = egui_style.visuals.strong_text_color()
     } else {
         format.color = egui_style.visuals.text_color()
@@ -230,6 +248,26 @@ fn format_rule_spellcheck(text: &str, ctx: &EditorContext) -> Vec<StyleMarker> {
         .collect()
 }
 
+fn format_rule_comments(text: &str, _ctx: &EditorContext) -> Vec<StyleMarker> {
+    comment_ranges(text)
+        .into_iter()
+        .flat_map(|range| {
+            [
+                StyleMarker {
+                    idx: range.start,
+                    style: StyleOption::Comment,
+                    on: true,
+                },
+                StyleMarker {
+                    idx: range.end,
+                    style: StyleOption::Comment,
+                    on: false,
+                },
+            ]
+        })
+        .collect()
+}
+
 fn format_rule_search(_text: &str, search_result: &TextBoxSearchResult) -> Vec<StyleMarker> {
     let mut res = Vec::new();
 
@@ -273,13 +311,14 @@ pub fn compute_layout_job(
     search_result_focus: Option<&WordFind>,
     egui_style: &egui::Style,
 ) -> LayoutJob {
-    let mut applied_rules = Vec::with_capacity(5);
+    let mut applied_rules = Vec::with_capacity(6);
 
     let (bold, italic) = format_rule_bold_italic(text, ctx);
     applied_rules.push(bold);
     applied_rules.push(italic);
     applied_rules.push(format_rule_newlines(text, ctx));
     applied_rules.push(format_rule_spellcheck(text, ctx));
+    applied_rules.push(format_rule_comments(text, ctx));
     if let Some(search_result) = search_result {
         applied_rules.push(format_rule_search(text, search_result));
     }
@@ -297,6 +336,7 @@ pub fn compute_layout_job(
     let mut job = LayoutJob::default();
     let mut text_style = Style::default();
     let mut start = 0;
+    let show_comments = ctx.settings.show_comments();
 
     for marker in styles {
         let end = marker.idx;
@@ -313,7 +353,7 @@ pub fn compute_layout_job(
             job.append(
                 &text[start..end],
                 leading_space,
-                format_from_style(egui_style, &text_style),
+                format_from_style(egui_style, &text_style, show_comments),
             );
             text_style.newline = false;
 
@@ -328,6 +368,62 @@ pub fn compute_layout_job(
     job
 }
 
+/// The mark drawn over a whitespace character when invisibles are shown
+fn invisible_mark(chr: char) -> Option<&'static str> {
+    match chr {
+        ' ' => Some("·"),
+        '\t' => Some("→"),
+        _ => None,
+    }
+}
+
+/// Draw marks over the whitespace in laid out text: dots for spaces, arrows for tabs, and
+/// pilcrows at the end of each line. This is painted on top of the text rather than being part of
+/// the layout job, so that the text (and every cursor position in it) stays exactly the same
+pub fn paint_invisibles(ui: &Ui, galley: &Galley, galley_pos: Pos2) {
+    let painter = ui.painter();
+    let color = ui.visuals().weak_text_color();
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let clip_rect = ui.clip_rect();
+
+    for row in &galley.rows {
+        let row_rect = row.rect().translate(galley_pos.to_vec2());
+        if !clip_rect.intersects(row_rect) {
+            continue;
+        }
+
+        for glyph in &row.glyphs {
+            let Some(mark) = invisible_mark(glyph.chr) else {
+                continue;
+            };
+
+            // hidden comments are shrunk down to almost nothing, and so is the whitespace in them
+            if glyph.advance_width < 1.0 {
+                continue;
+            }
+
+            let glyph_rect = glyph.logical_rect().translate(row_rect.min.to_vec2());
+            painter.text(
+                glyph_rect.center(),
+                Align2::CENTER_CENTER,
+                mark,
+                font_id.clone(),
+                color,
+            );
+        }
+
+        if row.ends_with_newline {
+            painter.text(
+                row_rect.right_center(),
+                Align2::LEFT_CENTER,
+                "¶",
+                font_id.clone(),
+                color,
+            );
+        }
+    }
+}
+
 fn vec_merge(formats: Vec<Vec<StyleMarker>>) -> Vec<StyleMarker> {
     let mut res = Vec::new();
     let mut iters: Vec<_> = formats
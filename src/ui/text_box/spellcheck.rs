@@ -1,6 +1,8 @@
 use crate::ui::prelude::*;
 
+use crate::ui::project_editor::TypingStatus;
 use cow_utils::CowUtils;
+use spellbook::Dictionary;
 
 use std::borrow::Cow;
 use std::ops::Range;
@@ -122,46 +124,119 @@ fn test_trim_word_for_spellcheck() {
     assert_eq!(trim_word_for_spellcheck("*wo*rd").1, 1..6);
 }
 
+/// Patterns for words that shouldn't be flagged as misspelled, for vocabularies that are too big
+/// (or too regular) to ignore one word at a time
+#[derive(Debug, Default)]
+pub struct IgnorePatterns(Vec<Regex>);
+
+impl IgnorePatterns {
+    /// Compile the patterns, each of which has to match an entire word. Invalid patterns are
+    /// skipped (with a warning) so that the rest still work
+    pub fn new(patterns: &[String]) -> Self {
+        Self(
+            patterns
+                .iter()
+                .filter(|pattern| !pattern.trim().is_empty())
+                .filter_map(|pattern| match Regex::new(&format!("^(?:{pattern})$")) {
+                    Ok(regex) => Some(regex),
+                    Err(err) => {
+                        log::warn!("Skipping invalid spellcheck ignore pattern {pattern:?}: {err}");
+                        None
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    pub fn matches(&self, word: &str) -> bool {
+        self.0.iter().any(|regex| regex.is_match(word))
+    }
+}
+
 pub fn find_misspelled_words(text: &str, ctx: &EditorContext) -> Vec<(usize, usize)> {
+    // we only spellcheck if we have a dictionary:
+    match &ctx.dictionary_state.dictionary {
+        Some(dict) => find_misspelled_words_with(
+            text,
+            dict,
+            &ctx.dictionary_state.ignore_patterns,
+            &ctx.typing_status,
+        ),
+        None => Vec::new(),
+    }
+}
+
+fn find_misspelled_words_with(
+    text: &str,
+    dict: &Dictionary,
+    ignore_patterns: &IgnorePatterns,
+    typing_status: &TypingStatus,
+) -> Vec<(usize, usize)> {
     // Indexes of all of the misspelled words
     let mut misspelled_words = Vec::new();
 
-    // we only spellcheck if we have a dictionary:
-    if let Some(dict) = &ctx.dictionary_state.dictionary {
-        // words in this case means everything that isn't whitespace, we'll take care of
-        // trimming. We also split on `/` because those separate words without whitespace
-        static WORD_REGEX: SavedRegex = SavedRegex::new(|| Regex::new(r"([^\s/]+)").unwrap());
-
-        for word_match in WORD_REGEX.find_iter(text) {
-            let (check_word, word_range) = trim_word_for_spellcheck(word_match.as_str());
-
-            // floating punctuation isn't misspelled
-            if !check_word.is_empty() && !dict.check(&check_word) {
-                // We have a misspelled word now, compute boundaries
-
-                let start_pos = word_match.start() + word_range.start;
-                let end_pos = word_match.start() + word_range.end;
-
-                assert!(start_pos < end_pos);
-
-                // Check for the word that's currently being typed and
-                // avoid adding it to the list of misspelled words. This delays
-                // the detection a little bit, but I don't have a super nice way
-                // of getting that to work
-                if ctx.typing_status.is_new_word
-                    && ctx.typing_status.current_word.contains(&start_pos)
-                {
-                    continue;
-                }
-
-                misspelled_words.push((start_pos, end_pos));
+    // words in this case means everything that isn't whitespace, we'll take care of
+    // trimming. We also split on `/` because those separate words without whitespace
+    static WORD_REGEX: SavedRegex = SavedRegex::new(|| Regex::new(r"([^\s/]+)").unwrap());
+
+    for word_match in WORD_REGEX.find_iter(text) {
+        let (check_word, word_range) = trim_word_for_spellcheck(word_match.as_str());
+
+        // floating punctuation isn't misspelled
+        if !check_word.is_empty()
+            && !dict.check(&check_word)
+            && !ignore_patterns.matches(&check_word)
+        {
+            // We have a misspelled word now, compute boundaries
+
+            let start_pos = word_match.start() + word_range.start;
+            let end_pos = word_match.start() + word_range.end;
+
+            assert!(start_pos < end_pos);
+
+            // Check for the word that's currently being typed and
+            // avoid adding it to the list of misspelled words. This delays
+            // the detection a little bit, but I don't have a super nice way
+            // of getting that to work
+            if typing_status.is_new_word && typing_status.current_word.contains(&start_pos) {
+                continue;
             }
+
+            misspelled_words.push((start_pos, end_pos));
         }
     }
 
     misspelled_words
 }
 
+#[test]
+fn test_ignore_patterns() {
+    let dict = Dictionary::new("SET UTF-8\n", "3\nthe\nelf\nsang\n").unwrap();
+    let text = "the elf Aerandiel sang to Gabrielle";
+
+    let misspelled = |patterns: &[&str]| {
+        let patterns: Vec<String> = patterns.iter().map(|pattern| pattern.to_string()).collect();
+        find_misspelled_words_with(
+            text,
+            &dict,
+            &IgnorePatterns::new(&patterns),
+            &TypingStatus::default(),
+        )
+        .into_iter()
+        .map(|(start, end)| &text[start..end])
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(misspelled(&[]), vec!["Aerandiel", "to", "Gabrielle"]);
+
+    // patterns have to match the entire word
+    assert_eq!(misspelled(&[r"\w+iel"]), vec!["to", "Gabrielle"]);
+    assert_eq!(misspelled(&[r"\w+iel", "to|Gab.*"]), Vec::<&str>::new());
+
+    // invalid patterns are skipped without affecting the rest
+    assert_eq!(misspelled(&["(bad", r"\w+iel"]), vec!["to", "Gabrielle"]);
+}
+
 pub fn word_count(text: &str) -> usize {
     static WORD_COUNT_REGEX: SavedRegex = SavedRegex::new(|| Regex::new(r"\s+").unwrap());
     let words: Vec<&str> = WORD_COUNT_REGEX.split(text).collect();
@@ -0,0 +1,191 @@
+use crate::components::text::Text;
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long typing has to pause before the changes become their own undo step
+const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// The longest that continuous typing is kept in a single undo step
+const MAX_GROUP_DURATION: Duration = Duration::from_secs(10);
+
+/// How many undo steps are kept for each text box, older ones are forgotten
+const MAX_UNDO_STEPS: usize = 100;
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: String,
+    /// Where the cursor was (as a character index) when the snapshot was taken
+    cursor: usize,
+}
+
+/// The undo and redo history of a text box. This is kept outside of the text edit so that it
+/// isn't lost when the text box loses focus, and so that it also covers changes that were made
+/// some other way (e.g., formatting shortcuts or replacing text)
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    undo_stack: VecDeque<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    /// The most recent undo step, which undoing returns to. `None` until the text is first seen
+    current: Option<Snapshot>,
+    /// The version of the text that was last seen, so it only has to be compared when it changes
+    seen_version: Option<usize>,
+    /// When the changes that aren't part of an undo step yet were started and last changed
+    pending: Option<(Instant, Instant)>,
+}
+
+impl UndoHistory {
+    /// Look for changes to the text, turning them into an undo step once typing has paused
+    pub fn record(&mut self, text: &Text, cursor: usize, now: Instant) {
+        let Some(current) = &self.current else {
+            self.current = Some(Snapshot {
+                text: text.text.clone(),
+                cursor,
+            });
+            self.seen_version = Some(text.version);
+            return;
+        };
+
+        if self.seen_version != Some(text.version) {
+            self.seen_version = Some(text.version);
+
+            if text.as_str() == current.text {
+                // changed back to how it was (e.g., by undoing)
+                self.pending = None;
+            } else {
+                let started = self.pending.map_or(now, |(started, _)| started);
+                self.pending = Some((started, now));
+            }
+        }
+
+        if let Some((started, last_change)) = self.pending
+            && (now.duration_since(last_change) >= DEBOUNCE
+                || now.duration_since(started) >= MAX_GROUP_DURATION)
+        {
+            self.commit(text, cursor);
+        }
+    }
+
+    /// Make the changes so far into an undo step
+    fn commit(&mut self, text: &Text, cursor: usize) {
+        let snapshot = Snapshot {
+            text: text.text.clone(),
+            cursor,
+        };
+
+        if let Some(previous) = self.current.replace(snapshot) {
+            if self.undo_stack.len() == MAX_UNDO_STEPS {
+                self.undo_stack.pop_front();
+            }
+            self.undo_stack.push_back(previous);
+        }
+
+        self.redo_stack.clear();
+        self.pending = None;
+    }
+
+    /// Go back to the last undo step (including any changes that haven't become a step yet),
+    /// returning where the cursor should go, or `None` if there's nothing to undo
+    pub fn undo(&mut self, text: &mut Text, cursor: usize) -> Option<usize> {
+        if self.has_changed(text) {
+            self.commit(text, cursor);
+        }
+
+        let previous = self.undo_stack.pop_back()?;
+        if let Some(current) = self.current.replace(previous.clone()) {
+            self.redo_stack.push(current);
+        }
+
+        self.restore(text, &previous);
+        Some(previous.cursor)
+    }
+
+    /// Go forward to the step that was last undone, returning where the cursor should go, or
+    /// `None` if there's nothing to redo
+    pub fn redo(&mut self, text: &mut Text) -> Option<usize> {
+        // typing after undoing starts a new history, so there's nothing to redo anymore
+        if self.has_changed(text) {
+            return None;
+        }
+
+        let next = self.redo_stack.pop()?;
+        if let Some(current) = self.current.replace(next.clone()) {
+            self.undo_stack.push_back(current);
+        }
+
+        self.restore(text, &next);
+        Some(next.cursor)
+    }
+
+    /// If the text is different from the most recent undo step
+    fn has_changed(&self, text: &Text) -> bool {
+        self.current
+            .as_ref()
+            .is_some_and(|current| current.text != text.text)
+    }
+
+    fn restore(&mut self, text: &mut Text, snapshot: &Snapshot) {
+        text.text.clone_from(&snapshot.text);
+        text.version += 1;
+        self.seen_version = Some(text.version);
+        self.pending = None;
+    }
+}
+
+#[test]
+fn test_undo_history() {
+    let start = Instant::now();
+    let mut history = UndoHistory::default();
+    let mut text: Text = "It was".to_string().into();
+    history.record(&text, 6, start);
+
+    // Two bursts of typing, separated by a pause
+    text.push_str(" raining");
+    history.record(&text, 14, start + Duration::from_millis(100));
+    history.record(&text, 14, start + Duration::from_secs(2));
+
+    text.push_str(" again.");
+    history.record(&text, 21, start + Duration::from_secs(3));
+
+    // Undoing includes the typing that hadn't become a step yet
+    assert_eq!(history.undo(&mut text, 21), Some(14));
+    assert_eq!(text.as_str(), "It was raining");
+    assert_eq!(history.undo(&mut text, 14), Some(6));
+    assert_eq!(text.as_str(), "It was");
+    assert_eq!(history.undo(&mut text, 6), None);
+    assert_eq!(text.as_str(), "It was");
+
+    assert_eq!(history.redo(&mut text), Some(14));
+    assert_eq!(text.as_str(), "It was raining");
+    assert_eq!(history.redo(&mut text), Some(21));
+    assert_eq!(text.as_str(), "It was raining again.");
+    assert_eq!(history.redo(&mut text), None);
+
+    // Typing after an undo means the undone text can't be redone
+    history.undo(&mut text, 21);
+    text.push('!');
+    history.record(&text, 15, start + Duration::from_secs(10));
+    assert_eq!(history.redo(&mut text), None);
+    assert_eq!(text.as_str(), "It was raining!");
+    assert_eq!(history.undo(&mut text, 15), Some(14));
+    assert_eq!(text.as_str(), "It was raining");
+}
+
+#[test]
+fn test_undo_history_long_typing() {
+    let start = Instant::now();
+    let mut history = UndoHistory::default();
+    let mut text = Text::default();
+    history.record(&text, 0, start);
+
+    // Typing without ever pausing still gets split up into steps
+    for second in 1..=15 {
+        text.push('a');
+        history.record(&text, second, start + Duration::from_secs(second as u64));
+    }
+
+    assert_eq!(history.undo(&mut text, 15), Some(11));
+    assert_eq!(text.as_str(), "a".repeat(11));
+    assert_eq!(history.undo(&mut text, 11), Some(0));
+    assert_eq!(text.as_str(), "");
+}
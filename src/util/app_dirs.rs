@@ -0,0 +1,40 @@
+use directories::ProjectDirs;
+
+use std::path::{Path, PathBuf};
+
+/// Where the editor keeps its settings and app data
+#[derive(Debug, Clone)]
+pub struct AppDirs {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+}
+
+impl AppDirs {
+    /// Keep everything in `data_dir` if it's set (e.g., for a portable install, or to keep configs
+    /// separate), otherwise use the platform's normal directories
+    pub fn new(data_dir: Option<PathBuf>) -> Self {
+        match data_dir {
+            Some(data_dir) => Self {
+                config_dir: data_dir.clone(),
+                data_dir,
+            },
+            None => {
+                let project_dirs = ProjectDirs::from("", "", "cheese-paper")
+                    .expect("it should be possible to write to system dirs");
+
+                Self {
+                    config_dir: project_dirs.config_dir().to_path_buf(),
+                    data_dir: project_dirs.data_dir().to_path_buf(),
+                }
+            }
+        }
+    }
+
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+}
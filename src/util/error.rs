@@ -4,11 +4,25 @@ use std::fmt::Display;
 #[derive(Debug)]
 pub struct CheeseError {
     msg: String,
+    /// The kind of I/O error this came from, if it came from one
+    io_kind: Option<std::io::ErrorKind>,
 }
 
 impl CheeseError {
     pub fn new<S: Into<String>>(msg: S) -> Self {
-        Self { msg: msg.into() }
+        Self {
+            msg: msg.into(),
+            io_kind: None,
+        }
+    }
+
+    /// If this was caused by not being allowed to write (e.g., the project is on read-only media
+    /// or its permissions changed), rather than something going wrong with the write itself
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self.io_kind,
+            Some(std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem)
+        )
     }
 }
 
@@ -27,7 +41,10 @@ macro_rules! cheese_error {
 
 impl From<std::io::Error> for CheeseError {
     fn from(err: std::io::Error) -> Self {
-        CheeseError::new(format!("I/O error: {err}"))
+        CheeseError {
+            msg: format!("I/O error: {err}"),
+            io_kind: Some(err.kind()),
+        }
     }
 }
 
@@ -1,3 +1,7 @@
+mod app_dirs;
 mod error;
+#[cfg(feature = "metrics")]
+pub mod write_counters;
 
+pub use app_dirs::AppDirs;
 pub use error::CheeseError;
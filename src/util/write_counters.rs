@@ -0,0 +1,25 @@
+// Counters for how often saving actually writes to disk, for checking that saves are being
+// coalesced properly. These are global since saves happen all over the place
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static WRITES: AtomicUsize = AtomicUsize::new(0);
+static WRITES_AVOIDED: AtomicUsize = AtomicUsize::new(0);
+
+/// A file was written to disk
+pub fn record_write() {
+    WRITES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// A save was requested, but nothing had changed so nothing was written
+pub fn record_write_avoided() {
+    WRITES_AVOIDED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The total (writes, writes avoided) since the program started
+pub fn write_counts() -> (usize, usize) {
+    (
+        WRITES.load(Ordering::Relaxed),
+        WRITES_AVOIDED.load(Ordering::Relaxed),
+    )
+}